@@ -8,12 +8,14 @@ import {
   Typography,
   Switch,
   CircularProgress,
+  Chip,
 } from '@mui/material';
-import { SmartToy, Refresh } from '@mui/icons-material';
+import { SmartToy, Refresh, Logout } from '@mui/icons-material';
 import { DashboardPage } from './pages/DashboardPage';
 import { BotsPage } from './pages/BotsPage';
 import { BotDetailPage } from './pages/BotDetailPage';
 import { BotSummariesPage } from './pages/BotSummariesPage';
+import { BotSchedulesPage } from './pages/BotSchedulesPage';
 import { EventsPage } from './pages/EventsPage';
 import { FollowerCachePage } from './pages/FollowerCachePage';
 import { BotBehaviorSettingsPage } from './pages/BotBehaviorSettingsPage';
@@ -23,12 +25,21 @@ import { GptSettingsPage } from './pages/GptSettingsPage';
 import { RelaySettingsPage } from './pages/RelaySettingsPage';
 import { BlacklistSettingsPage } from './pages/BlacklistSettingsPage';
 import TokenDetailsPage from './pages/TokenDetailsPage';
+import { LoginPage } from './pages/LoginPage';
+import { LivePage } from './pages/LivePage';
 import { useBots } from './hooks/useBots';
 import { useStats } from './hooks/useStats';
 import { useDailyReplies } from './hooks/useDailyReplies';
 import { botApi } from './api/botApi';
+import { authApi } from './api/authApi';
+import type { AuthUser } from './types';
 
-function AppContent() {
+interface AppContentProps {
+  user: AuthUser;
+  onLogout: () => void;
+}
+
+function AppContent({ user, onLogout }: AppContentProps) {
   const location = useLocation();
   const navigate = useNavigate();
   const { loading: botsLoading, reload: reloadBots } = useBots();
@@ -112,7 +123,7 @@ function AppContent() {
             <Switch
               checked={!globalPause}
               onChange={handleGlobalPauseToggle}
-              disabled={pauseLoading}
+              disabled={pauseLoading || user.role !== 'admin'}
               sx={{
                 '& .MuiSwitch-switchBase.Mui-checked': {
                   color: '#a5f3fc',
@@ -150,6 +161,22 @@ function AppContent() {
                 更新
               </Button>
             )}
+            {user.auth_enabled && (
+              <>
+                <Chip
+                  size="small"
+                  label={user.role === 'admin' ? '管理者' : '閲覧のみ'}
+                  sx={{ color: 'white', bgcolor: 'rgba(255,255,255,0.2)' }}
+                />
+                <Button
+                  startIcon={<Logout />}
+                  onClick={onLogout}
+                  sx={{ color: 'white' }}
+                >
+                  ログアウト
+                </Button>
+              </>
+            )}
           </Box>
         </Toolbar>
       </AppBar>
@@ -159,7 +186,9 @@ function AppContent() {
         <Route path="/bots" element={<BotsPage />} />
         <Route path="/bots/:pubkey" element={<BotDetailPage />} />
         <Route path="/bots/:pubkey/summaries" element={<BotSummariesPage />} />
+        <Route path="/bots/:pubkey/schedules" element={<BotSchedulesPage />} />
         <Route path="/events" element={<EventsPage />} />
+        <Route path="/live" element={<LivePage />} />
         <Route path="/follower-cache" element={<FollowerCachePage />} />
         <Route path="/analytics/token-details" element={<TokenDetailsPage />} />
         <Route path="/settings/bot-behavior" element={<BotBehaviorSettingsPage />} />
@@ -174,9 +203,42 @@ function AppContent() {
 }
 
 function App() {
+  // undefined: 確認中、null: 未ログイン
+  const [user, setUser] = useState<AuthUser | null | undefined>(undefined);
+
+  const loadUser = async () => {
+    try {
+      setUser(await authApi.me());
+    } catch (error) {
+      console.error('ログイン状態の取得エラー:', error);
+      setUser(null);
+    }
+  };
+
+  useEffect(() => {
+    loadUser();
+  }, []);
+
+  const handleLogout = async () => {
+    await authApi.logout();
+    setUser(null);
+  };
+
+  if (user === undefined) {
+    return (
+      <Box sx={{ display: 'flex', justifyContent: 'center', alignItems: 'center', height: '100vh' }}>
+        <CircularProgress />
+      </Box>
+    );
+  }
+
+  if (user === null) {
+    return <LoginPage onLogin={loadUser} />;
+  }
+
   return (
     <BrowserRouter>
-      <AppContent />
+      <AppContent user={user} onLogout={handleLogout} />
     </BrowserRouter>
   );
 }
@@ -0,0 +1,63 @@
+import type { AuthUser } from '../types';
+
+// NIP-07 ブラウザ拡張
+declare global {
+  interface Window {
+    nostr?: {
+      signEvent(event: {
+        kind: number;
+        created_at: number;
+        tags: string[][];
+        content: string;
+      }): Promise<Record<string, unknown>>;
+    };
+  }
+}
+
+const LOGIN_PATH = '/api/auth/login';
+
+// ログインAPI
+export const authApi = {
+  // 未ログインならnull
+  async me(): Promise<AuthUser | null> {
+    const res = await fetch('/api/auth/me');
+    if (res.status === 401) return null;
+    if (!res.ok) throw new Error('ログイン状態の取得に失敗しました');
+    return res.json();
+  },
+
+  async loginWithToken(token: string): Promise<void> {
+    const res = await fetch(LOGIN_PATH, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ token }),
+    });
+    if (!res.ok) throw new Error(await res.text());
+  },
+
+  // NIP-98（kind 27235）のイベントをNIP-07拡張で署名してログイン
+  async loginWithNostr(): Promise<void> {
+    if (!window.nostr) {
+      throw new Error('NIP-07対応のブラウザ拡張が見つかりません');
+    }
+    const event = await window.nostr.signEvent({
+      kind: 27235,
+      created_at: Math.floor(Date.now() / 1000),
+      tags: [
+        ['u', `${window.location.origin}${LOGIN_PATH}`],
+        ['method', 'POST'],
+      ],
+      content: '',
+    });
+    const encoded = btoa(unescape(encodeURIComponent(JSON.stringify(event))));
+    const res = await fetch(LOGIN_PATH, {
+      method: 'POST',
+      headers: { Authorization: `Nostr ${encoded}` },
+    });
+    if (!res.ok) throw new Error(await res.text());
+  },
+
+  async logout(): Promise<void> {
+    await fetch('/api/auth/logout', { method: 'POST' });
+  },
+};
@@ -1,4 +1,4 @@
-import type { Stats, BotData, BotRequest } from '../types';
+import type { Stats, BotData, BotRequest, PlaygroundRequest, PlaygroundResult, PersonaVersionData, PersonaDiff, PersonaVersion } from '../types';
 
 // Bot API
 export const botApi = {
@@ -67,5 +67,36 @@ export const botApi = {
     if (!res.ok) throw new Error('日別返信数の取得に失敗しました');
     return res.json();
   },
+
+  // 返信を送らずに生成だけ試す
+  async runPlayground(pubkey: string, data: PlaygroundRequest): Promise<PlaygroundResult> {
+    const res = await fetch(`/api/bots/${pubkey}/playground`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify(data),
+    });
+    if (!res.ok) throw new Error('プロンプトの試行に失敗しました');
+    return res.json();
+  },
+
+  // プロンプト・kind 0の変更履歴
+  async getPersonaVersions(pubkey: string): Promise<PersonaVersionData[]> {
+    const res = await fetch(`/api/bots/${pubkey}/persona-versions`);
+    if (!res.ok) throw new Error('プロンプト履歴の取得に失敗しました');
+    return res.json();
+  },
+
+  async getPersonaDiff(pubkey: string, id: number, against?: number): Promise<PersonaDiff> {
+    const query = against === undefined ? '' : `?against=${against}`;
+    const res = await fetch(`/api/bots/${pubkey}/persona-versions/${id}/diff${query}`);
+    if (!res.ok) throw new Error('差分の取得に失敗しました');
+    return res.json();
+  },
+
+  async rollbackPersona(pubkey: string, id: number): Promise<PersonaVersion> {
+    const res = await fetch(`/api/bots/${pubkey}/persona-versions/${id}/rollback`, { method: 'POST' });
+    if (!res.ok) throw new Error('ロールバックに失敗しました');
+    return res.json();
+  },
 };
 
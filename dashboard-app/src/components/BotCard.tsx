@@ -1,5 +1,5 @@
 import { Card, CardContent, Typography, Box, Chip, IconButton, Tooltip, Avatar, Dialog, DialogTitle, DialogContent, DialogActions, TextField, Button, Snackbar, Alert } from '@mui/material';
-import { CheckCircle, Cancel, PlayArrow, Pause, Edit, Delete, SmartToy, Send, Info, Summarize, Publish, Close } from '@mui/icons-material';
+import { CheckCircle, Cancel, PlayArrow, Pause, Edit, Delete, SmartToy, Send, Info, Summarize, Schedule, Publish, Close } from '@mui/icons-material';
 import { useNavigate } from 'react-router-dom';
 import type { BotData } from '../types';
 import { useMemo, useState } from 'react';
@@ -225,6 +225,23 @@ export const BotCard = ({ bot, onEdit, onDelete, onToggle }: BotCardProps) => {
                 <Summarize fontSize="small" />
               </IconButton>
             </Tooltip>
+            <Tooltip title="定期投稿">
+              <IconButton 
+                onClick={() => navigate(`/bots/${bot.pubkey}/schedules`)}
+                sx={{
+                  color: 'text.secondary',
+                  bgcolor: 'rgba(0, 0, 0, 0.04)',
+                  '&:hover': {
+                    bgcolor: 'rgba(0, 150, 136, 0.08)',
+                    color: '#009688',
+                  },
+                  transition: 'all 0.2s',
+                }}
+                size="small"
+              >
+                <Schedule fontSize="small" />
+              </IconButton>
+            </Tooltip>
             <Tooltip title="投稿">
               <IconButton 
                 onClick={() => setPostDialogOpen(true)}
@@ -15,13 +15,74 @@ import {
   Alert,
 } from '@mui/material';
 import { VpnKey, Psychology, Description, Save, Close, Add, Delete, CloudDownload, Casino, Publish } from '@mui/icons-material';
-import type { BotData, BotRequest } from '../types';
+import type { BotData, BotRequest, BotOverrides, CustomEmoji } from '../types';
 
 interface JsonField {
   key: string;
   value: string;
 }
 
+// 個別設定の入力値（空欄 = 共通設定を使用）
+type OverrideForm = Record<keyof BotOverrides, string>;
+
+const emptyOverrideForm: OverrideForm = {
+  model: '',
+  temperature: '',
+  max_tokens: '',
+  answer_length: '',
+  reaction_percent: '',
+  emoji_reaction_percent: '',
+  ignore_percent: '',
+  custom_emojis: '',
+  allowed_tools: '',
+};
+
+const toOverrideForm = (overrides?: BotOverrides): OverrideForm => ({
+  model: overrides?.model ?? '',
+  temperature: overrides?.temperature != null ? String(overrides.temperature) : '',
+  max_tokens: overrides?.max_tokens != null ? String(overrides.max_tokens) : '',
+  answer_length: overrides?.answer_length != null ? String(overrides.answer_length) : '',
+  reaction_percent: overrides?.reaction_percent != null ? String(overrides.reaction_percent) : '',
+  emoji_reaction_percent: overrides?.emoji_reaction_percent != null ? String(overrides.emoji_reaction_percent) : '',
+  ignore_percent: overrides?.ignore_percent != null ? String(overrides.ignore_percent) : '',
+  custom_emojis: (overrides?.custom_emojis ?? []).map((e) => `${e.shortcode} ${e.url}`).join('\n'),
+  allowed_tools: (overrides?.allowed_tools ?? []).join(', '),
+});
+
+const parseNumber = (value: string): number | null => {
+  if (value.trim() === '') return null;
+  const num = Number(value);
+  return Number.isFinite(num) ? num : null;
+};
+
+// 「shortcode URL」を1行ずつ
+const parseCustomEmojis = (value: string): CustomEmoji[] | null => {
+  const emojis = value
+    .split('\n')
+    .map((line) => line.trim().split(/\s+/))
+    .filter((parts) => parts.length === 2)
+    .map(([shortcode, url]) => ({ shortcode: shortcode.replace(/^:|:$/g, ''), url }));
+  return emojis.length > 0 ? emojis : null;
+};
+
+// ツール名をカンマ・空白区切りで
+const parseAllowedTools = (value: string): string[] | null => {
+  const tools = value.split(/[\s,]+/).filter((name) => name !== '');
+  return tools.length > 0 ? tools : null;
+};
+
+const toOverrides = (form: OverrideForm): BotOverrides => ({
+  model: form.model.trim() || null,
+  temperature: parseNumber(form.temperature),
+  max_tokens: parseNumber(form.max_tokens),
+  answer_length: parseNumber(form.answer_length),
+  reaction_percent: parseNumber(form.reaction_percent),
+  emoji_reaction_percent: parseNumber(form.emoji_reaction_percent),
+  ignore_percent: parseNumber(form.ignore_percent),
+  custom_emojis: parseCustomEmojis(form.custom_emojis),
+  allowed_tools: parseAllowedTools(form.allowed_tools),
+});
+
 interface BotDialogProps {
   open: boolean;
   bot: BotData | null;
@@ -35,6 +96,7 @@ export const BotDialog = ({ open, bot, onClose, onSave }: BotDialogProps) => {
     prompt: '',
     air_reply_single_ratio: 30,
   });
+  const [overrideForm, setOverrideForm] = useState<OverrideForm>(emptyOverrideForm);
   const [jsonFields, setJsonFields] = useState<JsonField[]>([{ key: '', value: '' }]);
   const [fetchingKind0, setFetchingKind0] = useState(false);
   const [publishing, setPublishing] = useState(false);
@@ -48,10 +110,11 @@ export const BotDialog = ({ open, bot, onClose, onSave }: BotDialogProps) => {
   useEffect(() => {
     if (bot) {
       setFormData({
-        secretkey: bot.secretkey || '',
+        secretkey: '',
         prompt: bot.prompt || '',
         air_reply_single_ratio: bot.air_reply_single_ratio !== undefined ? bot.air_reply_single_ratio : 30,
       });
+      setOverrideForm(toOverrideForm(bot.overrides));
       
       // contentをJSONとしてパースしてフィールドに展開
       if (bot.content) {
@@ -70,6 +133,7 @@ export const BotDialog = ({ open, bot, onClose, onSave }: BotDialogProps) => {
       }
     } else {
       setFormData({ secretkey: '', prompt: '' });
+      setOverrideForm(emptyOverrideForm);
       setJsonFields([{ key: '', value: '' }]);
     }
   }, [bot, open]);
@@ -151,7 +215,9 @@ export const BotDialog = ({ open, bot, onClose, onSave }: BotDialogProps) => {
     
     const content = Object.keys(contentObj).length > 0 ? JSON.stringify(contentObj) : '';
     
-    onSave({ ...formData, content }, bot?.pubkey);
+    // 編集時に秘密鍵が空欄なら送らない（サーバー側で既存の鍵を使う）
+    const secretkey = formData.secretkey.trim() || undefined;
+    onSave({ ...formData, secretkey, content, overrides: toOverrides(overrideForm) }, bot?.pubkey);
   };
 
   const handlePublishKind0 = async () => {
@@ -198,9 +264,9 @@ export const BotDialog = ({ open, bot, onClose, onSave }: BotDialogProps) => {
                 label="Secret Key"
                 value={formData.secretkey}
                 onChange={(e) => setFormData({ ...formData, secretkey: e.target.value })}
-                required
+                required={!bot}
                 fullWidth
-                placeholder="nsec1... または hex形式"
+                placeholder={bot ? '変更しない場合は空欄' : 'nsec1... または hex形式'}
                 InputProps={{
                   startAdornment: (
                     <InputAdornment position="start">
@@ -286,6 +352,100 @@ export const BotDialog = ({ open, bot, onClose, onSave }: BotDialogProps) => {
               </Box>
             </Box>
             
+            <Box>
+              <Typography variant="subtitle2" gutterBottom>
+                Bot個別設定
+              </Typography>
+              <Typography variant="caption" color="text.secondary" display="block" sx={{ mb: 2 }}>
+                空欄の項目は共通設定を使用します
+              </Typography>
+              <Box sx={{ display: 'grid', gridTemplateColumns: 'repeat(auto-fill, minmax(180px, 1fr))', gap: 2 }}>
+                <TextField
+                  label="モデル"
+                  size="small"
+                  value={overrideForm.model}
+                  onChange={(e) => setOverrideForm({ ...overrideForm, model: e.target.value })}
+                  placeholder="gpt-5-nano"
+                />
+                <TextField
+                  label="Temperature"
+                  size="small"
+                  type="number"
+                  inputProps={{ step: 0.1, min: 0, max: 2 }}
+                  value={overrideForm.temperature}
+                  onChange={(e) => setOverrideForm({ ...overrideForm, temperature: e.target.value })}
+                />
+                <TextField
+                  label="最大トークン数"
+                  size="small"
+                  type="number"
+                  value={overrideForm.max_tokens}
+                  onChange={(e) => setOverrideForm({ ...overrideForm, max_tokens: e.target.value })}
+                />
+                <TextField
+                  label="返信文字数"
+                  size="small"
+                  type="number"
+                  value={overrideForm.answer_length}
+                  onChange={(e) => setOverrideForm({ ...overrideForm, answer_length: e.target.value })}
+                />
+                <TextField
+                  label="反応確率"
+                  size="small"
+                  type="number"
+                  value={overrideForm.reaction_percent}
+                  onChange={(e) => setOverrideForm({ ...overrideForm, reaction_percent: e.target.value })}
+                  InputProps={{
+                    endAdornment: <InputAdornment position="end">%</InputAdornment>,
+                  }}
+                />
+                <TextField
+                  label="絵文字リアクション"
+                  size="small"
+                  type="number"
+                  value={overrideForm.emoji_reaction_percent}
+                  onChange={(e) => setOverrideForm({ ...overrideForm, emoji_reaction_percent: e.target.value })}
+                  helperText="返信の代わりに絵文字で反応できる確率"
+                  InputProps={{
+                    endAdornment: <InputAdornment position="end">%</InputAdornment>,
+                  }}
+                />
+                <TextField
+                  label="無視"
+                  size="small"
+                  type="number"
+                  value={overrideForm.ignore_percent}
+                  onChange={(e) => setOverrideForm({ ...overrideForm, ignore_percent: e.target.value })}
+                  helperText="反応しない確率"
+                  InputProps={{
+                    endAdornment: <InputAdornment position="end">%</InputAdornment>,
+                  }}
+                />
+              </Box>
+              <TextField
+                label="カスタム絵文字"
+                size="small"
+                fullWidth
+                multiline
+                minRows={2}
+                value={overrideForm.custom_emojis}
+                onChange={(e) => setOverrideForm({ ...overrideForm, custom_emojis: e.target.value })}
+                placeholder="nostrchan https://example.com/nostrchan.png"
+                helperText="1行に「ショートコード URL」を1つずつ"
+                sx={{ mt: 2 }}
+              />
+              <TextField
+                label="使えるツール"
+                size="small"
+                fullWidth
+                value={overrideForm.allowed_tools}
+                onChange={(e) => setOverrideForm({ ...overrideForm, allowed_tools: e.target.value })}
+                placeholder="get_current_time, get_user_profile, get_thread, get_zap_total, search_events"
+                helperText="メンション返信で呼び出せるツール（空欄ならツールを使わない）"
+                sx={{ mt: 2 }}
+              />
+            </Box>
+            
             <Box>
               <Box sx={{ display: 'flex', alignItems: 'center', justifyContent: 'space-between', mb: 2 }}>
                 <Typography variant="subtitle1" fontWeight="bold">
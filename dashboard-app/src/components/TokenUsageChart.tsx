@@ -17,6 +17,8 @@ const CATEGORY_COLORS: Record<string, string> = {
   'search_initial_reply': '#4facfe',
   'search_keyword_extraction': '#43e97b',
   'search_final_reply': '#fa709a',
+  'direct_message': '#f6a04d',
+  'zap_thanks': '#f7c948',
 };
 
 const CATEGORY_LABELS: Record<string, string> = {
@@ -26,6 +28,8 @@ const CATEGORY_LABELS: Record<string, string> = {
   'search_initial_reply': '検索一次回答',
   'search_keyword_extraction': 'キーワード抽出',
   'search_final_reply': '検索最終回答',
+  'direct_message': 'DM返信',
+  'zap_thanks': 'Zapのお礼',
 };
 
 export const TokenUsageChart = () => {
@@ -0,0 +1,42 @@
+import { useState, useEffect } from 'react';
+import type { LiveMessage } from '../types';
+
+const LIVE_EVENT_NAMES = ['incoming', 'queue', 'llm', 'outgoing'] as const;
+
+// /api/live を購読し、新しい順にmaxItems件まで保持する
+export const useLiveEvents = (bot: string, paused: boolean, maxItems = 200) => {
+  const [messages, setMessages] = useState<LiveMessage[]>([]);
+  const [connected, setConnected] = useState(false);
+  const [lagged, setLagged] = useState(0);
+
+  useEffect(() => {
+    if (paused) return;
+
+    const params = bot ? `?bot=${encodeURIComponent(bot)}` : '';
+    const source = new EventSource(`/api/live${params}`);
+    const handleMessage = (e: MessageEvent) => {
+      const message = JSON.parse(e.data) as LiveMessage;
+      setMessages((prev) => [message, ...prev].slice(0, maxItems));
+    };
+    const handleLagged = (e: MessageEvent) => {
+      setLagged((prev) => prev + Number(e.data));
+    };
+
+    source.onopen = () => setConnected(true);
+    source.onerror = () => setConnected(false);
+    LIVE_EVENT_NAMES.forEach((name) => source.addEventListener(name, handleMessage));
+    source.addEventListener('lagged', handleLagged);
+
+    return () => {
+      source.close();
+      setConnected(false);
+    };
+  }, [bot, paused, maxItems]);
+
+  const clear = () => {
+    setMessages([]);
+    setLagged(0);
+  };
+
+  return { messages, connected, lagged, clear };
+};
@@ -0,0 +1,306 @@
+import { useState, useEffect } from 'react';
+import { useParams, useNavigate } from 'react-router-dom';
+import {
+  Container, Box, Typography, IconButton, Paper, TextField, Select, MenuItem,
+  FormControl, InputLabel, Button, Dialog, DialogTitle, DialogContent, DialogActions,
+  List, ListItem, ListItemText, Chip, Snackbar, Alert, Switch, FormControlLabel, Tooltip
+} from '@mui/material';
+import { ArrowBack, Schedule, Add, Edit, Delete } from '@mui/icons-material';
+import { useBots } from '../hooks/useBots';
+
+interface BotSchedule {
+  id: number;
+  bot_pubkey: string;
+  kind: string;
+  cron: string;
+  instruction: string | null;
+  enabled: boolean;
+  last_run_at: number | null;
+  next_run_at: number | null;
+  created_at: number;
+  updated_at: number;
+}
+
+interface ScheduleKind {
+  name: string;
+  display_name: string;
+}
+
+interface ScheduleForm {
+  kind: string;
+  cron: string;
+  instruction: string;
+  enabled: boolean;
+}
+
+const emptyForm: ScheduleForm = { kind: 'greeting', cron: '0 7 * * *', instruction: '', enabled: true };
+
+const formatTime = (timestamp: number | null) =>
+  timestamp ? new Date(timestamp * 1000).toLocaleString('ja-JP') : '-';
+
+export const BotSchedulesPage = () => {
+  const { pubkey } = useParams<{ pubkey: string }>();
+  const navigate = useNavigate();
+  const { bots } = useBots();
+  const [schedules, setSchedules] = useState<BotSchedule[]>([]);
+  const [kinds, setKinds] = useState<ScheduleKind[]>([]);
+  const [loading, setLoading] = useState(true);
+
+  // 編集ダイアログ（editingIdがnullなら追加）
+  const [dialogOpen, setDialogOpen] = useState(false);
+  const [editingId, setEditingId] = useState<number | null>(null);
+  const [form, setForm] = useState<ScheduleForm>(emptyForm);
+
+  const [snackbar, setSnackbar] = useState({
+    open: false,
+    message: '',
+    severity: 'success' as 'success' | 'error',
+  });
+
+  const bot = bots.find(b => b.pubkey === pubkey);
+
+  useEffect(() => {
+    const loadKinds = async () => {
+      try {
+        const response = await fetch('/api/schedule-kinds');
+        if (response.ok) {
+          setKinds(await response.json());
+        }
+      } catch (error) {
+        console.error('定期投稿の種類取得エラー:', error);
+      }
+    };
+    loadKinds();
+  }, []);
+
+  useEffect(() => {
+    if (pubkey) {
+      loadSchedules();
+    }
+  }, [pubkey]);
+
+  const loadSchedules = async () => {
+    try {
+      setLoading(true);
+      const response = await fetch(`/api/bots/${pubkey}/schedules`);
+      if (!response.ok) throw new Error('Failed to fetch schedules');
+      setSchedules(await response.json());
+    } catch (error) {
+      console.error('定期投稿一覧取得エラー:', error);
+      setSnackbar({ open: true, message: '定期投稿の取得に失敗しました', severity: 'error' });
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  const kindLabel = (name: string) => kinds.find(k => k.name === name)?.display_name || name;
+
+  const handleAdd = () => {
+    setEditingId(null);
+    setForm(emptyForm);
+    setDialogOpen(true);
+  };
+
+  const handleEdit = (schedule: BotSchedule) => {
+    setEditingId(schedule.id);
+    setForm({
+      kind: schedule.kind,
+      cron: schedule.cron,
+      instruction: schedule.instruction || '',
+      enabled: schedule.enabled,
+    });
+    setDialogOpen(true);
+  };
+
+  const handleSave = async () => {
+    const url = editingId === null ? `/api/bots/${pubkey}/schedules` : `/api/schedules/${editingId}`;
+    try {
+      const response = await fetch(url, {
+        method: editingId === null ? 'POST' : 'PUT',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ ...form, instruction: form.instruction || null }),
+      });
+      if (response.status === 400) {
+        setSnackbar({ open: true, message: 'cronの書式が正しくありません', severity: 'error' });
+        return;
+      }
+      if (!response.ok) throw new Error('Failed to save schedule');
+      setDialogOpen(false);
+      setSnackbar({ open: true, message: '保存しました', severity: 'success' });
+      loadSchedules();
+    } catch (error) {
+      console.error('定期投稿保存エラー:', error);
+      setSnackbar({ open: true, message: '保存に失敗しました', severity: 'error' });
+    }
+  };
+
+  const handleToggle = async (schedule: BotSchedule) => {
+    try {
+      const response = await fetch(`/api/schedules/${schedule.id}`, {
+        method: 'PUT',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({
+          kind: schedule.kind,
+          cron: schedule.cron,
+          instruction: schedule.instruction,
+          enabled: !schedule.enabled,
+        }),
+      });
+      if (!response.ok) throw new Error('Failed to toggle schedule');
+      loadSchedules();
+    } catch (error) {
+      console.error('定期投稿切替エラー:', error);
+      setSnackbar({ open: true, message: '切替に失敗しました', severity: 'error' });
+    }
+  };
+
+  const handleDelete = async (id: number) => {
+    if (!confirm('この定期投稿を削除しますか？')) return;
+    try {
+      const response = await fetch(`/api/schedules/${id}`, { method: 'DELETE' });
+      if (!response.ok) throw new Error('Failed to delete schedule');
+      setSnackbar({ open: true, message: '削除しました', severity: 'success' });
+      loadSchedules();
+    } catch (error) {
+      console.error('定期投稿削除エラー:', error);
+      setSnackbar({ open: true, message: '削除に失敗しました', severity: 'error' });
+    }
+  };
+
+  if (!bot) {
+    return (
+      <Container maxWidth="lg" sx={{ py: 4 }}>
+        <Typography>Botが見つかりません</Typography>
+      </Container>
+    );
+  }
+
+  return (
+    <Container maxWidth="lg" sx={{ py: 4 }}>
+      {/* ヘッダー */}
+      <Box sx={{ display: 'flex', alignItems: 'center', gap: 2, mb: 3 }}>
+        <IconButton onClick={() => navigate('/bots')} size="large">
+          <ArrowBack />
+        </IconButton>
+        <Box sx={{ display: 'flex', alignItems: 'center', gap: 2, flex: 1 }}>
+          <Schedule sx={{ fontSize: 32, color: 'primary.main' }} />
+          <Box>
+            <Typography variant="h4" fontWeight="bold">
+              {bot.content ? JSON.parse(bot.content).name || 'Bot' : 'Bot'} の定期投稿
+            </Typography>
+            <Typography variant="body2" color="text.secondary" sx={{ fontFamily: 'monospace' }}>
+              {pubkey?.substring(0, 16)}...
+            </Typography>
+          </Box>
+        </Box>
+        <Button variant="contained" startIcon={<Add />} onClick={handleAdd}>
+          追加
+        </Button>
+      </Box>
+
+      {/* 定期投稿一覧 */}
+      {loading ? (
+        <Box sx={{ display: 'flex', justifyContent: 'center', py: 4 }}>
+          <Typography>読み込み中...</Typography>
+        </Box>
+      ) : schedules.length === 0 ? (
+        <Paper sx={{ p: 4, textAlign: 'center' }}>
+          <Typography color="text.secondary">まだ定期投稿がありません</Typography>
+        </Paper>
+      ) : (
+        <Paper>
+          <List>
+            {schedules.map((schedule) => (
+              <ListItem
+                key={schedule.id}
+                divider
+                secondaryAction={
+                  <Box sx={{ display: 'flex', alignItems: 'center', gap: 0.5 }}>
+                    <Tooltip title={schedule.enabled ? '有効' : '無効'}>
+                      <Switch checked={schedule.enabled} onChange={() => handleToggle(schedule)} />
+                    </Tooltip>
+                    <IconButton onClick={() => handleEdit(schedule)} size="small">
+                      <Edit fontSize="small" />
+                    </IconButton>
+                    <IconButton onClick={() => handleDelete(schedule.id)} size="small" color="error">
+                      <Delete fontSize="small" />
+                    </IconButton>
+                  </Box>
+                }
+              >
+                <ListItemText
+                  primary={
+                    <Box sx={{ display: 'flex', alignItems: 'center', gap: 1 }}>
+                      <Chip label={kindLabel(schedule.kind)} size="small" color="primary" />
+                      <Typography sx={{ fontFamily: 'monospace' }}>{schedule.cron}</Typography>
+                    </Box>
+                  }
+                  secondary={
+                    <>
+                      {schedule.instruction && <>{schedule.instruction}<br /></>}
+                      次回: {schedule.enabled ? formatTime(schedule.next_run_at) : '-'} ／ 前回: {formatTime(schedule.last_run_at)}
+                    </>
+                  }
+                />
+              </ListItem>
+            ))}
+          </List>
+        </Paper>
+      )}
+
+      {/* 追加・編集ダイアログ */}
+      <Dialog open={dialogOpen} onClose={() => setDialogOpen(false)} maxWidth="sm" fullWidth>
+        <DialogTitle>{editingId === null ? '定期投稿を追加' : '定期投稿を編集'}</DialogTitle>
+        <DialogContent>
+          <Box sx={{ display: 'flex', flexDirection: 'column', gap: 2, mt: 1 }}>
+            <FormControl fullWidth>
+              <InputLabel>種類</InputLabel>
+              <Select
+                value={form.kind}
+                label="種類"
+                onChange={(e) => setForm({ ...form, kind: e.target.value })}
+              >
+                {kinds.map((kind) => (
+                  <MenuItem key={kind.name} value={kind.name}>{kind.display_name}</MenuItem>
+                ))}
+              </Select>
+            </FormControl>
+            <TextField
+              label="cron（分 時 日 月 曜日）"
+              value={form.cron}
+              onChange={(e) => setForm({ ...form, cron: e.target.value })}
+              helperText="日本時間で評価します。例: 0 7 * * *（毎朝7時）、0 21 * * 0（毎週日曜21時）"
+              fullWidth
+            />
+            <TextField
+              label="追加の指示（任意）"
+              value={form.instruction}
+              onChange={(e) => setForm({ ...form, instruction: e.target.value })}
+              multiline
+              rows={3}
+              fullWidth
+            />
+            <FormControlLabel
+              control={<Switch checked={form.enabled} onChange={(e) => setForm({ ...form, enabled: e.target.checked })} />}
+              label="有効"
+            />
+          </Box>
+        </DialogContent>
+        <DialogActions>
+          <Button onClick={() => setDialogOpen(false)}>キャンセル</Button>
+          <Button onClick={handleSave} variant="contained">保存</Button>
+        </DialogActions>
+      </Dialog>
+
+      <Snackbar
+        open={snackbar.open}
+        autoHideDuration={3000}
+        onClose={() => setSnackbar({ ...snackbar, open: false })}
+      >
+        <Alert severity={snackbar.severity} onClose={() => setSnackbar({ ...snackbar, open: false })}>
+          {snackbar.message}
+        </Alert>
+      </Snackbar>
+    </Container>
+  );
+};
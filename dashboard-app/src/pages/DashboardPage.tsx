@@ -1,5 +1,5 @@
 import { Container, Paper, Typography, Box, Button, Grid } from '@mui/material';
-import { SmartToy, People, ChevronRight, Speed, Chat, Search, Psychology, Wifi, Block, List } from '@mui/icons-material';
+import { SmartToy, People, ChevronRight, Speed, Chat, Search, Psychology, Wifi, Block, List, Stream } from '@mui/icons-material';
 import { useNavigate } from 'react-router-dom';
 import { StatisticsSection, ReplyTrendSection } from '../sections/StatisticsSection';
 import { TokenUsageChart } from '../components/TokenUsageChart';
@@ -21,6 +21,13 @@ export const DashboardPage = () => {
       path: '/bots',
       color: '#667eea',
     },
+    {
+      title: 'ライブ',
+      description: '受信・LLM呼び出し・返信をリアルタイム表示',
+      icon: Stream,
+      path: '/live',
+      color: '#f5576c',
+    },
     {
       title: 'イベント一覧',
       description: 'ベクトル化されたイベント',
@@ -0,0 +1,139 @@
+import { useState } from 'react';
+import {
+  Container, Box, Typography, IconButton, Paper, Chip, Select, MenuItem, FormControl, InputLabel,
+  Button, Alert,
+} from '@mui/material';
+import { ArrowBack, Pause, PlayArrow, DeleteSweep } from '@mui/icons-material';
+import { useNavigate } from 'react-router-dom';
+import { useBots } from '../hooks/useBots';
+import { useLiveEvents } from '../hooks/useLiveEvents';
+import type { BotData, LiveMessage } from '../types';
+
+const TYPE_LABELS: Record<LiveMessage['type'], { label: string; color: string }> = {
+  incoming: { label: '受信', color: '#90a4ae' },
+  queue: { label: 'キュー', color: '#9575cd' },
+  llm: { label: 'LLM', color: '#fa709a' },
+  outgoing: { label: '送信', color: '#38ef7d' },
+};
+
+const QUEUE_STATUS_LABELS: Record<string, string> = {
+  enqueued: '追加',
+  processing: '処理中',
+  completed: '完了',
+  retrying: '再試行待ち',
+  dead_lettered: 'dead letter',
+};
+
+const botName = (bots: BotData[], pubkey: string | null | undefined) => {
+  if (!pubkey) return null;
+  const bot = bots.find((b) => b.pubkey === pubkey);
+  try {
+    const kind0 = bot?.content ? JSON.parse(bot.content) : null;
+    if (kind0?.display_name || kind0?.name) return kind0.display_name || kind0.name;
+  } catch {
+    // kind 0が壊れていればpubkeyを表示
+  }
+  return `${pubkey.substring(0, 8)}...`;
+};
+
+const describe = (message: LiveMessage) => {
+  switch (message.type) {
+    case 'incoming':
+      return message.content ?? `kind ${message.kind}（暗号化）`;
+    case 'queue':
+      return `${QUEUE_STATUS_LABELS[message.status] ?? message.status} #${message.queue_id ?? '-'} ${message.event_id.substring(0, 8)}...${message.error ? ` - ${message.error}` : ''}`;
+    case 'llm':
+      return `${message.category} ${message.model} ${message.latency_ms}ms 入力${message.prompt_tokens}/出力${message.completion_tokens}トークン${message.error ? ` - ❌ ${message.error}` : ''}`;
+    case 'outgoing':
+      return message.content ?? `kind ${message.kind}（DM）`;
+  }
+};
+
+export const LivePage = () => {
+  const navigate = useNavigate();
+  const { bots } = useBots();
+  const [bot, setBot] = useState('');
+  const [paused, setPaused] = useState(false);
+  const { messages, connected, lagged, clear } = useLiveEvents(bot, paused);
+
+  return (
+    <Container maxWidth="lg" sx={{ py: 4 }}>
+      <Box sx={{ display: 'flex', alignItems: 'center', gap: 2, mb: 3 }}>
+        <IconButton onClick={() => navigate('/')}>
+          <ArrowBack />
+        </IconButton>
+        <Typography variant="h5" fontWeight="bold" sx={{ flex: 1 }}>
+          📡 ライブ
+        </Typography>
+        <Chip
+          size="small"
+          label={paused ? '停止中' : connected ? '接続中' : '再接続中...'}
+          color={!paused && connected ? 'success' : 'default'}
+        />
+        <FormControl size="small" sx={{ minWidth: 200 }}>
+          <InputLabel>Bot</InputLabel>
+          <Select value={bot} label="Bot" onChange={(e) => setBot(e.target.value)}>
+            <MenuItem value="">すべて（受信を含む）</MenuItem>
+            {bots.map((b) => (
+              <MenuItem key={b.pubkey} value={b.pubkey}>
+                {botName(bots, b.pubkey)}
+              </MenuItem>
+            ))}
+          </Select>
+        </FormControl>
+        <Button
+          variant="outlined"
+          startIcon={paused ? <PlayArrow /> : <Pause />}
+          onClick={() => setPaused(!paused)}
+        >
+          {paused ? '再開' : '一時停止'}
+        </Button>
+        <IconButton onClick={clear}>
+          <DeleteSweep />
+        </IconButton>
+      </Box>
+
+      {lagged > 0 && (
+        <Alert severity="warning" sx={{ mb: 2 }}>
+          表示が追いつかず {lagged} 件を取りこぼしました
+        </Alert>
+      )}
+
+      <Paper elevation={0} sx={{ border: '1px solid', borderColor: 'divider', borderRadius: 2 }}>
+        {messages.length === 0 ? (
+          <Typography color="text.secondary" sx={{ p: 3, textAlign: 'center' }}>
+            イベントを待っています...
+          </Typography>
+        ) : (
+          messages.map((message, index) => {
+            const type = TYPE_LABELS[message.type];
+            const name = botName(bots, 'bot_pubkey' in message ? message.bot_pubkey : null);
+            return (
+              <Box
+                key={`${message.at}-${index}`}
+                sx={{
+                  display: 'flex',
+                  alignItems: 'flex-start',
+                  gap: 1.5,
+                  px: 2,
+                  py: 1,
+                  borderBottom: index < messages.length - 1 ? '1px solid' : 'none',
+                  borderColor: 'divider',
+                }}
+              >
+                <Typography variant="caption" color="text.secondary" sx={{ minWidth: 64, pt: 0.5 }}>
+                  {new Date(message.at).toLocaleTimeString('ja-JP')}
+                </Typography>
+                <Chip size="small" label={type.label} sx={{ bgcolor: type.color, color: 'white', minWidth: 64 }} />
+                {name && <Chip size="small" variant="outlined" label={name} />}
+                <Typography variant="body2" sx={{ flex: 1, whiteSpace: 'pre-wrap', wordBreak: 'break-all' }}>
+                  {describe(message)}
+                </Typography>
+              </Box>
+            );
+          })
+        )}
+      </Paper>
+    </Container>
+  );
+};
@@ -0,0 +1,94 @@
+import { useState } from 'react';
+import { Box, Paper, Typography, TextField, Button, Divider, Alert, InputAdornment } from '@mui/material';
+import { SmartToy, VpnKey, Login } from '@mui/icons-material';
+import { authApi } from '../api/authApi';
+
+interface LoginPageProps {
+  onLogin: () => void;
+}
+
+export const LoginPage = ({ onLogin }: LoginPageProps) => {
+  const [token, setToken] = useState('');
+  const [error, setError] = useState<string | null>(null);
+  const [loading, setLoading] = useState(false);
+
+  const login = async (method: () => Promise<void>) => {
+    setLoading(true);
+    setError(null);
+    try {
+      await method();
+      onLogin();
+    } catch (e) {
+      console.error('ログインエラー:', e);
+      setError(e instanceof Error && e.message ? e.message : 'ログインに失敗しました');
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  const handleTokenSubmit = (e: React.FormEvent) => {
+    e.preventDefault();
+    login(() => authApi.loginWithToken(token));
+  };
+
+  return (
+    <Box
+      sx={{
+        minHeight: '100vh',
+        display: 'flex',
+        alignItems: 'center',
+        justifyContent: 'center',
+        background: 'linear-gradient(135deg, #667eea 0%, #764ba2 100%)',
+      }}
+    >
+      <Paper sx={{ p: 4, width: '100%', maxWidth: 420, borderRadius: 3 }}>
+        <Box sx={{ display: 'flex', alignItems: 'center', gap: 1, mb: 3 }}>
+          <SmartToy sx={{ fontSize: 32, color: '#667eea' }} />
+          <Typography variant="h6" fontWeight="bold">
+            📊 Nostr Bot Dashboard
+          </Typography>
+        </Box>
+
+        {error && (
+          <Alert severity="error" sx={{ mb: 2 }}>
+            {error}
+          </Alert>
+        )}
+
+        <Button
+          fullWidth
+          variant="contained"
+          startIcon={<Login />}
+          onClick={() => login(authApi.loginWithNostr)}
+          disabled={loading}
+          sx={{ background: 'linear-gradient(135deg, #667eea 0%, #764ba2 100%)' }}
+        >
+          Nostrでログイン（NIP-07）
+        </Button>
+
+        <Divider sx={{ my: 3 }}>または</Divider>
+
+        <form onSubmit={handleTokenSubmit}>
+          <TextField
+            label="アクセストークン"
+            type="password"
+            value={token}
+            onChange={(e) => setToken(e.target.value)}
+            fullWidth
+            required
+            InputProps={{
+              startAdornment: (
+                <InputAdornment position="start">
+                  <VpnKey />
+                </InputAdornment>
+              ),
+            }}
+          />
+          <Button type="submit" fullWidth variant="outlined" disabled={loading || !token} sx={{ mt: 2 }}>
+            トークンでログイン
+          </Button>
+        </form>
+      </Paper>
+    </Box>
+  );
+};
@@ -33,6 +33,7 @@ interface TokenDetail {
   prompt_text: string;
   completion_text: string;
   created_at: number;
+  persona_version_id: number | null;
 }
 
 export default function TokenDetailsPage() {
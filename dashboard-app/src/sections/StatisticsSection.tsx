@@ -1,5 +1,5 @@
-import { Box, Typography, Paper } from '@mui/material';
-import { AccessTime, Wifi, Circle, ChatBubble, Storage, Block } from '@mui/icons-material';
+import { Box, Typography, Paper, Chip } from '@mui/material';
+import { AccessTime, Wifi, Circle, ChatBubble, Storage, Block, Favorite, Bolt } from '@mui/icons-material';
 import { StatsCard } from '../components/StatsCard';
 import { ReplyTrendChart } from '../components/ReplyTrendChart';
 import type { Stats, BotData } from '../types';
@@ -28,7 +28,7 @@ export const StatisticsSection = ({ stats, dailyRepliesData, bots, dailyRepliesL
       </Typography>
       
       <Paper elevation={0} sx={{ p: 2, border: '1px solid', borderColor: 'divider', borderRadius: 2 }}>
-        <Box sx={{ display: 'grid', gridTemplateColumns: { xs: 'repeat(2, 1fr)', sm: 'repeat(3, 1fr)', md: 'repeat(6, 1fr)' }, gap: 2 }}>
+        <Box sx={{ display: 'grid', gridTemplateColumns: { xs: 'repeat(2, 1fr)', sm: 'repeat(3, 1fr)', md: 'repeat(4, 1fr)', lg: 'repeat(8, 1fr)' }, gap: 2 }}>
           <Box sx={{ textAlign: 'center' }}>
             <Box sx={{ display: 'flex', alignItems: 'center', justifyContent: 'center', gap: 0.5, mb: 0.5 }}>
               <AccessTime sx={{ fontSize: 16, color: 'info.main' }} />
@@ -91,8 +91,59 @@ export const StatisticsSection = ({ stats, dailyRepliesData, bots, dailyRepliesL
               {stats.conversation_stats.rate_limited_users}
             </Typography>
           </Box>
+
+          <Box sx={{ textAlign: 'center' }}>
+            <Box sx={{ display: 'flex', alignItems: 'center', justifyContent: 'center', gap: 0.5, mb: 0.5 }}>
+              <Favorite sx={{ fontSize: 16, color: 'error.main' }} />
+              <Typography variant="caption" color="text.secondary">今日のいいね</Typography>
+            </Box>
+            <Typography variant="h6" fontWeight="bold" color="error.main">
+              {stats.engagement_stats.reactions_today}
+            </Typography>
+          </Box>
+
+          <Box sx={{ textAlign: 'center' }}>
+            <Box sx={{ display: 'flex', alignItems: 'center', justifyContent: 'center', gap: 0.5, mb: 0.5 }}>
+              <Bolt sx={{ fontSize: 16, color: 'warning.main' }} />
+              <Typography variant="caption" color="text.secondary">今日のZap</Typography>
+            </Box>
+            <Typography variant="h6" fontWeight="bold" color="warning.main">
+              {stats.engagement_stats.zaps_today}
+            </Typography>
+            <Typography variant="caption" color="text.secondary">
+              {stats.engagement_stats.zap_sats_today.toLocaleString()} sats
+            </Typography>
+          </Box>
         </Box>
       </Paper>
+
+      {stats.error_log.length > 0 && (
+        <Paper elevation={0} sx={{ p: 2, mt: 2, border: '1px solid', borderColor: 'divider', borderRadius: 2 }}>
+          <Typography variant="subtitle2" fontWeight="bold" mb={1}>
+            最近のエラー
+          </Typography>
+          {stats.error_log.slice(0, 5).map((entry, index) => (
+            <Box key={index} sx={{ display: 'flex', alignItems: 'flex-start', gap: 1, py: 0.5 }}>
+              <Chip
+                label={entry.level}
+                size="small"
+                color={entry.level === 'ERROR' ? 'error' : 'warning'}
+                sx={{ minWidth: 64 }}
+              />
+              <Box sx={{ minWidth: 0 }}>
+                <Typography variant="body2" sx={{ wordBreak: 'break-all' }}>
+                  {entry.message}
+                </Typography>
+                <Typography variant="caption" color="text.secondary">
+                  {new Date(entry.timestamp * 1000).toLocaleString()} · {entry.error_type}
+                  {entry.event_id && ` · event ${entry.event_id.slice(0, 8)}`}
+                  {entry.bot_pubkey && ` · bot ${entry.bot_pubkey.slice(0, 8)}`}
+                </Typography>
+              </Box>
+            </Box>
+          ))}
+        </Paper>
+      )}
     </Box>
   );
 };
@@ -1,17 +1,38 @@
+// Bot個別設定（nullの項目は共通設定を使用）
+export interface BotOverrides {
+  model: string | null;
+  temperature: number | null;
+  max_tokens: number | null;
+  answer_length: number | null;
+  reaction_percent: number | null;
+  emoji_reaction_percent: number | null;
+  ignore_percent: number | null;
+  custom_emojis: CustomEmoji[] | null;
+  allowed_tools: string[] | null;
+}
+
+// カスタム絵文字（NIP-30）
+export interface CustomEmoji {
+  shortcode: string;
+  url: string;
+}
+
 export interface BotData {
   pubkey: string;
-  secretkey: string;
+  secretkey: string | null; // 管理者が include_secret=true で取得したときのみ
   prompt: string;
   content: string;
   status: number; // 0: active, 1: inactive
   air_reply_single_ratio?: number;
+  overrides?: BotOverrides;
 }
 
 export interface BotRequest {
-  secretkey: string;
+  secretkey?: string; // 編集時は省略すると変更しない
   prompt: string;
   content: string;
   air_reply_single_ratio?: number;
+  overrides?: BotOverrides;
 }
 
 export interface Stats {
@@ -19,6 +40,7 @@ export interface Stats {
   reply_stats: ReplyStats;
   conversation_stats: ConversationStats;
   rag_stats: RagStats;
+  engagement_stats: EngagementStats;
   error_log: ErrorEntry[];
 }
 
@@ -49,10 +71,75 @@ export interface RagStats {
   average_similarity: number;
 }
 
+export interface EngagementStats {
+  reactions_today: number;
+  zaps_today: number;
+  zap_sats_today: number;
+}
+
 export interface ErrorEntry {
   timestamp: number;
   error_type: string;
   message: string;
+  level: 'WARN' | 'ERROR' | string;
+  event_id: string | null;
+  bot_pubkey: string | null;
+  user_pubkey: string | null;
+}
+
+export interface PlaygroundRequest {
+  message: string;
+  user_pubkey?: string;
+  user_name?: string;
+  mode?: 'mention' | 'air_reply';
+}
+
+export interface PlaygroundResult {
+  mode: 'mention' | 'air_reply';
+  user_pubkey: string;
+  system_prompt: string;
+  context: string | null;
+  raw_output: string;
+  parsed: {
+    reply: string;
+    reaction: string | null;
+    user_attributes: Record<string, unknown>;
+    mental_diary: Record<string, unknown>;
+  } | null;
+  parse_error: string | null;
+  prompt_tokens: number;
+  completion_tokens: number;
+}
+
+export interface PersonaVersion {
+  id: number;
+  bot_pubkey: string;
+  prompt: string;
+  content: string;
+  author: string | null;
+  source: 'initial' | 'dashboard' | 'admin_command' | 'rollback' | string;
+  created_at: number;
+}
+
+export interface PersonaVersionData extends PersonaVersion {
+  active: boolean;
+  usage: {
+    calls: number;
+    prompt_tokens: number;
+    completion_tokens: number;
+  };
+}
+
+export interface DiffLine {
+  op: 'equal' | 'insert' | 'delete';
+  text: string;
+}
+
+export interface PersonaDiff {
+  from: PersonaVersion | null;
+  to: PersonaVersion;
+  prompt: DiffLine[];
+  content: DiffLine[];
 }
 
 export interface VectorizedEvent {
@@ -78,3 +165,37 @@ export interface EventsResponse {
   total_pages: number;
 }
 
+
+// ダッシュボードのログインユーザー
+export type Role = 'admin' | 'viewer';
+
+export interface AuthUser {
+  role: Role;
+  pubkey: string | null;
+  auth_enabled: boolean;
+}
+
+// ライブ配信（/api/live のSSE）
+export type LiveMessage = { at: number } & (
+  | { type: 'incoming'; event_id: string; kind: number; pubkey: string; relay: string; content: string | null }
+  | {
+      type: 'queue';
+      queue_id: number | null;
+      event_id: string;
+      status: 'enqueued' | 'processing' | 'completed' | 'retrying' | 'dead_lettered';
+      bot_pubkey: string | null;
+      error?: string;
+    }
+  | {
+      type: 'llm';
+      bot_pubkey: string;
+      category: string;
+      provider: string;
+      model: string;
+      latency_ms: number;
+      prompt_tokens: number;
+      completion_tokens: number;
+      error?: string;
+    }
+  | { type: 'outgoing'; bot_pubkey: string; event_id: string; kind: number; reply_to: string | null; content: string | null }
+);
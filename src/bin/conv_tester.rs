@@ -6,9 +6,16 @@ use bot::database as db;
 use bot::conversation;
 use bot::gpt;
 use nostr_sdk::prelude::*;
+use tracing_subscriber::EnvFilter;
 
 #[tokio::main]
 async fn main() {
+    // ライブラリのログは標準エラーへ（標準出力はコマンドの結果に使う）
+    tracing_subscriber::fmt()
+        .with_writer(io::stderr)
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn")))
+        .init();
+
     let args: Vec<String> = env::args().collect();
     if args.len() < 3 {
         eprintln!("Usage: conv_tester <SUBCOMMAND> --db <PATH> [...options]\n  SUBCOMMANDS: init-db | seed | chat | metrics | dump-context");
@@ -134,6 +141,7 @@ async fn chat_repl(db_path: &str, bot_pubkey: &str, bot_secret: &str, user_secre
     let config: config::AppConfig = serde_yaml::from_reader(file)?;
 
     let bot_keys = Keys::parse(bot_secret)?;
+    let bot = db::get_person(&conn, bot_pubkey)?;
     let user_keys = match user_secret { Some(s) => Keys::parse(s)?, None => Keys::generate() };
     let user_pubkey = user_keys.public_key().to_string();
 
@@ -161,7 +169,7 @@ async fn chat_repl(db_path: &str, bot_pubkey: &str, bot_secret: &str, user_secre
         let context = conversation::prepare_context_for_reply(&conn, bot_pubkey, &user_pubkey, input, 50, &config, None, None).await?;
 
         let prompt = "あなたは有益で礼儀正しい日本語のアシスタントです。".to_string();
-        let reply = gpt::get_reply_with_context(bot_pubkey, &prompt, input, true, if context.is_empty() { None } else { Some(context) }, &config).await?;
+        let reply = gpt::get_reply_with_context(&bot, &prompt, input, true, if context.is_empty() { None } else { Some(context) }, &config).await?;
         println!("BOT> {}", reply);
 
         if reply.is_empty() { continue; }
@@ -1,24 +1,29 @@
 use crate::config;
+use crate::dashboard;
 use crate::database as db;
 use crate::database;
 use crate::util;
 use nostr_sdk::prelude::*;
 use serde_json::Value;
-use std::future::Future;
+use super::CommandFuture;
+use super::parser::{CommandArgs, UsageError};
+use tracing::{error, info};
 
 // 管理者コマンド定義
 pub struct AdminCommand {
     pub name: &'static str,
     pub pattern: &'static str,
     pub description: &'static str,
-    pub handler: fn(config::AppConfig, db::Person, Event, Vec<String>) -> std::pin::Pin<Box<dyn Future<Output = Result<()>> + Send>>,
+    pub usage: &'static str,
+    pub handler: fn(config::AppConfig, db::Person, Event, CommandArgs) -> CommandFuture,
 }
 
 pub struct AdminCommandSimple {
     pub name: &'static str,
     pub pattern: &'static str,
     pub description: &'static str,
-    pub handler: fn(config::AppConfig, Event, Vec<String>) -> std::pin::Pin<Box<dyn Future<Output = Result<()>> + Send>>,
+    pub usage: &'static str,
+    pub handler: fn(config::AppConfig, Event, CommandArgs) -> CommandFuture,
 }
 
 // 管理者コマンドテーブル（person必要）
@@ -28,32 +33,93 @@ pub fn get_admin_commands() -> Vec<AdminCommand> {
             name: "get_kind0",
             pattern: "get kind 0",
             description: "リレーからkind 0を取得してDBを更新",
+            usage: "get kind 0",
             handler: |c, p, e, _| Box::pin(admin_get_kind0(c, p, e)),
         },
         AdminCommand {
             name: "update_kind0",
             pattern: "update kind 0",
             description: "DBのkind 0を更新してブロードキャスト",
-            handler: |c, p, e, l| Box::pin(admin_update_kind0(c, p, e, l)),
+            usage: "update kind 0 (改行) kind 0のJSON",
+            handler: |c, p, e, a| Box::pin(admin_update_kind0(c, p, e, a)),
         },
         AdminCommand {
             name: "broadcast_kind0",
             pattern: "broadcast kind 0",
             description: "DBのkind 0をブロードキャスト",
+            usage: "broadcast kind 0",
             handler: |c, p, e, _| Box::pin(admin_broadcast_kind0(c, p, e)),
         },
         AdminCommand {
             name: "clear_follower_cache",
             pattern: "clear_follower_cache",
             description: "全フォロワーキャッシュをクリア",
+            usage: "clear_follower_cache",
             handler: |c, p, e, _| Box::pin(admin_clear_follower_cache(c, p, e)),
         },
         AdminCommand {
             name: "clear_follower_cache_space",
             pattern: "clear follower cache",
             description: "全フォロワーキャッシュをクリア",
+            usage: "clear follower cache",
             handler: |c, p, e, _| Box::pin(admin_clear_follower_cache(c, p, e)),
         },
+        AdminCommand {
+            name: "pause",
+            pattern: "pause",
+            description: "全Botを一時停止",
+            usage: "pause",
+            handler: |c, p, e, _| Box::pin(admin_set_global_pause(c, p, e, true)),
+        },
+        AdminCommand {
+            name: "resume",
+            pattern: "resume",
+            description: "全Botの一時停止を解除",
+            usage: "resume",
+            handler: |c, p, e, _| Box::pin(admin_set_global_pause(c, p, e, false)),
+        },
+        AdminCommand {
+            name: "enable",
+            pattern: "enable",
+            description: "Botを有効化（省略時はメンションしたBot）",
+            usage: "enable [npub/hex]",
+            handler: |c, p, e, a| Box::pin(admin_set_bot_status(c, p, e, a, true)),
+        },
+        AdminCommand {
+            name: "disable",
+            pattern: "disable",
+            description: "Botを無効化（省略時はメンションしたBot）",
+            usage: "disable [npub/hex]",
+            handler: |c, p, e, a| Box::pin(admin_set_bot_status(c, p, e, a, false)),
+        },
+        AdminCommand {
+            name: "blacklist",
+            pattern: "blacklist",
+            description: "ブラックリストの表示・追加・削除",
+            usage: "blacklist [add/remove npub/hex]",
+            handler: |c, p, e, a| Box::pin(admin_blacklist(c, p, e, a)),
+        },
+        AdminCommand {
+            name: "setting",
+            pattern: "setting",
+            description: "システム設定の一覧・表示・変更",
+            usage: "setting [キー [値]]",
+            handler: |c, p, e, a| Box::pin(admin_setting(c, p, e, a)),
+        },
+        AdminCommand {
+            name: "queue_status",
+            pattern: "queue status",
+            description: "イベントキューの状況を表示",
+            usage: "queue status",
+            handler: |c, p, e, _| Box::pin(admin_queue_status(c, p, e)),
+        },
+        AdminCommand {
+            name: "token_report",
+            pattern: "token report",
+            description: "日別のトークン使用量を表示（省略時は今日）",
+            usage: "token report [日数]",
+            handler: |c, p, e, a| Box::pin(admin_token_report(c, p, e, a)),
+        },
     ]
 }
 
@@ -64,7 +130,8 @@ pub fn get_admin_commands_simple() -> Vec<AdminCommandSimple> {
             name: "new",
             pattern: "new",
             description: "新しいボットを作成",
-            handler: |c, e, l| Box::pin(admin_new(c, e, l)),
+            usage: "new (改行) プロンプト (改行) kind 0のJSON",
+            handler: |c, e, a| Box::pin(admin_new(c, e, a)),
         },
     ]
 }
@@ -74,28 +141,30 @@ pub fn get_admin_commands_simple() -> Vec<AdminCommandSimple> {
 async fn admin_new(
     config: config::AppConfig,
     event: Event,
-    lines: Vec<String>,
+    args: CommandArgs,
 ) -> Result<()> {
+    let prompt = args.line(1, "プロンプト")?;
+    let content = args.line(2, "kind 0のJSON")?;
+    let metadata: Value = serde_json::from_str(content)
+        .map_err(|e| UsageError::new(format!("kind 0のJSONが正しくありません: {}", e)))?;
     let conn = db::connect()?;
     let keys = Keys::generate();
-    let prompt = &lines[1];
-    let content = &lines[2];
-    database::person::insert_person(&conn, &keys, &prompt, &content)?;
+    database::person::insert_person(&conn, &keys, prompt, content)?;
+    db::record_persona_version(&conn, &keys.public_key().to_hex(), Some(&event.pubkey.to_hex()), "admin_command")?;
     let new_person = db::get_person(&conn, &keys.public_key().to_string()).unwrap();
     
     // kind 0をpublish
-    println!("[Bot Creation] Publishing kind 0 for new bot: {}", new_person.pubkey);
+    info!("[Bot Creation] Publishing kind 0 for new bot: {}", new_person.pubkey);
     match util::send_kind0(&new_person.secretkey.to_string(), content).await {
-        Ok(_) => println!("[Bot Creation] ✓ kind 0 published successfully"),
+        Ok(_) => info!("[Bot Creation] ✓ kind 0 published successfully"),
         Err(e) => {
-            eprintln!("[Bot Creation] ✗ Failed to publish kind 0: {:?}", e);
+            error!("[Bot Creation] ✗ Failed to publish kind 0: {:?}", e);
             // 失敗してもbot作成は続行
         }
     }
     
-    let content: Value = serde_json::from_str(content)?;
     let display_name =
-        &content["display_name"].to_string()[1..content["display_name"].to_string().len() - 1];
+        &metadata["display_name"].to_string()[1..metadata["display_name"].to_string().len() - 1];
     util::reply_to(
         &config,
         event.clone(),
@@ -111,15 +180,17 @@ async fn admin_get_kind0(
     person: db::Person,
     event: Event,
 ) -> Result<()> {
-    println!("get kind 0");
+    info!("get kind 0");
     let _meta_event = util::get_kind0(&person.pubkey, &person.secretkey).await?;
     let conn = db::connect()?;
+    db::ensure_initial_persona_versions(&conn, Some(&person.pubkey))?;
     database::person::update_person_content(&conn, &person.pubkey, &_meta_event.content.to_string())?;
+    db::record_persona_version(&conn, &person.pubkey, Some(&event.pubkey.to_hex()), "admin_command")?;
     util::reply_to(
         &config,
         event.clone(),
         person.clone(),
-        &format!("リレーからkind 0を取得してデータベース情報を更新しました"),
+        "リレーからkind 0を取得してデータベース情報を更新しました",
     )
     .await?;
     Ok(())
@@ -129,17 +200,22 @@ async fn admin_update_kind0(
     config: config::AppConfig,
     person: db::Person,
     event: Event,
-    lines: Vec<String>,
+    args: CommandArgs,
 ) -> Result<()> {
-    println!("update kind 0");
+    info!("update kind 0");
+    let content = args.line(1, "kind 0のJSON")?;
+    serde_json::from_str::<Value>(content)
+        .map_err(|e| UsageError::new(format!("kind 0のJSONが正しくありません: {}", e)))?;
     let conn = db::connect()?;
-    database::person::update_person_content(&conn, &person.pubkey, &lines[1])?;
-    util::send_kind0(&person.secretkey.to_string(), &lines[1]).await?;
+    db::ensure_initial_persona_versions(&conn, Some(&person.pubkey))?;
+    database::person::update_person_content(&conn, &person.pubkey, content)?;
+    db::record_persona_version(&conn, &person.pubkey, Some(&event.pubkey.to_hex()), "admin_command")?;
+    util::send_kind0(&person.secretkey.to_string(), content).await?;
     util::reply_to(
         &config,
         event.clone(),
         person.clone(),
-        &format!("データベースのkind 0を更新してブロードキャストしました"),
+        "データベースのkind 0を更新してブロードキャストしました",
     )
     .await?;
     Ok(())
@@ -150,13 +226,13 @@ async fn admin_broadcast_kind0(
     person: db::Person,
     event: Event,
 ) -> Result<()> {
-    println!("broadcast kind 0");
+    info!("broadcast kind 0");
     util::send_kind0(&person.secretkey.to_string(), &person.content.to_string()).await?;
     util::reply_to(
         &config,
         event.clone(),
         person.clone(),
-        &format!("データベースのkind 0の情報をブロードキャストしました"),
+        "データベースのkind 0の情報をブロードキャストしました",
     )
     .await?;
     Ok(())
@@ -167,7 +243,7 @@ async fn admin_clear_follower_cache(
     person: db::Person,
     event: Event,
 ) -> Result<()> {
-    println!("clear follower cache");
+    info!("clear follower cache");
     let conn = db::connect()?;
     let deleted_count = db::clear_follower_cache(&conn)?;
     util::reply_to(
@@ -179,3 +255,242 @@ async fn admin_clear_follower_cache(
     .await?;
     Ok(())
 }
+
+async fn admin_set_global_pause(
+    config: config::AppConfig,
+    person: db::Person,
+    event: Event,
+    paused: bool,
+) -> Result<()> {
+    let conn = db::connect()?;
+    db::set_system_setting(&conn, "global_pause", if paused { "true" } else { "false" })?;
+    info!("🔔 グローバル一時停止: {}", if paused { "有効" } else { "無効" });
+    let reply = if paused {
+        "⏸️ 全Botを一時停止しました（コマンドは引き続き使えます）"
+    } else {
+        "▶️ 全Botの一時停止を解除しました"
+    };
+    util::reply_to(&config, event, person, reply).await?;
+    Ok(())
+}
+
+async fn admin_set_bot_status(
+    config: config::AppConfig,
+    person: db::Person,
+    event: Event,
+    args: CommandArgs,
+    enabled: bool,
+) -> Result<()> {
+    let mut tokens = args.tokens()?;
+    let target = tokens.optional::<PublicKey>("公開鍵")?;
+    tokens.finish()?;
+
+    let conn = db::connect()?;
+    let target = match target {
+        Some(pubkey) => db::find_person(&conn, &pubkey.to_hex())?
+            .ok_or_else(|| UsageError::new(format!("Botが見つかりません: {}", pubkey.to_bech32().unwrap_or_default())))?,
+        None => person.clone(),
+    };
+    db::update_person_status(&conn, &target.pubkey, if enabled { 0 } else { 1 })?;
+    info!("🔔 Bot{}: {}", if enabled { "有効化" } else { "無効化" }, target.pubkey);
+
+    let name = bot_display_name(&target);
+    let reply = if enabled {
+        format!("✅ {}を有効にしました", name)
+    } else {
+        format!("🚫 {}を無効にしました", name)
+    };
+    util::reply_to(&config, event, person, &reply).await?;
+    Ok(())
+}
+
+async fn admin_blacklist(
+    config: config::AppConfig,
+    person: db::Person,
+    event: Event,
+    args: CommandArgs,
+) -> Result<()> {
+    let mut tokens = args.tokens()?;
+    let action = tokens.optional::<String>("操作")?;
+    let conn = db::connect()?;
+    let mut blacklist: Vec<String> = db::get_system_setting(&conn, "blacklist")?
+        .unwrap_or_default()
+        .split(',')
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect();
+
+    let reply = match action.as_deref() {
+        None => {
+            if blacklist.is_empty() {
+                "ブラックリストは空です".to_string()
+            } else {
+                let lines: Vec<String> = blacklist
+                    .iter()
+                    .map(|pubkey| {
+                        PublicKey::from_hex(pubkey)
+                            .ok()
+                            .and_then(|pk| pk.to_bech32().ok())
+                            .unwrap_or_else(|| pubkey.clone())
+                    })
+                    .collect();
+                format!("【ブラックリスト】{}件\n{}", lines.len(), lines.join("\n"))
+            }
+        }
+        Some(action @ ("add" | "remove")) => {
+            let pubkey = tokens.required::<PublicKey>("公開鍵")?;
+            tokens.finish()?;
+            let hex = pubkey.to_hex();
+            let npub = pubkey.to_bech32().unwrap_or_else(|_| hex.clone());
+            let exists = blacklist.contains(&hex);
+            if action == "add" {
+                if exists {
+                    format!("{} はすでにブラックリストにあります", npub)
+                } else {
+                    blacklist.push(hex);
+                    db::set_system_setting(&conn, "blacklist", &blacklist.join(","))?;
+                    info!("🚫 ブラックリスト更新: {}件", blacklist.len());
+                    format!("🚫 {} をブラックリストに追加しました", npub)
+                }
+            } else if exists {
+                blacklist.retain(|p| *p != hex);
+                db::set_system_setting(&conn, "blacklist", &blacklist.join(","))?;
+                info!("🚫 ブラックリスト更新: {}件", blacklist.len());
+                format!("{} をブラックリストから削除しました", npub)
+            } else {
+                format!("{} はブラックリストにありません", npub)
+            }
+        }
+        Some(other) => return Err(UsageError::new(format!("不明な操作です: {}", other)).into()),
+    };
+    util::reply_to(&config, event, person, &reply).await?;
+    Ok(())
+}
+
+async fn admin_setting(
+    config: config::AppConfig,
+    person: db::Person,
+    event: Event,
+    args: CommandArgs,
+) -> Result<()> {
+    let mut tokens = args.tokens()?;
+    let key = tokens.optional::<String>("キー")?;
+    let value = tokens.rest();
+    let conn = db::connect()?;
+
+    // 変更できる数値設定のみ扱う（ブラックリストなど他の設定は公開のリプライに出さない）
+    let reply = match key {
+        None => {
+            let mut lines = Vec::new();
+            for (key, _) in dashboard::EDITABLE_SETTINGS {
+                let value = db::get_system_setting(&conn, key)?;
+                lines.push(format!("{} = {}", key, value.as_deref().unwrap_or("(未設定)")));
+            }
+            format!("【システム設定】\n{}", lines.join("\n"))
+        }
+        Some(key) => {
+            if !dashboard::EDITABLE_SETTINGS.iter().any(|(name, _)| *name == key) {
+                return Err(UsageError::new(format!("{} は変更できる設定ではありません", key)).into());
+            }
+            if value.is_empty() {
+                match db::get_system_setting(&conn, &key)? {
+                    Some(value) => format!("{} = {}", key, value),
+                    None => format!("{} は未設定です（config.ymlの値を使います）", key),
+                }
+            } else {
+                let value = dashboard::validate_setting(&key, &value).map_err(UsageError::new)?;
+                let old = db::get_system_setting(&conn, &key)?;
+                db::set_system_setting(&conn, &key, &value)?;
+                info!("[Admin] 設定を変更: {} = {}", key, value);
+                format!("{} を {} → {} に変更しました", key, old.as_deref().unwrap_or("(未設定)"), value)
+            }
+        }
+    };
+    util::reply_to(&config, event, person, &reply).await?;
+    Ok(())
+}
+
+async fn admin_queue_status(
+    config: config::AppConfig,
+    person: db::Person,
+    event: Event,
+) -> Result<()> {
+    let conn = db::connect()?;
+    let queue_size = db::get_queue_size(&conn)?;
+    let paused = db::is_global_pause(&conn)?;
+    let reply = format!(
+        "【キュー】\n処理待ち・処理中: {}件\n状態: {}",
+        queue_size,
+        if paused { "一時停止中" } else { "稼働中" }
+    );
+    util::reply_to(&config, event, person, &reply).await?;
+    Ok(())
+}
+
+async fn admin_token_report(
+    config: config::AppConfig,
+    person: db::Person,
+    event: Event,
+    args: CommandArgs,
+) -> Result<()> {
+    let mut tokens = args.tokens()?;
+    let days = tokens.optional::<u32>("日数")?.unwrap_or(1);
+    tokens.finish()?;
+    if days == 0 {
+        return Err(UsageError::new("日数は1以上を指定してください").into());
+    }
+
+    // 今日（JST）の0時から、日数分さかのぼる
+    let jst = util::jst();
+    let now = chrono::Utc::now().with_timezone(&jst);
+    let from_date = now.date_naive() - chrono::Duration::days(days as i64 - 1);
+    let from = from_date
+        .and_hms_opt(0, 0, 0)
+        .and_then(|t| t.and_local_timezone(jst).single())
+        .map(|t| t.timestamp())
+        .unwrap_or(0);
+
+    let conn = db::connect()?;
+    let stats = db::get_token_usage_stats(&conn, from, now.timestamp())?;
+    let period = if days == 1 {
+        format!("{}", now.format("%m/%d"))
+    } else {
+        format!("{}〜{}", from_date.format("%m/%d"), now.format("%m/%d"))
+    };
+
+    let reply = if stats.is_empty() {
+        format!("【トークン使用量 {}】\n使用はありません", period)
+    } else {
+        let lines: Vec<String> = stats
+            .iter()
+            .map(|stat| {
+                let name = db::TokenCategory::from_str(&stat.category)
+                    .map(|c| c.display_name())
+                    .unwrap_or(&stat.category);
+                format!("・{}: {}トークン（{}回）", name, stat.total_tokens, stat.count)
+            })
+            .collect();
+        let total: i64 = stats.iter().map(|stat| stat.total_tokens).sum();
+        let count: i64 = stats.iter().map(|stat| stat.count).sum();
+        format!(
+            "【トークン使用量 {}】\n{}\n合計: {}トークン（{}回）",
+            period,
+            lines.join("\n"),
+            total,
+            count
+        )
+    };
+    util::reply_to(&config, event, person, &reply).await?;
+    Ok(())
+}
+
+/// kind 0のdisplay_name（なければname、どちらもなければpubkeyの先頭）
+fn bot_display_name(person: &db::Person) -> String {
+    let content: Value = serde_json::from_str(&person.content).unwrap_or_default();
+    content["display_name"]
+        .as_str()
+        .or_else(|| content["name"].as_str())
+        .filter(|name| !name.is_empty())
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| person.pubkey.chars().take(8).collect())
+}
@@ -1,37 +1,79 @@
 pub mod user;
 pub mod admin;
+pub mod parser;
 
 use crate::config;
 use crate::database as db;
+use crate::metrics;
 use crate::util;
 use nostr_sdk::prelude::*;
+use parser::UsageError;
 use rusqlite::Connection;
 use std::future::Future;
+use std::pin::Pin;
+use tracing::{error, info, info_span, Instrument};
 
-// ヘルパー関数: コマンドを非同期実行
-fn spawn_command<F>(future: F, error_msg: String)
-where
+// コマンドハンドラーが返すFuture
+pub type CommandFuture = Pin<Box<dyn Future<Output = Result<()>> + Send>>;
+
+// ヘルパー関数: コマンドを非同期実行（使い方の誤りは使い方を返信する）
+fn spawn_command<F>(
+    future: F,
+    name: &'static str,
+    usage: &'static str,
+    config: config::AppConfig,
+    person: db::Person,
+    event: Event,
+) where
     F: Future<Output = Result<()>> + Send + 'static,
 {
+    // spawnしたタスクには呼び出し元のspanが引き継がれないので付け直す
+    let span = info_span!(
+        "command",
+        command = name,
+        event_id = %event.id,
+        bot = %person.pubkey,
+        user = %event.pubkey
+    );
     tokio::spawn(async move {
-        if let Err(e) = future.await {
-            eprintln!("{}: {}", error_msg, e);
+        let usage_reply = match future.await {
+            Ok(()) => {
+                metrics::command_invoked(name, "ok");
+                None
+            }
+            Err(e) => match e.downcast_ref::<UsageError>() {
+                Some(usage_error) => {
+                    metrics::command_invoked(name, "usage_error");
+                    Some(format!("{}\n使い方: {}", usage_error, usage))
+                }
+                None => {
+                    metrics::command_invoked(name, "error");
+                    error!("{} error: {}", name, e);
+                    None
+                }
+            },
+        };
+        if let Some(reply) = usage_reply {
+            if let Err(e) = util::reply_to(&config, event, person, &reply).await {
+                error!("{} error: {}", name, e);
+            }
         }
-    });
+    }.instrument(span));
 }
 
 // コマンドハンドラー（メインエントリーポイント）
 pub async fn command_handler(
     config: &config::AppConfig,
     conn: &Connection,
-    persons: &Vec<db::Person>,
+    persons: &[db::Person],
     event: &Event,
 ) -> Result<bool> {
     let admin_pubkeys = &config.bot.admin_pubkeys;
-    let persons_ = persons.clone();
-    let person_op = util::extract_mention(persons_, &event).unwrap();
+    let persons_ = persons.to_vec();
+    let person_op = util::extract_mention(persons_, event).unwrap();
     
-    if event.content.contains("silent") {
+    // 「silent」という語を含む投稿にはコマンド反応しない
+    if parser::contains_phrase(&event.content, "silent") {
         return Ok(false);
     }
 
@@ -46,9 +88,9 @@ pub async fn command_handler(
     }
     
     // ブラックリストチェック（管理者以外）
-    let is_admin = admin_pubkeys.iter().any(|s| *s == event_pubkey);
+    let is_admin = admin_pubkeys.contains(&event_pubkey);
     if !is_admin && is_blacklisted(conn, &event_pubkey)? {
-        println!("[Command] ブラックリストのユーザーからのコマンドをスキップ: {}", event_pubkey);
+        info!("[Command] ブラックリストのユーザーからのコマンドをスキップ: {}", event_pubkey);
         return Ok(false);
     }
     
@@ -56,68 +98,34 @@ pub async fn command_handler(
     
     // ユーザーコマンドをチェック
     for cmd in user::get_user_commands() {
-        for pattern in &cmd.patterns {
-            let matched = if cmd.require_start {
-                // 文頭チェック（メンション後も考慮）
-                // 1. 完全な文頭
-                // 2. 改行後の文頭
-                // 3. nostr:npub1... または nostr:note1... の後
-                let content = &event.content;
-                content.starts_with(pattern) || 
-                content.contains(&format!("\n{}", pattern)) ||
-                {
-                    // nostr:npub1... または nostr:note1... の後にスペース+コマンドがあるかチェック
-                    if let Some(nostr_end) = content.find("nostr:npub1") {
-                        // nostr:npub1... の後の部分を取得
-                        if let Some(space_pos) = content[nostr_end..].find(' ') {
-                            let after_mention = &content[nostr_end + space_pos..].trim_start();
-                            after_mention.starts_with(pattern)
-                        } else {
-                            false
-                        }
-                    } else if let Some(nostr_end) = content.find("nostr:note1") {
-                        // nostr:note1... の後の部分を取得
-                        if let Some(space_pos) = content[nostr_end..].find(' ') {
-                            let after_mention = &content[nostr_end + space_pos..].trim_start();
-                            after_mention.starts_with(pattern)
-                        } else {
-                            false
-                        }
-                    } else {
-                        false
-                    }
-                }
-            } else {
-                event.content.contains(pattern)
-            };
-            
-            if matched {
-                spawn_command(
-                    (cmd.handler)(config.clone(), person.clone(), event.clone()),
-                    format!("{} error", cmd.name)
-                );
-                return Ok(true);
-            }
+        if let Some(args) = parser::match_command(&event.content, &cmd.patterns, cmd.require_start) {
+            spawn_command(
+                (cmd.handler)(config.clone(), person.clone(), event.clone(), args),
+                cmd.name,
+                cmd.usage,
+                config.clone(),
+                person.clone(),
+                event.clone(),
+            );
+            return Ok(true);
         }
     }
     
     // 管理者コマンドをチェック
-    let is_admin = admin_pubkeys.iter().any(|s| *s == event.pubkey.to_string());
     if !is_admin {
         return Ok(false);
     }
     
-    let lines: Vec<String> = event.content.lines().map(|line| line.to_string()).collect();
-    if lines.is_empty() {
-        return Ok(false);
-    }
-    
     // 管理者コマンド（person不要）
     for cmd in admin::get_admin_commands_simple() {
-        if lines[0].contains(cmd.pattern) {
+        if let Some(args) = parser::match_command(&event.content, &[cmd.pattern], true) {
             spawn_command(
-                (cmd.handler)(config.clone(), event.clone(), lines.clone()),
-                format!("{} error", cmd.name)
+                (cmd.handler)(config.clone(), event.clone(), args),
+                cmd.name,
+                cmd.usage,
+                config.clone(),
+                person.clone(),
+                event.clone(),
             );
             return Ok(true);
         }
@@ -125,10 +133,14 @@ pub async fn command_handler(
     
     // 管理者コマンド（person必要）
     for cmd in admin::get_admin_commands() {
-        if lines[0].contains(cmd.pattern) {
+        if let Some(args) = parser::match_command(&event.content, &[cmd.pattern], true) {
             spawn_command(
-                (cmd.handler)(config.clone(), person.clone(), event.clone(), lines.clone()),
-                format!("{} error", cmd.name)
+                (cmd.handler)(config.clone(), person.clone(), event.clone(), args),
+                cmd.name,
+                cmd.usage,
+                config.clone(),
+                person.clone(),
+                event.clone(),
             );
             return Ok(true);
         }
@@ -0,0 +1,220 @@
+// コマンドの構文解析
+// 先頭のメンション（NIP-27の参照・bech32）を取り除き、文頭のコマンド語と引数を読み取る
+
+use regex::Regex;
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+/// 引数が足りない・形式が違うなど、使い方の誤り（コマンド側で返すと使い方を返信する）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsageError(pub String);
+
+impl UsageError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self(message.into())
+    }
+}
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for UsageError {}
+
+/// 先頭のメンション・参照（nostr:npub1...、@npub1...、nostr:note1... など）を取り除いた本文
+pub fn strip_leading_mentions(content: &str) -> &str {
+    static REFERENCE: OnceLock<Regex> = OnceLock::new();
+    let reference = REFERENCE.get_or_init(|| {
+        Regex::new(r"^(?:nostr:|@)?(?:npub|nprofile|note|nevent|naddr)1[02-9ac-hj-np-z]+").unwrap()
+    });
+
+    let mut rest = content.trim_start();
+    while let Some(m) = reference.find(rest) {
+        rest = rest[m.end()..].trim_start();
+    }
+    rest
+}
+
+/// 英数字で終わる語の直後に英数字が続いていないか（"new" と "news" を区別する）
+fn is_boundary(word_edge: Option<char>, next: Option<char>) -> bool {
+    match (word_edge, next) {
+        (Some(edge), Some(next)) if edge.is_ascii_alphanumeric() => !next.is_ascii_alphanumeric(),
+        _ => true,
+    }
+}
+
+/// 本文の先頭がコマンド語なら、その後ろ（引数部分）を返す
+///
+/// 英字は大文字小文字を区別せず、複数語のコマンド（"zap ranking"）は語の間の空白の数を問わない。
+pub fn match_start<'a>(body: &'a str, pattern: &str) -> Option<&'a str> {
+    let mut rest = body;
+    for (i, word) in pattern.split_whitespace().enumerate() {
+        if i > 0 {
+            let trimmed = rest.trim_start();
+            if trimmed.len() == rest.len() {
+                return None;
+            }
+            rest = trimmed;
+        }
+        let head = rest.get(..word.len())?;
+        if !head.eq_ignore_ascii_case(word) {
+            return None;
+        }
+        rest = &rest[word.len()..];
+        if !is_boundary(word.chars().last(), rest.chars().next()) {
+            return None;
+        }
+    }
+    Some(rest)
+}
+
+/// 本文のどこかに語句があるか（英数字の語は前後が区切られている場合のみ）
+pub fn contains_phrase(body: &str, phrase: &str) -> bool {
+    if phrase.is_empty() {
+        return false;
+    }
+    let haystack = body.to_ascii_lowercase();
+    let needle = phrase.to_ascii_lowercase();
+    haystack.match_indices(&needle).any(|(start, _)| {
+        let before = haystack[..start].chars().last();
+        let after = haystack[start + needle.len()..].chars().next();
+        is_boundary(needle.chars().next(), before) && is_boundary(needle.chars().last(), after)
+    })
+}
+
+/// コマンドの照合
+///
+/// `require_start` のコマンドは先頭のコマンド語だけを見て、その後ろを引数とする。
+/// それ以外（「占って」のような依頼の言い回し）は本文のどこにあってもよく、本文全体を引数とする。
+pub fn match_command(content: &str, patterns: &[&str], require_start: bool) -> Option<CommandArgs> {
+    let body = strip_leading_mentions(content);
+    patterns.iter().find_map(|pattern| {
+        if require_start {
+            match_start(body, pattern).map(CommandArgs::new)
+        } else if contains_phrase(body, pattern) {
+            Some(CommandArgs::new(body))
+        } else {
+            None
+        }
+    })
+}
+
+/// コマンドの引数（コマンド語より後ろの文字列）
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandArgs {
+    raw: String,
+}
+
+impl CommandArgs {
+    pub fn new(raw: impl Into<String>) -> Self {
+        Self { raw: raw.into() }
+    }
+
+    /// 前後の空白を除いた引数全体
+    pub fn text(&self) -> &str {
+        self.raw.trim()
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.text().is_empty()
+    }
+
+    /// 空白区切りの引数（"..." で囲むと空白を含められる）
+    pub fn tokens(&self) -> Result<Tokens, UsageError> {
+        let mut tokens = VecDeque::new();
+        let mut chars = self.raw.chars().peekable();
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() {
+                chars.next();
+                continue;
+            }
+            let mut token = String::new();
+            if c == '"' {
+                chars.next();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => token.push(c),
+                        None => return Err(UsageError::new("引用符 \" が閉じられていません")),
+                    }
+                }
+            } else {
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() {
+                        break;
+                    }
+                    token.push(c);
+                    chars.next();
+                }
+            }
+            tokens.push_back(token);
+        }
+        Ok(Tokens { tokens })
+    }
+
+    /// 行ごとの引数（0行目はコマンド語と同じ行の残り）。空行なら未指定の扱い
+    pub fn line(&self, index: usize, name: &str) -> Result<&str, UsageError> {
+        self.raw
+            .lines()
+            .nth(index)
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .ok_or_else(|| UsageError::new(format!("{}を指定してください", name)))
+    }
+}
+
+/// 先頭から順に読み取る引数の列
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tokens {
+    tokens: VecDeque<String>,
+}
+
+impl Tokens {
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<String> {
+        self.tokens.pop_front()
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// 必須の引数を型に変換して読み取る
+    pub fn required<T: FromStr>(&mut self, name: &str) -> Result<T, UsageError> {
+        self.optional(name)?
+            .ok_or_else(|| UsageError::new(format!("{}を指定してください", name)))
+    }
+
+    /// 省略できる引数を型に変換して読み取る
+    pub fn optional<T: FromStr>(&mut self, name: &str) -> Result<Option<T>, UsageError> {
+        match self.next() {
+            Some(token) => token
+                .parse()
+                .map(Some)
+                .map_err(|_| UsageError::new(format!("{}の形式が正しくありません: {}", name, token))),
+            None => Ok(None),
+        }
+    }
+
+    /// 残りの引数を空白でつないで読み取る
+    pub fn rest(&mut self) -> String {
+        let rest: Vec<String> = self.tokens.drain(..).collect();
+        rest.join(" ")
+    }
+
+    /// 余分な引数がないことを確認する
+    pub fn finish(self) -> Result<(), UsageError> {
+        if self.tokens.is_empty() {
+            Ok(())
+        } else {
+            let extra: Vec<String> = self.tokens.into_iter().collect();
+            Err(UsageError::new(format!("余分な引数があります: {}", extra.join(" "))))
+        }
+    }
+}
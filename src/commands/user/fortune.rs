@@ -10,7 +10,7 @@ pub async fn fortune(config: config::AppConfig, person: db::Person, event: Event
         "今日のわたしの運勢を占って。結果はランダムで決めて、その結果に従って占いの内容を運の良さは★マークを５段階でラッキーアイテム、ラッキーカラーとかも教えて。\n{}",
         event.content
     );
-    let reply = gpt::get_reply(&person.pubkey, &person.prompt, &user_text, true, None, &config).await?;
+    let reply = gpt::get_reply(&person, &person.prompt, &user_text, true, None, &config).await?;
     util::reply_to(&config, event, person, &reply).await?;
     Ok(())
 }
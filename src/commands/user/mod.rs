@@ -1,14 +1,17 @@
 use crate::config;
 use crate::database as db;
 use nostr_sdk::prelude::*;
-use std::future::Future;
+use super::CommandFuture;
+use super::parser::CommandArgs;
 
 mod fortune;
 mod zap_ranking;
 mod update_follower;
 mod search_posts;
+mod search_natural;
 mod help;
 mod search_web;
+mod remind;
 
 // ユーザーコマンド定義
 pub struct UserCommand {
@@ -16,8 +19,9 @@ pub struct UserCommand {
     pub patterns: Vec<&'static str>,
     pub description: &'static str,
     pub detailed_help: Option<&'static str>,  // 詳細ヘルプ
-    pub require_start: bool,  // コマンドが文頭にあることを要求
-    pub handler: fn(config::AppConfig, db::Person, Event) -> std::pin::Pin<Box<dyn Future<Output = Result<()>> + Send>>,
+    pub usage: &'static str,  // 使い方（引数の誤りを返信するときに使う）
+    pub require_start: bool,  // コマンド語が文頭（メンションの後）にあることを要求。falseなら文中の言い回しで反応
+    pub handler: fn(config::AppConfig, db::Person, Event, CommandArgs) -> CommandFuture,
 }
 
 // ユーザーコマンドテーブル
@@ -28,24 +32,27 @@ pub fn get_user_commands() -> Vec<UserCommand> {
             patterns: vec!["占って"],
             description: "今日の運勢を占います",
             detailed_help: Some("占い師があなたの今日の運勢を占います。\n運の良さ、ラッキーアイテム、ラッキーカラーなどを教えてくれます。"),
+            usage: "占って",
             require_start: false,
-            handler: |c, p, e| Box::pin(fortune::fortune(c, p, e)),
+            handler: |c, p, e, _| Box::pin(fortune::fortune(c, p, e)),
         },
         UserCommand {
             name: "zap_ranking",
             patterns: vec!["zap ranking"],
             description: "過去1年分のzapランキングを表示します",
             detailed_help: Some("過去1年間に受け取ったzapの合計金額ランキングを表示します。"),
-            require_start: false,
-            handler: |c, p, e| Box::pin(zap_ranking::zap_ranking(c, p, e)),
+            usage: "zap ranking",
+            require_start: true,
+            handler: |c, p, e, _| Box::pin(zap_ranking::zap_ranking(c, p, e)),
         },
         UserCommand {
             name: "update_follower",
             patterns: vec!["update follower", "フォロワー更新"],
             description: "自分のフォロワーキャッシュを更新します",
             detailed_help: Some("あなたのフォロワー状態のキャッシュを強制的に更新します。\nフォローしたばかりなのに反応がない場合などに使用してください。"),
-            require_start: false,
-            handler: |c, p, e| Box::pin(update_follower::update_my_follower_cache(c, p, e)),
+            usage: "update follower",
+            require_start: true,
+            handler: |c, p, e, _| Box::pin(update_follower::update_my_follower_cache(c, p, e)),
         },
         UserCommand {
             name: "search",
@@ -76,6 +83,8 @@ pub fn get_user_commands() -> Vec<UserCommand> {
 
 ※ ~ の代わりに 〜 も使用可能
 ※ 期間と投稿者は順不同
+※ Botが受信済みの投稿から探し、足りない場合は検索リレーにも問い合わせます
+※ 「去年の8月くらいのNostrの話題なんだっけ？」のように文章で聞くと、条件を読み取って答えます
 
 【例】
 検索 Nostr
@@ -87,24 +96,36 @@ pub fn get_user_commands() -> Vec<UserCommand> {
 検索 Nostr 7d @npub1...
 検索 Nostr 2024-10-01 14:30〜2024-10-31 18:00 @npub1..."
             ),
+            usage: "検索 キーワード [期間] [@投稿者]",
             require_start: true,  // 文頭必須
-            handler: |c, p, e| Box::pin(search_posts::search_posts(c, p, e)),
+            handler: |c, p, e, a| Box::pin(search_posts::search_posts(c, p, e, a)),
+        },
+        UserCommand {
+            name: "remind",
+            patterns: vec!["リマインド", "remind"],
+            description: "指定した時刻にリマインドします",
+            detailed_help: Some("指定した時刻に、依頼した投稿へのリプライでお知らせします。\n\n【使い方】\n[時刻]に[内容]ってリマインドして\nremind me [時刻] to [内容]\nリマインド一覧: 予定しているリマインドを表示\nリマインド取消 番号: リマインドを取り消す\n\n【時刻の例】\n30分後 / 1時間半後 / 明日9時 / 午後3時半 / 10月20日 18:00 / in 2h / tomorrow at 9am\n\n【例】\n30分後に会議ってリマインドして\nremind me in 2h to stretch"),
+            usage: "[時刻]に[内容]ってリマインドして / リマインド一覧 / リマインド取消 番号",
+            require_start: false,
+            handler: |c, p, e, a| Box::pin(remind::remind(c, p, e, a)),
         },
         UserCommand {
             name: "search_web",
             patterns: vec!["調べて"],
-            description: "Web検索を行います（Gemini CLI使用）",
-            detailed_help: Some("Gemini CLIを使ってWeb検索を行い、結果を要約して返答します。\n\n【使い方】\n調べて [検索したい内容]\n\n【例】\n調べて Rustの最新バージョン\n調べて 今日の天気"),
+            description: "Web検索を行い、出典付きで答えます",
+            detailed_help: Some("設定された検索プロバイダー（Gemini CLI / SearxNG）でWeb検索を行い、結果を出典付きで要約して返答します。\n\n【使い方】\n調べて [検索したい内容]\n\n【例】\n調べて Rustの最新バージョン\n調べて 今日の天気"),
+            usage: "調べて [検索したい内容]",
             require_start: false,
-            handler: |c, p, e| Box::pin(search_web::search_web(c, p, e)),
+            handler: |c, p, e, a| Box::pin(search_web::search_web(c, p, e, a)),
         },
         UserCommand {
             name: "help",
             patterns: vec!["help", "ヘルプ"],
             description: "利用可能なコマンド一覧を表示します",
             detailed_help: Some("利用可能なコマンドの一覧を表示します。\n\n【使い方】\nhelp: 全コマンド一覧\nhelp コマンド名: 特定コマンドの詳細ヘルプ\n\n【例】\nhelp\nhelp search"),
-            require_start: false,
-            handler: |c, p, e| Box::pin(help::show_help(c, p, e)),
+            usage: "help [コマンド名]",
+            require_start: true,
+            handler: |c, p, e, a| Box::pin(help::show_help(c, p, e, a)),
         },
     ]
 }
@@ -0,0 +1,73 @@
+use crate::config;
+use crate::database as db;
+use crate::scheduler::reminder::{self, format_remind_at};
+use crate::util;
+use crate::commands::parser::CommandArgs;
+use chrono::Utc;
+use nostr_sdk::prelude::*;
+use regex::Regex;
+use tracing::info;
+
+/// 使い方（時刻を読み取れなかったとき）
+const USAGE: &str = "いつリマインドすればいいか分からなかったよ。\n\
+例: 30分後に会議ってリマインドして\n\
+例: 明日9時に薬を飲むってリマインドして\n\
+例: remind me in 2h to stretch\n\
+一覧: リマインド一覧\n\
+取り消し: リマインド取消 番号";
+
+// リマインドコマンド（登録・一覧・取り消し）
+pub async fn remind(config: config::AppConfig, person: db::Person, event: Event, args: CommandArgs) -> Result<()> {
+    // メンションを除いた本文
+    let text = args.text().to_string();
+    let user_pubkey = event.pubkey.to_hex();
+
+    let cancel = Regex::new(r"(?i)(?:キャンセル|取り?消し?|cancel)\s*#?(\d+)|#?(\d+)\s*(?:を|の)?\s*(?:キャンセル|取り?消)").unwrap();
+    let list = Regex::new(r"(?i)一覧|remind\s+list|reminders").unwrap();
+
+    let reply = if let Some(caps) = cancel.captures(&text) {
+        let id: i64 = caps.get(1).or(caps.get(2)).map_or("0", |m| m.as_str()).parse().unwrap_or(0);
+        let conn = db::connect()?;
+        if db::cancel_reminder(&conn, id, &person.pubkey, &user_pubkey)? {
+            info!("[Reminder] #{} を取り消し", id);
+            format!("#{} のリマインドを取り消したよ", id)
+        } else {
+            format!("#{} のリマインドは見つからなかったよ", id)
+        }
+    } else if list.is_match(&text) {
+        let conn = db::connect()?;
+        let reminders = db::get_pending_reminders(&conn, &person.pubkey, &user_pubkey)?;
+        if reminders.is_empty() {
+            "予定しているリマインドはないよ".to_string()
+        } else {
+            let lines: Vec<String> = reminders
+                .iter()
+                .map(|r| format!("#{} {} {}", r.id, format_remind_at(r.remind_at), r.message))
+                .collect();
+            format!("【リマインド一覧】\n{}", lines.join("\n"))
+        }
+    } else {
+        let now = Utc::now().timestamp();
+        match reminder::parse_reminder(&text, now) {
+            None => USAGE.to_string(),
+            Some(parsed) if parsed.remind_at <= now => "その時刻はもう過ぎているよ".to_string(),
+            Some(parsed) => {
+                let conn = db::connect()?;
+                let id = db::insert_reminder(
+                    &conn,
+                    &person.pubkey,
+                    &user_pubkey,
+                    &event.id.to_hex(),
+                    &event.as_json(),
+                    &parsed.message,
+                    parsed.remind_at,
+                )?;
+                info!("[Reminder] #{} を登録: {} {}", id, parsed.remind_at, parsed.message);
+                format!("⏰ {} にリマインドするね（#{}）", format_remind_at(parsed.remind_at), id)
+            }
+        }
+    };
+
+    util::reply_to(&config, event, person, &reply).await?;
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use crate::config;
+use crate::database as db;
+use crate::gpt;
+use crate::llm::tools::{ToolContext, ToolRegistry};
+use crate::util;
+use nostr_sdk::prelude::*;
+use chrono::Utc;
+use tracing::{error, info};
+
+/// 検索ツールの名前
+const SEARCH_TOOL_NAME: &str = "search_events";
+
+/// 回答にリンクがないときに付ける投稿の数
+const FALLBACK_LINK_COUNT: usize = 3;
+
+// 自然文での投稿検索（LLMが検索条件を決め、結果をBotらしく答える）
+pub async fn natural_search(config: config::AppConfig, person: db::Person, event: Event, question: String) -> Result<()> {
+    info!("[Search] 自然文検索: {}", question);
+
+    let now = Utc::now().with_timezone(&util::jst());
+    let weekdays = ["日", "月", "火", "水", "木", "金", "土"];
+    let weekday = weekdays[now.format("%w").to_string().parse::<usize>().unwrap_or(0)];
+    let answer_length = config.get_i32_setting("search_answer_length");
+    let prompt = format!(
+        "{}\n\n現在日時: {} ({}) {} JST\n\n\
+        あなたはNostrの投稿を検索して質問に答えます。search_eventsツールで投稿を検索し、見つかった投稿をもとに{}文字程度であなたらしく答えてください。\n\
+        ・「去年の8月」「先月」などの相対的な日付は、現在日時をもとに具体的な日付（YYYY-MM-DD）にしてください\n\
+        ・キーワードは投稿本文に含まれそうな短い語にしてください\n\
+        ・紹介する投稿には必ず nostr:note1... のリンクを付けてください\n\
+        ・見つからなかった場合は、見つからなかったことを伝えてください\n\
+        ・返答のみを出力してください",
+        person.prompt,
+        now.format("%Y-%m-%d"),
+        weekday,
+        now.format("%H:%M"),
+        answer_length
+    );
+
+    let tools = ToolRegistry::builtin().only(&[SEARCH_TOOL_NAME]);
+    let ctx = ToolContext {
+        config: &config,
+        bot: &person,
+        event: &event,
+    };
+    let max_rounds = config.get_usize_setting("tool_max_rounds");
+    // 最初の呼び出しは検索条件の抽出、検索結果を受け取った後は最終回答として記録する
+    let categories = ("search_keyword_extraction", "search_final_reply");
+    let outcome = gpt::call_gpt_with_tool_loop(&prompt, &question, &tools, &ctx, categories, max_rounds)
+        .await
+        .map_err(|e| e.to_string());
+    let outcome = match outcome {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            error!("[Search] LLM呼び出しエラー: {}", e);
+            // 内部のエラー内容は公開の返信に含めない
+            util::reply_to(&config, event, person, "検索に失敗しました。しばらくしてからもう一度試してください。").await?;
+            return Ok(());
+        }
+    };
+
+    let mut answer = match outcome.answer.trim() {
+        "" => "該当する投稿が見つかりませんでした。".to_string(),
+        answer => answer.to_string(),
+    };
+
+    // リンクを付け忘れた場合は見つかった投稿のリンクを添える
+    if !answer.contains("nostr:note1") {
+        let mut links: Vec<String> = Vec::new();
+        for result in outcome.results.iter().filter(|result| !result.is_error) {
+            let found: serde_json::Value = serde_json::from_str(&result.content).unwrap_or_default();
+            let notes = found["results"].as_array().into_iter().flatten().filter_map(|hit| hit["note"].as_str());
+            for note in notes {
+                if !links.iter().any(|link| link == note) {
+                    links.push(note.to_string());
+                }
+            }
+        }
+        if !links.is_empty() {
+            links.truncate(FALLBACK_LINK_COUNT);
+            answer = format!("{}\n\n{}", answer, links.join("\n"));
+        }
+    }
+
+    util::reply_to(&config, event, person, &answer).await?;
+    Ok(())
+}
@@ -2,37 +2,34 @@ use crate::config;
 use crate::database as db;
 use crate::gpt;
 use crate::util;
+use crate::web_search;
+use crate::commands::parser::CommandArgs;
 use nostr_sdk::prelude::*;
+use tracing::{error, info};
 
-// Web検索コマンド（Gemini CLI使用）
-pub async fn search_web(config: config::AppConfig, person: db::Person, event: Event) -> Result<()> {
-    let content_clone = event.content.clone();
-    
-    // メンション部分を除去（nostr:npub1..., nostr:note1..., @npub1... など）
-    let cleaned_content = regex::Regex::new(r"(nostr:npub1\w+|nostr:note1\w+|@npub1\w+|npub1\w+)")
-        .unwrap()
-        .replace_all(&content_clone, "")
-        .trim()
-        .to_string();
+// Web検索コマンド（プロバイダーはweb_search設定で切り替え）
+pub async fn search_web(config: config::AppConfig, person: db::Person, event: Event, args: CommandArgs) -> Result<()> {
+    // メンションを除いた本文
+    let cleaned_content = args.text().to_string();
     
     // 一次回答を生成（検索前に投稿）
     let user_input = format!(
         "# 質問内容\n{}\n\n# 指示\n上記の質問内容について「これから調べるので待ってて欲しい」という文章を50文字程度であなたらしく作成してください。あくまでこれから調べることに対する一次回答で、質問の回答ではないことに注意。返答のみを出力してください。",
         cleaned_content
     );
-    let initial_reply = match gpt::call_gpt_with_category(&person.prompt, &user_input, &person.pubkey, "search_initial_reply", &config).await {
+    let initial_reply = match gpt::call_gpt_with_category(&person.prompt, &user_input, &person, "search_initial_reply", &config).await {
         Ok(reply) => reply,
         Err(e) => {
-            eprintln!("Failed to generate initial reply: {}", e);
+            error!("Failed to generate initial reply: {}", e);
             "調べてみるね！".to_string()
         }
     };
     
     // 一次回答を投稿
-    println!("Initial reply generated: {}", initial_reply);
+    info!("Initial reply generated: {}", initial_reply);
     let initial_event = match util::reply_to(&config, event.clone(), person.clone(), &initial_reply).await {
         Ok(event) => {
-            println!("✓ Initial reply posted successfully: {:?}", event.id);
+            info!("✓ Initial reply posted successfully: {:?}", event.id);
             
             // 会話履歴に記録
             let _ = util::log_event_to_conversation(&event, &person.pubkey, true);
@@ -40,14 +37,14 @@ pub async fn search_web(config: config::AppConfig, person: db::Person, event: Ev
             Some(event)
         },
         Err(e) => {
-            eprintln!("✗ Failed to post initial reply: {}", e);
-            eprintln!("Error details: {:?}", e);
+            error!("✗ Failed to post initial reply: {}", e);
+            error!("Error details: {:?}", e);
             None
         }
     };
     
     if initial_event.is_none() {
-        eprintln!("WARNING: Initial reply was not posted!");
+        error!("WARNING: Initial reply was not posted!");
     }
     
     // botの名前を取得
@@ -78,10 +75,10 @@ pub async fn search_web(config: config::AppConfig, person: db::Person, event: Ev
         ・検索キーワードのみを返し、説明や前置きは不要".to_string()
     };
     
-    let search_keyword = match gpt::call_gpt_with_category(&extract_prompt, &cleaned_content, &person.pubkey, "search_keyword_extraction", &config).await {
+    let search_keyword = match gpt::call_gpt_with_category(&extract_prompt, &cleaned_content, &person, "search_keyword_extraction", &config).await {
         Ok(keyword) => keyword.trim().to_string(),
         Err(e) => {
-            eprintln!("Failed to extract search keyword: {}", e);
+            error!("Failed to extract search keyword: {}", e);
             // フォールバック: 「調べて」以降のテキストを使用（メンション除去済み）
             if let Some(pos) = cleaned_content.find("調べて") {
                 let after = &cleaned_content[pos + "調べて".len()..];
@@ -97,34 +94,44 @@ pub async fn search_web(config: config::AppConfig, person: db::Person, event: Ev
         return Ok(());
     }
     
-    println!("Gemini search query: {}", search_keyword);
-    
-    let gemini_timeout = config.get_i32_setting("gemini_search_timeout");
+    info!("[WebSearch] 検索クエリ: {}", search_keyword);
     
-    match util::gemini_search(&search_keyword, gemini_timeout).await {
-        Ok(search_result) => {
-            // 検索結果を一次回答を踏まえて要約
+    match web_search::search(&config, &search_keyword).await {
+        Ok(results) if results.is_empty() => {
+            let no_result_reply = "検索結果が見つかりませんでした";
+            if let Some(initial_evt) = initial_event {
+                let reply_event = util::reply_to(&config, initial_evt, person.clone(), no_result_reply).await?;
+                let _ = util::log_event_to_conversation(&reply_event, &person.pubkey, true);
+            } else {
+                util::reply_to(&config, event, person, no_result_reply).await?;
+            }
+        }
+        Ok(results) => {
+            // 検索結果を一次回答を踏まえて要約（番号で出典を引用させる）
             let search_answer_length = config.get_i32_setting("search_answer_length");
             let summary_prompt = format!(
-                "{}\n\nユーザーからの質問:「{}」\nあなたの一次回答:「{}」\n\n以下の検索結果を読んで、一次回答に続く形で{}文字程度であなたらしく要約して返答してください。返答のみを出力してください。説明や前置きは不要です。",
+                "{}\n\nユーザーからの質問:「{}」\nあなたの一次回答:「{}」\n\n以下の番号付きの検索結果を読んで、一次回答に続く形で{}文字程度であなたらしく要約して返答してください。根拠にした検索結果は[1]のように番号で示してください。返答のみを出力してください。説明や前置きは不要です。",
                 person.prompt,
                 cleaned_content,
                 initial_reply,
                 search_answer_length
             );
-            let final_reply = match gpt::call_gpt_with_category(&summary_prompt, &search_result, &person.pubkey, "search_final_reply", &config).await {
+            let search_result = web_search::format_results_for_prompt(&results);
+            let answer = match gpt::call_gpt_with_category(&summary_prompt, &search_result, &person, "search_final_reply", &config).await {
                 Ok(summary) => summary,
                 Err(e) => {
-                    eprintln!("Failed to summarize search result: {}", e);
-                    // フォールバック: 検索結果をそのまま返す（文字数制限）
-                    let max_len = search_answer_length as usize;
-                    if search_result.len() > max_len {
-                        format!("{}...", &search_result[..max_len])
+                    error!("Failed to summarize search result: {}", e);
+                    // フォールバック: 最初の検索結果の要約をそのまま返す（文字数制限）
+                    let max_len = search_answer_length.max(0) as usize;
+                    let snippet = &results[0].snippet;
+                    if snippet.chars().count() > max_len {
+                        format!("{}...", snippet.chars().take(max_len).collect::<String>())
                     } else {
-                        search_result
+                        snippet.clone()
                     }
                 }
             };
+            let final_reply = web_search::append_sources(&answer, &results);
             
             // 最終回答を一次回答へのリプライとして投稿
             if let Some(initial_evt) = initial_event {
@@ -140,7 +147,7 @@ pub async fn search_web(config: config::AppConfig, person: db::Person, event: Ev
             }
         }
         Err(e) => {
-            eprintln!("Gemini search error: {}", e);
+            error!("[WebSearch] 検索エラー: {}", e);
             let error_reply = format!("検索に失敗しました: {}", e);
             // エラーも一次回答へのリプライとして投稿
             if let Some(initial_evt) = initial_event {
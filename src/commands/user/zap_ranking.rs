@@ -5,19 +5,19 @@ use crate::util;
 use nostr_sdk::prelude::*;
 use std::collections::HashMap;
 use serde_json::Value;
+use tracing::info;
 
 // zapランキングコマンド
 pub async fn zap_ranking(config: config::AppConfig, person: db::Person, event: Event) -> Result<()> {
-    println!("zap_ranking");
+    info!("zap_ranking");
     let pubkey = &event.pubkey.to_string();
-    let text = &format!("「現在から過去1年分のzapを集計します。しばらくお待ち下さい。」をあなたらしく言い換えてください。元の文章に含まれる内容が欠落しないようにしてください。「」内に入る文字だけを返信してください。カギカッコは不要です。");
-    let reply = gpt::get_reply(&person.pubkey, &person.prompt, text, true, None, &config).await.unwrap();
-    let root_event: Event;
-    if reply.len() > 0 {
-        root_event = util::reply_to(&config, event.clone(), person.clone(), &reply).await?;
+    let text = &"「現在から過去1年分のzapを集計します。しばらくお待ち下さい。」をあなたらしく言い換えてください。元の文章に含まれる内容が欠落しないようにしてください。「」内に入る文字だけを返信してください。カギカッコは不要です。".to_string();
+    let reply = gpt::get_reply(&person, &person.prompt, text, true, None, &config).await.unwrap();
+    let root_event: Event = if !reply.is_empty() {
+        util::reply_to(&config, event.clone(), person.clone(), &reply).await?
     } else {
         return Ok(());
-    }
+    };
     
     let receive_zap_events = util::get_zap_received(pubkey).await?;
     let mut all_zap: u64 = 0;
@@ -33,7 +33,7 @@ pub async fn zap_ranking(config: config::AppConfig, person: db::Person, event: E
                         bolt11_string = bolt11.to_string();
                     }
                     TagStandard::Description(description) => {
-                        if let Ok(content_json) = serde_json::from_str::<Value>(&description) {
+                        if let Ok(content_json) = serde_json::from_str::<Value>(description) {
                             if let Some(pk) = content_json.get("pubkey").and_then(|k| k.as_str()) {
                                 // descriptionの中のhex pubkeyをnpub形式に変換
                                 if let Ok(key) = PublicKey::parse(pk) {
@@ -58,12 +58,12 @@ pub async fn zap_ranking(config: config::AppConfig, person: db::Person, event: E
         }
     }
     
-    println!("Total raw amount: {:?}", all_zap);
+    info!("Total raw amount: {:?}", all_zap);
     
     // HashMapからVecへ変換
     let mut zap_vec: Vec<(String, (u64, u64))> = zap_by_pubkey.into_iter().collect();
     // zap合計で降順ソート
-    zap_vec.sort_by(|a, b| b.1.0.cmp(&a.1.0));
+    zap_vec.sort_by_key(|b| std::cmp::Reverse(b.1.0));
     
     let sender_ranking = zap_vec
         .iter()
@@ -75,7 +75,7 @@ pub async fn zap_ranking(config: config::AppConfig, person: db::Person, event: E
         .collect::<Vec<_>>()
         .join("\n");
     
-    println!("Top 10 pubkeys by zap:");
+    info!("Top 10 pubkeys by zap:");
 
     util::reply_to(
         &config,
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use crate::database as db;
+use std::collections::HashMap;
 use std::fs::File;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,6 +17,37 @@ pub struct BotConfig {
     pub conversation_limit_count: usize,
     pub conversation_limit_minutes: i64,
     pub blacklist: Vec<String>,
+    /// 起動時に取りこぼしたメンションを遡る最大秒数（0で無効）
+    #[serde(default = "default_catch_up_max_lookback")]
+    pub catch_up_max_lookback: i64,
+    /// この金額（sats）以上のZapにお礼のリプライを送る（0で無効）
+    #[serde(default)]
+    pub zap_thanks_min_sats: i64,
+    /// キューのイベントを並行して処理するワーカー数
+    #[serde(default = "default_event_workers")]
+    pub event_workers: usize,
+    /// 過去の会話・要約を関連するものとして扱うコサイン類似度の下限
+    #[serde(default = "default_rag_similarity_threshold")]
+    pub rag_similarity_threshold: f64,
+    /// 1回の返信でツールを呼び出せる最大回数
+    #[serde(default = "default_tool_max_rounds")]
+    pub tool_max_rounds: usize,
+}
+
+fn default_catch_up_max_lookback() -> i64 {
+    6 * 60 * 60
+}
+
+fn default_event_workers() -> usize {
+    4
+}
+
+fn default_rag_similarity_threshold() -> f64 {
+    0.5
+}
+
+fn default_tool_max_rounds() -> usize {
+    3
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,6 +73,291 @@ pub struct RelayConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DashboardConfig {
     pub port: u16,
+    /// 待ち受けるアドレス
+    #[serde(default = "default_dashboard_bind")]
+    pub bind: String,
+    #[serde(default)]
+    pub auth: DashboardAuthConfig,
+}
+
+fn default_dashboard_bind() -> String {
+    "127.0.0.1".to_string()
+}
+
+/// ダッシュボードのログイン設定
+///
+/// admin_pubkeysはNIP-98で管理者として、viewer_pubkeysは閲覧のみでログインできる。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DashboardAuthConfig {
+    /// falseにすると誰でも管理者として操作できる
+    pub enabled: bool,
+    /// 管理者としてログインできる固定トークン
+    pub admin_token: Option<String>,
+    /// 閲覧のみでログインできる固定トークン
+    pub viewer_token: Option<String>,
+    /// NIP-98で閲覧のみログインできるpubkey（hex）
+    pub viewer_pubkeys: Vec<String>,
+    /// セッションの有効期間（秒）
+    pub session_ttl: i64,
+    /// ダッシュボードを公開しているURL（ログインを有効にする場合は必須。NIP-98のuタグの照合、httpsならCookieのSecure属性に使う）
+    pub public_url: Option<String>,
+}
+
+impl Default for DashboardAuthConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            admin_token: None,
+            viewer_token: None,
+            viewer_pubkeys: vec![],
+            session_ttl: 7 * 24 * 60 * 60,
+            public_url: None,
+        }
+    }
+}
+
+/// LLMプロバイダーの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LlmProviderKind {
+    #[serde(rename = "openai")]
+    OpenAi,
+    #[serde(rename = "openai_compatible")]
+    OpenAiCompatible,
+    #[serde(rename = "scripted")]
+    Scripted,
+}
+
+/// スクリプト応答の1ルール（テスト用）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptedRule {
+    pub contains: Option<String>,   // ユーザー入力またはプロンプトに含まれる文字列
+    pub json_mode: Option<bool>,    // JSON modeの呼び出しにのみ適用する場合はtrue
+    pub response: String,
+    #[serde(default)]
+    pub tool_call: Option<ScriptedToolCall>,  // ツール付きチャットでは応答の代わりにこの呼び出しを返す
+}
+
+/// スクリプト応答のツール呼び出し（テスト用）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptedToolCall {
+    pub name: String,
+    pub arguments: String,  // 引数（JSON文字列）
+    #[serde(default)]
+    pub repeat: bool,       // ツールの結果を受け取った後も呼び出し続ける（回数上限の確認用）
+}
+
+/// スクリプトプロバイダーの設定
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScriptedConfig {
+    pub default_reply: String,
+    pub default_json: String,
+    pub rules: Vec<ScriptedRule>,
+}
+
+/// Bot個別のLLM設定（未指定の項目は共通設定を使用）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LlmBotConfig {
+    pub provider: Option<LlmProviderKind>,
+    pub model: Option<String>,
+    pub base_url: Option<String>,
+    pub api_key_env: Option<String>,
+    pub temperature: Option<f64>,
+    pub max_tokens: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LlmConfig {
+    pub provider: LlmProviderKind,
+    pub model: String,
+    pub base_url: Option<String>,
+    pub api_key_env: Option<String>,
+    pub temperature: Option<f64>,
+    pub max_tokens: Option<i64>,
+    pub scripted: ScriptedConfig,
+    pub bots: HashMap<String, LlmBotConfig>,  // bot pubkey -> 個別設定
+}
+
+impl Default for LlmConfig {
+    fn default() -> Self {
+        Self {
+            provider: LlmProviderKind::OpenAi,
+            model: "gpt-5-nano".to_string(),
+            base_url: None,
+            api_key_env: Some("OPEN_AI_API_KEY".to_string()),
+            temperature: None,
+            max_tokens: None,
+            scripted: ScriptedConfig::default(),
+            bots: HashMap::new(),
+        }
+    }
+}
+
+/// Botごとに解決済みのLLM設定
+#[derive(Debug, Clone)]
+pub struct LlmSettings {
+    pub provider: LlmProviderKind,
+    pub model: String,
+    pub base_url: Option<String>,
+    pub api_key_env: Option<String>,
+    pub temperature: Option<f64>,
+    pub max_tokens: Option<i64>,
+    pub scripted: ScriptedConfig,
+}
+
+impl LlmSettings {
+    /// Personsテーブルに保存されたBot個別設定を適用
+    pub fn apply_person_overrides(&mut self, overrides: &db::PersonOverrides) {
+        if let Some(model) = &overrides.model {
+            self.model = model.clone();
+        }
+        if overrides.temperature.is_some() {
+            self.temperature = overrides.temperature;
+        }
+        if overrides.max_tokens.is_some() {
+            self.max_tokens = overrides.max_tokens;
+        }
+    }
+}
+
+impl LlmConfig {
+    /// 共通設定にBot個別設定を重ねた設定を返す
+    pub fn for_bot(&self, bot_pubkey: &str) -> LlmSettings {
+        let bot = self.bots.get(bot_pubkey).cloned().unwrap_or_default();
+        LlmSettings {
+            provider: bot.provider.unwrap_or(self.provider),
+            model: bot.model.unwrap_or_else(|| self.model.clone()),
+            base_url: bot.base_url.or_else(|| self.base_url.clone()),
+            api_key_env: bot.api_key_env.or_else(|| self.api_key_env.clone()),
+            temperature: bot.temperature.or(self.temperature),
+            max_tokens: bot.max_tokens.or(self.max_tokens),
+            scripted: self.scripted.clone(),
+        }
+    }
+}
+
+/// ログの出力形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    Text,
+    /// 1行1オブジェクトのJSON（ログ収集基盤向け）
+    Json,
+}
+
+/// ログの設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// 出力するレベル（RUST_LOGと同じ書式。環境変数RUST_LOGがあればそちらを優先）
+    pub level: String,
+    pub format: LogFormat,
+    /// error_logテーブルに記録するレベル（"warn"ならWARNとERROR）
+    pub persist_level: String,
+    /// error_logテーブルに残す件数
+    pub max_persisted: usize,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            format: LogFormat::Text,
+            persist_level: "warn".to_string(),
+            max_persisted: 1000,
+        }
+    }
+}
+
+/// 埋め込み（ベクトル化）バックエンドの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EmbeddingProviderKind {
+    /// ベクトル化しない（類似検索を行わず時系列のみ）
+    #[serde(rename = "none")]
+    Disabled,
+    #[serde(rename = "openai")]
+    OpenAi,
+    #[serde(rename = "openai_compatible")]
+    OpenAiCompatible,
+    /// CPUで動くローカルモデル（local-embedding featureが必要）
+    #[serde(rename = "local")]
+    Local,
+    #[serde(rename = "scripted")]
+    Scripted,
+}
+
+/// 埋め込みバックエンドの設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmbeddingConfig {
+    pub provider: EmbeddingProviderKind,
+    pub model: String,
+    pub base_url: Option<String>,
+    pub api_key_env: Option<String>,
+    /// ローカルモデルのダウンロード先
+    pub cache_dir: Option<String>,
+    /// バックグラウンドで1度にベクトル化する件数
+    pub batch_size: usize,
+    /// バックグラウンドのベクトル化を行う間隔（秒）
+    pub interval_secs: u64,
+}
+
+impl Default for EmbeddingConfig {
+    fn default() -> Self {
+        Self {
+            provider: EmbeddingProviderKind::Disabled,
+            model: "text-embedding-3-small".to_string(),
+            base_url: None,
+            api_key_env: Some("OPEN_AI_API_KEY".to_string()),
+            cache_dir: None,
+            batch_size: 32,
+            interval_secs: 30,
+        }
+    }
+}
+
+/// Web検索バックエンドの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WebSearchProviderKind {
+    /// Gemini CLI（geminiコマンド）
+    #[serde(rename = "gemini_cli")]
+    GeminiCli,
+    /// SearxNG互換のHTTP API（/search?format=json）
+    #[serde(rename = "searxng")]
+    Searxng,
+    /// fixtureのJSONから結果を返す（テスト用）
+    #[serde(rename = "fake")]
+    Fake,
+}
+
+/// Web検索バックエンドの設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebSearchConfig {
+    pub provider: WebSearchProviderKind,
+    /// Gemini CLIのコマンド
+    pub command: String,
+    /// SearxNGのURL
+    pub base_url: Option<String>,
+    /// fakeの検索結果（JSONファイル）
+    pub fixture: Option<String>,
+    /// 要約に使う検索結果の最大件数
+    pub max_results: usize,
+}
+
+impl Default for WebSearchConfig {
+    fn default() -> Self {
+        Self {
+            provider: WebSearchProviderKind::GeminiCli,
+            command: "gemini".to_string(),
+            base_url: None,
+            fixture: None,
+            max_results: 5,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,6 +366,14 @@ pub struct AppConfig {
     pub bot: BotConfig,
     pub gpt: GptConfig,
     pub dashboard: DashboardConfig,
+    #[serde(default)]
+    pub llm: LlmConfig,
+    #[serde(default)]
+    pub embedding: EmbeddingConfig,
+    #[serde(default)]
+    pub web_search: WebSearchConfig,
+    #[serde(default)]
+    pub logging: LoggingConfig,
 }
 
 /// 設定値取得のユーティリティ関数群
@@ -120,6 +445,8 @@ impl AppConfig {
             "reaction_freq" => self.bot.reaction_freq,
             "follower_cache_ttl" => self.bot.follower_cache_ttl,
             "conversation_limit_minutes" => self.bot.conversation_limit_minutes,
+            "catch_up_max_lookback" => self.bot.catch_up_max_lookback,
+            "zap_thanks_min_sats" => self.bot.zap_thanks_min_sats,
             _ => 0,
         }
     }
@@ -140,6 +467,8 @@ impl AppConfig {
         match key {
             "timeline_size" => self.bot.timeline_size,
             "conversation_limit_count" => self.bot.conversation_limit_count,
+            "event_workers" => self.bot.event_workers,
+            "tool_max_rounds" => self.bot.tool_max_rounds,
             "recent_context_count" => self.gpt.recent_context_count,
             "summary_threshold" => self.gpt.summary_threshold,
             "max_summary_tokens" => self.gpt.max_summary_tokens,
@@ -157,6 +486,29 @@ impl AppConfig {
             _ => 0,
         }
     }
+
+    /// f64型の設定値を取得（DB優先、なければconfig値）
+    pub fn get_f64_setting(&self, key: &str) -> f64 {
+        match db::connect() {
+            Ok(conn) => {
+                match db::get_system_setting(&conn, key) {
+                    Ok(Some(value)) => {
+                        value.parse::<f64>().unwrap_or_else(|_| self.get_default_f64(key))
+                    }
+                    _ => self.get_default_f64(key),
+                }
+            }
+            Err(_) => self.get_default_f64(key),
+        }
+    }
+
+    /// デフォルト値を取得（f64）
+    fn get_default_f64(&self, key: &str) -> f64 {
+        match key {
+            "rag_similarity_threshold" => self.bot.rag_similarity_threshold,
+            _ => 0.0,
+        }
+    }
     
     /// ブラックリストを取得（DB優先、なければconfig値）
     pub fn get_blacklist(&self) -> Vec<String> {
@@ -178,9 +530,14 @@ impl AppConfig {
     }
 }
 
+/// config.ymlのパス（環境変数NOSTRCHAN_CONFIG_PATHで上書き可能）
+pub fn config_path() -> String {
+    std::env::var("NOSTRCHAN_CONFIG_PATH").unwrap_or_else(|_| "../config.yml".to_string())
+}
+
 /// config.ymlを読み込んでAppConfigを返すユーティリティ関数
 pub fn load_config() -> Result<AppConfig, Box<dyn std::error::Error>> {
-    let file = File::open("../config.yml")?;
+    let file = File::open(config_path())?;
     let config: AppConfig = serde_yaml::from_reader(file)?;
     Ok(config)
 }
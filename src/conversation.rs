@@ -1,12 +1,23 @@
 use crate::config::AppConfig;
 use crate::database as db;
+use crate::embedding::{self, SimilarityQuery};
 use crate::gpt;
 use chrono::{Local, TimeZone};
 use rusqlite::Connection;
 use tiktoken_rs::o200k_base;
+use tracing::info;
 
 const SUMMARY_MAX_LENGTH: usize = 1000;
 
+/// 類似度で選ぶときに見る候補の倍率（タイムライン件数の何倍まで遡るか）
+const SIMILARITY_CANDIDATE_FACTOR: usize = 3;
+
+/// 類似度で選ぶ件数の割合（残りは直近の発言で埋める）
+const SIMILAR_EVENT_RATIO: f64 = 0.8;
+
+/// 類似要約を探すときに見る要約の件数
+const SIMILAR_SUMMARY_CANDIDATES: usize = 50;
+
 /// トークン数を正確に計算（o200k_base: GPT-4o, GPT-5用）
 fn estimate_tokens(text: &str) -> usize {
     let bpe = o200k_base().expect("[Token] tiktoken (o200k_base) 初期化に失敗しました");
@@ -52,7 +63,7 @@ fn find_events_within_token_limit(
         count += 1;
     }
     
-    println!("[Token Limit] 最大{}件のイベントが{}トークン（制限: {}トークン）", 
+    info!("[Token Limit] 最大{}件のイベントが{}トークン（制限: {}トークン）", 
              count, accumulated_tokens, available_tokens);
     
     Ok(count)
@@ -83,23 +94,77 @@ fn format_timeline_text(conn: &Connection, events: Vec<db::EventRecord>) -> Resu
 }
 
 /// 会話タイムラインを文字列として構築（最大5000文字）
-/// user_inputが指定された場合、80%高類似度 + 20%低類似度で多様性を持たせる
-pub async fn build_conversation_timeline_with_diversity(
+/// 類似検索の問い合わせがある場合、類似度が閾値以上の発言を8割まで選び、残りは直近の発言で埋めて多様性を持たせる
+pub fn build_conversation_timeline_with_diversity(
     conn: &Connection,
     bot_pubkey: &str,
-    _user_input: Option<&str>,
+    query: Option<&SimilarityQuery>,
     limit: usize,
 ) -> Result<String, Box<dyn std::error::Error>> {
-    let events = db::get_conversation_timeline(conn, bot_pubkey, limit)?;
+    let query = match query {
+        Some(query) => query,
+        None => {
+            // 類似検索なしは時系列順のみ
+            let events = db::get_conversation_timeline(conn, bot_pubkey, limit)?;
+            return format_timeline_text(conn, events);
+        }
+    };
     
-    if events.is_empty() {
-        return Ok(String::new());
+    let candidates = db::get_conversation_timeline(conn, bot_pubkey, limit * SIMILARITY_CANDIDATE_FACTOR)?;
+    let selected_events = select_diverse_events(conn, candidates, query, limit)?;
+    
+    format_timeline_text(conn, selected_events)
+}
+
+/// 候補（古い順）から類似度の高い発言と直近の発言を選ぶ（古い順で返す）
+fn select_diverse_events(
+    conn: &Connection,
+    candidates: Vec<db::EventRecord>,
+    query: &SimilarityQuery,
+    limit: usize,
+) -> Result<Vec<db::EventRecord>, Box<dyn std::error::Error>> {
+    if candidates.len() <= limit {
+        return Ok(candidates);
     }
     
-    // 時系列順のみ（embedding無効化のため類似度選別を削除）
-    let selected_events = events;
+    let ids: Vec<i64> = candidates.iter().map(|e| e.id).collect();
+    let vectors = db::get_embeddings(conn, db::EmbeddingSource::Event, &query.model, &ids)?;
     
-    format_timeline_text(conn, selected_events)
+    // 閾値以上のものを類似度順に
+    let mut similar: Vec<(usize, f64)> = candidates
+        .iter()
+        .enumerate()
+        .filter_map(|(i, event)| vectors.get(&event.id).map(|v| (i, query.similarity(v))))
+        .filter(|(_, similarity)| *similarity >= query.threshold)
+        .collect();
+    similar.sort_by(|a, b| b.1.total_cmp(&a.1));
+    
+    let similar_quota = (limit as f64 * SIMILAR_EVENT_RATIO).round() as usize;
+    let mut selected = vec![false; candidates.len()];
+    for (i, _) in similar.iter().take(similar_quota) {
+        selected[*i] = true;
+    }
+    let similar_count = selected.iter().filter(|s| **s).count();
+    
+    // 残りは新しい方から埋める
+    let mut remaining = limit - similar_count;
+    for i in (0..candidates.len()).rev() {
+        if remaining == 0 {
+            break;
+        }
+        if !selected[i] {
+            selected[i] = true;
+            remaining -= 1;
+        }
+    }
+    
+    info!("[Conversation] 類似度で{}件、直近で{}件を選びました（閾値: {}）", similar_count, limit - similar_count, query.threshold);
+    
+    Ok(candidates
+        .into_iter()
+        .zip(selected)
+        .filter_map(|(event, keep)| keep.then_some(event))
+        .collect())
 }
 
 /// 旧インターフェース（互換性のため）
@@ -109,10 +174,33 @@ pub async fn build_conversation_timeline(
     bot_pubkey: &str,
     limit: usize,
 ) -> Result<String, Box<dyn std::error::Error>> {
-    build_conversation_timeline_with_diversity(conn, bot_pubkey, None, limit).await
+    build_conversation_timeline_with_diversity(conn, bot_pubkey, None, limit)
 }
 
-/// 会話が指定文字数を超える場合に要約を作成
+/// そのユーザーとの過去の要約から、入力に最も近いもの（類似度が閾値以上）を探す
+fn find_similar_summary(
+    conn: &Connection,
+    bot_pubkey: &str,
+    user_pubkey: &str,
+    query: &SimilarityQuery,
+) -> Result<Option<db::ConversationSummary>, Box<dyn std::error::Error>> {
+    let summaries = db::get_conversation_summaries(conn, bot_pubkey, user_pubkey, SIMILAR_SUMMARY_CANDIDATES)?;
+    let ids: Vec<i64> = summaries.iter().map(|s| s.id).collect();
+    let vectors = db::get_embeddings(conn, db::EmbeddingSource::Summary, &query.model, &ids)?;
+    
+    let best = summaries
+        .into_iter()
+        .filter_map(|summary| vectors.get(&summary.id).map(|v| (query.similarity(v), summary)))
+        .filter(|(similarity, _)| *similarity >= query.threshold)
+        .max_by(|a, b| a.0.total_cmp(&b.0));
+    
+    Ok(best.map(|(similarity, summary)| {
+        info!("[Conversation] 類似する過去の要約（類似度: {:.3}）", similarity);
+        summary
+    }))
+}
+
+/// 会話が指定文字数を超える場合に要約を作成（save_summaryがfalseならDBに保存しない）
 /// 戻り値: Option<(要約テキスト, 要約の終了タイムスタンプ)>
 pub async fn summarize_conversation_if_needed(
     conn: &Connection,
@@ -120,6 +208,7 @@ pub async fn summarize_conversation_if_needed(
     user_pubkey: &str,
     user_input: &str,
     timeline_text: &str,
+    save_summary: bool,
     config: &AppConfig,
 ) -> Result<Option<(String, i64)>, Box<dyn std::error::Error>> {
     let summary_threshold = config.get_usize_setting("summary_threshold");
@@ -128,10 +217,13 @@ pub async fn summarize_conversation_if_needed(
         return Ok(None);
     }
     
-    println!("[Conversation] タイムラインが{}文字のため要約を作成します（閾値: {}文字）", timeline_text.len(), summary_threshold);
+    info!("[Conversation] タイムラインが{}文字のため要約を作成します（閾値: {}文字）", timeline_text.len(), summary_threshold);
     
-    // embedding無効化のため、類似要約検索は行わない
-    let similar_summary: Option<db::ConversationSummary> = None;
+    // 類似する過去の要約があれば、それ以降の会話だけを要約に足す
+    let similar_summary = match embedding::similarity_query(config, user_input).await {
+        Some(query) => find_similar_summary(conn, bot_pubkey, user_pubkey, &query)?,
+        None => None,
+    };
     
     // Botのパーソナリティを取得
     let bot_person = db::get_person(conn, bot_pubkey)?;
@@ -149,7 +241,7 @@ pub async fn summarize_conversation_if_needed(
     
     let content_to_summarize = if let Some(prev_summary) = similar_summary {
         // 類似する過去の要約がある場合は、それ以降の会話のみを要約
-        println!("[Conversation] 類似する過去の要約を発見: {}", prev_summary.summary);
+        info!("[Conversation] 類似する過去の要約を発見: {}", prev_summary.summary);
         
         // 要約の終了時刻以降のイベントを取得（そのユーザーとの会話のみ）
         let events = db::get_conversation_timeline_with_user(conn, bot_pubkey, user_pubkey, 200)?;
@@ -170,7 +262,7 @@ pub async fn summarize_conversation_if_needed(
         
         if event_count == 0 {
             // トークン制限により新規イベントを含められない場合は過去の要約をそのまま使用
-            println!("[Conversation] トークン制限により新規イベントを含められません");
+            info!("[Conversation] トークン制限により新規イベントを含められません");
             return Ok(Some((prev_summary.summary, prev_summary.to_timestamp)));
         }
         
@@ -210,7 +302,7 @@ pub async fn summarize_conversation_if_needed(
                 .unwrap_or(timeline_text.len());
             
             let trimmed = &timeline_text[safe_start..];
-            println!("[Conversation] トークン制限により{}文字から{}文字に切り詰めました", timeline_text.len(), trimmed.len());
+            info!("[Conversation] トークン制限により{}文字から{}文字に切り詰めました", timeline_text.len(), trimmed.len());
             trimmed.to_string()
         } else {
             timeline_text.to_string()
@@ -218,9 +310,9 @@ pub async fn summarize_conversation_if_needed(
     };
     
     // GPT APIで要約を生成（カテゴリ: summary）
-    let summary = gpt::call_gpt_with_category(&summary_prompt, &content_to_summarize, bot_pubkey, "summary", config).await?;
+    let summary = gpt::call_gpt_with_category(&summary_prompt, &content_to_summarize, &bot_person, "summary", config).await?;
     
-    println!("[Conversation] 要約完了: {} 文字", summary.len());
+    info!("[Conversation] 要約完了: {} 文字", summary.len());
     
     // 要約をDBに保存（そのユーザーとの会話履歴のみ）
     let events = db::get_conversation_timeline_with_user(conn, bot_pubkey, user_pubkey, 100)?;
@@ -235,21 +327,24 @@ pub async fn summarize_conversation_if_needed(
     participants.sort();
     participants.dedup();
     
-    db::insert_conversation_summary(
-        conn,
-        bot_pubkey,
-        &summary,
-        user_input,
-        Some(&participants),
-        from_timestamp,
-        to_timestamp,
-    )?;
+    if save_summary {
+        db::insert_conversation_summary(
+            conn,
+            bot_pubkey,
+            &summary,
+            user_input,
+            Some(&participants),
+            from_timestamp,
+            to_timestamp,
+        )?;
+    }
     
     Ok(Some((summary, to_timestamp)))
 }
 
 /// 返信用のコンテキストを準備
 #[allow(dead_code)]
+#[allow(clippy::too_many_arguments)]
 pub async fn prepare_context_for_reply(
     conn: &Connection,
     bot_pubkey: &str,
@@ -260,8 +355,41 @@ pub async fn prepare_context_for_reply(
     thread_root_id: Option<&str>,
     user_name: Option<&str>,
 ) -> Result<String, Box<dyn std::error::Error>> {
-    // 会話タイムラインを構築（80%高類似度 + 20%低類似度）
-    let timeline_text = build_conversation_timeline_with_diversity(conn, bot_pubkey, Some(user_input), limit).await?;
+    build_context_for_reply(conn, bot_pubkey, user_pubkey, user_input, limit, config, thread_root_id, user_name, true).await
+}
+
+/// 返信用のコンテキストを準備するが、作った要約は保存しない（ダッシュボードのプロンプト試行用）
+#[allow(clippy::too_many_arguments)]
+pub async fn preview_context_for_reply(
+    conn: &Connection,
+    bot_pubkey: &str,
+    user_pubkey: &str,
+    user_input: &str,
+    limit: usize,
+    config: &AppConfig,
+    thread_root_id: Option<&str>,
+    user_name: Option<&str>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    build_context_for_reply(conn, bot_pubkey, user_pubkey, user_input, limit, config, thread_root_id, user_name, false).await
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn build_context_for_reply(
+    conn: &Connection,
+    bot_pubkey: &str,
+    user_pubkey: &str,
+    user_input: &str,
+    limit: usize,
+    config: &AppConfig,
+    thread_root_id: Option<&str>,
+    user_name: Option<&str>,
+    save_summary: bool,
+) -> Result<String, Box<dyn std::error::Error>> {
+    // 入力文のベクトル（埋め込みが無効ならNoneで、時系列のみになる）
+    let query = embedding::similarity_query(config, user_input).await;
+    
+    // 会話タイムラインを構築（類似度の高い発言 + 直近の発言）
+    let timeline_text = build_conversation_timeline_with_diversity(conn, bot_pubkey, query.as_ref(), limit)?;
     
     if timeline_text.is_empty() {
         return Ok(String::new());
@@ -287,10 +415,10 @@ pub async fn prepare_context_for_reply(
             
             if !old_events_text.is_empty() {
                 // 古い部分を要約
-                if let Some((summary, _)) = summarize_conversation_if_needed(conn, bot_pubkey, user_pubkey, user_input, &old_events_text, config).await? {
+                if let Some((summary, _)) = summarize_conversation_if_needed(conn, bot_pubkey, user_pubkey, user_input, &old_events_text, save_summary, config).await? {
                     let recent_timeline = format_timeline_text(conn, recent_events.to_vec())?;
                     
-                    println!("[Conversation] 要約対象: {}件, 最近のやり取り: {}件", old_events.len(), recent_events.len());
+                    info!("[Conversation] 要約対象: {}件, 最近のやり取り: {}件", old_events.len(), recent_events.len());
                     
                     let user_label = if let Some(name) = user_name {
                         format!("【{}からあなたへの質問・発言】", name)
@@ -310,14 +438,48 @@ pub async fn prepare_context_for_reply(
         }
     }
     
-    // 閾値以下の場合はそのまま返す
+    // 閾値以下の場合はそのまま返す（関連する過去の要約があれば添える）
     let user_label = if let Some(name) = user_name {
         format!("【{}からあなたへの質問・発言】", name)
     } else {
         "【あなたへの質問・発言】".to_string()
     };
     
-    Ok(format!("【会話履歴】\n{}\n\n{}\n{}", timeline_text, user_label, user_input))
+    let similar_summary = match &query {
+        Some(query) => find_similar_summary(conn, bot_pubkey, user_pubkey, query)?,
+        None => None,
+    };
+    let related_summary = match similar_summary {
+        Some(summary) => format!("【関連する過去の会話の要約】\n{}\n\n", summary.summary),
+        None => String::new(),
+    };
+    
+    Ok(format!("{}【会話履歴】\n{}\n\n{}\n{}", related_summary, timeline_text, user_label, user_input))
+}
+
+/// DM返信用のコンテキストを準備（同じ相手とのDM履歴のみを使う）
+pub fn prepare_context_for_direct_message(
+    conn: &Connection,
+    bot_pubkey: &str,
+    user_pubkey: &str,
+    user_input: &str,
+    limit: usize,
+    user_name: Option<&str>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let history = db::get_direct_message_timeline(conn, bot_pubkey, user_pubkey, limit)?;
+    let history_text = format_timeline_text(conn, history)?;
+    
+    let user_label = if let Some(name) = user_name {
+        format!("【{}からのDM】", name)
+    } else {
+        "【あなたへのDM】".to_string()
+    };
+    
+    if history_text.is_empty() {
+        Ok(format!("{}\n{}", user_label, user_input))
+    } else {
+        Ok(format!("【DM履歴】\n{}\n\n{}\n{}", history_text, user_label, user_input))
+    }
 }
 
 /// エアリプ用の日本語タイムライン構築（従来のtimeline機能の代替）
@@ -332,6 +494,7 @@ pub fn build_japanese_timeline_for_air_reply(
                 language
          FROM events
          WHERE language = 'ja'
+           AND kind NOT IN (4, 14)
          ORDER BY created_at DESC
          LIMIT ?"
     )?;
@@ -0,0 +1,342 @@
+use axum::{
+    body::Bytes,
+    extract::{Request, State},
+    http::{header, HeaderMap, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+};
+use super::types::DashboardState;
+use crate::config::{AppConfig, DashboardAuthConfig};
+use chrono::Utc;
+use nostr_sdk::base64::engine::{general_purpose, Engine};
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tracing::{info, warn};
+
+/// セッションCookieの名前
+const SESSION_COOKIE: &str = "nostrchan_session";
+
+/// 使用済みのNIP-98イベントを覚えておく秒数（NIP-98の有効期間より長くする）
+const NIP98_REPLAY_WINDOW_SECS: i64 = 120;
+
+/// 閲覧のみのユーザーでもGETできない（秘密鍵を返す）API
+const ADMIN_ONLY_GET_PATHS: &[&str] = &["/api/bots/generate-key"];
+
+/// ダッシュボードの権限
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// 閲覧のみ
+    Viewer,
+    /// 全ての操作
+    Admin,
+}
+
+/// ログイン中のユーザー（ハンドラーにはExtensionで渡す）
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthSession {
+    pub role: Role,
+    /// NIP-98でログインした場合のpubkey（hex）
+    pub pubkey: Option<String>,
+    #[serde(skip)]
+    pub expires_at: i64,
+}
+
+impl AuthSession {
+    pub fn is_admin(&self) -> bool {
+        self.role == Role::Admin
+    }
+
+    /// 変更履歴に残す操作者（NIP-98ならpubkey、トークンなら"dashboard"）
+    pub fn author(&self) -> &str {
+        self.pubkey.as_deref().unwrap_or("dashboard")
+    }
+}
+
+/// ログインの設定とセッション（メモリ上のみ。再起動でログアウトされる）
+pub struct AuthState {
+    config: DashboardAuthConfig,
+    admin_pubkeys: Vec<String>,
+    sessions: Mutex<HashMap<String, AuthSession>>,
+    /// ログインに使われたNIP-98イベントのIDとcreated_at（同じイベントでの再ログインを防ぐ）
+    used_nip98_events: Mutex<HashMap<EventId, i64>>,
+}
+
+impl AuthState {
+    pub fn new(config: &AppConfig) -> Self {
+        let auth = &config.dashboard.auth;
+        if auth.enabled && auth.admin_token.is_none() && config.bot.admin_pubkeys.is_empty() {
+            warn!("⚠️ ダッシュボードに管理者としてログインする手段がありません（dashboard.auth.admin_token か bot.admin_pubkeys を設定してください）");
+        }
+        Self {
+            config: auth.clone(),
+            admin_pubkeys: config.bot.admin_pubkeys.clone(),
+            sessions: Mutex::new(HashMap::new()),
+            used_nip98_events: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// ログインを有効にするのに必要な設定が揃っているか（public_urlはNIP-98の照合とCookieのSecure属性に使う）
+    pub fn check_config(&self) -> Result<(), String> {
+        if self.config.enabled && self.config.public_url.as_deref().is_none_or(|url| url.trim().is_empty()) {
+            return Err("ダッシュボードのログインを有効にする場合は dashboard.auth.public_url を設定してください".to_string());
+        }
+        Ok(())
+    }
+
+    /// pubkeyの権限（admin_pubkeys → 管理者、viewer_pubkeys → 閲覧のみ）
+    fn role_for_pubkey(&self, pubkey: &str) -> Option<Role> {
+        if self.admin_pubkeys.iter().any(|p| p == pubkey) {
+            Some(Role::Admin)
+        } else if self.config.viewer_pubkeys.iter().any(|p| p == pubkey) {
+            Some(Role::Viewer)
+        } else {
+            None
+        }
+    }
+
+    /// 固定トークンの権限
+    fn role_for_token(&self, token: &str) -> Option<Role> {
+        let matches = |expected: &Option<String>| {
+            expected
+                .as_deref()
+                .is_some_and(|expected| !expected.is_empty() && constant_time_eq(expected.as_bytes(), token.as_bytes()))
+        };
+        if matches(&self.config.admin_token) {
+            Some(Role::Admin)
+        } else if matches(&self.config.viewer_token) {
+            Some(Role::Viewer)
+        } else {
+            None
+        }
+    }
+
+    /// セッションを作成し、そのIDを返す
+    fn create_session(&self, role: Role, pubkey: Option<String>) -> (String, AuthSession) {
+        // 推測できないID（秘密鍵の生成と同じ乱数を使う）
+        let id = Keys::generate().secret_key().to_secret_hex();
+        let now = Utc::now().timestamp();
+        let session = AuthSession {
+            role,
+            pubkey,
+            expires_at: now + self.config.session_ttl,
+        };
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions.retain(|_, s| s.expires_at > now);
+        sessions.insert(id.clone(), session.clone());
+        (id, session)
+    }
+
+    fn remove_session(&self, id: &str) {
+        self.sessions.lock().unwrap().remove(id);
+    }
+
+    /// Cookieのセッション、または Authorization: Bearer の固定トークンからユーザーを求める
+    pub fn authenticate(&self, headers: &HeaderMap) -> Option<AuthSession> {
+        if let Some(id) = session_id(headers) {
+            let now = Utc::now().timestamp();
+            let sessions = self.sessions.lock().unwrap();
+            if let Some(session) = sessions.get(&id).filter(|s| s.expires_at > now) {
+                return Some(session.clone());
+            }
+        }
+        let token = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))?;
+        self.role_for_token(token.trim()).map(|role| AuthSession {
+            role,
+            pubkey: None,
+            expires_at: i64::MAX,
+        })
+    }
+
+    /// NIP-98のuタグと照合するURL（Hostヘッダーは偽装できるので、設定したpublic_urlだけを使う）
+    fn request_url(&self, path: &str) -> Option<Url> {
+        let base = self.config.public_url.as_deref()?.trim_end_matches('/');
+        Url::parse(&format!("{}{}", base, path)).ok()
+    }
+
+    /// NIP-98イベントを使用済みにする（既に使われていたらfalse）
+    fn mark_nip98_used(&self, auth_header: &str) -> bool {
+        let Some(event) = nip98_event(auth_header) else {
+            return false;
+        };
+        let now = Utc::now().timestamp();
+        let mut used = self.used_nip98_events.lock().unwrap();
+        used.retain(|_, created_at| *created_at > now - NIP98_REPLAY_WINDOW_SECS);
+        used.insert(event.id, event.created_at.as_u64() as i64).is_none()
+    }
+
+    /// セッションCookie（public_urlがhttpsならSecureを付ける）
+    fn session_cookie(&self, id: &str, max_age: i64) -> String {
+        let secure = self.config.public_url.as_deref().is_some_and(|url| url.trim().starts_with("https://"));
+        format!(
+            "{}={}; Path=/; HttpOnly; SameSite=Strict; Max-Age={}{}",
+            SESSION_COOKIE,
+            id,
+            max_age,
+            if secure { "; Secure" } else { "" }
+        )
+    }
+}
+
+/// Authorization: Nostr ヘッダーのイベントを取り出す
+fn nip98_event(auth_header: &str) -> Option<Event> {
+    let encoded = auth_header.strip_prefix("Nostr ")?.trim();
+    let json = general_purpose::STANDARD.decode(encoded).ok()?;
+    Event::from_json(json).ok()
+}
+
+/// タイミングで一致した長さが分からないよう、全バイトを比較する
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// CookieヘッダーからセッションIDを取り出す
+fn session_id(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(name, _)| *name == SESSION_COOKIE)
+        .map(|(_, value)| value.to_string())
+}
+
+/// APIの認証（GETは閲覧のみのユーザーも可、それ以外は管理者のみ）
+pub async fn require_auth(
+    State(state): State<DashboardState>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let session = if state.auth.enabled() {
+        match state.auth.authenticate(req.headers()) {
+            Some(session) => session,
+            None => return (StatusCode::UNAUTHORIZED, "ログインしてください").into_response(),
+        }
+    } else {
+        AuthSession {
+            role: Role::Admin,
+            pubkey: None,
+            expires_at: i64::MAX,
+        }
+    };
+
+    let read_only = matches!(*req.method(), Method::GET | Method::HEAD)
+        && !ADMIN_ONLY_GET_PATHS.contains(&req.uri().path());
+    if !read_only && !session.is_admin() {
+        return (StatusCode::FORBIDDEN, "この操作には管理者権限が必要です").into_response();
+    }
+
+    req.extensions_mut().insert(session);
+    next.run(req).await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LoginRequest {
+    pub token: Option<String>,
+}
+
+/// ログイン（Authorization: Nostr のNIP-98イベント、または固定トークン）
+pub async fn login_handler(
+    State(state): State<DashboardState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let auth = &state.auth;
+    if !auth.enabled() {
+        return (StatusCode::BAD_REQUEST, "ログインは無効化されています").into_response();
+    }
+
+    let nip98_header = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| v.starts_with("Nostr "));
+
+    let (role, pubkey) = if let Some(auth_header) = nip98_header {
+        let url = match auth.request_url("/api/auth/login") {
+            Some(url) => url,
+            None => return (StatusCode::BAD_REQUEST, "dashboard.auth.public_url が設定されていません").into_response(),
+        };
+        let pubkey = match nip98::verify_auth_header(auth_header, &url, nip98::HttpMethod::POST, Timestamp::now(), None) {
+            Ok(pubkey) => pubkey.to_hex(),
+            Err(e) => {
+                info!("[Dashboard] NIP-98ログイン失敗: {}", e);
+                return (StatusCode::UNAUTHORIZED, "NIP-98の認証に失敗しました").into_response();
+            }
+        };
+        if !auth.mark_nip98_used(auth_header) {
+            info!("[Dashboard] 使用済みのNIP-98イベントでのログイン: {}", pubkey);
+            return (StatusCode::UNAUTHORIZED, "NIP-98の認証に失敗しました").into_response();
+        }
+        match auth.role_for_pubkey(&pubkey) {
+            Some(role) => (role, Some(pubkey)),
+            None => {
+                info!("[Dashboard] 権限のないpubkeyからのログイン: {}", pubkey);
+                return (StatusCode::FORBIDDEN, "このpubkeyにはダッシュボードの権限がありません").into_response();
+            }
+        }
+    } else {
+        let token = serde_json::from_slice::<LoginRequest>(&body)
+            .ok()
+            .and_then(|req| req.token)
+            .unwrap_or_default();
+        match auth.role_for_token(token.trim()) {
+            Some(role) => (role, None),
+            None => return (StatusCode::UNAUTHORIZED, "トークンが正しくありません").into_response(),
+        }
+    };
+
+    let (id, session) = auth.create_session(role, pubkey);
+    info!("[Dashboard] ログイン: {:?} {}", session.role, session.pubkey.as_deref().unwrap_or("(token)"));
+    (
+        [(header::SET_COOKIE, auth.session_cookie(&id, auth.config.session_ttl))],
+        Json(session),
+    )
+        .into_response()
+}
+
+/// ログアウト
+pub async fn logout_handler(
+    State(state): State<DashboardState>,
+    headers: HeaderMap,
+) -> Response {
+    if let Some(id) = session_id(&headers) {
+        state.auth.remove_session(&id);
+    }
+    (
+        [(header::SET_COOKIE, state.auth.session_cookie("", 0))],
+        StatusCode::NO_CONTENT,
+    )
+        .into_response()
+}
+
+/// ログイン中のユーザー
+pub async fn me_handler(
+    State(state): State<DashboardState>,
+    headers: HeaderMap,
+) -> Response {
+    if !state.auth.enabled() {
+        return Json(serde_json::json!({ "role": Role::Admin, "pubkey": null, "auth_enabled": false })).into_response();
+    }
+    match state.auth.authenticate(&headers) {
+        Some(session) => Json(serde_json::json!({
+            "role": session.role,
+            "pubkey": session.pubkey,
+            "auth_enabled": true,
+        }))
+        .into_response(),
+        None => (StatusCode::UNAUTHORIZED, "ログインしてください").into_response(),
+    }
+}
@@ -1,26 +1,74 @@
 use axum::{
-    extract::{State, Path, Query},
+    extract::{Extension, State, Path, Query},
     response::Json,
     http::StatusCode,
 };
+use super::auth::AuthSession;
 use super::types::{DashboardState, BotData, BotRequest};
+use super::validate_setting;
 use crate::database as db;
 use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
+use tracing::{error, info};
 
-/// Bot一覧取得
+/// Bot個別設定の温度の範囲（画面の入力欄と同じ）
+const TEMPERATURE_RANGE: RangeInclusive<f64> = 0.0..=2.0;
+/// Bot個別設定の最大トークン数の範囲
+const MAX_TOKENS_RANGE: RangeInclusive<i64> = 1..=128000;
+
+/// Bot個別設定を検証（範囲外の値・無視と絵文字リアクションの確率の合計が100%超ならBAD_REQUEST）
+///
+/// 返信の文字数目安・エアリプの反応確率は共通設定（gpt_answer_length・reaction_percent）と同じ範囲
+fn check_overrides(overrides: &db::PersonOverrides) -> Result<(), StatusCode> {
+    let shared = [
+        ("gpt_answer_length", overrides.answer_length.map(i64::from)),
+        ("reaction_percent", overrides.reaction_percent),
+        ("reaction_percent", overrides.emoji_reaction_percent),
+        ("reaction_percent", overrides.ignore_percent),
+    ];
+    for (key, value) in shared {
+        if let Some(value) = value {
+            validate_setting(key, &value.to_string()).map_err(|_| StatusCode::BAD_REQUEST)?;
+        }
+    }
+    if overrides.temperature.is_some_and(|t| !TEMPERATURE_RANGE.contains(&t))
+        || overrides.max_tokens.is_some_and(|n| !MAX_TOKENS_RANGE.contains(&n))
+    {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if overrides.ignore_percent.unwrap_or(0) + overrides.emoji_reaction_percent.unwrap_or(0) > 100 {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListBotsQuery {
+    /// 秘密鍵も返す（管理者のみ）
+    #[serde(default)]
+    pub include_secret: bool,
+}
+
+/// Bot一覧取得（秘密鍵は管理者が include_secret=true を指定したときだけ返す）
 pub async fn list_bots_handler(
-    State(_state): State<DashboardState>
+    State(_state): State<DashboardState>,
+    Extension(session): Extension<AuthSession>,
+    Query(query): Query<ListBotsQuery>,
 ) -> Result<Json<Vec<BotData>>, StatusCode> {
+    if query.include_secret && !session.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
     let conn = db::connect().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     let persons = db::get_all_persons(&conn).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
     let bots: Vec<BotData> = persons.into_iter().map(|p| BotData {
         pubkey: p.pubkey,
-        secretkey: p.secretkey,
+        secretkey: query.include_secret.then_some(p.secretkey),
         prompt: p.prompt,
         content: p.content,
         status: p.status,
         air_reply_single_ratio: Some(p.air_reply_single_ratio),
+        overrides: p.overrides,
     }).collect();
     
     Ok(Json(bots))
@@ -29,6 +77,7 @@ pub async fn list_bots_handler(
 /// Bot作成
 pub async fn create_bot_handler(
     State(_state): State<DashboardState>,
+    Extension(session): Extension<AuthSession>,
     Json(req): Json<BotRequest>,
 ) -> Result<Json<BotData>, StatusCode> {
     use nostr_sdk::prelude::*;
@@ -36,29 +85,38 @@ pub async fn create_bot_handler(
     let conn = db::connect().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
     // secretkeyからpubkeyを取得
-    let keys = Keys::parse(&req.secretkey).map_err(|_| StatusCode::BAD_REQUEST)?;
+    let secretkey = req.secretkey.clone().ok_or(StatusCode::BAD_REQUEST)?;
+    let keys = Keys::parse(&secretkey).map_err(|_| StatusCode::BAD_REQUEST)?;
     let pubkey = keys.public_key().to_string();
+    let overrides = req.overrides.unwrap_or_default();
+    check_overrides(&overrides)?;
     
     // DBに追加
-    db::add_person(&conn, &pubkey, &req.secretkey, &req.prompt, &req.content, req.air_reply_single_ratio)
+    db::add_person(&conn, &pubkey, &secretkey, &req.prompt, &req.content, req.air_reply_single_ratio)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    db::record_persona_version(&conn, &pubkey, Some(session.author()), "dashboard")
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    
+    // Bot個別設定
+    db::update_person_overrides(&conn, &pubkey, &overrides)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
     // 誕生投稿を非同期で送信
-    let secretkey = req.secretkey.clone();
     let content = req.content.clone();
     tokio::spawn(async move {
         if let Err(e) = post_birth_announcement(&secretkey, &content).await {
-            eprintln!("誕生投稿エラー: {}", e);
+            error!("誕生投稿エラー: {}", e);
         }
     });
     
     Ok(Json(BotData {
         pubkey,
-        secretkey: req.secretkey,
+        secretkey: None,
         prompt: req.prompt,
         content: req.content,
         status: 0,
         air_reply_single_ratio: req.air_reply_single_ratio,
+        overrides,
     }))
 }
 
@@ -82,31 +140,24 @@ async fn post_birth_announcement(secretkey: &str, content_json: &str) -> Result<
     };
     
     let keys = Keys::parse(secretkey)?;
-    let client = Client::new(keys);
+    let pool = crate::relay_pool::shared();
     
     // config.ymlから設定を読み込む
-    let config_path = "../config.yml";
+    let config_path = crate::config::config_path();
     let file = std::fs::File::open(config_path)?;
     let config: crate::config::AppConfig = serde_yaml::from_reader(file)?;
     
-    // リレーに接続
-    for relay in &config.relay_servers.write {
-        let _ = client.add_relay(relay).await;
-    }
-    
-    client.connect().await;
-    
     // kind 0（メタデータ）を送信
-    println!("[Bot Creation] Publishing kind 0 metadata...");
+    info!("[Bot Creation] Publishing kind 0 metadata...");
     if !content_json.is_empty() {
         match Metadata::from_json(content_json) {
             Ok(metadata) => {
-                match client.set_metadata(&metadata).await {
-                    Ok(_) => println!("✓ kind 0 published successfully"),
-                    Err(e) => eprintln!("✗ Failed to publish kind 0: {}", e),
+                match pool.publish_as(&keys, EventBuilder::metadata(&metadata), &config.relay_servers.write).await {
+                    Ok(_) => info!("✓ kind 0 published successfully"),
+                    Err(e) => error!("✗ Failed to publish kind 0: {}", e),
                 }
             }
-            Err(e) => eprintln!("✗ Failed to parse metadata: {}", e),
+            Err(e) => error!("✗ Failed to parse metadata: {}", e),
         }
     }
     
@@ -114,9 +165,9 @@ async fn post_birth_announcement(secretkey: &str, content_json: &str) -> Result<
     let message = format!("{}です。コンゴトモヨロシク！", bot_name);
     
     let builder = EventBuilder::text_note(message);
-    client.send_event_builder(builder).await?;
+    pool.publish_as(&keys, builder, &config.relay_servers.write).await?;
     
-    println!("✨ {}の誕生投稿を送信しました", bot_name);
+    info!("✨ {}の誕生投稿を送信しました", bot_name);
     
     Ok(())
 }
@@ -124,9 +175,12 @@ async fn post_birth_announcement(secretkey: &str, content_json: &str) -> Result<
 /// Bot更新
 pub async fn update_bot_handler(
     State(_state): State<DashboardState>,
+    Extension(session): Extension<AuthSession>,
     Path(pubkey): Path<String>,
     Json(req): Json<BotRequest>,
 ) -> Result<Json<BotData>, StatusCode> {
+    use nostr_sdk::prelude::*;
+    
     let conn = db::connect().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
     // 既存のbotを取得
@@ -135,17 +189,39 @@ pub async fn update_bot_handler(
     
     let air_reply_single_ratio = req.air_reply_single_ratio.unwrap_or(30);
     
-    // 更新
-    db::update_person(&conn, &pubkey, &req.secretkey, &req.prompt, &req.content, air_reply_single_ratio)
+    // 秘密鍵は指定されたときだけ変更
+    let secretkey = req.secretkey.as_deref().filter(|s| !s.trim().is_empty()).unwrap_or(&existing.secretkey);
+    Keys::parse(secretkey).map_err(|_| StatusCode::BAD_REQUEST)?;
+    if let Some(overrides) = &req.overrides {
+        check_overrides(overrides)?;
+    }
+    
+    // 更新（プロンプト・kind 0が変わったら版を記録）
+    db::ensure_initial_persona_versions(&conn, Some(&pubkey))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    db::update_person(&conn, &pubkey, secretkey, &req.prompt, &req.content, air_reply_single_ratio)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    db::record_persona_version(&conn, &pubkey, Some(session.author()), "dashboard")
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
+    // Bot個別設定（指定時のみ更新）
+    let overrides = match req.overrides {
+        Some(overrides) => {
+            db::update_person_overrides(&conn, &pubkey, &overrides)
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+            overrides
+        }
+        None => existing.overrides.clone(),
+    };
+    
     Ok(Json(BotData {
         pubkey,
-        secretkey: req.secretkey,
+        secretkey: None,
         prompt: req.prompt,
         content: req.content,
         status: existing.status,
         air_reply_single_ratio: Some(air_reply_single_ratio),
+        overrides,
     }))
 }
 
@@ -180,11 +256,12 @@ pub async fn toggle_bot_handler(
     
     Ok(Json(BotData {
         pubkey: existing.pubkey.clone(),
-        secretkey: existing.secretkey.clone(),
+        secretkey: None,
         prompt: existing.prompt.clone(),
         content: existing.content.clone(),
         status: new_status,
         air_reply_single_ratio: Some(existing.air_reply_single_ratio),
+        overrides: existing.overrides.clone(),
     }))
 }
 
@@ -210,73 +287,49 @@ pub async fn fetch_kind0_handler(
     use nostr_sdk::prelude::*;
     
     let conn = db::connect().map_err(|e| {
-        eprintln!("DB接続エラー: {}", e);
+        error!("DB接続エラー: {}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
     
     let persons = db::get_all_persons(&conn).map_err(|e| {
-        eprintln!("Bot情報取得エラー: {}", e);
+        error!("Bot情報取得エラー: {}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
     
     let bot = persons.iter().find(|p| p.pubkey == pubkey)
         .ok_or_else(|| {
-            eprintln!("Botが見つかりません: {}", pubkey);
+            error!("Botが見つかりません: {}", pubkey);
             StatusCode::NOT_FOUND
         })?;
     
     let keys = Keys::parse(&bot.secretkey).map_err(|e| {
-        eprintln!("秘密鍵のパースエラー: {}", e);
+        error!("秘密鍵のパースエラー: {}", e);
         StatusCode::BAD_REQUEST
     })?;
     
-    let client = Client::new(keys);
-    
-    let config_path = "../config.yml";
+    let config_path = crate::config::config_path();
     let file = std::fs::File::open(config_path).map_err(|e| {
-        eprintln!("設定ファイルオープンエラー: {}", e);
+        error!("設定ファイルオープンエラー: {}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
     
     let config: crate::config::AppConfig = serde_yaml::from_reader(file).map_err(|e| {
-        eprintln!("設定ファイルパースエラー: {}", e);
+        error!("設定ファイルパースエラー: {}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
     
-    for relay in &config.relay_servers.read {
-        if let Err(e) = client.add_relay(relay).await {
-            eprintln!("リレー追加エラー ({}): {}", relay, e);
-        }
-    }
-    
-    client.connect().await;
-    
-    let signer = client.signer().await
-        .map_err(|e| {
-            eprintln!("Signer取得エラー: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
-    
-    let pubkey = signer.get_public_key().await
-        .map_err(|e| {
-            eprintln!("公開鍵取得エラー: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
-    
     let filter = Filter::new()
         .kind(Kind::Metadata)
-        .author(pubkey);
+        .author(keys.public_key());
     
-    let events = client.fetch_events(filter, std::time::Duration::from_secs(10))
+    let latest_event = crate::relay_pool::shared()
+        .fetch_latest(filter, &config.relay_servers.read, std::time::Duration::from_secs(10))
         .await
         .map_err(|e| {
-            eprintln!("Kind 0取得エラー: {}", e);
+            error!("Kind 0取得エラー: {}", e);
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
     
-    let latest_event = events.iter()
-        .max_by_key(|e| e.created_at);
-    
     if let Some(event) = latest_event {
         Ok(Json(serde_json::json!({ 
             "content": event.content.clone() 
@@ -297,60 +350,53 @@ pub async fn post_as_bot_handler(
     use nostr_sdk::prelude::*;
     
     let conn = db::connect().map_err(|e| {
-        eprintln!("DB接続エラー: {}", e);
+        error!("DB接続エラー: {}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
     
     let persons = db::get_all_persons(&conn).map_err(|e| {
-        eprintln!("Bot情報取得エラー: {}", e);
+        error!("Bot情報取得エラー: {}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
     
     let bot = persons.iter().find(|p| p.pubkey == pubkey)
         .ok_or_else(|| {
-            eprintln!("Botが見つかりません: {}", pubkey);
+            error!("Botが見つかりません: {}", pubkey);
             StatusCode::NOT_FOUND
         })?;
     
     let keys = Keys::parse(&bot.secretkey).map_err(|e| {
-        eprintln!("秘密鍵のパースエラー: {}", e);
+        error!("秘密鍵のパースエラー: {}", e);
         StatusCode::BAD_REQUEST
     })?;
     
-    let client = Client::new(keys);
-    
-    let config_path = "../config.yml";
+    let config_path = crate::config::config_path();
     let file = std::fs::File::open(config_path).map_err(|e| {
-        eprintln!("設定ファイルオープンエラー: {}", e);
+        error!("設定ファイルオープンエラー: {}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
     
     let config: crate::config::AppConfig = serde_yaml::from_reader(file).map_err(|e| {
-        eprintln!("設定ファイルパースエラー: {}", e);
+        error!("設定ファイルパースエラー: {}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
     
-    for relay in &config.relay_servers.write {
-        if let Err(e) = client.add_relay(relay).await {
-            eprintln!("リレー追加エラー ({}): {}", relay, e);
-        }
-    }
-    
-    client.connect().await;
-    
     let builder = EventBuilder::text_note(&req.content);
-    let event_id = client.send_event_builder(builder)
+    let (_, result) = crate::relay_pool::shared()
+        .publish_as(&keys, builder, &config.relay_servers.write)
         .await
         .map_err(|e| {
-            eprintln!("投稿送信エラー: {}", e);
+            error!("投稿送信エラー: {}", e);
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
     
-    println!("📝 {}として投稿しました: {}", pubkey, req.content);
+    info!("📝 {}として投稿しました: {}", pubkey, req.content);
     
     Ok(Json(serde_json::json!({ 
-        "success": true,
-        "event_id": event_id.to_string()
+        "success": result.is_accepted(),
+        "event_id": result.event_id,
+        "accepted": result.accepted,
+        "rejected": result.rejected
     })))
 }
 
@@ -490,7 +536,7 @@ pub async fn publish_kind0_handler(
     // kind 0を公開
     tokio::spawn(async move {
         if let Err(e) = publish_kind0_only(&person.secretkey, &person.content).await {
-            eprintln!("[Publish Kind0] Error: {}", e);
+            error!("[Publish Kind0] Error: {}", e);
         }
     });
     
@@ -505,56 +551,42 @@ async fn publish_kind0_only(secretkey: &str, content_json: &str) -> Result<(), B
     use nostr_sdk::prelude::*;
     
     let keys = Keys::parse(secretkey)?;
-    let client = Client::new(keys.clone());
     
     // config.ymlから設定を読み込む
-    let config_path = "../config.yml";
+    let config_path = crate::config::config_path();
     let file = std::fs::File::open(config_path)?;
     let config: crate::config::AppConfig = serde_yaml::from_reader(file)?;
     
-    // リレーに接続
-    for relay in &config.relay_servers.write {
-        let _ = client.add_relay(relay).await;
-    }
-    
-    client.connect().await;
-    
     // kind 0（メタデータ）を送信
-    println!("[Publish Kind0] Publishing kind 0 metadata...");
+    info!("[Publish Kind0] Publishing kind 0 metadata...");
     if !content_json.is_empty() {
         match Metadata::from_json(content_json) {
             Ok(metadata) => {
-                match client.set_metadata(&metadata).await {
-                    Ok(_) => {
-                        println!("✓ kind 0 published successfully");
+                let builder = EventBuilder::metadata(&metadata);
+                match crate::relay_pool::shared().publish_as(&keys, builder, &config.relay_servers.write).await {
+                    Ok((event, result)) => {
+                        info!("✓ kind 0 published successfully (accepted: {:?})", result.accepted);
                         
                         // ローカルDBにも保存（upsert処理で最新のみ保持）
                         if let Ok(conn) = db::connect() {
-                            // 署名済みイベントを作成
-                            let event_builder = EventBuilder::metadata(&metadata);
-                            if let Ok(event) = event_builder.sign(&keys).await {
-                                // DBに保存（upsert処理）
-                                if let Err(e) = db::insert_event(&conn, &event, None) {
-                                    eprintln!("✗ Failed to save kind 0 to DB: {}", e);
-                                }
+                            if let Err(e) = db::insert_event(&conn, &event, None) {
+                                error!("✗ Failed to save kind 0 to DB: {}", e);
                             }
                         }
                     },
                     Err(e) => {
-                        eprintln!("✗ Failed to publish kind 0: {}", e);
+                        error!("✗ Failed to publish kind 0: {}", e);
                         return Err(Box::new(e));
                     }
                 }
             }
             Err(e) => {
-                eprintln!("✗ Failed to parse metadata JSON: {}", e);
+                error!("✗ Failed to parse metadata JSON: {}", e);
                 return Err(Box::new(e));
             }
         }
     }
     
-    client.disconnect().await;
-    
     Ok(())
 }
 
@@ -65,18 +65,24 @@ pub async fn list_events_handler(
         let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
         
         if let Some(search_text) = &search {
-            where_clauses.push("(content LIKE ? OR event_id LIKE ? OR pubkey LIKE ?)");
+            // 本文は3文字以上なら全文検索の索引を使う
             let search_pattern = format!("%{}%", search_text);
-            params.push(Box::new(search_pattern.clone()));
+            if let Some(phrase) = db::fts_phrase(search_text) {
+                where_clauses.push("(id IN (SELECT rowid FROM events_fts WHERE events_fts MATCH ?) OR event_id LIKE ? OR pubkey LIKE ?)");
+                params.push(Box::new(phrase));
+            } else {
+                where_clauses.push("(content LIKE ? OR event_id LIKE ? OR pubkey LIKE ?)");
+                params.push(Box::new(search_pattern.clone()));
+            }
             params.push(Box::new(search_pattern.clone()));
             params.push(Box::new(search_pattern));
         }
         
         if let Some(has_emb) = has_embedding {
             if has_emb {
-                where_clauses.push("embedding IS NOT NULL");
+                where_clauses.push("EXISTS (SELECT 1 FROM embeddings em WHERE em.source_type = 'event' AND em.source_id = events.id)");
             } else {
-                where_clauses.push("embedding IS NULL");
+                where_clauses.push("NOT EXISTS (SELECT 1 FROM embeddings em WHERE em.source_type = 'event' AND em.source_id = events.id)");
             }
         }
         
@@ -113,7 +119,9 @@ pub async fn list_events_handler(
         // イベントを取得
         let query_sql = format!(
             "SELECT id, event_id, pubkey, kind, content, created_at, received_at, 
-                    language, embedding, event_json
+                    language,
+                    EXISTS (SELECT 1 FROM embeddings em WHERE em.source_type = 'event' AND em.source_id = events.id),
+                    event_json
              FROM events{}
              ORDER BY {} {}
              LIMIT ? OFFSET ?",
@@ -138,13 +146,13 @@ pub async fn list_events_handler(
                 created_at: row.get(5)?,
                 received_at: row.get(6)?,
                 language: row.get(7)?,
-                has_embedding: row.get::<_, Option<Vec<u8>>>(8)?.is_some(),
+                has_embedding: row.get(8)?,
                 event_json: row.get(9)?,
             })
         }).ok()?
         .collect::<Result<Vec<_>, _>>().ok()?;
         
-        let total_pages = (total + page_size - 1) / page_size;
+        let total_pages = total.div_ceil(page_size);
         
         Some(EventsResponse {
             events,
@@ -208,18 +216,24 @@ pub async fn bulk_delete_events_handler(
         let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
         
         if let Some(search_text) = &search {
-            where_clauses.push("(content LIKE ? OR event_id LIKE ? OR pubkey LIKE ?)");
+            // 本文は3文字以上なら全文検索の索引を使う
             let search_pattern = format!("%{}%", search_text);
-            params.push(Box::new(search_pattern.clone()));
+            if let Some(phrase) = db::fts_phrase(search_text) {
+                where_clauses.push("(id IN (SELECT rowid FROM events_fts WHERE events_fts MATCH ?) OR event_id LIKE ? OR pubkey LIKE ?)");
+                params.push(Box::new(phrase));
+            } else {
+                where_clauses.push("(content LIKE ? OR event_id LIKE ? OR pubkey LIKE ?)");
+                params.push(Box::new(search_pattern.clone()));
+            }
             params.push(Box::new(search_pattern.clone()));
             params.push(Box::new(search_pattern));
         }
         
         if let Some(has_emb) = has_embedding {
             if has_emb {
-                where_clauses.push("embedding IS NOT NULL");
+                where_clauses.push("EXISTS (SELECT 1 FROM embeddings em WHERE em.source_type = 'event' AND em.source_id = events.id)");
             } else {
-                where_clauses.push("embedding IS NULL");
+                where_clauses.push("NOT EXISTS (SELECT 1 FROM embeddings em WHERE em.source_type = 'event' AND em.source_id = events.id)");
             }
         }
         
@@ -6,6 +6,7 @@ use axum::{
 use serde::{Deserialize, Serialize};
 use super::types::DashboardState;
 use crate::database as db;
+use tracing::{error, info};
 
 /// フォロワーキャッシュ一覧
 #[derive(Debug, Serialize)]
@@ -31,18 +32,18 @@ pub async fn list_follower_cache_handler(
     State(_state): State<DashboardState>,
 ) -> Result<Json<Vec<FollowerCacheEntry>>, StatusCode> {
     let conn = db::connect().map_err(|e| {
-        eprintln!("DB接続エラー: {}", e);
+        error!("DB接続エラー: {}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
     
     let caches = db::get_all_follower_cache(&conn).map_err(|e| {
-        eprintln!("フォロワーキャッシュ取得エラー: {}", e);
+        error!("フォロワーキャッシュ取得エラー: {}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
     
     // Bot情報を取得
     let persons = db::get_all_persons(&conn).map_err(|e| {
-        eprintln!("Bot情報取得エラー: {}", e);
+        error!("Bot情報取得エラー: {}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
     
@@ -90,7 +91,7 @@ pub async fn clear_follower_cache_handler(
 ) -> Result<Json<serde_json::Value>, StatusCode> {
     let conn = db::connect().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     let deleted = db::clear_follower_cache(&conn).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    println!("🗑️ フォロワーキャッシュを全削除しました ({}件)", deleted);
+    info!("🗑️ フォロワーキャッシュを全削除しました ({}件)", deleted);
     Ok(Json(serde_json::json!({ "deleted": deleted })))
 }
 
@@ -5,6 +5,7 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 use crate::database as db;
+use tracing::error;
 
 /// ユーザー印象のレスポンス
 #[derive(Debug, Serialize)]
@@ -134,60 +135,60 @@ pub async fn update_user_impression_handler(
     Path((bot_pubkey, user_pubkey)): Path<(String, String)>,
     Json(payload): Json<UpdateImpressionRequest>,
 ) -> Result<StatusCode, StatusCode> {
-    eprintln!("[UpdateImpression] bot_pubkey: {}, user_pubkey: {}", bot_pubkey, user_pubkey);
-    eprintln!("[UpdateImpression] impression length: {}", payload.impression.len());
+    error!("[UpdateImpression] bot_pubkey: {}, user_pubkey: {}", bot_pubkey, user_pubkey);
+    error!("[UpdateImpression] impression length: {}", payload.impression.len());
     
     // config.ymlから最大文字数を取得
     let config = crate::config::load_config()
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
     let max_length = config.get_usize_setting("max_impression_length");
-    eprintln!("[UpdateImpression] max_impression_length: {}", max_length);
+    error!("[UpdateImpression] max_impression_length: {}", max_length);
     
     let conn = db::connect().map_err(|e| {
-        eprintln!("[UpdateImpression] DB接続エラー: {}", e);
+        error!("[UpdateImpression] DB接続エラー: {}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
     
     // 印象の長さチェック
     if payload.impression.len() > max_length {
-        eprintln!("[UpdateImpression] 印象が長すぎます: {} > {}", payload.impression.len(), max_length);
+        error!("[UpdateImpression] 印象が長すぎます: {} > {}", payload.impression.len(), max_length);
         return Err(StatusCode::BAD_REQUEST);
     }
     
     // 既存のUserAttributesを取得
     let mut user_attrs = db::get_user_attributes(&conn, &bot_pubkey, &user_pubkey)
         .map_err(|e| {
-            eprintln!("[UpdateImpression] get_user_attributes エラー: {}", e);
+            error!("[UpdateImpression] get_user_attributes エラー: {}", e);
             StatusCode::INTERNAL_SERVER_ERROR
         })?
         .unwrap_or_else(|| {
-            eprintln!("[UpdateImpression] 既存データなし、新規作成");
+            error!("[UpdateImpression] 既存データなし、新規作成");
             db::UserAttributes::empty()
         });
     
-    eprintln!("[UpdateImpression] 既存データ取得完了");
+    error!("[UpdateImpression] 既存データ取得完了");
     
     // impressionフィールドのみを更新
     user_attrs.impression = Some(payload.impression.clone());
-    eprintln!("[UpdateImpression] impression更新完了");
+    error!("[UpdateImpression] impression更新完了");
     
     // JSON化して保存
     let json_str = user_attrs.to_json()
         .map_err(|e| {
-            eprintln!("[UpdateImpression] JSON化エラー: {}", e);
+            error!("[UpdateImpression] JSON化エラー: {}", e);
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
     
-    eprintln!("[UpdateImpression] JSON: {}", json_str);
+    error!("[UpdateImpression] JSON: {}", json_str);
     
     db::save_user_impression(&conn, &bot_pubkey, &user_pubkey, &json_str)
         .map_err(|e| {
-            eprintln!("[UpdateImpression] save_user_impression エラー: {}", e);
+            error!("[UpdateImpression] save_user_impression エラー: {}", e);
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
     
-    eprintln!("[UpdateImpression] 保存完了");
+    error!("[UpdateImpression] 保存完了");
     Ok(StatusCode::OK)
 }
 
@@ -0,0 +1,48 @@
+use axum::{
+    extract::{Query, State},
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures_util::stream::{self, Stream};
+use serde::Deserialize;
+use tokio::sync::broadcast::error::RecvError;
+use super::types::DashboardState;
+use crate::live;
+
+#[derive(Debug, Deserialize)]
+pub struct LiveQuery {
+    /// 指定したBotに関係するものだけを流す（受信イベントは流さない）
+    pub bot: Option<String>,
+}
+
+/// 受信・キュー・LLM呼び出し・送信のライブ配信（Server-Sent Events）
+///
+/// イベント名は incoming / queue / llm / outgoing で、dataはJSON。
+/// 受け取りが遅れて取りこぼした場合は lagged イベントで件数を知らせる。
+pub async fn live_handler(
+    State(_state): State<DashboardState>,
+    Query(query): Query<LiveQuery>,
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    let receiver = live::subscribe();
+    let bot = query.bot.filter(|bot| !bot.is_empty());
+
+    let events = stream::unfold((receiver, bot), |(mut receiver, bot)| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(message) => {
+                    if bot.as_deref().is_some_and(|bot| message.bot_pubkey() != Some(bot)) {
+                        continue;
+                    }
+                    let event = Event::default().event(message.name()).json_data(&message);
+                    return Some((event, (receiver, bot)));
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    let event = Event::default().event("lagged").data(skipped.to_string());
+                    return Some((Ok(event), (receiver, bot)));
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
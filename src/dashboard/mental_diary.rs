@@ -5,6 +5,7 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 use crate::database as db;
+use tracing::info;
 
 /// Bot心境のレスポンス
 #[derive(Debug, Serialize)]
@@ -109,7 +110,7 @@ pub async fn update_bot_mental_diary_handler(
     db::save_bot_mental_state(&conn, &bot_pubkey, &mental_diary)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
-    println!("📔 Bot心境を手動更新: {}", bot_pubkey);
+    info!("📔 Bot心境を手動更新: {}", bot_pubkey);
     
     Ok(Json(serde_json::json!({ "success": true })))
 }
@@ -0,0 +1,28 @@
+use axum::{
+    extract::State,
+    http::header,
+    response::IntoResponse,
+};
+use super::types::DashboardState;
+use crate::database as db;
+use crate::metrics;
+use tracing::error;
+
+/// Prometheusのメトリクス（テキスト形式）
+pub async fn metrics_handler(State(_state): State<DashboardState>) -> impl IntoResponse {
+    // キューの件数は取得できなければ0として出力する（メトリクス自体は返す）
+    let (queue_depth, dead_letters) = match db::connect() {
+        Ok(conn) => (
+            db::get_queue_size(&conn).unwrap_or_default(),
+            db::count_dead_letters(&conn).unwrap_or_default(),
+        ),
+        Err(e) => {
+            error!("DB接続エラー: {}", e);
+            (0, 0)
+        }
+    };
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")],
+        metrics::render(queue_depth, dead_letters),
+    )
+}
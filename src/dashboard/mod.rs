@@ -1,4 +1,5 @@
 mod types;
+mod auth;
 mod stats;
 mod bots;
 mod follower_cache;
@@ -8,10 +9,20 @@ mod users;
 mod events;
 mod impressions;
 mod mental_diary;
+mod queue;
+mod tools;
+mod schedules;
+mod live;
+mod metrics;
+mod playground;
+mod persona_versions;
 
 pub use types::{DashboardState, BotInfo};
+pub use auth::AuthState;
+pub(crate) use settings::{validate_setting, EDITABLE_SETTINGS};
 
 use axum::{
+    middleware,
     routing::{get, post, put, delete},
     Router,
     response::{IntoResponse, Html},
@@ -21,19 +32,38 @@ use tower_http::services::ServeDir;
 use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::RwLock;
+use tracing::info;
 
 /// ダッシュボードサーバーを起動
 pub async fn start_dashboard(
+    bind: String,
     port: u16,
     db_path: String,
     bot_info: Arc<RwLock<BotInfo>>,
+    auth: Arc<AuthState>,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    auth.check_config()?;
     let state = DashboardState {
         db_path,
         start_time: Arc::new(Instant::now()),
         bot_info,
+        auth,
     };
+    let app = build_router(state);
 
+    let addr = format!("{}:{}", bind, port);
+    info!("🌐 Dashboard server starting on http://{}", addr);
+    info!("   ローカルアクセス: http://127.0.0.1:{}", port);
+    info!("   ネットワークアクセス: http://<your-ip>:{}", port);
+    
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    axum::serve(listener, app).await?;
+
+    Ok(())
+}
+
+/// APIと静的ファイルのルーター
+pub fn build_router(state: DashboardState) -> Router {
     // APIルート
     let api_router = Router::new()
         // 統計
@@ -52,6 +82,10 @@ pub async fn start_dashboard(
         .route("/api/bots/{pubkey}/kind0/publish", post(bots::publish_kind0_handler))
         .route("/api/bots/{pubkey}/post", post(bots::post_as_bot_handler))
         .route("/api/bots/{pubkey}/replies", get(bots::get_bot_replies_handler))
+        .route("/api/bots/{pubkey}/playground", post(playground::playground_handler))
+        .route("/api/bots/{pubkey}/persona-versions", get(persona_versions::list_persona_versions_handler))
+        .route("/api/bots/{pubkey}/persona-versions/{id}/diff", get(persona_versions::persona_version_diff_handler))
+        .route("/api/bots/{pubkey}/persona-versions/{id}/rollback", post(persona_versions::rollback_persona_version_handler))
         .route("/api/bots/{pubkey}/summaries", get(summaries::list_summaries_handler))
         .route("/api/bots/{pubkey}/summaries/bulk-delete", post(summaries::delete_summaries_bulk_handler))
         .route("/api/summaries/{id}", put(summaries::update_summary_handler))
@@ -62,6 +96,14 @@ pub async fn start_dashboard(
         .route("/api/events", get(events::list_events_handler))
         .route("/api/events/{id}", delete(events::delete_event_handler))
         .route("/api/events/bulk-delete", post(events::bulk_delete_events_handler))
+        // イベントキュー
+        .route("/api/queue", get(queue::get_queue_handler))
+        .route("/api/queue/{id}", delete(queue::delete_queue_item_handler))
+        .route("/api/queue/dead-letter", delete(queue::purge_dead_letters_handler))
+        .route("/api/queue/dead-letter/{id}", delete(queue::delete_dead_letter_handler))
+        .route("/api/queue/dead-letter/{id}/requeue", post(queue::requeue_dead_letter_handler))
+        // ライブ配信
+        .route("/api/live", get(live::live_handler))
         // フォロワーキャッシュ
         .route("/api/follower-cache", get(follower_cache::list_follower_cache_handler))
         .route("/api/follower-cache", delete(follower_cache::clear_follower_cache_handler))
@@ -93,6 +135,22 @@ pub async fn start_dashboard(
         .route("/api/bots/{bot_pubkey}/mental-diary", get(mental_diary::get_bot_latest_mental_diary_handler))
         .route("/api/bots/{bot_pubkey}/mental-diary", put(mental_diary::update_bot_mental_diary_handler))
         .route("/api/bots/{bot_pubkey}/mental-diary/history", get(mental_diary::get_bot_mental_diary_history_handler))
+        // ツール
+        .route("/api/tools", get(tools::list_tools_handler))
+        .route("/api/bots/{bot_pubkey}/tool-calls", get(tools::list_tool_calls_handler))
+        // 定期投稿
+        .route("/api/schedule-kinds", get(schedules::list_schedule_kinds_handler))
+        .route("/api/bots/{pubkey}/schedules", get(schedules::list_schedules_handler))
+        .route("/api/bots/{pubkey}/schedules", post(schedules::create_schedule_handler))
+        .route("/api/schedules/{id}", put(schedules::update_schedule_handler))
+        .route("/api/schedules/{id}", delete(schedules::delete_schedule_handler))
+        // Prometheus
+        .route("/metrics", get(metrics::metrics_handler))
+        .route_layer(middleware::from_fn_with_state(state.clone(), auth::require_auth))
+        // ログイン（認証不要）
+        .route("/api/auth/login", post(auth::login_handler))
+        .route("/api/auth/logout", post(auth::logout_handler))
+        .route("/api/auth/me", get(auth::me_handler))
         .with_state(state);
 
     // 静的ファイル配信 + APIルート
@@ -101,26 +159,26 @@ pub async fn start_dashboard(
     let dashboard_dir = format!("{}/dashboard", manifest_dir);
     let assets_dir = format!("{}/dashboard/assets", manifest_dir);
     
-    println!("📂 Dashboard directory: {}", dashboard_dir);
-    println!("📂 Assets directory: {}", assets_dir);
+    info!("📂 Dashboard directory: {}", dashboard_dir);
+    info!("📂 Assets directory: {}", assets_dir);
     
     // ディレクトリの存在確認（デバッグ用）
     if std::path::Path::new(&dashboard_dir).exists() {
-        println!("✅ Dashboard directory exists");
+        info!("✅ Dashboard directory exists");
     } else {
-        println!("❌ Dashboard directory NOT found!");
+        info!("❌ Dashboard directory NOT found!");
     }
     
     if std::path::Path::new(&assets_dir).exists() {
-        println!("✅ Assets directory exists");
+        info!("✅ Assets directory exists");
     } else {
-        println!("❌ Assets directory NOT found!");
+        info!("❌ Assets directory NOT found!");
     }
 
     // SPAフォールバックハンドラ
     let dashboard_dir_for_fallback = dashboard_dir.clone();
     
-    let app = Router::new()
+    Router::new()
         .merge(api_router)
         .nest_service("/assets", ServeDir::new(assets_dir))
         .fallback(move || {
@@ -131,16 +189,5 @@ pub async fn start_dashboard(
                     Err(_) => (StatusCode::NOT_FOUND, "index.html not found").into_response(),
                 }
             }
-        });
-
-    let addr = format!("0.0.0.0:{}", port);
-    println!("🌐 Dashboard server starting on http://{}", addr);
-    println!("   ローカルアクセス: http://127.0.0.1:{}", port);
-    println!("   ネットワークアクセス: http://<your-ip>:{}", port);
-    
-    let listener = tokio::net::TcpListener::bind(&addr).await?;
-    axum::serve(listener, app).await?;
-
-    Ok(())
+        })
 }
-
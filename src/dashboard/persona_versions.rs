@@ -0,0 +1,149 @@
+use axum::{
+    extract::{Extension, Path, Query},
+    http::StatusCode,
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+use super::auth::AuthSession;
+use crate::database as db;
+use tracing::{error, info};
+
+/// 版と、その版で使ったトークン
+#[derive(Debug, Serialize)]
+pub struct PersonaVersionData {
+    #[serde(flatten)]
+    pub version: db::PersonaVersion,
+    /// 現在使われている版か
+    pub active: bool,
+    pub usage: db::PersonaVersionUsage,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DiffQuery {
+    /// 比較元の版（省略時は1つ前の版）
+    pub against: Option<i64>,
+}
+
+/// 差分の1行
+#[derive(Debug, Serialize)]
+pub struct DiffLine {
+    /// "equal" / "insert" / "delete"
+    pub op: &'static str,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PersonaDiff {
+    /// 比較元（最初の版ならnull）
+    pub from: Option<db::PersonaVersion>,
+    pub to: db::PersonaVersion,
+    pub prompt: Vec<DiffLine>,
+    /// kind 0は整形したJSONで比較する
+    pub content: Vec<DiffLine>,
+}
+
+/// Botの版を取得（別のBotの版なら404）
+fn find_bot_version(conn: &rusqlite::Connection, pubkey: &str, id: i64) -> Result<db::PersonaVersion, StatusCode> {
+    db::get_persona_version(conn, id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .filter(|v| v.bot_pubkey == pubkey)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    // 最後の行の改行の有無で差分にならないように揃える
+    let terminated = |text: &str| match text.is_empty() || text.ends_with('\n') {
+        true => text.to_string(),
+        false => format!("{}\n", text),
+    };
+    let (old, new) = (terminated(old), terminated(new));
+    TextDiff::from_lines(&old, &new)
+        .iter_all_changes()
+        .map(|change| DiffLine {
+            op: match change.tag() {
+                ChangeTag::Equal => "equal",
+                ChangeTag::Insert => "insert",
+                ChangeTag::Delete => "delete",
+            },
+            text: change.value().trim_end_matches('\n').to_string(),
+        })
+        .collect()
+}
+
+/// kind 0のJSONを1項目1行に整形（JSONでなければそのまま）
+fn pretty_kind0(content: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(content)
+        .and_then(|json| serde_json::to_string_pretty(&json))
+        .unwrap_or_else(|_| content.to_string())
+}
+
+/// Botのプロンプト・kind 0の変更履歴（新しい順）
+pub async fn list_persona_versions_handler(
+    Path(pubkey): Path<String>,
+) -> Result<Json<Vec<PersonaVersionData>>, StatusCode> {
+    let conn = db::connect().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    db::find_person(&conn, &pubkey)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let versions = db::list_persona_versions(&conn, &pubkey).map_err(|e| {
+        error!("プロンプト履歴取得エラー: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let mut usage = db::get_persona_version_usage(&conn, &pubkey).map_err(|e| {
+        error!("版ごとのトークン使用量取得エラー: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let data = versions
+        .into_iter()
+        .enumerate()
+        .map(|(i, version)| PersonaVersionData {
+            active: i == 0,
+            usage: usage.remove(&version.id).unwrap_or_default(),
+            version,
+        })
+        .collect();
+    Ok(Json(data))
+}
+
+/// 版同士の差分
+pub async fn persona_version_diff_handler(
+    Path((pubkey, id)): Path<(String, i64)>,
+    Query(query): Query<DiffQuery>,
+) -> Result<Json<PersonaDiff>, StatusCode> {
+    let conn = db::connect().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let to = find_bot_version(&conn, &pubkey, id)?;
+    let from = match query.against {
+        Some(against) => Some(find_bot_version(&conn, &pubkey, against)?),
+        None => db::get_previous_persona_version(&conn, &to).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+    };
+
+    let (old_prompt, old_content) = from
+        .as_ref()
+        .map(|v| (v.prompt.as_str(), pretty_kind0(&v.content)))
+        .unwrap_or_default();
+    Ok(Json(PersonaDiff {
+        prompt: diff_lines(old_prompt, &to.prompt),
+        content: diff_lines(&old_content, &pretty_kind0(&to.content)),
+        from,
+        to,
+    }))
+}
+
+/// 指定した版に戻す（戻した内容が新しい版になる）
+pub async fn rollback_persona_version_handler(
+    Extension(session): Extension<AuthSession>,
+    Path((pubkey, id)): Path<(String, i64)>,
+) -> Result<Json<db::PersonaVersion>, StatusCode> {
+    let conn = db::connect().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let version_id = db::rollback_persona(&conn, &pubkey, id, Some(session.author()))
+        .map_err(|e| {
+            error!("プロンプトのロールバックエラー: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    info!("⏪ プロンプトを版{}に戻しました: {} (新しい版: {})", id, pubkey, version_id);
+
+    find_bot_version(&conn, &pubkey, version_id).map(Json)
+}
@@ -0,0 +1,144 @@
+use axum::{
+    extract::Path,
+    http::StatusCode,
+    response::Json,
+};
+use chrono::Local;
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::config::{self, AppConfig};
+use crate::conversation;
+use crate::database as db;
+use crate::gpt::{self, GptResponseWithMentalDiary};
+use tracing::{error, info};
+
+/// 返信の種類
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaygroundMode {
+    /// Bot宛てのメンション（会話履歴とユーザーの印象を使う）
+    #[default]
+    Mention,
+    /// タイムラインの投稿へのエアリプ
+    AirReply,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PlaygroundRequest {
+    pub message: String,
+    /// 話しかけるユーザー（省略時はランダムな鍵）
+    #[serde(default)]
+    pub user_pubkey: Option<String>,
+    #[serde(default)]
+    pub user_name: Option<String>,
+    #[serde(default)]
+    pub mode: PlaygroundMode,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlaygroundResponse {
+    pub mode: PlaygroundMode,
+    pub user_pubkey: String,
+    pub system_prompt: String,
+    /// 会話履歴などから組み立てたコンテキスト（なければメッセージをそのまま渡す）
+    pub context: Option<String>,
+    pub raw_output: String,
+    pub parsed: Option<GptResponseWithMentalDiary>,
+    pub parse_error: Option<String>,
+    pub prompt_tokens: usize,
+    pub completion_tokens: usize,
+}
+
+/// Botの返信を試す（送信・会話ログ・印象・心境の保存は行わない）
+pub async fn playground_handler(
+    Path(pubkey): Path<String>,
+    Json(req): Json<PlaygroundRequest>,
+) -> Result<Json<PlaygroundResponse>, StatusCode> {
+    if req.message.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let user_pubkey = match req.user_pubkey.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        Some(user) => PublicKey::parse(user).map_err(|_| StatusCode::BAD_REQUEST)?.to_hex(),
+        None => Keys::generate().public_key().to_hex(),
+    };
+    let config = config::load_config().map_err(|e| {
+        error!("設定ファイル読み込みエラー: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let person = {
+        let conn = db::connect().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        db::find_person(&conn, &pubkey)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .ok_or(StatusCode::NOT_FOUND)?
+    };
+    info!("🧪 プロンプト試行: {} ({:?})", person.pubkey, req.mode);
+
+    // コンテキストの準備はConnectionを保持したまま待つので、ワーカーと同じく専用のスレッドで実行する
+    tokio::task::spawn_blocking(move || {
+        let rt = tokio::runtime::Builder::new_current_thread().enable_all().build().map_err(|e| e.to_string())?;
+        rt.block_on(dry_run(config, person, user_pubkey, req)).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    .map(Json)
+    .map_err(|e| {
+        error!("プロンプト試行エラー: {}", e);
+        StatusCode::BAD_GATEWAY
+    })
+}
+
+async fn dry_run(
+    config: AppConfig,
+    person: db::Person,
+    user_pubkey: String,
+    req: PlaygroundRequest,
+) -> Result<PlaygroundResponse, Box<dyn std::error::Error>> {
+    let user_name = req.user_name.as_deref().map(str::trim).filter(|s| !s.is_empty());
+    let context = match req.mode {
+        PlaygroundMode::Mention => {
+            // 会話が長ければ要約も作る（保存はしない）
+            let conn = db::connect()?;
+            let context = conversation::preview_context_for_reply(
+                &conn,
+                &person.pubkey,
+                &user_pubkey,
+                &req.message,
+                50,
+                &config,
+                None,
+                user_name,
+            )
+            .await?;
+            Some(context).filter(|c| !c.is_empty())
+        }
+        PlaygroundMode::AirReply => {
+            // エアリプの単一投稿モードと同じ形式
+            let display_name = user_name.map(str::to_string).unwrap_or_else(|| format!("{}...", &user_pubkey[..8]));
+            let time_str = Local::now().format("%m/%d %H:%M");
+            Some(format!("【投稿】[{}] {}: {}", time_str, display_name, req.message))
+        }
+    };
+
+    let trace = gpt::dry_run_reply_with_mental_diary(
+        &person,
+        (req.mode == PlaygroundMode::Mention).then_some(user_pubkey.as_str()),
+        &person.prompt,
+        &req.message,
+        context.clone(),
+        user_name,
+        &config,
+    )
+    .await?;
+
+    Ok(PlaygroundResponse {
+        mode: req.mode,
+        user_pubkey,
+        system_prompt: trace.system_prompt,
+        context,
+        raw_output: trace.raw_output,
+        parsed: trace.response,
+        parse_error: trace.parse_error,
+        prompt_tokens: trace.prompt_tokens,
+        completion_tokens: trace.completion_tokens,
+    })
+}
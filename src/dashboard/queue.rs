@@ -0,0 +1,114 @@
+use axum::{
+    extract::{State, Path, Query},
+    response::Json,
+    http::StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use super::types::DashboardState;
+use crate::database as db;
+use tracing::{error, info};
+
+/// キューの状態
+#[derive(Debug, Serialize)]
+pub struct QueueOverview {
+    pub queue_size: i64,
+    pub dead_letter_count: i64,
+    pub max_attempts: i64,
+    pub items: Vec<db::QueueItem>,
+    pub dead_letters: Vec<db::DeadLetter>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QueueQuery {
+    pub limit: Option<usize>,
+}
+
+/// キューとdead_letterの一覧
+pub async fn get_queue_handler(
+    State(_state): State<DashboardState>,
+    Query(query): Query<QueueQuery>,
+) -> Result<Json<QueueOverview>, StatusCode> {
+    let conn = db::connect().map_err(|e| {
+        error!("DB接続エラー: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let limit = query.limit.unwrap_or(100);
+
+    let queue_size = db::get_queue_size(&conn).map_err(|e| {
+        error!("キューサイズ取得エラー: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let dead_letter_count = db::count_dead_letters(&conn).map_err(|e| {
+        error!("dead_letter件数取得エラー: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let items = db::list_queue_items(&conn, limit).map_err(|e| {
+        error!("キュー取得エラー: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let dead_letters = db::list_dead_letters(&conn, limit).map_err(|e| {
+        error!("dead_letter取得エラー: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(QueueOverview {
+        queue_size,
+        dead_letter_count,
+        max_attempts: db::MAX_QUEUE_ATTEMPTS,
+        items,
+        dead_letters,
+    }))
+}
+
+/// キューからイベントを削除
+pub async fn delete_queue_item_handler(
+    State(_state): State<DashboardState>,
+    Path(id): Path<i64>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let conn = db::connect().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let deleted = db::delete_queue_event(&conn, id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !deleted {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
+/// dead_letterのイベントをキューに戻す
+pub async fn requeue_dead_letter_handler(
+    State(_state): State<DashboardState>,
+    Path(id): Path<i64>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let conn = db::connect().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let queue_id = db::requeue_dead_letter(&conn, id)
+        .map_err(|e| {
+            error!("dead_letter再投入エラー: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    info!("🔁 dead_letter {} をキューに戻しました (queue_id: {})", id, queue_id);
+    crate::event_processor::notify_queue_workers();
+    Ok(Json(serde_json::json!({ "queue_id": queue_id })))
+}
+
+/// dead_letterから削除（単一）
+pub async fn delete_dead_letter_handler(
+    State(_state): State<DashboardState>,
+    Path(id): Path<i64>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let conn = db::connect().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let deleted = db::delete_dead_letter(&conn, id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !deleted {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
+/// dead_letter全削除
+pub async fn purge_dead_letters_handler(
+    State(_state): State<DashboardState>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let conn = db::connect().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let deleted = db::purge_dead_letters(&conn).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    info!("🗑️ dead_letterを全削除しました ({}件)", deleted);
+    Ok(Json(serde_json::json!({ "deleted": deleted })))
+}
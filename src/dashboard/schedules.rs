@@ -0,0 +1,134 @@
+use axum::{
+    extract::Path,
+    http::StatusCode,
+    response::Json,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use crate::database as db;
+use crate::scheduler::{self, ScheduleKind};
+use tracing::{error, info};
+
+/// 定期投稿の種類
+#[derive(Debug, Serialize)]
+pub struct ScheduleKindInfo {
+    pub name: String,
+    pub display_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScheduleRequest {
+    pub kind: String,
+    pub cron: String,
+    #[serde(default)]
+    pub instruction: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl ScheduleRequest {
+    /// 種類とcronを検証し、次回の実行時刻を返す
+    fn validate(&self) -> Result<Option<i64>, StatusCode> {
+        if ScheduleKind::from_str(&self.kind).is_none() {
+            error!("不明な定期投稿の種類: {}", self.kind);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+        scheduler::next_run_at(&self.cron, Utc::now().timestamp()).map_err(|e| {
+            error!("{}", e);
+            StatusCode::BAD_REQUEST
+        })
+    }
+
+    fn instruction(&self) -> Option<&str> {
+        self.instruction.as_deref().map(str::trim).filter(|s| !s.is_empty())
+    }
+}
+
+/// 定期投稿の種類一覧
+pub async fn list_schedule_kinds_handler() -> Json<Vec<ScheduleKindInfo>> {
+    let kinds = ScheduleKind::all()
+        .into_iter()
+        .map(|kind| ScheduleKindInfo {
+            name: kind.name().to_string(),
+            display_name: kind.display_name().to_string(),
+        })
+        .collect();
+    Json(kinds)
+}
+
+/// Botの定期投稿一覧
+pub async fn list_schedules_handler(
+    Path(pubkey): Path<String>,
+) -> Result<Json<Vec<db::BotSchedule>>, StatusCode> {
+    let conn = db::connect().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let schedules = db::get_bot_schedules(&conn, &pubkey).map_err(|e| {
+        error!("定期投稿一覧取得エラー: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(Json(schedules))
+}
+
+/// 定期投稿を追加
+pub async fn create_schedule_handler(
+    Path(pubkey): Path<String>,
+    Json(req): Json<ScheduleRequest>,
+) -> Result<Json<db::BotSchedule>, StatusCode> {
+    let next_run_at = req.validate()?;
+    let conn = db::connect().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if db::find_person(&conn, &pubkey).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?.is_none() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let id = db::insert_schedule(&conn, &pubkey, &req.kind, req.cron.trim(), req.instruction(), req.enabled, next_run_at)
+        .map_err(|e| {
+            error!("定期投稿追加エラー: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    let schedule = db::get_schedule(&conn, id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+    info!("🗓️ 定期投稿を追加: {} {} ({})", pubkey, req.kind, req.cron);
+    Ok(Json(schedule))
+}
+
+/// 定期投稿を更新（次回の実行時刻は現在時刻から計算し直す）
+pub async fn update_schedule_handler(
+    Path(id): Path<i64>,
+    Json(req): Json<ScheduleRequest>,
+) -> Result<Json<db::BotSchedule>, StatusCode> {
+    let next_run_at = req.validate()?;
+    let conn = db::connect().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let updated = db::update_schedule(&conn, id, &req.kind, req.cron.trim(), req.instruction(), req.enabled, next_run_at)
+        .map_err(|e| {
+            error!("定期投稿更新エラー: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if !updated {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    let schedule = db::get_schedule(&conn, id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(schedule))
+}
+
+/// 定期投稿を削除
+pub async fn delete_schedule_handler(
+    Path(id): Path<i64>,
+) -> Result<StatusCode, StatusCode> {
+    let conn = db::connect().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let deleted = db::delete_schedule(&conn, id).map_err(|e| {
+        error!("定期投稿削除エラー: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    if deleted {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(StatusCode::NOT_FOUND)
+    }
+}
@@ -5,6 +5,57 @@ use axum::{
 };
 use super::types::DashboardState;
 use crate::database as db;
+use std::ops::RangeInclusive;
+use tracing::info;
+
+/// 値の範囲が決まっているシステム設定の許容範囲
+pub(crate) enum SettingRange {
+    Int(RangeInclusive<i64>),
+    Float(RangeInclusive<f64>),
+}
+
+/// 画面・管理者コマンドから変更できる数値のシステム設定（保存APIと管理者コマンドで同じ範囲を使う）
+pub(crate) const EDITABLE_SETTINGS: &[(&str, SettingRange)] = &[
+    ("follower_cache_ttl", SettingRange::Int(60..=604800)), // 最小1分、最大7日間
+    ("reaction_percent", SettingRange::Int(0..=100)),
+    ("reaction_freq", SettingRange::Int(1..=i64::MAX)),
+    ("timeline_size", SettingRange::Int(1..=1000)),
+    ("conversation_limit_count", SettingRange::Int(1..=100)),
+    ("conversation_limit_minutes", SettingRange::Int(1..=1440)),
+    ("rag_similarity_threshold", SettingRange::Float(0.0..=1.0)),
+    ("gpt_answer_length", SettingRange::Int(10..=1000)),
+    ("gpt_timeout", SettingRange::Int(10..=300)),
+    ("gemini_search_timeout", SettingRange::Int(10..=600)),
+    ("recent_context_count", SettingRange::Int(1..=100)),
+    ("summary_threshold", SettingRange::Int(1000..=50000)),
+    ("max_summary_tokens", SettingRange::Int(1000..=100000)),
+    ("max_impression_length", SettingRange::Int(50..=2000)),
+    ("max_mental_diary_length", SettingRange::Int(100..=5000)),
+];
+
+/// 設定値を検証して保存する形に整える（変更できないキー・範囲外の値はエラーメッセージ）
+pub(crate) fn validate_setting(key: &str, value: &str) -> Result<String, String> {
+    let (_, range) = EDITABLE_SETTINGS
+        .iter()
+        .find(|(name, _)| *name == key)
+        .ok_or_else(|| format!("{} は変更できる設定ではありません", key))?;
+    match range {
+        SettingRange::Int(range) => match value.trim().parse::<i64>() {
+            Ok(v) if range.contains(&v) => Ok(v.to_string()),
+            _ if *range.end() == i64::MAX => Err(format!("{} は{}以上の整数で指定してください", key, range.start())),
+            _ => Err(format!("{} は{}〜{}の整数で指定してください", key, range.start(), range.end())),
+        },
+        SettingRange::Float(range) => match value.trim().parse::<f64>() {
+            Ok(v) if range.contains(&v) => Ok(v.to_string()),
+            _ => Err(format!("{} は{}〜{}の数値で指定してください", key, range.start(), range.end())),
+        },
+    }
+}
+
+/// 保存APIの数値を検証（範囲外はBAD_REQUEST）
+fn check_setting(key: &str, value: impl ToString) -> Result<(), StatusCode> {
+    validate_setting(key, &value.to_string()).map(|_| ()).map_err(|_| StatusCode::BAD_REQUEST)
+}
 
 /// グローバル一時停止状態の取得
 pub async fn get_global_pause_handler(
@@ -26,7 +77,7 @@ pub async fn set_global_pause_handler(
     let value = if paused { "true" } else { "false" };
     db::set_system_setting(&conn, "global_pause", value).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
-    println!("🔔 グローバル一時停止: {}", if paused { "有効" } else { "無効" });
+    info!("🔔 グローバル一時停止: {}", if paused { "有効" } else { "無効" });
     
     Ok(Json(serde_json::json!({ "paused": paused })))
 }
@@ -55,15 +106,12 @@ pub async fn set_follower_cache_ttl_handler(
     let conn = db::connect().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     let ttl_seconds = req["ttl_seconds"].as_i64().ok_or(StatusCode::BAD_REQUEST)?;
     
-    // 最小1分、最大7日間
-    if ttl_seconds < 60 || ttl_seconds > 604800 {
-        return Err(StatusCode::BAD_REQUEST);
-    }
+    check_setting("follower_cache_ttl", ttl_seconds)?;
     
     db::set_system_setting(&conn, "follower_cache_ttl", &ttl_seconds.to_string())
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
-    println!("⏰ フォロワーキャッシュ有効時間: {}秒 ({}時間)", ttl_seconds, ttl_seconds / 3600);
+    info!("⏰ フォロワーキャッシュ有効時間: {}秒 ({}時間)", ttl_seconds, ttl_seconds / 3600);
     
     Ok(Json(serde_json::json!({ "ttl_seconds": ttl_seconds })))
 }
@@ -97,30 +145,24 @@ pub async fn set_bot_behavior_settings_handler(
     let conn = db::connect().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
     if let Some(reaction_percent) = req["reaction_percent"].as_i64() {
-        if reaction_percent < 0 || reaction_percent > 100 {
-            return Err(StatusCode::BAD_REQUEST);
-        }
+        check_setting("reaction_percent", reaction_percent)?;
         db::set_system_setting(&conn, "reaction_percent", &reaction_percent.to_string())
             .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-        println!("🎲 リアクション確率: {}%", reaction_percent);
+        info!("🎲 リアクション確率: {}%", reaction_percent);
     }
     
     if let Some(reaction_freq) = req["reaction_freq"].as_i64() {
-        if reaction_freq < 1 {
-            return Err(StatusCode::BAD_REQUEST);
-        }
+        check_setting("reaction_freq", reaction_freq)?;
         db::set_system_setting(&conn, "reaction_freq", &reaction_freq.to_string())
             .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-        println!("⏱️ リアクション頻度: {}秒", reaction_freq);
+        info!("⏱️ リアクション頻度: {}秒", reaction_freq);
     }
     
     if let Some(timeline_size) = req["timeline_size"].as_i64() {
-        if timeline_size < 1 || timeline_size > 1000 {
-            return Err(StatusCode::BAD_REQUEST);
-        }
+        check_setting("timeline_size", timeline_size)?;
         db::set_system_setting(&conn, "timeline_size", &timeline_size.to_string())
             .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-        println!("📜 タイムラインサイズ: {}", timeline_size);
+        info!("📜 タイムラインサイズ: {}", timeline_size);
     }
     
     Ok(Json(serde_json::json!({ "success": true })))
@@ -153,21 +195,17 @@ pub async fn set_conversation_limit_settings_handler(
     let conn = db::connect().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
     if let Some(count) = req["count"].as_i64() {
-        if count < 1 || count > 100 {
-            return Err(StatusCode::BAD_REQUEST);
-        }
+        check_setting("conversation_limit_count", count)?;
         db::set_system_setting(&conn, "conversation_limit_count", &count.to_string())
             .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-        println!("💬 会話制限回数: {}回", count);
+        info!("💬 会話制限回数: {}回", count);
     }
     
     if let Some(minutes) = req["minutes"].as_i64() {
-        if minutes < 1 || minutes > 1440 {
-            return Err(StatusCode::BAD_REQUEST);
-        }
+        check_setting("conversation_limit_minutes", minutes)?;
         db::set_system_setting(&conn, "conversation_limit_minutes", &minutes.to_string())
             .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-        println!("⏰ 会話制限時間: {}分", minutes);
+        info!("⏰ 会話制限時間: {}分", minutes);
     }
     
     Ok(Json(serde_json::json!({ "success": true })))
@@ -202,12 +240,10 @@ pub async fn set_rag_settings_handler(
     let conn = db::connect().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
     if let Some(threshold) = req["similarity_threshold"].as_f64() {
-        if threshold < 0.0 || threshold > 1.0 {
-            return Err(StatusCode::BAD_REQUEST);
-        }
+        check_setting("rag_similarity_threshold", threshold)?;
         db::set_system_setting(&conn, "rag_similarity_threshold", &threshold.to_string())
             .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-        println!("🔍 RAG類似度閾値: {}", threshold);
+        info!("🔍 RAG類似度閾値: {}", threshold);
     }
     
     Ok(Json(serde_json::json!({ "success": true })))
@@ -252,75 +288,59 @@ pub async fn set_gpt_settings_handler(
     let conn = db::connect().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
     if let Some(answer_length) = req["answer_length"].as_i64() {
-        if answer_length < 10 || answer_length > 1000 {
-            return Err(StatusCode::BAD_REQUEST);
-        }
+        check_setting("gpt_answer_length", answer_length)?;
         db::set_system_setting(&conn, "gpt_answer_length", &answer_length.to_string())
             .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-        println!("📝 GPT回答長: {}文字", answer_length);
+        info!("📝 GPT回答長: {}文字", answer_length);
     }
     
     if let Some(timeout) = req["timeout"].as_i64() {
-        if timeout < 10 || timeout > 300 {
-            return Err(StatusCode::BAD_REQUEST);
-        }
+        check_setting("gpt_timeout", timeout)?;
         db::set_system_setting(&conn, "gpt_timeout", &timeout.to_string())
             .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-        println!("⏱️ GPTタイムアウト: {}秒", timeout);
+        info!("⏱️ GPTタイムアウト: {}秒", timeout);
     }
     
     if let Some(gemini_search_timeout) = req["gemini_search_timeout"].as_i64() {
-        if gemini_search_timeout < 10 || gemini_search_timeout > 600 {
-            return Err(StatusCode::BAD_REQUEST);
-        }
+        check_setting("gemini_search_timeout", gemini_search_timeout)?;
         db::set_system_setting(&conn, "gemini_search_timeout", &gemini_search_timeout.to_string())
             .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-        println!("🔍 Gemini Searchタイムアウト: {}秒", gemini_search_timeout);
+        info!("🔍 Gemini Searchタイムアウト: {}秒", gemini_search_timeout);
     }
     
     if let Some(recent_context_count) = req["recent_context_count"].as_i64() {
-        if recent_context_count < 1 || recent_context_count > 100 {
-            return Err(StatusCode::BAD_REQUEST);
-        }
+        check_setting("recent_context_count", recent_context_count)?;
         db::set_system_setting(&conn, "recent_context_count", &recent_context_count.to_string())
             .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-        println!("💬 最近のやり取り件数: {}件", recent_context_count);
+        info!("💬 最近のやり取り件数: {}件", recent_context_count);
     }
     
     if let Some(summary_threshold) = req["summary_threshold"].as_i64() {
-        if summary_threshold < 1000 || summary_threshold > 50000 {
-            return Err(StatusCode::BAD_REQUEST);
-        }
+        check_setting("summary_threshold", summary_threshold)?;
         db::set_system_setting(&conn, "summary_threshold", &summary_threshold.to_string())
             .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-        println!("📊 要約開始閾値: {}文字", summary_threshold);
+        info!("📊 要約開始閾値: {}文字", summary_threshold);
     }
     
     if let Some(max_summary_tokens) = req["max_summary_tokens"].as_i64() {
-        if max_summary_tokens < 1000 || max_summary_tokens > 100000 {
-            return Err(StatusCode::BAD_REQUEST);
-        }
+        check_setting("max_summary_tokens", max_summary_tokens)?;
         db::set_system_setting(&conn, "max_summary_tokens", &max_summary_tokens.to_string())
             .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-        println!("🎫 要約最大トークン数: {}トークン", max_summary_tokens);
+        info!("🎫 要約最大トークン数: {}トークン", max_summary_tokens);
     }
     
     if let Some(max_impression_length) = req["max_impression_length"].as_i64() {
-        if max_impression_length < 50 || max_impression_length > 2000 {
-            return Err(StatusCode::BAD_REQUEST);
-        }
+        check_setting("max_impression_length", max_impression_length)?;
         db::set_system_setting(&conn, "max_impression_length", &max_impression_length.to_string())
             .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-        println!("💭 印象最大文字数: {}文字", max_impression_length);
+        info!("💭 印象最大文字数: {}文字", max_impression_length);
     }
     
     if let Some(max_mental_diary_length) = req["max_mental_diary_length"].as_i64() {
-        if max_mental_diary_length < 100 || max_mental_diary_length > 5000 {
-            return Err(StatusCode::BAD_REQUEST);
-        }
+        check_setting("max_mental_diary_length", max_mental_diary_length)?;
         db::set_system_setting(&conn, "max_mental_diary_length", &max_mental_diary_length.to_string())
             .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-        println!("📔 心境最大文字数: {}文字", max_mental_diary_length);
+        info!("📔 心境最大文字数: {}文字", max_mental_diary_length);
     }
     
     Ok(Json(serde_json::json!({ "success": true })))
@@ -367,7 +387,7 @@ pub async fn set_relay_settings_handler(
             .collect();
         db::set_system_setting(&conn, "relay_write", &write_relays.join(","))
             .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-        println!("📡 書き込みリレー更新: {}", write_relays.join(", "));
+        info!("📡 書き込みリレー更新: {}", write_relays.join(", "));
     }
     
     if let Some(read) = req["read"].as_array() {
@@ -377,7 +397,7 @@ pub async fn set_relay_settings_handler(
             .collect();
         db::set_system_setting(&conn, "relay_read", &read_relays.join(","))
             .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-        println!("📡 読み込みリレー更新: {}", read_relays.join(", "));
+        info!("📡 読み込みリレー更新: {}", read_relays.join(", "));
     }
     
     if let Some(search) = req["search"].as_array() {
@@ -387,7 +407,7 @@ pub async fn set_relay_settings_handler(
             .collect();
         db::set_system_setting(&conn, "relay_search", &search_relays.join(","))
             .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-        println!("📡 検索リレー更新: {}", search_relays.join(", "));
+        info!("📡 検索リレー更新: {}", search_relays.join(", "));
     }
     
     Ok(Json(serde_json::json!({ "success": true })))
@@ -479,7 +499,7 @@ pub async fn set_blacklist_settings_handler(
             .collect();
         db::set_system_setting(&conn, "blacklist", &blacklist_pubkeys.join(","))
             .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-        println!("🚫 ブラックリスト更新: {}件", blacklist_pubkeys.len());
+        info!("🚫 ブラックリスト更新: {}件", blacklist_pubkeys.len());
     }
     
     Ok(Json(serde_json::json!({ "success": true })))
@@ -3,26 +3,48 @@ use axum::{
     response::Json,
     http::StatusCode,
 };
-use super::types::{DashboardState, Stats};
+use super::types::{DashboardState, ErrorEntry, Stats};
 use crate::database as db;
 use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
+use tracing::error;
+
+/// 統計情報に含める最近のエラーの件数
+const RECENT_ERRORS: usize = 50;
 
 /// 統計情報を取得
 pub async fn stats_handler(
     State(state): State<DashboardState>,
 ) -> Result<Json<Stats>, StatusCode> {
     let conn = db::connect().map_err(|e| {
-        eprintln!("DB接続エラー: {}", e);
+        error!("DB接続エラー: {}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
     
     // DBから統計情報を取得
     let db_stats = db::get_dashboard_stats(&conn).map_err(|e| {
-        eprintln!("統計情報取得エラー: {}", e);
+        error!("統計情報取得エラー: {}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
     
+    // 最近のWARN/ERRORのログ
+    let error_log = db::get_recent_errors(&conn, RECENT_ERRORS)
+        .map_err(|e| {
+            error!("エラーログ取得エラー: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .into_iter()
+        .map(|entry| ErrorEntry {
+            timestamp: entry.timestamp,
+            error_type: entry.target,
+            message: entry.message,
+            level: entry.level,
+            event_id: entry.event_id,
+            bot_pubkey: entry.bot_pubkey,
+            user_pubkey: entry.user_pubkey,
+        })
+        .collect();
+    
     // bot_infoから実行時情報を取得
     let bot_info = state.bot_info.read().await;
     let uptime = state.start_time.elapsed().as_secs();
@@ -51,7 +73,12 @@ pub async fn stats_handler(
             total_searches: db_stats.total_searches,
             average_similarity: db_stats.average_similarity as f32,
         },
-        error_log: vec![],
+        engagement_stats: super::types::EngagementStats {
+            reactions_today: db_stats.reactions_today,
+            zaps_today: db_stats.zaps_today,
+            zap_sats_today: db_stats.zap_sats_today,
+        },
+        error_log,
     };
     
     Ok(Json(stats))
@@ -62,12 +89,12 @@ pub async fn daily_replies_handler(
     State(_state): State<DashboardState>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
     let conn = db::connect().map_err(|e| {
-        eprintln!("DB接続エラー: {}", e);
+        error!("DB接続エラー: {}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
     
     let results = db::get_bot_daily_reply_counts(&conn, 30).map_err(|e| {
-        eprintln!("日別返信数取得エラー: {}", e);
+        error!("日別返信数取得エラー: {}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
     
@@ -77,7 +104,7 @@ pub async fn daily_replies_handler(
     for (bot_pubkey, date, count) in results {
         bot_data
             .entry(bot_pubkey)
-            .or_insert_with(Vec::new)
+            .or_default()
             .push(serde_json::json!({
                 "date": date,
                 "count": count
@@ -93,14 +120,14 @@ pub async fn token_usage_stats_handler(
     axum::extract::Query(params): axum::extract::Query<std::collections::HashMap<String, String>>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
     let conn = db::connect().map_err(|e| {
-        eprintln!("DB接続エラー: {}", e);
+        error!("DB接続エラー: {}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
     
     let daily_usage = if let (Some(from), Some(to)) = (params.get("from"), params.get("to")) {
         // 日付範囲指定
         db::get_daily_token_usage_with_range(&conn, from, to).map_err(|e| {
-            eprintln!("トークン使用量取得エラー: {}", e);
+            error!("トークン使用量取得エラー: {}", e);
             StatusCode::INTERNAL_SERVER_ERROR
         })?
     } else {
@@ -111,7 +138,7 @@ pub async fn token_usage_stats_handler(
             .clamp(1, 365);
         
         db::get_daily_token_usage(&conn, days).map_err(|e| {
-            eprintln!("トークン使用量取得エラー: {}", e);
+            error!("トークン使用量取得エラー: {}", e);
             StatusCode::INTERNAL_SERVER_ERROR
         })?
     };
@@ -138,6 +165,8 @@ pub struct TokenDetail {
     prompt_text: String,
     completion_text: String,
     created_at: i64,
+    /// 使用時のプロンプトの版
+    persona_version_id: Option<i64>,
 }
 
 /// トークン使用量の詳細を取得
@@ -146,7 +175,7 @@ pub async fn token_details_handler(
     Query(query): Query<TokenDetailsQuery>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
     let conn = db::connect().map_err(|e| {
-        eprintln!("DB接続エラー: {}", e);
+        error!("DB接続エラー: {}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
     
@@ -159,13 +188,13 @@ pub async fn token_details_handler(
                 (SELECT content FROM events WHERE pubkey = tu.bot_pubkey AND kind = 0 LIMIT 1) as bot_kind0_content,
                 tc.name, tc.display_name, 
                 tu.prompt_tokens, tu.completion_tokens, tu.total_tokens,
-                tu.prompt_text, tu.completion_text, tu.created_at
+                tu.prompt_text, tu.completion_text, tu.created_at, tu.persona_version_id
          FROM token_usage tu
          INNER JOIN token_categories tc ON tu.category_id = tc.id
          ORDER BY tu.created_at DESC
          LIMIT ? OFFSET ?"
     ).map_err(|e| {
-        eprintln!("クエリ準備エラー: {}", e);
+        error!("クエリ準備エラー: {}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
     
@@ -182,16 +211,17 @@ pub async fn token_details_handler(
             prompt_text: row.get(8)?,
             completion_text: row.get(9)?,
             created_at: row.get(10)?,
+            persona_version_id: row.get(11)?,
         })
     }).map_err(|e| {
-        eprintln!("クエリ実行エラー: {}", e);
+        error!("クエリ実行エラー: {}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
     
     let mut results = Vec::new();
     for detail in details {
         results.push(detail.map_err(|e| {
-            eprintln!("行取得エラー: {}", e);
+            error!("行取得エラー: {}", e);
             StatusCode::INTERNAL_SERVER_ERROR
         })?);
     }
@@ -202,7 +232,7 @@ pub async fn token_details_handler(
         [],
         |row| row.get(0)
     ).map_err(|e| {
-        eprintln!("件数取得エラー: {}", e);
+        error!("件数取得エラー: {}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
     
@@ -6,6 +6,7 @@ use axum::{
 use super::types::DashboardState;
 use crate::database as db;
 use serde::{Deserialize, Serialize};
+use tracing::info;
 
 #[derive(Debug, Serialize)]
 pub struct SummaryData {
@@ -138,7 +139,7 @@ pub async fn update_summary_handler(
     )
     .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
-    println!("📝 要約ID{}を更新しました", id);
+    info!("📝 要約ID{}を更新しました", id);
     
     Ok(Json(serde_json::json!({ "success": true })))
 }
@@ -156,7 +157,7 @@ pub async fn delete_summary_handler(
     )
     .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
-    println!("🗑️ 要約ID{}を削除しました", id);
+    info!("🗑️ 要約ID{}を削除しました", id);
     
     Ok(Json(serde_json::json!({ "success": true })))
 }
@@ -197,10 +198,10 @@ pub async fn delete_summaries_bulk_handler(
     let deleted_count = conn.execute(&delete_query, [])
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
-    if req.search.is_some() && req.search.as_ref().unwrap().is_empty() == false {
-        println!("🗑️ Bot {} のフィルタ後要約 {}件を削除しました", pubkey, deleted_count);
+    if req.search.is_some() && !req.search.as_ref().unwrap().is_empty() {
+        info!("🗑️ Bot {} のフィルタ後要約 {}件を削除しました", pubkey, deleted_count);
     } else {
-        println!("🗑️ Bot {} の全要約 {}件を削除しました", pubkey, deleted_count);
+        info!("🗑️ Bot {} の全要約 {}件を削除しました", pubkey, deleted_count);
     }
     
     Ok(Json(serde_json::json!({ 
@@ -0,0 +1,50 @@
+use axum::{
+    extract::{Path, Query},
+    http::StatusCode,
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use crate::database as db;
+use crate::llm::tools::ToolRegistry;
+use tracing::error;
+
+/// 利用できるツール
+#[derive(Debug, Serialize)]
+pub struct ToolInfo {
+    pub name: String,
+    pub description: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ToolCallQuery {
+    pub limit: Option<usize>,
+}
+
+/// Botに許可できるツールの一覧
+pub async fn list_tools_handler() -> Json<Vec<ToolInfo>> {
+    let tools = ToolRegistry::builtin()
+        .definitions()
+        .into_iter()
+        .map(|tool| ToolInfo {
+            name: tool.name,
+            description: tool.description,
+        })
+        .collect();
+    Json(tools)
+}
+
+/// Botのツール呼び出し履歴（新しい順）
+pub async fn list_tool_calls_handler(
+    Path(bot_pubkey): Path<String>,
+    Query(query): Query<ToolCallQuery>,
+) -> Result<Json<Vec<db::ToolCallLog>>, StatusCode> {
+    let conn = db::connect().map_err(|e| {
+        error!("DB接続エラー: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let logs = db::get_tool_call_logs(&conn, &bot_pubkey, query.limit.unwrap_or(100)).map_err(|e| {
+        error!("ツール呼び出し履歴取得エラー: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(Json(logs))
+}
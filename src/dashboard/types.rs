@@ -3,6 +3,8 @@ use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::RwLock;
 use chrono::Utc;
+use crate::database::PersonOverrides;
+use super::auth::AuthState;
 
 /// ダッシュボードの状態
 #[derive(Clone)]
@@ -11,6 +13,7 @@ pub struct DashboardState {
     pub db_path: String,
     pub start_time: Arc<Instant>,
     pub bot_info: Arc<RwLock<BotInfo>>,
+    pub auth: Arc<AuthState>,
 }
 
 /// Bot実行情報
@@ -28,6 +31,7 @@ pub struct Stats {
     pub reply_stats: ReplyStats,
     pub conversation_stats: ConversationStats,
     pub rag_stats: RagStats,
+    pub engagement_stats: EngagementStats,
     pub error_log: Vec<ErrorEntry>,
 }
 
@@ -62,11 +66,25 @@ pub struct RagStats {
     pub average_similarity: f32,
 }
 
+/// Botの投稿に届いたリアクション・Zap（今日の分）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngagementStats {
+    pub reactions_today: u32,
+    pub zaps_today: u32,
+    pub zap_sats_today: i64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ErrorEntry {
     pub timestamp: i64,
+    /// ログを出したモジュール
     pub error_type: String,
     pub message: String,
+    /// "WARN" / "ERROR"
+    pub level: String,
+    pub event_id: Option<String>,
+    pub bot_pubkey: Option<String>,
+    pub user_pubkey: Option<String>,
 }
 
 impl Default for Stats {
@@ -95,6 +113,11 @@ impl Default for Stats {
                 total_searches: 0,
                 average_similarity: 0.0,
             },
+            engagement_stats: EngagementStats {
+                reactions_today: 0,
+                zaps_today: 0,
+                zap_sats_today: 0,
+            },
             error_log: vec![],
         }
     }
@@ -104,18 +127,21 @@ impl Default for Stats {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BotData {
     pub pubkey: String,
-    pub secretkey: String,
+    /// 管理者が明示的に求めたときだけ返す
+    pub secretkey: Option<String>,
     pub prompt: String,
     pub content: String,
     pub status: i32,
     pub air_reply_single_ratio: Option<i32>,
+    pub overrides: PersonOverrides,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct BotRequest {
-    pub secretkey: String,
+    pub secretkey: Option<String>,  // 更新時は省略すると既存の秘密鍵を維持
     pub prompt: String,
     pub content: String,
     pub air_reply_single_ratio: Option<i32>,
+    pub overrides: Option<PersonOverrides>,  // 省略時は既存の個別設定を維持
 }
 
@@ -6,9 +6,10 @@ use axum::{
 use super::types::DashboardState;
 use crate::{database as db, config};
 use serde::Serialize;
-use nostr_sdk::{ToBech32, Keys, Client, Filter, Kind, PublicKey};
+use nostr_sdk::{ToBech32, Filter, Kind, PublicKey};
 use std::time::Duration;
 use std::fs::File;
+use tracing::info;
 
 #[derive(Debug, Serialize)]
 pub struct UserKind0 {
@@ -58,7 +59,7 @@ pub async fn get_kind0_handler(
     }
     
     // DBにない場合はリレーから取得
-    println!("[Kind0] DBに情報がないため、リレーから取得: {}", pubkey);
+    info!("[Kind0] DBに情報がないため、リレーから取得: {}", pubkey);
     
     match fetch_kind0_from_relay(&pubkey).await {
         Ok(kind0_info) => {
@@ -69,7 +70,7 @@ pub async fn get_kind0_handler(
                     if let Ok(conn) = db::connect() {
                         if let Ok(event) = serde_json::from_str::<nostr_sdk::Event>(&event_json_clone) {
                             let _ = db::insert_event(&conn, &event, None);
-                            println!("[Kind0] リレーから取得してDBに保存");
+                            info!("[Kind0] リレーから取得してDBに保存");
                         }
                     }
                 });
@@ -87,7 +88,7 @@ pub async fn get_kind0_handler(
         }
         Err(e) => {
             // リレーからも取得できなかった場合
-            println!("[Kind0] リレーからも取得できませんでした: {} ({})", pubkey, e);
+            info!("[Kind0] リレーからも取得できませんでした: {} ({})", pubkey, e);
         }
     }
     
@@ -115,29 +116,23 @@ struct Kind0Info {
 }
 
 async fn fetch_kind0_from_relay(pubkey: &str) -> Result<Kind0Info, Box<dyn std::error::Error>> {
-    let file = File::open("../config.yml")?;
+    let file = File::open(crate::config::config_path())?;
     let config: config::AppConfig = serde_yaml::from_reader(file)?;
     
     let public_key = PublicKey::from_hex(pubkey)?;
-    let keys = Keys::generate();
-    let client = Client::new(keys);
-    
-    // relay_readから取得
-    for relay in config.relay_servers.read.iter() {
-        let _ = client.add_relay(relay.clone()).await;
-    }
-    client.connect().await;
     
     let filter = Filter::new()
         .author(public_key)
         .kind(Kind::Metadata)
         .limit(1);
     
-    let events = client.fetch_events(filter, Duration::from_secs(10)).await?;
-    let _ = client.shutdown().await;
+    // relay_readから取得
+    let latest = crate::relay_pool::shared()
+        .fetch_latest(filter, &config.relay_servers.read, Duration::from_secs(10))
+        .await?;
     
-    if let Some(event) = events.first() {
-        let event_json = serde_json::to_string(event).map_err(|e| e.to_string());
+    if let Some(event) = latest {
+        let event_json = serde_json::to_string(&event).map_err(|e| e.to_string());
         
         if let Ok(metadata) = serde_json::from_str::<serde_json::Value>(&event.content) {
             return Ok(Kind0Info {
@@ -1,4 +1,4 @@
-use rusqlite::{params, Connection, Result};
+use rusqlite::{params, Connection, OptionalExtension, Result};
 use chrono::Utc;
 
 pub fn get_follower_cache(conn: &Connection, user_pubkey: &str, bot_pubkey: &str, ttl: i64) -> Result<Option<(bool, i64)>> {
@@ -106,6 +106,29 @@ pub fn set_kind0_cache(conn: &Connection, pubkey: &str, name: &str, content: Opt
     Ok(())
 }
 
+// Zapper cache functions（BotのLNURLサーバーのZap用公開鍵）
+
+/// キャッシュ済みなら`Some(nostrPubkey)`（Zap非対応のサーバーは`Some(None)`）、未取得・期限切れならNone
+pub fn get_zapper_cache(conn: &Connection, bot_pubkey: &str, lud16: &str, ttl: i64) -> Result<Option<Option<String>>> {
+    let now = Utc::now().timestamp();
+    let cached = conn
+        .query_row(
+            "SELECT nostr_pubkey, cached_at FROM zapper_cache WHERE bot_pubkey = ? AND lud16 = ?",
+            params![bot_pubkey, lud16],
+            |row| Ok((row.get::<_, Option<String>>(0)?, row.get::<_, i64>(1)?)),
+        )
+        .optional()?;
+    Ok(cached.filter(|(_, cached_at)| now - cached_at < ttl).map(|(nostr_pubkey, _)| nostr_pubkey))
+}
+
+pub fn set_zapper_cache(conn: &Connection, bot_pubkey: &str, lud16: &str, nostr_pubkey: Option<&str>) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO zapper_cache (bot_pubkey, lud16, nostr_pubkey, cached_at) VALUES (?, ?, ?, ?)",
+        params![bot_pubkey, lud16, nostr_pubkey, Utc::now().timestamp()],
+    )?;
+    Ok(())
+}
+
 // Timeline functions
 #[allow(dead_code)]
 pub fn add_timeline_post(conn: &Connection, pubkey: &str, name: Option<&str>, content: &str, timestamp: i64) -> Result<()> {
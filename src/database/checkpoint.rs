@@ -0,0 +1,29 @@
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use chrono::Utc;
+
+/// リレー・購読毎のチェックポイントを更新（既存より古いcreated_atでは巻き戻さない）
+///
+/// created_atは送信者が自由に付けられるため、未来の日時は現在時刻に丸める
+pub fn update_relay_checkpoint(conn: &Connection, relay_url: &str, subscription: &str, created_at: i64) -> Result<()> {
+    let now = Utc::now().timestamp();
+    let created_at = created_at.min(now);
+    conn.execute(
+        "INSERT INTO relay_checkpoints (relay_url, subscription, last_created_at, updated_at)
+         VALUES (?, ?, ?, ?)
+         ON CONFLICT(relay_url, subscription) DO UPDATE SET
+             last_created_at = MAX(last_created_at, excluded.last_created_at),
+             updated_at = excluded.updated_at",
+        params![relay_url, subscription, created_at, now],
+    )?;
+    Ok(())
+}
+
+/// リレー・購読毎のチェックポイントを取得
+pub fn get_relay_checkpoint(conn: &Connection, relay_url: &str, subscription: &str) -> Result<Option<i64>> {
+    conn.query_row(
+        "SELECT last_created_at FROM relay_checkpoints WHERE relay_url = ? AND subscription = ?",
+        params![relay_url, subscription],
+        |row| row.get(0),
+    )
+    .optional()
+}
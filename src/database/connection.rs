@@ -1,12 +1,23 @@
 use rusqlite::{Connection, Result};
+use std::env;
+use std::time::Duration;
+
+/// 他の接続が書き込み中のときに待つ時間（複数のワーカーが同時にキューを更新するため）
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// デフォルトのデータベースパス（環境変数NOSTRCHAN_DB_PATHで上書き可能）
+pub fn db_path() -> String {
+    env::var("NOSTRCHAN_DB_PATH").unwrap_or_else(|_| "../nostrchan.db".to_string())
+}
 
 /// デフォルトのデータベースに接続
 pub(crate) fn connect() -> Result<Connection> {
-    Connection::open("../nostrchan.db")
+    connect_at_path(&db_path())
 }
 
 /// 任意パスのSQLiteに接続（テーブル作成は行わない）
-#[allow(dead_code)]
 pub fn connect_at_path(path: &str) -> Result<Connection> {
-    Connection::open(path)
+    let conn = Connection::open(path)?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    Ok(conn)
 }
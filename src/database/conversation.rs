@@ -1,7 +1,18 @@
-use rusqlite::{params, Connection, Result};
+use rusqlite::{params, Connection, OptionalExtension, Result};
 use chrono::Utc;
 use super::EventRecord;
 
+/// Botの会話ログに記録済みのイベントならその会話ログのID
+pub fn find_conversation_log(conn: &Connection, bot_pubkey: &str, event_ref_id: i64) -> Result<Option<i64>> {
+    conn.query_row(
+        "SELECT id FROM conversation_logs WHERE bot_pubkey = ? AND event_ref_id = ? LIMIT 1",
+        params![bot_pubkey, event_ref_id],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+/// 会話ログを記録（記録済みなら既存のIDを返す。キューの再試行で二重に記録しないように）
 pub fn insert_conversation_log(
     conn: &Connection,
     bot_pubkey: &str,
@@ -11,6 +22,9 @@ pub fn insert_conversation_log(
     is_bot_message: bool,
     is_bot_conversation: bool,
 ) -> Result<i64> {
+    if let Some(id) = find_conversation_log(conn, bot_pubkey, event_ref_id)? {
+        return Ok(id);
+    }
     let mentioned_pubkeys_json = mentioned_pubkeys.map(|pks| {
         serde_json::to_string(pks).unwrap_or_default()
     });
@@ -34,6 +48,74 @@ pub fn insert_conversation_log(
     Ok(conn.last_insert_rowid())
 }
 
+/// DMの会話ログを記録（peer_pubkeyはDMの相手。エアリプや公開の会話履歴には含めない。記録済みなら既存のID）
+pub fn insert_direct_message_log(
+    conn: &Connection,
+    bot_pubkey: &str,
+    event_ref_id: i64,
+    peer_pubkey: &str,
+    is_bot_message: bool,
+) -> Result<i64> {
+    if let Some(id) = find_conversation_log(conn, bot_pubkey, event_ref_id)? {
+        return Ok(id);
+    }
+    let mentioned_pubkeys_json = serde_json::to_string(&[peer_pubkey]).unwrap_or_default();
+    let now = Utc::now().timestamp();
+    
+    conn.execute(
+        "INSERT INTO conversation_logs (bot_pubkey, event_ref_id, thread_root_id, mentioned_pubkeys_json, is_bot_message, is_bot_conversation, is_direct_message, logged_at)
+         VALUES (?, ?, NULL, ?, ?, 0, 1, ?)",
+        params![
+            bot_pubkey,
+            event_ref_id,
+            mentioned_pubkeys_json,
+            if is_bot_message { 1 } else { 0 },
+            now,
+        ],
+    )?;
+    
+    Ok(conn.last_insert_rowid())
+}
+
+/// 特定ユーザーとのDM履歴を取得
+pub fn get_direct_message_timeline(
+    conn: &Connection,
+    bot_pubkey: &str,
+    user_pubkey: &str,
+    limit: usize,
+) -> Result<Vec<EventRecord>> {
+    let mut stmt = conn.prepare(
+        "SELECT e.id, e.event_id, e.event_json, e.pubkey, e.kind, e.content, e.created_at, e.received_at,
+                e.language
+         FROM events e
+         INNER JOIN conversation_logs cl ON e.id = cl.event_ref_id
+         WHERE cl.bot_pubkey = ?
+           AND cl.is_direct_message = 1
+           AND cl.mentioned_pubkeys_json = ?
+         ORDER BY e.created_at DESC
+         LIMIT ?"
+    )?;
+    
+    let peer_json = serde_json::to_string(&[user_pubkey]).unwrap_or_default();
+    let events = stmt.query_map(params![bot_pubkey, peer_json, limit], |row| {
+        Ok(EventRecord {
+            id: row.get(0)?,
+            event_id: row.get(1)?,
+            event_json: row.get(2)?,
+            pubkey: row.get(3)?,
+            kind: row.get(4)?,
+            content: row.get(5)?,
+            created_at: row.get(6)?,
+            received_at: row.get(7)?,
+            language: row.get(8)?,
+        })
+    })?
+    .collect::<Result<Vec<_>>>()?;
+    
+    // 時系列順（古い順）に反転
+    Ok(events.into_iter().rev().collect())
+}
+
 /// bot別の会話タイムラインを取得
 #[allow(dead_code)]
 pub fn get_conversation_timeline(
@@ -46,6 +128,7 @@ pub fn get_conversation_timeline(
          FROM events e
          INNER JOIN conversation_logs cl ON e.id = cl.event_ref_id
          WHERE cl.bot_pubkey = ?
+           AND cl.is_direct_message = 0
          ORDER BY e.created_at DESC
          LIMIT ?"
     )?;
@@ -69,6 +152,42 @@ pub fn get_conversation_timeline(
     Ok(events.into_iter().rev().collect())
 }
 
+/// bot別の指定時刻以降の会話（DMを除く、古い順）
+pub fn get_conversation_timeline_since(
+    conn: &Connection,
+    bot_pubkey: &str,
+    since: i64,
+    limit: usize,
+) -> Result<Vec<EventRecord>> {
+    let mut stmt = conn.prepare(
+        "SELECT e.id, e.event_id, e.event_json, e.pubkey, e.kind, e.content, e.created_at, e.received_at, e.language
+         FROM events e
+         INNER JOIN conversation_logs cl ON e.id = cl.event_ref_id
+         WHERE cl.bot_pubkey = ?
+           AND cl.is_direct_message = 0
+           AND e.created_at >= ?
+         ORDER BY e.created_at DESC
+         LIMIT ?"
+    )?;
+    
+    let events = stmt.query_map(params![bot_pubkey, since, limit], |row| {
+        Ok(EventRecord {
+            id: row.get(0)?,
+            event_id: row.get(1)?,
+            event_json: row.get(2)?,
+            pubkey: row.get(3)?,
+            kind: row.get(4)?,
+            content: row.get(5)?,
+            created_at: row.get(6)?,
+            received_at: row.get(7)?,
+            language: row.get(8)?,
+        })
+    })?
+    .collect::<Result<Vec<_>>>()?;
+    
+    Ok(events.into_iter().rev().collect())
+}
+
 /// 特定ユーザーとの会話履歴を取得
 pub fn get_conversation_timeline_with_user(
     conn: &Connection,
@@ -82,6 +201,7 @@ pub fn get_conversation_timeline_with_user(
          FROM events e
          INNER JOIN conversation_logs cl ON e.id = cl.event_ref_id
          WHERE cl.bot_pubkey = ?
+           AND cl.is_direct_message = 0
            AND e.pubkey IN (?, ?)
          ORDER BY e.created_at DESC
          LIMIT ?"
@@ -122,6 +242,7 @@ pub fn get_conversation_timeline_in_thread(
              FROM events e
              INNER JOIN conversation_logs cl ON e.id = cl.event_ref_id
              WHERE cl.bot_pubkey = ?
+               AND cl.is_direct_message = 0
                AND cl.thread_root_id = ?
                AND e.pubkey IN (?, ?)
              ORDER BY e.created_at DESC
@@ -135,6 +256,7 @@ pub fn get_conversation_timeline_in_thread(
              FROM events e
              INNER JOIN conversation_logs cl ON e.id = cl.event_ref_id
              WHERE cl.bot_pubkey = ?
+               AND cl.is_direct_message = 0
                AND cl.thread_root_id IS NULL
                AND e.pubkey IN (?, ?)
              ORDER BY e.created_at DESC
@@ -0,0 +1,134 @@
+use rusqlite::{params, params_from_iter, Connection, Result};
+use chrono::Utc;
+use std::collections::HashMap;
+
+/// ベクトル化の対象
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmbeddingSource {
+    /// eventsテーブルの投稿（kind 1 / kind 42のみ）
+    Event,
+    /// conversation_summariesテーブルの要約
+    Summary,
+}
+
+impl EmbeddingSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Event => "event",
+            Self::Summary => "summary",
+        }
+    }
+}
+
+/// ベクトルをBLOB（f32リトルエンディアン）に変換
+fn encode_vector(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+/// BLOBからベクトルを復元
+fn decode_vector(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect()
+}
+
+/// ベクトルを保存（同じモデルで既にあれば上書き）
+pub fn insert_embedding(conn: &Connection, source: EmbeddingSource, source_id: i64, model: &str, vector: &[f32]) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO embeddings (source_type, source_id, model, dimensions, vector, created_at)
+         VALUES (?, ?, ?, ?, ?, ?)",
+        params![source.as_str(), source_id, model, vector.len() as i64, encode_vector(vector), Utc::now().timestamp()],
+    )?;
+    Ok(())
+}
+
+/// この回数ベクトル化に失敗した行は対象から外す
+pub const MAX_EMBEDDING_ATTEMPTS: i64 = 3;
+
+/// まだベクトル化していないBotとの会話の投稿（新しい順、DM・失敗が続いたものは対象外）
+///
+/// 類似検索は会話履歴にしか使わないので、タイムライン全体はベクトル化しない
+pub fn get_events_without_embedding(conn: &Connection, model: &str, limit: usize) -> Result<Vec<(i64, String)>> {
+    let mut stmt = conn.prepare(
+        "SELECT e.id, e.content FROM events e
+         WHERE e.kind IN (1, 42) AND e.content != ''
+           AND EXISTS (
+               SELECT 1 FROM conversation_logs cl
+               WHERE cl.event_ref_id = e.id AND cl.is_direct_message = 0
+           )
+           AND NOT EXISTS (
+               SELECT 1 FROM embeddings em
+               WHERE em.source_type = 'event' AND em.source_id = e.id AND em.model = ?1
+           )
+           AND NOT EXISTS (
+               SELECT 1 FROM embedding_failures f
+               WHERE f.source_type = 'event' AND f.source_id = e.id AND f.model = ?1 AND f.attempts >= ?2
+           )
+         ORDER BY e.created_at DESC
+         LIMIT ?3",
+    )?;
+    let rows = stmt.query_map(params![model, MAX_EMBEDDING_ATTEMPTS, limit as i64], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+/// まだベクトル化していない要約（新しい順、失敗が続いたものは対象外）
+pub fn get_summaries_without_embedding(conn: &Connection, model: &str, limit: usize) -> Result<Vec<(i64, String)>> {
+    let mut stmt = conn.prepare(
+        "SELECT s.id, s.summary FROM conversation_summaries s
+         WHERE NOT EXISTS (
+             SELECT 1 FROM embeddings em
+             WHERE em.source_type = 'summary' AND em.source_id = s.id AND em.model = ?1
+         )
+           AND NOT EXISTS (
+             SELECT 1 FROM embedding_failures f
+             WHERE f.source_type = 'summary' AND f.source_id = s.id AND f.model = ?1 AND f.attempts >= ?2
+         )
+         ORDER BY s.created_at DESC
+         LIMIT ?3",
+    )?;
+    let rows = stmt.query_map(params![model, MAX_EMBEDDING_ATTEMPTS, limit as i64], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+/// ベクトル化の失敗を記録（失敗回数を1増やす）
+pub fn record_embedding_failure(conn: &Connection, source: EmbeddingSource, source_id: i64, model: &str, error: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO embedding_failures (source_type, source_id, model, attempts, last_error, failed_at)
+         VALUES (?, ?, ?, 1, ?, ?)
+         ON CONFLICT(source_type, source_id, model) DO UPDATE SET
+             attempts = attempts + 1,
+             last_error = excluded.last_error,
+             failed_at = excluded.failed_at",
+        params![source.as_str(), source_id, model, error, Utc::now().timestamp()],
+    )?;
+    Ok(())
+}
+
+/// 指定した行のベクトルをまとめて取得（ベクトル化済みのものだけ）
+pub fn get_embeddings(conn: &Connection, source: EmbeddingSource, model: &str, source_ids: &[i64]) -> Result<HashMap<i64, Vec<f32>>> {
+    if source_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let placeholders = vec!["?"; source_ids.len()].join(", ");
+    let mut stmt = conn.prepare(&format!(
+        "SELECT source_id, vector FROM embeddings
+         WHERE source_type = ? AND model = ? AND source_id IN ({})",
+        placeholders
+    ))?;
+    let mut values: Vec<rusqlite::types::Value> = vec![source.as_str().to_string().into(), model.to_string().into()];
+    values.extend(source_ids.iter().map(|id| rusqlite::types::Value::from(*id)));
+    let rows = stmt.query_map(params_from_iter(values), |row| {
+        let blob: Vec<u8> = row.get(1)?;
+        Ok((row.get::<_, i64>(0)?, decode_vector(&blob)))
+    })?;
+    rows.collect()
+}
+
+/// ベクトル化済みの件数（モデルを問わない）
+pub fn count_embeddings(conn: &Connection, source: EmbeddingSource) -> Result<u32> {
+    conn.query_row(
+        "SELECT COUNT(DISTINCT source_id) FROM embeddings WHERE source_type = ?",
+        params![source.as_str()],
+        |row| row.get(0),
+    )
+}
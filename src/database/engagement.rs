@@ -0,0 +1,98 @@
+use rusqlite::{params, Connection, Result};
+use chrono::Utc;
+use serde::Serialize;
+
+/// Botの投稿に届いたリアクション（kind 7）・Zap（kind 9735）の記録
+#[derive(Debug, Clone, Serialize)]
+pub struct EngagementRecord {
+    pub id: i64,
+    pub bot_pubkey: String,
+    /// "reaction" または "zap"
+    pub kind: String,
+    pub event_id: String,
+    /// リアクションした人・Zapを送った人
+    pub from_pubkey: String,
+    pub target_event_id: Option<String>,
+    /// リアクションの内容（"+"や絵文字）またはZapのメッセージ
+    pub content: String,
+    pub amount_sats: i64,
+    pub created_at: i64,
+    pub received_at: i64,
+}
+
+/// 種類別の件数集計
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EngagementCounts {
+    pub reactions: u32,
+    pub zaps: u32,
+    pub zap_sats: i64,
+}
+
+/// リアクション・Zapを記録（同じイベントは1度だけ。新規に記録した場合はtrue）
+#[allow(clippy::too_many_arguments)]
+pub fn insert_engagement(
+    conn: &Connection,
+    bot_pubkey: &str,
+    kind: &str,
+    event_id: &str,
+    from_pubkey: &str,
+    target_event_id: Option<&str>,
+    content: &str,
+    amount_sats: i64,
+    created_at: i64,
+) -> Result<bool> {
+    let now = Utc::now().timestamp();
+    let inserted = conn.execute(
+        "INSERT OR IGNORE INTO bot_engagements
+            (bot_pubkey, kind, event_id, from_pubkey, target_event_id, content, amount_sats, created_at, received_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        params![bot_pubkey, kind, event_id, from_pubkey, target_event_id, content, amount_sats, created_at, now],
+    )?;
+    Ok(inserted > 0)
+}
+
+/// Botに届いたリアクション・Zapを新しい順に取得
+pub fn get_recent_engagements(conn: &Connection, bot_pubkey: &str, since: i64, limit: usize) -> Result<Vec<EngagementRecord>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, bot_pubkey, kind, event_id, from_pubkey, target_event_id, content, amount_sats, created_at, received_at
+         FROM bot_engagements
+         WHERE bot_pubkey = ? AND created_at >= ?
+         ORDER BY created_at DESC
+         LIMIT ?",
+    )?;
+    let records = stmt.query_map(params![bot_pubkey, since, limit as i64], |row| {
+        Ok(EngagementRecord {
+            id: row.get(0)?,
+            bot_pubkey: row.get(1)?,
+            kind: row.get(2)?,
+            event_id: row.get(3)?,
+            from_pubkey: row.get(4)?,
+            target_event_id: row.get(5)?,
+            content: row.get(6)?,
+            amount_sats: row.get(7)?,
+            created_at: row.get(8)?,
+            received_at: row.get(9)?,
+        })
+    })?;
+    records.collect()
+}
+
+/// 指定時刻以降のリアクション・Zapの件数（bot_pubkeyがNoneなら全Bot）
+pub fn count_engagements_since(conn: &Connection, bot_pubkey: Option<&str>, since: i64) -> Result<EngagementCounts> {
+    conn.query_row(
+        "SELECT
+            COALESCE(SUM(CASE WHEN kind = 'reaction' THEN 1 ELSE 0 END), 0),
+            COALESCE(SUM(CASE WHEN kind = 'zap' THEN 1 ELSE 0 END), 0),
+            COALESCE(SUM(CASE WHEN kind = 'zap' THEN amount_sats ELSE 0 END), 0)
+         FROM bot_engagements
+         WHERE created_at >= ? AND (? IS NULL OR bot_pubkey = ?)",
+        params![since, bot_pubkey, bot_pubkey],
+        |row| {
+            Ok(EngagementCounts {
+                reactions: row.get(0)?,
+                zaps: row.get(1)?,
+                zap_sats: row.get(2)?,
+            })
+        },
+    )
+}
@@ -0,0 +1,59 @@
+use rusqlite::{params, Connection, Result};
+use serde::Serialize;
+
+/// 記録したWARN/ERRORのログ
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorLogEntry {
+    pub id: i64,
+    pub timestamp: i64,
+    /// "WARN" / "ERROR"
+    pub level: String,
+    /// ログを出したモジュール（例: bot::event_processor）
+    pub target: String,
+    pub message: String,
+    /// 処理中だったイベント・Bot・ユーザー（spanから取得）
+    pub event_id: Option<String>,
+    pub bot_pubkey: Option<String>,
+    pub user_pubkey: Option<String>,
+}
+
+/// ログを記録し、古いものをmax_entries件まで削る
+pub fn insert_error_log(conn: &Connection, entry: &ErrorLogEntry, max_entries: usize) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO error_log (timestamp, level, target, message, event_id, bot_pubkey, user_pubkey)
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+        params![
+            entry.timestamp,
+            entry.level,
+            entry.target,
+            entry.message,
+            entry.event_id,
+            entry.bot_pubkey,
+            entry.user_pubkey
+        ],
+    )?;
+    let id = conn.last_insert_rowid();
+    conn.execute("DELETE FROM error_log WHERE id <= ?", params![id - max_entries as i64])?;
+    Ok(id)
+}
+
+/// 新しい順にlimit件
+pub fn get_recent_errors(conn: &Connection, limit: usize) -> Result<Vec<ErrorLogEntry>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, timestamp, level, target, message, event_id, bot_pubkey, user_pubkey
+         FROM error_log ORDER BY id DESC LIMIT ?",
+    )?;
+    let rows = stmt.query_map(params![limit as i64], |row| {
+        Ok(ErrorLogEntry {
+            id: row.get(0)?,
+            timestamp: row.get(1)?,
+            level: row.get(2)?,
+            target: row.get(3)?,
+            message: row.get(4)?,
+            event_id: row.get(5)?,
+            bot_pubkey: row.get(6)?,
+            user_pubkey: row.get(7)?,
+        })
+    })?;
+    rows.collect()
+}
@@ -2,6 +2,7 @@ use rusqlite::{params, Connection, Result};
 use serde_json::Value;
 use nostr_sdk::prelude::Event;
 use chrono::Utc;
+use tracing::error;
 
 #[derive(Debug, Clone)]
 pub struct EventRecord {
@@ -80,6 +81,31 @@ pub fn insert_event(
     Ok(conn.last_insert_rowid())
 }
 
+/// 復号済みのDMをeventsテーブルに保存（languageはNULLにしてエアリプのタイムラインに載せない）
+///
+/// 既に保存済みの場合は既存の行IDを返す
+pub fn insert_direct_message_event(
+    conn: &Connection,
+    event_id: &str,
+    pubkey: &str,
+    kind: u16,
+    content: &str,
+    created_at: i64,
+    event_json: &str,
+) -> Result<i64> {
+    let now = Utc::now().timestamp();
+    conn.execute(
+        "INSERT OR IGNORE INTO events (event_id, event_json, pubkey, kind, content, created_at, received_at, language)
+         VALUES (?, ?, ?, ?, ?, ?, ?, NULL)",
+        params![event_id, event_json, pubkey, kind as i32, content, created_at, now],
+    )?;
+    conn.query_row(
+        "SELECT id FROM events WHERE event_id = ?",
+        params![event_id],
+        |row| row.get(0),
+    )
+}
+
 /// kind 0イベントをupsert（同じpubkeyの古いkind 0は削除して最新のみ保持）
 fn upsert_kind0_event(conn: &Connection, event: &Event) -> Result<i64> {
     let event_json = serde_json::to_string(event)
@@ -98,7 +124,7 @@ fn upsert_kind0_event(conn: &Connection, event: &Event) -> Result<i64> {
     // 既存のものより古い場合は保存しない
     if let Some(existing_ts) = existing_created_at {
         if event_created_at <= existing_ts {
-            eprintln!("[Kind0] Ignoring older kind 0 event (existing: {}, new: {})", existing_ts, event_created_at);
+            error!("[Kind0] Ignoring older kind 0 event (existing: {}, new: {})", existing_ts, event_created_at);
             return Ok(0); // 保存しない
         }
     }
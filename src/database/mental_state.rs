@@ -1,9 +1,10 @@
 use rusqlite::{params, Connection, Result};
 use serde::{Deserialize, Serialize};
 use chrono::Utc;
+use tracing::error;
 
 /// Bot心境の構造化データ
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(default)]
 pub struct MentalDiary {
     pub mood: String,                          // 現在の気分
@@ -23,28 +24,6 @@ pub struct MentalDiary {
     pub personality_state: String,             // 人格の状態
 }
 
-impl Default for MentalDiary {
-    fn default() -> Self {
-        Self {
-            mood: String::new(),
-            favorite_people: Vec::new(),
-            disliked_people: Vec::new(),
-            trusted_people: Vec::new(),
-            current_interests: Vec::new(),
-            want_to_learn: Vec::new(),
-            bored_with: Vec::new(),
-            short_term_goals: String::new(),
-            long_term_goals: String::new(),
-            concerns: String::new(),
-            recent_happy_events: String::new(),
-            recent_sad_events: String::new(),
-            recent_surprises: String::new(),
-            self_changes: String::new(),
-            personality_state: String::new(),
-        }
-    }
-}
-
 impl MentalDiary {
     /// YAML形式の文字列に変換（プロンプト用）
     pub fn to_yaml_string(&self) -> String {
@@ -174,7 +153,7 @@ pub fn get_bot_mental_state(
         match record.parse_mental_diary() {
             Ok(diary) => Ok(Some(diary)),
             Err(e) => {
-                eprintln!("[MentalState] JSONパースエラー: {}", e);
+                error!("[MentalState] JSONパースエラー: {}", e);
                 Ok(None)
             }
         }
@@ -1,5 +1,6 @@
 use rusqlite::{Connection, Result, params};
 use crate::database::token_usage::TokenCategory;
+use tracing::info;
 
 /// Personsテーブルにair_reply_single_ratioカラムを追加するマイグレーション
 pub(crate) fn migrate_add_air_reply_single_ratio(conn: &Connection) -> Result<()> {
@@ -13,12 +14,119 @@ pub(crate) fn migrate_add_air_reply_single_ratio(conn: &Connection) -> Result<()
         .unwrap_or(0) > 0;
     
     if !column_exists {
-        println!("🔄 マイグレーション: Personsテーブルにair_reply_single_ratioカラムを追加");
+        info!("🔄 マイグレーション: Personsテーブルにair_reply_single_ratioカラムを追加");
         conn.execute(
             "ALTER TABLE Persons ADD COLUMN air_reply_single_ratio INTEGER NOT NULL DEFAULT 30",
             [],
         )?;
-        println!("✅ マイグレーション完了: air_reply_single_ratio (デフォルト: 30%)");
+        info!("✅ マイグレーション完了: air_reply_single_ratio (デフォルト: 30%)");
+    }
+    
+    Ok(())
+}
+
+/// PersonsテーブルにBot個別設定のカラムを追加するマイグレーション
+pub(crate) fn migrate_add_person_overrides(conn: &Connection) -> Result<()> {
+    let columns = [
+        ("model", "TEXT"),
+        ("temperature", "REAL"),
+        ("max_tokens", "INTEGER"),
+        ("answer_length", "INTEGER"),
+        ("reaction_percent", "INTEGER"),
+        ("emoji_reaction_percent", "INTEGER"),
+        ("ignore_percent", "INTEGER"),
+        ("custom_emojis", "TEXT"),
+        ("allowed_tools", "TEXT"),
+    ];
+    
+    for (name, column_type) in columns {
+        let column_exists: bool = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('Persons') WHERE name=?",
+                params![name],
+                |row| row.get(0),
+            )
+            .unwrap_or(0) > 0;
+        
+        if !column_exists {
+            info!("🔄 マイグレーション: Personsテーブルに{}カラムを追加", name);
+            conn.execute(
+                &format!("ALTER TABLE Persons ADD COLUMN {} {}", name, column_type),
+                [],
+            )?;
+            info!("✅ マイグレーション完了: {} (NULL = 共通設定を使用)", name);
+        }
+    }
+    
+    Ok(())
+}
+
+/// event_queueテーブルにevent_id・優先度・再試行のカラムを追加するマイグレーション
+pub(crate) fn migrate_event_queue_retry_columns(conn: &Connection) -> Result<()> {
+    let columns = [
+        ("event_id", "TEXT"),
+        ("priority", "INTEGER NOT NULL DEFAULT 0"),
+        ("attempts", "INTEGER NOT NULL DEFAULT 0"),
+        ("next_attempt_at", "INTEGER NOT NULL DEFAULT 0"),
+        ("last_error", "TEXT"),
+    ];
+    
+    for (name, column_type) in columns {
+        let column_exists: bool = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('event_queue') WHERE name=?",
+                params![name],
+                |row| row.get(0),
+            )
+            .unwrap_or(0) > 0;
+        
+        if !column_exists {
+            info!("🔄 マイグレーション: event_queueテーブルに{}カラムを追加", name);
+            conn.execute(
+                &format!("ALTER TABLE event_queue ADD COLUMN {} {}", name, column_type),
+                [],
+            )?;
+            if name == "event_id" {
+                // 既存の行はJSONからevent_idを埋める
+                conn.execute(
+                    "UPDATE event_queue SET event_id = json_extract(event_json, '$.id') WHERE event_id IS NULL",
+                    [],
+                )?;
+            }
+            info!("✅ マイグレーション完了: {}", name);
+        }
+    }
+    
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_event_queue_event_id ON event_queue(event_id)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_event_queue_ready
+         ON event_queue(status, priority DESC, next_attempt_at, added_at)",
+        [],
+    )?;
+    
+    Ok(())
+}
+
+/// conversation_logsテーブルにis_direct_messageカラムを追加するマイグレーション
+pub(crate) fn migrate_add_direct_message_flag(conn: &Connection) -> Result<()> {
+    let column_exists: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('conversation_logs') WHERE name='is_direct_message'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap_or(0) > 0;
+    
+    if !column_exists {
+        info!("🔄 マイグレーション: conversation_logsテーブルにis_direct_messageカラムを追加");
+        conn.execute(
+            "ALTER TABLE conversation_logs ADD COLUMN is_direct_message INTEGER NOT NULL DEFAULT 0",
+            [],
+        )?;
+        info!("✅ マイグレーション完了: is_direct_message (デフォルト: 0)");
     }
     
     Ok(())
@@ -39,7 +147,7 @@ pub(crate) fn migrate_remove_kind0_content(conn: &Connection) -> Result<()> {
         return Ok(());
     }
     
-    println!("🔄 マイグレーション: eventsテーブルからkind0_contentカラムを削除");
+    info!("🔄 マイグレーション: eventsテーブルからkind0_contentカラムを削除");
     
     // SQLiteではALTER TABLE DROP COLUMNが使えないので、テーブルを再作成する
     // 外部キー制約があるため、慎重に処理する
@@ -103,12 +211,12 @@ pub(crate) fn migrate_remove_kind0_content(conn: &Connection) -> Result<()> {
     match migration_result {
         Ok(_) => {
             conn.execute("COMMIT", [])?;
-            println!("✅ マイグレーション完了: kind0_contentカラムを削除（データは保持）");
+            info!("✅ マイグレーション完了: kind0_contentカラムを削除（データは保持）");
         }
         Err(e) => {
             let _ = conn.execute("ROLLBACK", []);
-            println!("❌ マイグレーション失敗: {:?}", e);
-            println!("🔄 ロールバックしました（データは元の状態に戻りました）");
+            info!("❌ マイグレーション失敗: {:?}", e);
+            info!("🔄 ロールバックしました（データは元の状態に戻りました）");
             return Err(e);
         }
     }
@@ -127,7 +235,7 @@ pub(crate) fn initialize_token_categories(conn: &Connection) -> Result<()> {
             params![category as i32, category.name(), category.display_name()],
         )?;
     }
-    println!("⚙️ トークンカテゴリ: {}種類を初期化", TokenCategory::all().len());
+    info!("⚙️ トークンカテゴリ: {}種類を初期化", TokenCategory::all().len());
     Ok(())
 }
 
@@ -165,7 +273,7 @@ pub(crate) fn migrate_token_usage_table(conn: &Connection) -> Result<()> {
     
     // category_idはあるがbot_pubkeyが無い場合（部分的なマイグレーション済み）
     if has_category_id_column && !has_bot_pubkey_column {
-        println!("🔄 マイグレーション: token_usageテーブルにbot_pubkeyカラムを追加");
+        info!("🔄 マイグレーション: token_usageテーブルにbot_pubkeyカラムを追加");
         
         // 外部キー制約を一時的に無効化
         conn.execute("PRAGMA foreign_keys = OFF", [])?;
@@ -223,13 +331,13 @@ pub(crate) fn migrate_token_usage_table(conn: &Connection) -> Result<()> {
             [],
         )?;
         
-        println!("✅ マイグレーション完了: bot_pubkeyカラムを追加");
+        info!("✅ マイグレーション完了: bot_pubkeyカラムを追加");
         return Ok(());
     }
     
     // 古いスキーマ（categoryカラムあり）からのマイグレーション
     if has_category_column {
-        println!("🔄 マイグレーション: token_usageテーブルを正規化");
+        info!("🔄 マイグレーション: token_usageテーブルを正規化");
         
         // 外部キー制約を一時的に無効化
         conn.execute("PRAGMA foreign_keys = OFF", [])?;
@@ -318,7 +426,7 @@ pub(crate) fn migrate_token_usage_table(conn: &Connection) -> Result<()> {
             [],
         )?;
         
-        println!("✅ マイグレーション完了: token_usageテーブルを正規化");
+        info!("✅ マイグレーション完了: token_usageテーブルを正規化");
     }
     
     Ok(())
@@ -342,7 +450,7 @@ pub(crate) fn migrate_add_token_text_columns(conn: &Connection) -> Result<()> {
         return Ok(());
     }
     
-    println!("🔄 マイグレーション: token_usageテーブルにテキストカラムを追加");
+    info!("🔄 マイグレーション: token_usageテーブルにテキストカラムを追加");
     
     // 外部キー制約を一時的に無効化
     conn.execute("PRAGMA foreign_keys = OFF", [])?;
@@ -397,7 +505,7 @@ pub(crate) fn migrate_add_token_text_columns(conn: &Connection) -> Result<()> {
         [],
     )?;
     
-    println!("✅ マイグレーション完了: テキストカラムを追加");
+    info!("✅ マイグレーション完了: テキストカラムを追加");
     
     Ok(())
 }
@@ -440,10 +548,10 @@ pub(crate) fn migrate_normalize_events_table(conn: &Connection) -> Result<()> {
         return Ok(()); // 新規環境
     }
     
-    println!("🔄 マイグレーション: eventsテーブルを正規化");
-    println!("   - is_japanese → language");
-    println!("   - event_type → 削除（不要）");
-    println!("   - kind0_name → 削除（kind0_cacheをJOINで参照）");
+    info!("🔄 マイグレーション: eventsテーブルを正規化");
+    info!("   - is_japanese → language");
+    info!("   - event_type → 削除（不要）");
+    info!("   - kind0_name → 削除（kind0_cacheをJOINで参照）");
     
     // 外部キー制約を一時的に無効化
     conn.execute("PRAGMA foreign_keys = OFF", [])?;
@@ -452,7 +560,7 @@ pub(crate) fn migrate_normalize_events_table(conn: &Connection) -> Result<()> {
     let migration_result = (|| {
         // 元のデータ件数を記録
         let original_count: i64 = conn.query_row("SELECT COUNT(*) FROM events", [], |row| row.get(0))?;
-        println!("   📊 元のデータ件数: {}", original_count);
+        info!("   📊 元のデータ件数: {}", original_count);
         
         // 前回の失敗で残っているかもしれないevents_newテーブルを削除
         conn.execute("DROP TABLE IF EXISTS events_new", [])?;
@@ -507,7 +615,7 @@ pub(crate) fn migrate_normalize_events_table(conn: &Connection) -> Result<()> {
         
         // データ件数を検証
         let new_count: i64 = conn.query_row("SELECT COUNT(*) FROM events_new", [], |row| row.get(0))?;
-        println!("   📊 コピー後のデータ件数: {}", new_count);
+        info!("   📊 コピー後のデータ件数: {}", new_count);
         
         if original_count != new_count {
             return Err(rusqlite::Error::QueryReturnedNoRows); // データ損失を検知したらエラー
@@ -531,12 +639,12 @@ pub(crate) fn migrate_normalize_events_table(conn: &Connection) -> Result<()> {
     match migration_result {
         Ok(_) => {
             conn.execute("COMMIT", [])?;
-            println!("✅ マイグレーション完了: eventsテーブルを正規化");
+            info!("✅ マイグレーション完了: eventsテーブルを正規化");
         }
         Err(e) => {
             let _ = conn.execute("ROLLBACK", []);
-            println!("❌ マイグレーション失敗: {:?}", e);
-            println!("🔄 ロールバックしました");
+            info!("❌ マイグレーション失敗: {:?}", e);
+            info!("🔄 ロールバックしました");
             return Err(e);
         }
     }
@@ -560,7 +668,7 @@ pub(crate) fn migrate_add_user_impressions(conn: &Connection) -> Result<()> {
         return Ok(());
     }
     
-    println!("🔄 マイグレーション: user_impressionsテーブルを作成（履歴保存）");
+    info!("🔄 マイグレーション: user_impressionsテーブルを作成（履歴保存）");
     
     conn.execute(
         "CREATE TABLE user_impressions (
@@ -579,7 +687,7 @@ pub(crate) fn migrate_add_user_impressions(conn: &Connection) -> Result<()> {
         [],
     )?;
     
-    println!("✅ マイグレーション完了: user_impressionsテーブルを作成（変遷履歴保存対応）");
+    info!("✅ マイグレーション完了: user_impressionsテーブルを作成（変遷履歴保存対応）");
     
     Ok(())
 }
@@ -597,7 +705,7 @@ pub(crate) fn migrate_add_bot_mental_state(conn: &Connection) -> Result<()> {
         return Ok(());
     }
     
-    println!("🔄 マイグレーション: bot_mental_stateテーブルを作成（履歴保存）");
+    info!("🔄 マイグレーション: bot_mental_stateテーブルを作成（履歴保存）");
     
     conn.execute(
         "CREATE TABLE bot_mental_state (
@@ -615,7 +723,7 @@ pub(crate) fn migrate_add_bot_mental_state(conn: &Connection) -> Result<()> {
         [],
     )?;
     
-    println!("✅ マイグレーション完了: bot_mental_stateテーブルを作成（履歴保存対応）");
+    info!("✅ マイグレーション完了: bot_mental_stateテーブルを作成（履歴保存対応）");
     
     Ok(())
 }
@@ -635,7 +743,7 @@ pub(crate) fn migrate_remove_embedding_from_events(conn: &Connection) -> Result<
         return Ok(());
     }
     
-    println!("🔄 マイグレーション: eventsテーブルからembeddingカラムを削除");
+    info!("🔄 マイグレーション: eventsテーブルからembeddingカラムを削除");
     
     // SQLiteではALTER TABLE DROP COLUMNが使えないので、テーブルを再作成する
     
@@ -699,7 +807,7 @@ pub(crate) fn migrate_remove_embedding_from_events(conn: &Connection) -> Result<
     // 9. 外部キー制約を再度有効化
     conn.execute("PRAGMA foreign_keys = ON", [])?;
     
-    println!("✅ マイグレーション完了: eventsテーブルからembeddingカラムを削除");
+    info!("✅ マイグレーション完了: eventsテーブルからembeddingカラムを削除");
     
     Ok(())
 }
@@ -719,7 +827,7 @@ pub(crate) fn migrate_remove_embedding_from_summaries(conn: &Connection) -> Resu
         return Ok(());
     }
     
-    println!("🔄 マイグレーション: conversation_summariesテーブルからuser_input_embeddingカラムを削除");
+    info!("🔄 マイグレーション: conversation_summariesテーブルからuser_input_embeddingカラムを削除");
     
     // SQLiteではALTER TABLE DROP COLUMNが使えないので、テーブルを再作成する
     
@@ -770,7 +878,165 @@ pub(crate) fn migrate_remove_embedding_from_summaries(conn: &Connection) -> Resu
     // 9. 外部キー制約を再度有効化
     conn.execute("PRAGMA foreign_keys = ON", [])?;
     
-    println!("✅ マイグレーション完了: conversation_summariesテーブルからuser_input_embeddingカラムを削除");
+    info!("✅ マイグレーション完了: conversation_summariesテーブルからuser_input_embeddingカラムを削除");
+    
+    Ok(())
+}
+
+/// event_queue・dead_letterテーブルに処理順を保証する単位（Bot・ユーザー）のカラムを追加するマイグレーション
+/// 既存の行はNULL（どのBot・ユーザーとも同時に処理しない）になる
+pub(crate) fn migrate_event_queue_lane_columns(conn: &Connection) -> Result<()> {
+    for table in ["event_queue", "dead_letter"] {
+        for name in ["bot_pubkey", "user_pubkey"] {
+            let column_exists: bool = conn
+                .query_row(
+                    &format!("SELECT COUNT(*) FROM pragma_table_info('{}') WHERE name=?", table),
+                    params![name],
+                    |row| row.get(0),
+                )
+                .unwrap_or(0) > 0;
+            
+            if !column_exists {
+                info!("🔄 マイグレーション: {}テーブルに{}カラムを追加", table, name);
+                conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} TEXT", table, name), [])?;
+                info!("✅ マイグレーション完了: {}", name);
+            }
+        }
+    }
+    
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_event_queue_lane ON event_queue(status, user_pubkey, bot_pubkey)",
+        [],
+    )?;
+    
+    Ok(())
+}
+
+/// embeddingsテーブルを追加するマイグレーション
+/// イベント・要約のベクトルを別テーブルに持ち、元の行が消えたら一緒に消す
+pub(crate) fn migrate_add_embeddings(conn: &Connection) -> Result<()> {
+    let table_exists: bool = conn
+        .prepare("SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='embeddings'")?
+        .query_row([], |row| row.get(0))
+        .map(|count: i32| count > 0)?;
+    
+    if !table_exists {
+        info!("🔄 マイグレーション: embeddingsテーブルを作成");
+        conn.execute(
+            "CREATE TABLE embeddings (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                source_type TEXT NOT NULL,
+                source_id INTEGER NOT NULL,
+                model TEXT NOT NULL,
+                dimensions INTEGER NOT NULL,
+                vector BLOB NOT NULL,
+                created_at INTEGER NOT NULL,
+                UNIQUE(source_type, source_id, model)
+            )",
+            [],
+        )?;
+        info!("✅ マイグレーション完了: embeddingsテーブルを作成");
+    }
+    
+    // ベクトル化に失敗した行（上限回数に達したら以後は対象外）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS embedding_failures (
+            source_type TEXT NOT NULL,
+            source_id INTEGER NOT NULL,
+            model TEXT NOT NULL,
+            attempts INTEGER NOT NULL,
+            last_error TEXT NOT NULL,
+            failed_at INTEGER NOT NULL,
+            PRIMARY KEY (source_type, source_id, model)
+        )",
+        [],
+    )?;
+    
+    // eventsテーブルは他のマイグレーションで作り直されることがあるので、トリガーは毎回確認する
+    conn.execute(
+        "CREATE TRIGGER IF NOT EXISTS trg_events_delete_embeddings AFTER DELETE ON events
+         BEGIN
+             DELETE FROM embeddings WHERE source_type = 'event' AND source_id = OLD.id;
+         END",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TRIGGER IF NOT EXISTS trg_summaries_delete_embeddings AFTER DELETE ON conversation_summaries
+         BEGIN
+             DELETE FROM embeddings WHERE source_type = 'summary' AND source_id = OLD.id;
+         END",
+        [],
+    )?;
+    
+    Ok(())
+}
+
+/// マイグレーション: 投稿の全文検索用FTS5テーブル（trigramで日本語も部分一致できる）
+pub(crate) fn migrate_add_events_fts(conn: &Connection) -> Result<()> {
+    let table_exists: bool = conn
+        .prepare("SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='events_fts'")?
+        .query_row([], |row| row.get(0))
+        .map(|count: i32| count > 0)?;
+    
+    if !table_exists {
+        info!("🔄 マイグレーション: events_ftsテーブルを作成");
+        conn.execute(
+            "CREATE VIRTUAL TABLE events_fts USING fts5(
+                content,
+                content='events',
+                content_rowid='id',
+                tokenize='trigram'
+            )",
+            [],
+        )?;
+        // 既存の投稿を索引に登録
+        conn.execute("INSERT INTO events_fts(events_fts) VALUES('rebuild')", [])?;
+        info!("✅ マイグレーション完了: events_ftsテーブルを作成");
+    }
+    
+    // eventsテーブルは他のマイグレーションで作り直されることがあるので、トリガーは毎回確認する
+    conn.execute(
+        "CREATE TRIGGER IF NOT EXISTS trg_events_fts_insert AFTER INSERT ON events
+         BEGIN
+             INSERT INTO events_fts(rowid, content) VALUES (NEW.id, NEW.content);
+         END",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TRIGGER IF NOT EXISTS trg_events_fts_delete AFTER DELETE ON events
+         BEGIN
+             INSERT INTO events_fts(events_fts, rowid, content) VALUES ('delete', OLD.id, OLD.content);
+         END",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TRIGGER IF NOT EXISTS trg_events_fts_update AFTER UPDATE OF content ON events
+         BEGIN
+             INSERT INTO events_fts(events_fts, rowid, content) VALUES ('delete', OLD.id, OLD.content);
+             INSERT INTO events_fts(rowid, content) VALUES (NEW.id, NEW.content);
+         END",
+        [],
+    )?;
+    
+    Ok(())
+}
+
+/// token_usageテーブルに使用時のプロンプトの版（persona_versions.id）のカラムを追加するマイグレーション
+/// 既存の行はNULL（版が分からない）になる
+pub(crate) fn migrate_add_token_usage_persona_version(conn: &Connection) -> Result<()> {
+    let column_exists: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('token_usage') WHERE name='persona_version_id'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap_or(0) > 0;
+    
+    if !column_exists {
+        info!("🔄 マイグレーション: token_usageテーブルにpersona_version_idカラムを追加");
+        conn.execute("ALTER TABLE token_usage ADD COLUMN persona_version_id INTEGER", [])?;
+        info!("✅ マイグレーション完了: persona_version_id");
+    }
     
     Ok(())
 }
@@ -17,18 +17,28 @@ pub mod token_usage;
 pub mod stats;
 pub mod impression;
 pub mod mental_state;
+pub mod checkpoint;
+pub mod engagement;
+pub mod embeddings;
+pub mod search;
+pub mod tool_calls;
+pub mod schedules;
+pub mod reminders;
+pub mod error_log;
+pub mod persona_versions;
 
 // 接続関数を再エクスポート
 pub(crate) use connection::connect;
-pub use connection::connect_at_path;
+pub use connection::{connect_at_path, db_path};
 
 // スキーマ初期化を再エクスポート
 pub use schema::initialize_db;
 
 // Person関連を再エクスポート
 pub use person::{
-    Person, add_person, update_person, delete_person, update_person_status,
-    get_bot_daily_reply_counts, get_all_persons, get_person, get_random_person
+    Person, PersonOverrides, CustomEmoji, add_person, update_person, update_person_overrides, delete_person,
+    update_person_status, get_bot_daily_reply_counts, get_all_persons, get_person, find_person,
+    get_random_person
 };
 
 // クレート内部用（再エクスポート不可）
@@ -42,29 +52,40 @@ pub use settings::{get_system_setting, set_system_setting, is_global_pause};
 pub use cache::{
     get_follower_cache, set_follower_cache, clear_follower_cache,
     delete_user_follower_cache, get_all_follower_cache, update_follower_cache,
-    get_kind0_cache, set_kind0_cache,
+    get_kind0_cache, set_kind0_cache, get_zapper_cache, set_zapper_cache,
     add_timeline_post, get_latest_timeline_posts, cleanup_old_timeline_posts
 };
 
 // イベント関連を再エクスポート
 pub use events::{
-    EventRecord, insert_event, get_event_by_event_id,
+    EventRecord, insert_event, insert_direct_message_event, get_event_by_event_id,
     extract_reply_to_event_id, extract_mentioned_pubkeys, extract_thread_root_id,
     detect_bot_conversation
 };
 
 // 会話ログ・要約を再エクスポート
 pub use conversation::{
-    insert_conversation_log, get_conversation_timeline,
+    find_conversation_log, insert_conversation_log, insert_direct_message_log, get_direct_message_timeline, get_conversation_timeline,
     get_conversation_timeline_with_user, get_conversation_timeline_in_thread,
-    get_thread_message_count, get_conversation_count_with_user,
+    get_thread_message_count, get_conversation_count_with_user, get_conversation_timeline_since,
     ConversationSummary, insert_conversation_summary, get_conversation_summaries
 };
 
 // キュー関連を再エクスポート
 pub use queue::{
-    enqueue_event, dequeue_event, complete_queue_event, retry_queue_event,
-    get_queue_size, reset_processing_events
+    QueuePriority, QueueItem, DeadLetter, RetryOutcome, MAX_QUEUE_ATTEMPTS,
+    enqueue_event, dequeue_event, complete_queue_event, retry_queue_event, dead_letter_queue_event,
+    list_queue_items, delete_queue_event, list_dead_letters, requeue_dead_letter, delete_dead_letter,
+    purge_dead_letters, count_dead_letters, next_queue_attempt_at, QueueLane,
+    get_queue_size, reset_processing_events, mark_event_processed, is_event_processed
+};
+
+// リレーのチェックポイントを再エクスポート
+pub use checkpoint::{update_relay_checkpoint, get_relay_checkpoint};
+
+// リアクション・Zapの記録を再エクスポート
+pub use engagement::{
+    EngagementRecord, EngagementCounts, insert_engagement, get_recent_engagements, count_engagements_since
 };
 
 // トークン使用量を再エクスポート
@@ -89,3 +110,37 @@ pub use mental_state::{
     get_bot_mental_state, save_bot_mental_state, get_bot_mental_state_history,
     count_bot_mental_state_history, MentalDiary, BotMentalStateRecord
 };
+
+// ベクトル（類似検索用）を再エクスポート
+pub use embeddings::{
+    EmbeddingSource, MAX_EMBEDDING_ATTEMPTS, insert_embedding, get_events_without_embedding,
+    get_summaries_without_embedding, record_embedding_failure, get_embeddings, count_embeddings
+};
+
+// 投稿の全文検索を再エクスポート
+pub use search::{LocalSearchQuery, fts_phrase, search_events_local};
+
+// ツール呼び出しの記録を再エクスポート
+pub use tool_calls::{ToolCallLog, insert_tool_call_log, get_tool_call_logs};
+
+// 定期投稿スケジュールを再エクスポート
+pub use schedules::{
+    BotSchedule, insert_schedule, update_schedule, delete_schedule, get_schedule, get_bot_schedules,
+    get_due_schedules, mark_schedule_run
+};
+
+// リマインドを再エクスポート
+pub use reminders::{
+    Reminder, insert_reminder, get_pending_reminders, get_due_reminders, cancel_reminder, finish_reminder
+};
+
+// エラーログを再エクスポート
+pub use error_log::{ErrorLogEntry, insert_error_log, get_recent_errors};
+
+
+// プロンプト・kind 0の版を再エクスポート
+pub use persona_versions::{
+    PersonaVersion, PersonaVersionUsage, ensure_initial_persona_versions, record_persona_version,
+    list_persona_versions, get_persona_version, get_latest_persona_version, get_previous_persona_version,
+    get_persona_version_usage, rollback_persona
+};
@@ -1,6 +1,7 @@
-use rusqlite::{params, Connection, Result};
+use rusqlite::{params, Connection, OptionalExtension, Result, Row};
 use chrono::Utc;
 use nostr_sdk::prelude::Keys;
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone)]
 pub struct Person {
@@ -17,6 +18,62 @@ pub struct Person {
     #[allow(dead_code)]
     pub updated_at: String,
     pub air_reply_single_ratio: i32,
+    pub overrides: PersonOverrides,
+}
+
+/// Bot個別の設定（Noneの項目は共通設定を使用）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PersonOverrides {
+    pub model: Option<String>,           // モデル名
+    pub temperature: Option<f64>,        // 温度
+    pub max_tokens: Option<i64>,         // 最大トークン数
+    pub answer_length: Option<i32>,      // 返信の文字数目安
+    pub reaction_percent: Option<i64>,   // エアリプの反応確率（%）
+    pub emoji_reaction_percent: Option<i64>, // 返信の代わりに絵文字リアクションを選べる確率（%）
+    pub ignore_percent: Option<i64>,     // 反応せずに無視する確率（%）
+    pub custom_emojis: Option<Vec<CustomEmoji>>, // リアクションに使えるカスタム絵文字（NIP-30）
+    pub allowed_tools: Option<Vec<String>>, // メンション返信で使えるツール（未指定ならツールを使わない）
+}
+
+/// カスタム絵文字（NIP-30の:shortcode:と画像URL）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomEmoji {
+    pub shortcode: String,
+    pub url: String,
+}
+
+// SELECT * の行からPersonを生成
+fn person_from_row(row: &Row) -> Result<Person> {
+    Ok(Person {
+        id: row.get(0)?,
+        status: row.get(1)?,
+        prompt: row.get(2)?,
+        pubkey: row.get(3)?,
+        secretkey: row.get(4)?,
+        content: row.get(5)?,
+        created_at: row.get(6)?,
+        updated_at: row.get(7)?,
+        air_reply_single_ratio: row.get(8).unwrap_or(30),
+        // マイグレーション前のDBでも読めるようにカラム名で取得
+        overrides: PersonOverrides {
+            model: row.get("model").unwrap_or(None),
+            temperature: row.get("temperature").unwrap_or(None),
+            max_tokens: row.get("max_tokens").unwrap_or(None),
+            answer_length: row.get("answer_length").unwrap_or(None),
+            reaction_percent: row.get("reaction_percent").unwrap_or(None),
+            emoji_reaction_percent: row.get("emoji_reaction_percent").unwrap_or(None),
+            ignore_percent: row.get("ignore_percent").unwrap_or(None),
+            custom_emojis: row
+                .get::<_, Option<String>>("custom_emojis")
+                .unwrap_or(None)
+                .and_then(|json| serde_json::from_str(&json).ok()),
+            allowed_tools: row
+                .get::<_, Option<String>>("allowed_tools")
+                .unwrap_or(None)
+                .and_then(|json| serde_json::from_str(&json).ok()),
+        },
+    })
 }
 
 /// Botを追加
@@ -39,12 +96,38 @@ pub fn update_person(conn: &Connection, pubkey: &str, secretkey: &str, prompt: &
     Ok(())
 }
 
+/// Bot個別設定を更新
+pub fn update_person_overrides(conn: &Connection, pubkey: &str, overrides: &PersonOverrides) -> Result<()> {
+    conn.execute(
+        "UPDATE Persons SET model = ?, temperature = ?, max_tokens = ?, answer_length = ?, reaction_percent = ?,
+             emoji_reaction_percent = ?, ignore_percent = ?, custom_emojis = ?, allowed_tools = ? WHERE pubkey = ?",
+        params![
+            overrides.model,
+            overrides.temperature,
+            overrides.max_tokens,
+            overrides.answer_length,
+            overrides.reaction_percent,
+            overrides.emoji_reaction_percent,
+            overrides.ignore_percent,
+            overrides.custom_emojis.as_ref().and_then(|emojis| serde_json::to_string(emojis).ok()),
+            overrides.allowed_tools.as_ref().and_then(|tools| serde_json::to_string(tools).ok()),
+            pubkey
+        ],
+    )?;
+    Ok(())
+}
+
 /// Bot削除
 pub fn delete_person(conn: &Connection, pubkey: &str) -> Result<()> {
     conn.execute(
         "DELETE FROM Persons WHERE pubkey = ?",
         params![pubkey],
     )?;
+    // Botがいなくなった定期投稿も消す
+    conn.execute(
+        "DELETE FROM bot_schedules WHERE bot_pubkey = ?",
+        params![pubkey],
+    )?;
     Ok(())
 }
 
@@ -90,19 +173,7 @@ pub fn get_bot_daily_reply_counts(conn: &Connection, days: i64) -> Result<Vec<(S
 pub fn get_all_persons(conn: &Connection) -> Result<Vec<Person>> {
     let mut stmt = conn.prepare("SELECT * FROM Persons")?;
     let persons = stmt
-        .query_map(params![], |row| {
-            Ok(Person {
-                id: row.get(0)?,
-                status: row.get(1)?,
-                prompt: row.get(2)?,
-                pubkey: row.get(3)?,
-                secretkey: row.get(4)?,
-                content: row.get(5)?,
-                created_at: row.get(6)?,
-                updated_at: row.get(7)?,
-                air_reply_single_ratio: row.get(8).unwrap_or(30),
-            })
-        })?
+        .query_map(params![], person_from_row)?
         .collect::<Result<Vec<Person>, _>>()?;
 
     Ok(persons.clone())
@@ -111,25 +182,19 @@ pub fn get_all_persons(conn: &Connection) -> Result<Vec<Person>> {
 pub fn get_person(conn: &Connection, pubkey: &str) -> Result<Person> {
     let mut stmt = conn.prepare("SELECT * FROM Persons WHERE pubkey = ?")?;
     let person = stmt
-        .query_map(params![pubkey], |row| {
-            Ok(Person {
-                id: row.get(0)?,
-                status: row.get(1)?,
-                prompt: row.get(2)?,
-                pubkey: row.get(3)?,
-                secretkey: row.get(4)?,
-                content: row.get(5)?,
-                created_at: row.get(6)?,
-                updated_at: row.get(7)?,
-                air_reply_single_ratio: row.get(8).unwrap_or(30),
-            })
-        })?
+        .query_map(params![pubkey], person_from_row)?
         .next()
         .unwrap()?;
 
     Ok(person.clone())
 }
 
+/// pubkeyでBotを検索（存在しなければNone）
+pub fn find_person(conn: &Connection, pubkey: &str) -> Result<Option<Person>> {
+    conn.query_row("SELECT * FROM Persons WHERE pubkey = ?", params![pubkey], person_from_row)
+        .optional()
+}
+
 pub(crate) fn insert_person(
     conn: &Connection,
     keys: &Keys,
@@ -165,19 +230,7 @@ pub fn get_random_person(conn: &Connection) -> Result<Person> {
     let mut stmt =
         conn.prepare("SELECT * FROM Persons WHERE status=0 ORDER BY RANDOM() LIMIT 1")?;
     let person = stmt
-        .query_map(params![], |row| {
-            Ok(Person {
-                id: row.get(0)?,
-                status: row.get(1)?,
-                prompt: row.get(2)?,
-                pubkey: row.get(3)?,
-                secretkey: row.get(4)?,
-                content: row.get(5)?,
-                created_at: row.get(6)?,
-                updated_at: row.get(7)?,
-                air_reply_single_ratio: row.get(8).unwrap_or(30),
-            })
-        })?
+        .query_map(params![], person_from_row)?
         .next()
         .unwrap()?;
 
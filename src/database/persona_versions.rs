@@ -0,0 +1,163 @@
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use serde::Serialize;
+
+/// Botのプロンプト・kind 0の版
+#[derive(Debug, Clone, Serialize)]
+pub struct PersonaVersion {
+    pub id: i64,
+    pub bot_pubkey: String,
+    pub prompt: String,
+    pub content: String,
+    /// 変更した人（管理者のpubkey、ダッシュボードのトークンなら"dashboard"）
+    pub author: Option<String>,
+    /// "initial" / "dashboard" / "admin_command" / "rollback"
+    pub source: String,
+    pub created_at: i64,
+}
+
+fn row_to_version(row: &rusqlite::Row) -> Result<PersonaVersion> {
+    Ok(PersonaVersion {
+        id: row.get(0)?,
+        bot_pubkey: row.get(1)?,
+        prompt: row.get(2)?,
+        content: row.get(3)?,
+        author: row.get(4)?,
+        source: row.get(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
+/// 履歴のないBotの現在のプロンプト・kind 0を最初の版として記録（bot_pubkey省略時は全Bot）
+pub fn ensure_initial_persona_versions(conn: &Connection, bot_pubkey: Option<&str>) -> Result<usize> {
+    conn.execute(
+        "INSERT INTO persona_versions (bot_pubkey, prompt, content, author, source, created_at)
+         SELECT p.pubkey, p.prompt, p.content, NULL, 'initial', ?
+         FROM Persons p
+         WHERE (?2 IS NULL OR p.pubkey = ?2)
+           AND NOT EXISTS (SELECT 1 FROM persona_versions v WHERE v.bot_pubkey = p.pubkey)",
+        params![chrono::Utc::now().timestamp(), bot_pubkey],
+    )
+}
+
+/// Botの現在のプロンプト・kind 0を新しい版として記録
+/// 最新の版と同じなら記録せずNoneを返す
+pub fn record_persona_version(
+    conn: &Connection,
+    bot_pubkey: &str,
+    author: Option<&str>,
+    source: &str,
+) -> Result<Option<i64>> {
+    let (prompt, content): (String, String) = conn.query_row(
+        "SELECT prompt, content FROM Persons WHERE pubkey = ?",
+        params![bot_pubkey],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    if let Some(latest) = get_latest_persona_version(conn, bot_pubkey)? {
+        if latest.prompt == prompt && latest.content == content {
+            return Ok(None);
+        }
+    }
+
+    conn.execute(
+        "INSERT INTO persona_versions (bot_pubkey, prompt, content, author, source, created_at)
+         VALUES (?, ?, ?, ?, ?, ?)",
+        params![bot_pubkey, prompt, content, author, source, chrono::Utc::now().timestamp()],
+    )?;
+    Ok(Some(conn.last_insert_rowid()))
+}
+
+/// Botの版を新しい順に取得
+pub fn list_persona_versions(conn: &Connection, bot_pubkey: &str) -> Result<Vec<PersonaVersion>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, bot_pubkey, prompt, content, author, source, created_at
+         FROM persona_versions WHERE bot_pubkey = ? ORDER BY id DESC",
+    )?;
+    let rows = stmt.query_map(params![bot_pubkey], row_to_version)?;
+    rows.collect()
+}
+
+/// IDで版を取得
+pub fn get_persona_version(conn: &Connection, id: i64) -> Result<Option<PersonaVersion>> {
+    conn.query_row(
+        "SELECT id, bot_pubkey, prompt, content, author, source, created_at
+         FROM persona_versions WHERE id = ?",
+        params![id],
+        row_to_version,
+    )
+    .optional()
+}
+
+/// 現在使われている（最新の）版
+pub fn get_latest_persona_version(conn: &Connection, bot_pubkey: &str) -> Result<Option<PersonaVersion>> {
+    conn.query_row(
+        "SELECT id, bot_pubkey, prompt, content, author, source, created_at
+         FROM persona_versions WHERE bot_pubkey = ? ORDER BY id DESC LIMIT 1",
+        params![bot_pubkey],
+        row_to_version,
+    )
+    .optional()
+}
+
+/// 指定した版の1つ前の版
+pub fn get_previous_persona_version(conn: &Connection, version: &PersonaVersion) -> Result<Option<PersonaVersion>> {
+    conn.query_row(
+        "SELECT id, bot_pubkey, prompt, content, author, source, created_at
+         FROM persona_versions WHERE bot_pubkey = ? AND id < ? ORDER BY id DESC LIMIT 1",
+        params![version.bot_pubkey, version.id],
+        row_to_version,
+    )
+    .optional()
+}
+
+/// 版ごとのトークン使用量（返信の質を版同士で比べるため）
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PersonaVersionUsage {
+    pub calls: i64,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+}
+
+/// Botの版ごとのトークン使用量（版ID → 使用量）
+pub fn get_persona_version_usage(
+    conn: &Connection,
+    bot_pubkey: &str,
+) -> Result<std::collections::HashMap<i64, PersonaVersionUsage>> {
+    let mut stmt = conn.prepare(
+        "SELECT persona_version_id, COUNT(*), SUM(prompt_tokens), SUM(completion_tokens)
+         FROM token_usage WHERE bot_pubkey = ? AND persona_version_id IS NOT NULL
+         GROUP BY persona_version_id",
+    )?;
+    let rows = stmt.query_map(params![bot_pubkey], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            PersonaVersionUsage {
+                calls: row.get(1)?,
+                prompt_tokens: row.get(2)?,
+                completion_tokens: row.get(3)?,
+            },
+        ))
+    })?;
+    rows.collect()
+}
+
+/// 指定した版のプロンプト・kind 0に戻し、戻した内容を新しい版として記録
+/// 版が見つからないか別のBotの版ならNoneを返す
+pub fn rollback_persona(
+    conn: &Connection,
+    bot_pubkey: &str,
+    version_id: i64,
+    author: Option<&str>,
+) -> Result<Option<i64>> {
+    let Some(version) = get_persona_version(conn, version_id)?.filter(|v| v.bot_pubkey == bot_pubkey) else {
+        return Ok(None);
+    };
+    conn.execute(
+        "UPDATE Persons SET prompt = ?, content = ? WHERE pubkey = ?",
+        params![version.prompt, version.content, bot_pubkey],
+    )?;
+    // 最新の版と同じ内容に戻した場合は新しい版を作らない
+    match record_persona_version(conn, bot_pubkey, author, "rollback")? {
+        Some(id) => Ok(Some(id)),
+        None => Ok(get_latest_persona_version(conn, bot_pubkey)?.map(|v| v.id)),
+    }
+}
@@ -1,116 +1,358 @@
-use rusqlite::{params, Connection, Result};
+use rusqlite::{params, Connection, OptionalExtension, Result, Row};
 use chrono::Utc;
+use serde::Serialize;
 
-pub fn enqueue_event(conn: &Connection, event_json: &str) -> Result<i64> {
+/// 処理済みイベントの記録を保持する期間（キャッチアップの遡り上限より長くする）
+const PROCESSED_EVENTS_RETENTION_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// この回数失敗したイベントはdead_letterに移す
+pub const MAX_QUEUE_ATTEMPTS: i64 = 5;
+
+/// 再試行の待ち時間（attempts回目の失敗で BASE * 2^(attempts-1) 秒、上限MAX秒）
+const RETRY_BASE_DELAY_SECS: i64 = 30;
+const RETRY_MAX_DELAY_SECS: i64 = 60 * 60;
+
+/// 待機中のエアリプ候補の上限（超えたら古いものから捨てる。メンションとDMは捨てない）
+const AIR_REPLY_QUEUE_LIMIT: i64 = 30;
+
+/// キューの優先度（大きいほど先に処理）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueuePriority {
+    AirReply = 0,
+    Mention = 10,
+    DirectMessage = 20,
+}
+
+/// 処理順を保証する単位（同じBot・同じユーザーのイベントは同時に処理せず、キューの順に処理する）
+/// Noneはまだ分からないことを表し、どのBot・ユーザーとも重なるものとして扱う
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueueLane {
+    /// 宛先のBot（エアリプ候補は処理するまで決まらない）
+    pub bot_pubkey: Option<String>,
+    /// 相手のユーザー（NIP-17のDMは復号するまで分からない）
+    pub user_pubkey: Option<String>,
+}
+
+/// キューの1件
+#[derive(Debug, Clone, Serialize)]
+pub struct QueueItem {
+    pub id: i64,
+    pub event_id: Option<String>,
+    pub event_json: String,
+    pub priority: i64,
+    pub status: String,
+    pub attempts: i64,
+    pub next_attempt_at: i64,
+    pub last_error: Option<String>,
+    pub added_at: i64,
+    pub bot_pubkey: Option<String>,
+    pub user_pubkey: Option<String>,
+}
+
+/// 処理を諦めたイベント
+#[derive(Debug, Clone, Serialize)]
+pub struct DeadLetter {
+    pub id: i64,
+    pub event_id: Option<String>,
+    pub event_json: String,
+    pub priority: i64,
+    pub attempts: i64,
+    pub last_error: String,
+    pub added_at: i64,
+    pub failed_at: i64,
+}
+
+/// 処理失敗時の扱い
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryOutcome {
+    /// next_attempt_at以降に再試行する
+    Retrying { attempts: i64, next_attempt_at: i64 },
+    /// 再試行の上限に達したためdead_letterに移した
+    DeadLettered,
+}
+
+const QUEUE_COLUMNS: &str =
+    "id, event_id, event_json, priority, status, attempts, next_attempt_at, last_error, added_at, bot_pubkey, user_pubkey";
+
+fn queue_item_from_row(row: &Row) -> Result<QueueItem> {
+    Ok(QueueItem {
+        id: row.get(0)?,
+        event_id: row.get(1)?,
+        event_json: row.get(2)?,
+        priority: row.get(3)?,
+        status: row.get(4)?,
+        attempts: row.get(5)?,
+        next_attempt_at: row.get(6)?,
+        last_error: row.get(7)?,
+        added_at: row.get(8)?,
+        bot_pubkey: row.get(9)?,
+        user_pubkey: row.get(10)?,
+    })
+}
+
+/// イベントJSONからevent_idを抽出
+fn extract_event_id(event_json: &str) -> Option<String> {
+    serde_json::from_str::<serde_json::Value>(event_json)
+        .ok()
+        .and_then(|parsed| parsed["id"].as_str().map(|s| s.to_string()))
+}
+
+/// attempts回目の失敗後に待つ秒数
+fn retry_delay_secs(attempts: i64) -> i64 {
+    let exponent = (attempts - 1).clamp(0, 16) as u32;
+    (RETRY_BASE_DELAY_SECS * 2_i64.pow(exponent)).min(RETRY_MAX_DELAY_SECS)
+}
+
+/// イベントをキューに追加（処理済みのイベントは追加せずNoneを返す）
+pub fn enqueue_event(conn: &Connection, event_json: &str, priority: QueuePriority, lane: &QueueLane) -> Result<Option<i64>> {
     let now = Utc::now().timestamp();
+    let event_id = extract_event_id(event_json);
     
-    // event_idを抽出（重複チェック用）
-    let event_id = if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(event_json) {
-        parsed["id"].as_str().map(|s| s.to_string())
-    } else {
-        None
-    };
-    
-    // 既にキューに存在するかチェック
     if let Some(ref eid) = event_id {
-        let exists: i64 = conn.query_row(
-            "SELECT COUNT(*) FROM event_queue WHERE event_json LIKE ? AND status IN ('pending', 'processing')",
-            params![format!("%\"id\":\"{}%", eid)],
-            |row| row.get(0)
-        )?;
+        // 既に処理済みならキューに入れない（キャッチアップでの二重返信防止）
+        if is_event_processed(conn, eid)? {
+            return Ok(None);
+        }
         
-        if exists > 0 {
-            // 既に存在する場合は、既存のIDを返す（重複登録しない）
-            return conn.query_row(
-                "SELECT id FROM event_queue WHERE event_json LIKE ? AND status IN ('pending', 'processing') LIMIT 1",
-                params![format!("%\"id\":\"{}%", eid)],
-                |row| row.get(0)
-            );
+        // 既にキューにある場合は既存のIDを返す（重複登録しない）
+        let existing: Option<i64> = conn
+            .query_row(
+                "SELECT id FROM event_queue WHERE event_id = ? LIMIT 1",
+                params![eid],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if existing.is_some() {
+            return Ok(existing);
         }
     }
     
-    // 現在のキューサイズを確認
-    let queue_size: i64 = conn.query_row(
-        "SELECT COUNT(*) FROM event_queue WHERE status = 'pending'",
-        [],
-        |row| row.get(0)
-    )?;
-    
-    // 30件を超える場合は古いものを削除
-    if queue_size >= 30 {
-        let to_delete = queue_size - 29; // 1つ分の余地を作る
-        conn.execute(
-            "DELETE FROM event_queue 
-             WHERE id IN (
-                 SELECT id FROM event_queue 
-                 WHERE status = 'pending' 
-                 ORDER BY added_at ASC 
-                 LIMIT ?
-             )",
-            params![to_delete],
+    // エアリプ候補が溜まりすぎたら古いものを捨てる（時間が経ったエアリプは価値がない）
+    if priority == QueuePriority::AirReply {
+        let air_reply_count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM event_queue WHERE status = 'pending' AND priority = ?",
+            params![QueuePriority::AirReply as i64],
+            |row| row.get(0),
         )?;
+        if air_reply_count >= AIR_REPLY_QUEUE_LIMIT {
+            conn.execute(
+                "DELETE FROM event_queue
+                 WHERE id IN (
+                     SELECT id FROM event_queue
+                     WHERE status = 'pending' AND priority = ?
+                     ORDER BY added_at ASC
+                     LIMIT ?
+                 )",
+                params![QueuePriority::AirReply as i64, air_reply_count - AIR_REPLY_QUEUE_LIMIT + 1],
+            )?;
+        }
     }
     
-    // イベントを追加
     conn.execute(
-        "INSERT INTO event_queue (event_json, added_at, status) VALUES (?, ?, 'pending')",
-        params![event_json, now],
+        "INSERT INTO event_queue (event_id, event_json, priority, added_at, status, attempts, next_attempt_at, bot_pubkey, user_pubkey)
+         VALUES (?, ?, ?, ?, 'pending', 0, 0, ?, ?)",
+        params![event_id, event_json, priority as i64, now, lane.bot_pubkey, lane.user_pubkey],
     )?;
     
-    Ok(conn.last_insert_rowid())
+    Ok(Some(conn.last_insert_rowid()))
 }
 
 /// キューから次の処理対象イベントを取得（ステータスを'processing'に更新）
-pub fn dequeue_event(conn: &Connection) -> Result<Option<(i64, String)>> {
-    // トランザクション開始
-    let tx = conn.unchecked_transaction()?;
-    
-    // 最も古いpendingイベントを取得
-    let result = tx.query_row(
-        "SELECT id, event_json FROM event_queue 
-         WHERE status = 'pending' 
-         ORDER BY added_at ASC 
-         LIMIT 1",
-        [],
-        |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
-    );
-    
-    match result {
-        Ok((id, event_json)) => {
-            // ステータスをprocessingに更新
-            tx.execute(
-                "UPDATE event_queue SET status = 'processing' WHERE id = ?",
-                params![id],
-            )?;
-            tx.commit()?;
-            Ok(Some((id, event_json)))
-        }
-        Err(rusqlite::Error::QueryReturnedNoRows) => {
-            tx.commit()?;
-            Ok(None)
-        }
-        Err(e) => Err(e),
-    }
+/// 優先度の高いものから、同じ優先度なら古いものから。再試行待ちのものは待ち時間が過ぎるまで取らない
+/// 同じBot・ユーザーのイベントを処理中のワーカーがいる場合はそのイベントを飛ばす
+/// 同じBot・ユーザーの先に入ったイベントが再試行待ちの間も、追い越さないよう飛ばす
+/// （Bot・ユーザーが分からないイベントの再試行待ちでは止めない。長いと最大1時間止まってしまうため）
+/// 複数のワーカーが同時に呼んでも同じイベントを取らないよう、1つのUPDATE文で取得する
+pub fn dequeue_event(conn: &Connection) -> Result<Option<QueueItem>> {
+    let now = Utc::now().timestamp();
+    conn.query_row(
+        &format!(
+            "UPDATE event_queue SET status = 'processing'
+             WHERE id = (
+                 SELECT q.id FROM event_queue q
+                 WHERE q.status = 'pending' AND q.next_attempt_at <= ?
+                   AND NOT EXISTS (
+                       SELECT 1 FROM event_queue p
+                       WHERE p.status = 'processing'
+                         AND (p.bot_pubkey IS NULL OR q.bot_pubkey IS NULL OR p.bot_pubkey = q.bot_pubkey)
+                         AND (p.user_pubkey IS NULL OR q.user_pubkey IS NULL OR p.user_pubkey = q.user_pubkey)
+                   )
+                   AND NOT EXISTS (
+                       SELECT 1 FROM event_queue e
+                       WHERE e.status = 'pending'
+                         AND e.bot_pubkey = q.bot_pubkey AND e.user_pubkey = q.user_pubkey
+                         AND (e.added_at < q.added_at OR (e.added_at = q.added_at AND e.id < q.id))
+                   )
+                 ORDER BY q.priority DESC, q.added_at ASC, q.id ASC
+                 LIMIT 1
+             )
+             RETURNING {}",
+            QUEUE_COLUMNS
+        ),
+        params![now],
+        queue_item_from_row,
+    )
+    .optional()
+}
+
+/// 次に再試行待ちのイベントが取り出せるようになる時刻（再試行待ちのイベントがなければNone）
+pub fn next_queue_attempt_at(conn: &Connection) -> Result<Option<i64>> {
+    let now = Utc::now().timestamp();
+    conn.query_row(
+        "SELECT MIN(next_attempt_at) FROM event_queue WHERE status = 'pending' AND next_attempt_at > ?",
+        params![now],
+        |row| row.get(0),
+    )
 }
 
-/// 処理完了したイベントをキューから削除
+/// 処理完了したイベントをキューから削除し、処理済みとして記録
 pub fn complete_queue_event(conn: &Connection, id: i64) -> Result<()> {
+    let event_id: Option<String> = conn
+        .query_row("SELECT event_id FROM event_queue WHERE id = ?", params![id], |row| row.get(0))
+        .optional()?
+        .flatten();
+    if let Some(event_id) = event_id {
+        mark_event_processed(conn, &event_id)?;
+    }
     conn.execute("DELETE FROM event_queue WHERE id = ?", params![id])?;
     Ok(())
 }
 
-/// 処理失敗したイベントをpendingに戻す
-#[allow(dead_code)]
-pub fn retry_queue_event(conn: &Connection, id: i64) -> Result<()> {
+/// イベントを処理済みとして記録（古い記録はここで削除）
+pub fn mark_event_processed(conn: &Connection, event_id: &str) -> Result<()> {
+    let now = Utc::now().timestamp();
     conn.execute(
-        "UPDATE event_queue SET status = 'pending' WHERE id = ?",
-        params![id],
+        "INSERT OR IGNORE INTO processed_events (event_id, processed_at) VALUES (?, ?)",
+        params![event_id, now],
+    )?;
+    conn.execute(
+        "DELETE FROM processed_events WHERE processed_at < ?",
+        params![now - PROCESSED_EVENTS_RETENTION_SECS],
     )?;
     Ok(())
 }
 
+/// 処理済みのイベントか
+pub fn is_event_processed(conn: &Connection, event_id: &str) -> Result<bool> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM processed_events WHERE event_id = ?",
+        params![event_id],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+/// 処理に失敗したイベントを指数バックオフで再試行待ちに戻す（上限回数に達したらdead_letterへ）
+pub fn retry_queue_event(conn: &Connection, id: i64, error: &str) -> Result<RetryOutcome> {
+    let attempts = conn.query_row(
+        "SELECT attempts FROM event_queue WHERE id = ?",
+        params![id],
+        |row| row.get::<_, i64>(0),
+    )? + 1;
+    
+    if attempts >= MAX_QUEUE_ATTEMPTS {
+        conn.execute("UPDATE event_queue SET attempts = ? WHERE id = ?", params![attempts, id])?;
+        dead_letter_queue_event(conn, id, error)?;
+        return Ok(RetryOutcome::DeadLettered);
+    }
+    
+    let next_attempt_at = Utc::now().timestamp() + retry_delay_secs(attempts);
+    conn.execute(
+        "UPDATE event_queue
+         SET status = 'pending', attempts = ?, next_attempt_at = ?, last_error = ?
+         WHERE id = ?",
+        params![attempts, next_attempt_at, error, id],
+    )?;
+    Ok(RetryOutcome::Retrying { attempts, next_attempt_at })
+}
+
+/// イベントを再試行せずにdead_letterへ移す（復元できないイベントなど）
+pub fn dead_letter_queue_event(conn: &Connection, id: i64, error: &str) -> Result<()> {
+    let now = Utc::now().timestamp();
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "INSERT INTO dead_letter (event_id, event_json, priority, attempts, last_error, added_at, failed_at, bot_pubkey, user_pubkey)
+         SELECT event_id, event_json, priority, attempts, ?, added_at, ?, bot_pubkey, user_pubkey FROM event_queue WHERE id = ?",
+        params![error, now, id],
+    )?;
+    tx.execute("DELETE FROM event_queue WHERE id = ?", params![id])?;
+    tx.commit()
+}
+
+/// キューの一覧（処理順）
+pub fn list_queue_items(conn: &Connection, limit: usize) -> Result<Vec<QueueItem>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM event_queue
+         ORDER BY status = 'processing' DESC, priority DESC, added_at ASC, id ASC
+         LIMIT ?",
+        QUEUE_COLUMNS
+    ))?;
+    let items = stmt.query_map(params![limit as i64], queue_item_from_row)?;
+    items.collect()
+}
+
+/// キューからイベントを削除（処理済みにはしない）
+pub fn delete_queue_event(conn: &Connection, id: i64) -> Result<bool> {
+    Ok(conn.execute("DELETE FROM event_queue WHERE id = ?", params![id])? > 0)
+}
+
+/// dead_letterの一覧（新しい順）
+pub fn list_dead_letters(conn: &Connection, limit: usize) -> Result<Vec<DeadLetter>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, event_id, event_json, priority, attempts, last_error, added_at, failed_at
+         FROM dead_letter
+         ORDER BY failed_at DESC, id DESC
+         LIMIT ?",
+    )?;
+    let letters = stmt.query_map(params![limit as i64], |row| {
+        Ok(DeadLetter {
+            id: row.get(0)?,
+            event_id: row.get(1)?,
+            event_json: row.get(2)?,
+            priority: row.get(3)?,
+            attempts: row.get(4)?,
+            last_error: row.get(5)?,
+            added_at: row.get(6)?,
+            failed_at: row.get(7)?,
+        })
+    })?;
+    letters.collect()
+}
+
+/// dead_letterのイベントをキューに戻す（試行回数はリセット。見つからなければNone）
+pub fn requeue_dead_letter(conn: &Connection, id: i64) -> Result<Option<i64>> {
+    let now = Utc::now().timestamp();
+    let tx = conn.unchecked_transaction()?;
+    let inserted = tx.execute(
+        "INSERT INTO event_queue (event_id, event_json, priority, added_at, status, attempts, next_attempt_at, bot_pubkey, user_pubkey)
+         SELECT event_id, event_json, priority, ?, 'pending', 0, 0, bot_pubkey, user_pubkey FROM dead_letter WHERE id = ?",
+        params![now, id],
+    )?;
+    if inserted == 0 {
+        return Ok(None);
+    }
+    let queue_id = tx.last_insert_rowid();
+    tx.execute("DELETE FROM dead_letter WHERE id = ?", params![id])?;
+    tx.commit()?;
+    Ok(Some(queue_id))
+}
+
+/// dead_letterから削除
+pub fn delete_dead_letter(conn: &Connection, id: i64) -> Result<bool> {
+    Ok(conn.execute("DELETE FROM dead_letter WHERE id = ?", params![id])? > 0)
+}
+
+/// dead_letterを全て削除
+pub fn purge_dead_letters(conn: &Connection) -> Result<usize> {
+    conn.execute("DELETE FROM dead_letter", [])
+}
+
+/// dead_letterの件数
+pub fn count_dead_letters(conn: &Connection) -> Result<i64> {
+    conn.query_row("SELECT COUNT(*) FROM dead_letter", [], |row| row.get(0))
+}
+
 /// キューサイズを取得
-#[allow(dead_code)]
 pub fn get_queue_size(conn: &Connection) -> Result<i64> {
     let size: i64 = conn.query_row(
         "SELECT COUNT(*) FROM event_queue WHERE status IN ('pending', 'processing')",
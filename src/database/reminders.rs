@@ -0,0 +1,100 @@
+use rusqlite::{params, Connection, Result, Row};
+use chrono::Utc;
+use serde::Serialize;
+
+/// ユーザーから頼まれたリマインド
+#[derive(Debug, Clone, Serialize)]
+pub struct Reminder {
+    pub id: i64,
+    pub bot_pubkey: String,
+    pub user_pubkey: String,
+    /// 依頼した投稿（このスレッドにリマインドを返信する）
+    pub event_id: String,
+    #[serde(skip)]
+    pub event_json: String,
+    pub message: String,
+    pub remind_at: i64,
+    /// "pending" / "sent" / "cancelled" / "failed"
+    pub status: String,
+    pub created_at: i64,
+    pub sent_at: Option<i64>,
+}
+
+const REMINDER_COLUMNS: &str =
+    "id, bot_pubkey, user_pubkey, event_id, event_json, message, remind_at, status, created_at, sent_at";
+
+fn reminder_from_row(row: &Row) -> Result<Reminder> {
+    Ok(Reminder {
+        id: row.get(0)?,
+        bot_pubkey: row.get(1)?,
+        user_pubkey: row.get(2)?,
+        event_id: row.get(3)?,
+        event_json: row.get(4)?,
+        message: row.get(5)?,
+        remind_at: row.get(6)?,
+        status: row.get(7)?,
+        created_at: row.get(8)?,
+        sent_at: row.get(9)?,
+    })
+}
+
+/// リマインドを登録
+pub fn insert_reminder(
+    conn: &Connection,
+    bot_pubkey: &str,
+    user_pubkey: &str,
+    event_id: &str,
+    event_json: &str,
+    message: &str,
+    remind_at: i64,
+) -> Result<i64> {
+    let now = Utc::now().timestamp();
+    conn.execute(
+        "INSERT INTO reminders (bot_pubkey, user_pubkey, event_id, event_json, message, remind_at, status, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, 'pending', ?)",
+        params![bot_pubkey, user_pubkey, event_id, event_json, message, remind_at, now],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// ユーザーがBotに頼んでいる未送信のリマインド（早い順）
+pub fn get_pending_reminders(conn: &Connection, bot_pubkey: &str, user_pubkey: &str) -> Result<Vec<Reminder>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM reminders
+         WHERE bot_pubkey = ? AND user_pubkey = ? AND status = 'pending'
+         ORDER BY remind_at, id",
+        REMINDER_COLUMNS
+    ))?;
+    let reminders = stmt.query_map(params![bot_pubkey, user_pubkey], reminder_from_row)?;
+    reminders.collect()
+}
+
+/// 時刻を過ぎた未送信のリマインド（早い順）
+pub fn get_due_reminders(conn: &Connection, now: i64) -> Result<Vec<Reminder>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM reminders WHERE status = 'pending' AND remind_at <= ? ORDER BY remind_at, id",
+        REMINDER_COLUMNS
+    ))?;
+    let reminders = stmt.query_map(params![now], reminder_from_row)?;
+    reminders.collect()
+}
+
+/// 本人の未送信のリマインドを取り消す（取り消せなければfalse）
+pub fn cancel_reminder(conn: &Connection, id: i64, bot_pubkey: &str, user_pubkey: &str) -> Result<bool> {
+    let updated = conn.execute(
+        "UPDATE reminders SET status = 'cancelled'
+         WHERE id = ? AND bot_pubkey = ? AND user_pubkey = ? AND status = 'pending'",
+        params![id, bot_pubkey, user_pubkey],
+    )?;
+    Ok(updated > 0)
+}
+
+/// 送信結果を記録（"sent" または "failed"）
+pub fn finish_reminder(conn: &Connection, id: i64, status: &str) -> Result<()> {
+    let now = Utc::now().timestamp();
+    conn.execute(
+        "UPDATE reminders SET status = ?, sent_at = ? WHERE id = ?",
+        params![status, now, id],
+    )?;
+    Ok(())
+}
@@ -0,0 +1,125 @@
+use rusqlite::{params, Connection, OptionalExtension, Result, Row};
+use chrono::Utc;
+use serde::Serialize;
+
+/// Botの定期投稿スケジュール
+#[derive(Debug, Clone, Serialize)]
+pub struct BotSchedule {
+    pub id: i64,
+    pub bot_pubkey: String,
+    /// "greeting"（朝の挨拶）/ "mood"（今日の気分）/ "weekly_summary"（1週間の振り返り）
+    pub kind: String,
+    /// cron形式（分 時 日 月 曜日、JST）
+    pub cron: String,
+    /// 追加の指示（任意）
+    pub instruction: Option<String>,
+    pub enabled: bool,
+    pub last_run_at: Option<i64>,
+    /// 次回の実行時刻（無効なcronの場合はNone）
+    pub next_run_at: Option<i64>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+const SCHEDULE_COLUMNS: &str =
+    "id, bot_pubkey, kind, cron, instruction, enabled, last_run_at, next_run_at, created_at, updated_at";
+
+fn schedule_from_row(row: &Row) -> Result<BotSchedule> {
+    Ok(BotSchedule {
+        id: row.get(0)?,
+        bot_pubkey: row.get(1)?,
+        kind: row.get(2)?,
+        cron: row.get(3)?,
+        instruction: row.get(4)?,
+        enabled: row.get(5)?,
+        last_run_at: row.get(6)?,
+        next_run_at: row.get(7)?,
+        created_at: row.get(8)?,
+        updated_at: row.get(9)?,
+    })
+}
+
+/// スケジュールを追加
+pub fn insert_schedule(
+    conn: &Connection,
+    bot_pubkey: &str,
+    kind: &str,
+    cron: &str,
+    instruction: Option<&str>,
+    enabled: bool,
+    next_run_at: Option<i64>,
+) -> Result<i64> {
+    let now = Utc::now().timestamp();
+    conn.execute(
+        "INSERT INTO bot_schedules (bot_pubkey, kind, cron, instruction, enabled, next_run_at, created_at, updated_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        params![bot_pubkey, kind, cron, instruction, enabled, next_run_at, now, now],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// スケジュールを更新（見つからなければfalse）
+pub fn update_schedule(
+    conn: &Connection,
+    id: i64,
+    kind: &str,
+    cron: &str,
+    instruction: Option<&str>,
+    enabled: bool,
+    next_run_at: Option<i64>,
+) -> Result<bool> {
+    let now = Utc::now().timestamp();
+    let updated = conn.execute(
+        "UPDATE bot_schedules
+         SET kind = ?, cron = ?, instruction = ?, enabled = ?, next_run_at = ?, updated_at = ?
+         WHERE id = ?",
+        params![kind, cron, instruction, enabled, next_run_at, now, id],
+    )?;
+    Ok(updated > 0)
+}
+
+/// スケジュールを削除（見つからなければfalse）
+pub fn delete_schedule(conn: &Connection, id: i64) -> Result<bool> {
+    Ok(conn.execute("DELETE FROM bot_schedules WHERE id = ?", params![id])? > 0)
+}
+
+/// スケジュールを1件取得
+pub fn get_schedule(conn: &Connection, id: i64) -> Result<Option<BotSchedule>> {
+    conn.query_row(
+        &format!("SELECT {} FROM bot_schedules WHERE id = ?", SCHEDULE_COLUMNS),
+        params![id],
+        schedule_from_row,
+    )
+    .optional()
+}
+
+/// Botのスケジュール一覧
+pub fn get_bot_schedules(conn: &Connection, bot_pubkey: &str) -> Result<Vec<BotSchedule>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM bot_schedules WHERE bot_pubkey = ? ORDER BY id",
+        SCHEDULE_COLUMNS
+    ))?;
+    let schedules = stmt.query_map(params![bot_pubkey], schedule_from_row)?;
+    schedules.collect()
+}
+
+/// 実行時刻を過ぎた有効なスケジュール（古い順）
+pub fn get_due_schedules(conn: &Connection, now: i64) -> Result<Vec<BotSchedule>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM bot_schedules
+         WHERE enabled = 1 AND next_run_at IS NOT NULL AND next_run_at <= ?
+         ORDER BY next_run_at",
+        SCHEDULE_COLUMNS
+    ))?;
+    let schedules = stmt.query_map(params![now], schedule_from_row)?;
+    schedules.collect()
+}
+
+/// 実行した時刻と次回の実行時刻を記録
+pub fn mark_schedule_run(conn: &Connection, id: i64, ran_at: i64, next_run_at: Option<i64>) -> Result<()> {
+    conn.execute(
+        "UPDATE bot_schedules SET last_run_at = ?, next_run_at = ? WHERE id = ?",
+        params![ran_at, next_run_at, id],
+    )?;
+    Ok(())
+}
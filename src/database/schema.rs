@@ -13,18 +13,41 @@ pub fn initialize_db(conn: &Connection) -> Result<()> {
     super::migration::migrate_token_usage_table(conn)?;
     super::migration::migrate_add_token_text_columns(conn)?;
     super::migration::migrate_add_air_reply_single_ratio(conn)?;
+    super::migration::migrate_add_person_overrides(conn)?; // Bot個別設定
+    super::migration::migrate_add_direct_message_flag(conn)?; // DMの会話ログ
+    super::migration::migrate_event_queue_retry_columns(conn)?; // キューの優先度・再試行
+    super::migration::migrate_event_queue_lane_columns(conn)?; // Bot・ユーザー毎の処理順
     super::migration::migrate_remove_kind0_content(conn)?;
     super::migration::migrate_normalize_events_table(conn)?; // events正規化
     super::migration::migrate_add_user_impressions(conn)?; // ユーザー印象テーブル
     super::migration::migrate_add_bot_mental_state(conn)?; // Bot心境テーブル
     super::migration::migrate_remove_embedding_from_events(conn)?; // embedding削除
     super::migration::migrate_remove_embedding_from_summaries(conn)?; // embedding削除
+    super::migration::migrate_add_embeddings(conn)?; // ベクトルは別テーブルで管理
+    super::migration::migrate_add_events_fts(conn)?; // 投稿の全文検索
+    super::migration::migrate_add_token_usage_persona_version(conn)?; // プロンプトの版
+    super::persona_versions::ensure_initial_persona_versions(conn, None)?; // 履歴のないBotの最初の版
     
     Ok(())
 }
 
 /// 全テーブルを作成
 fn create_tables(conn: &Connection) -> Result<()> {
+    // Persons table（Bot一覧、追加カラムはマイグレーションで付与）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS Persons (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            status INTEGER NOT NULL DEFAULT 0,
+            prompt TEXT NOT NULL,
+            pubkey TEXT NOT NULL UNIQUE,
+            secretkey TEXT NOT NULL,
+            content TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+    
     // follower_cache table
     conn.execute(
         "CREATE TABLE IF NOT EXISTS follower_cache (
@@ -47,6 +70,17 @@ fn create_tables(conn: &Connection) -> Result<()> {
         [],
     )?;
     
+    // zapper_cache table（BotのLNURLサーバーがZapレシートに使う公開鍵）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS zapper_cache (
+            bot_pubkey TEXT PRIMARY KEY,
+            lud16 TEXT NOT NULL,
+            nostr_pubkey TEXT,
+            cached_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    
     // timeline table
     conn.execute(
         "CREATE TABLE IF NOT EXISTS timeline (
@@ -80,7 +114,14 @@ fn create_tables(conn: &Connection) -> Result<()> {
             id INTEGER PRIMARY KEY AUTOINCREMENT,
             event_json TEXT NOT NULL,
             added_at INTEGER NOT NULL,
-            status TEXT NOT NULL DEFAULT 'pending'
+            status TEXT NOT NULL DEFAULT 'pending',
+            event_id TEXT,
+            priority INTEGER NOT NULL DEFAULT 0,
+            attempts INTEGER NOT NULL DEFAULT 0,
+            next_attempt_at INTEGER NOT NULL DEFAULT 0,
+            last_error TEXT,
+            bot_pubkey TEXT,
+            user_pubkey TEXT
         )",
         [],
     )?;
@@ -91,6 +132,23 @@ fn create_tables(conn: &Connection) -> Result<()> {
         [],
     )?;
     
+    // dead_letter table（再試行の上限に達したイベント）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS dead_letter (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            event_id TEXT,
+            event_json TEXT NOT NULL,
+            priority INTEGER NOT NULL,
+            attempts INTEGER NOT NULL,
+            last_error TEXT NOT NULL,
+            added_at INTEGER NOT NULL,
+            failed_at INTEGER NOT NULL,
+            bot_pubkey TEXT,
+            user_pubkey TEXT
+        )",
+        [],
+    )?;
+    
     // events table
     conn.execute(
         "CREATE TABLE IF NOT EXISTS events (
@@ -134,6 +192,10 @@ fn create_tables(conn: &Connection) -> Result<()> {
         "CREATE INDEX IF NOT EXISTS idx_conversation_logs_thread ON conversation_logs(thread_root_id)",
         [],
     )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_conversation_logs_event ON conversation_logs(event_ref_id)",
+        [],
+    )?;
     
     // conversation_summaries table
     conn.execute(
@@ -182,6 +244,154 @@ fn create_tables(conn: &Connection) -> Result<()> {
         [],
     )?;
     
+    // relay_checkpoints table（リレー・購読毎に最後に処理したイベントのcreated_at）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS relay_checkpoints (
+            relay_url TEXT NOT NULL,
+            subscription TEXT NOT NULL,
+            last_created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            PRIMARY KEY (relay_url, subscription)
+        )",
+        [],
+    )?;
+    
+    // processed_events table（処理済みイベント。キャッチアップ時の重複処理防止）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS processed_events (
+            event_id TEXT PRIMARY KEY,
+            processed_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_processed_events_processed_at ON processed_events(processed_at)",
+        [],
+    )?;
+    
+    // bot_engagements table（Botの投稿へのリアクション・Zap）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS bot_engagements (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            bot_pubkey TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            event_id TEXT UNIQUE NOT NULL,
+            from_pubkey TEXT NOT NULL,
+            target_event_id TEXT,
+            content TEXT NOT NULL,
+            amount_sats INTEGER NOT NULL DEFAULT 0,
+            created_at INTEGER NOT NULL,
+            received_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_bot_engagements_bot ON bot_engagements(bot_pubkey, created_at DESC)",
+        [],
+    )?;
+    
+    // tool_call_logs table（返信・検索で実行したツールの呼び出しと結果）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS tool_call_logs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            bot_pubkey TEXT NOT NULL,
+            user_pubkey TEXT,
+            event_id TEXT,
+            tool_name TEXT NOT NULL,
+            arguments TEXT NOT NULL,
+            result TEXT NOT NULL,
+            is_error INTEGER NOT NULL DEFAULT 0,
+            duration_ms INTEGER NOT NULL,
+            created_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_tool_call_logs_bot ON tool_call_logs(bot_pubkey, created_at DESC)",
+        [],
+    )?;
+    
+    // bot_schedules table（Botごとの定期投稿。cronはJSTで評価する）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS bot_schedules (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            bot_pubkey TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            cron TEXT NOT NULL,
+            instruction TEXT,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            last_run_at INTEGER,
+            next_run_at INTEGER,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_bot_schedules_next_run ON bot_schedules(enabled, next_run_at)",
+        [],
+    )?;
+    
+    // reminders table（ユーザーから頼まれたリマインド。再起動しても残る）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS reminders (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            bot_pubkey TEXT NOT NULL,
+            user_pubkey TEXT NOT NULL,
+            event_id TEXT NOT NULL,
+            event_json TEXT NOT NULL,
+            message TEXT NOT NULL,
+            remind_at INTEGER NOT NULL,
+            status TEXT NOT NULL DEFAULT 'pending',
+            created_at INTEGER NOT NULL,
+            sent_at INTEGER
+        )",
+        [],
+    )?;
+    
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_reminders_pending ON reminders(status, remind_at)",
+        [],
+    )?;
+    
+    // error_log table（WARN/ERRORのログ。ダッシュボードの統計に表示する）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS error_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp INTEGER NOT NULL,
+            level TEXT NOT NULL,
+            target TEXT NOT NULL,
+            message TEXT NOT NULL,
+            event_id TEXT,
+            bot_pubkey TEXT,
+            user_pubkey TEXT
+        )",
+        [],
+    )?;
+    
+    // persona_versions table（Botのプロンプト・kind 0の変更履歴）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS persona_versions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            bot_pubkey TEXT NOT NULL,
+            prompt TEXT NOT NULL,
+            content TEXT NOT NULL,
+            author TEXT,
+            source TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_persona_versions_bot ON persona_versions(bot_pubkey, id DESC)",
+        [],
+    )?;
+    
     Ok(())
 }
 
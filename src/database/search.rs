@@ -0,0 +1,97 @@
+use rusqlite::{params_from_iter, Connection, Result};
+use rusqlite::types::Value;
+use super::events::EventRecord;
+
+/// trigramで索引を引ける最小の文字数（これより短い語はLIKEで探す）
+const TRIGRAM_MIN_CHARS: usize = 3;
+
+/// ローカルの投稿検索の条件
+#[derive(Debug, Clone, Default)]
+pub struct LocalSearchQuery {
+    /// 検索語（空白区切りで全てを含む投稿を探す）
+    pub keyword: String,
+    /// 投稿者（hex）
+    pub author: Option<String>,
+    /// この時刻以降（UNIX秒）
+    pub since: Option<i64>,
+    /// この時刻以前（UNIX秒）
+    pub until: Option<i64>,
+    pub limit: usize,
+}
+
+/// FTS5の検索式に変換（短すぎてtrigramで引けない場合はNone）
+/// 記号がFTS5の演算子として解釈されないよう、語をフレーズとして引用する
+pub fn fts_phrase(term: &str) -> Option<String> {
+    let term = term.trim();
+    if term.chars().count() < TRIGRAM_MIN_CHARS {
+        return None;
+    }
+    Some(format!("\"{}\"", term.replace('"', "\"\"")))
+}
+
+/// LIKEのワイルドカード（%と_）とエスケープ文字をエスケープする（ESCAPE '\\'と組み合わせる）
+fn escape_like(term: &str) -> String {
+    term.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+/// ローカルに保存した投稿（kind 1）を全文検索（新しい順）
+pub fn search_events_local(conn: &Connection, query: &LocalSearchQuery) -> Result<Vec<EventRecord>> {
+    let mut where_clauses = vec!["e.kind = 1".to_string()];
+    let mut values: Vec<Value> = Vec::new();
+
+    // 3文字以上の語はFTS5、それより短い語はLIKE（どちらも全てを含むものに絞る）
+    let mut phrases = Vec::new();
+    for term in query.keyword.split_whitespace() {
+        match fts_phrase(term) {
+            Some(phrase) => phrases.push(phrase),
+            None => {
+                where_clauses.push("e.content LIKE ? ESCAPE '\\'".to_string());
+                values.push(Value::from(format!("%{}%", escape_like(term))));
+            }
+        }
+    }
+    if !phrases.is_empty() {
+        where_clauses.push("e.id IN (SELECT rowid FROM events_fts WHERE events_fts MATCH ?)".to_string());
+        values.push(Value::from(phrases.join(" AND ")));
+    }
+
+    if let Some(author) = &query.author {
+        where_clauses.push("e.pubkey = ?".to_string());
+        values.push(Value::from(author.clone()));
+    }
+    if let Some(since) = query.since {
+        where_clauses.push("e.created_at >= ?".to_string());
+        values.push(Value::from(since));
+    }
+    if let Some(until) = query.until {
+        where_clauses.push("e.created_at <= ?".to_string());
+        values.push(Value::from(until));
+    }
+    values.push(Value::from(query.limit as i64));
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT e.id, e.event_id, e.event_json, e.pubkey, e.kind, e.content, e.created_at, e.received_at, e.language
+         FROM events e
+         WHERE {}
+         ORDER BY e.created_at DESC
+         LIMIT ?",
+        where_clauses.join(" AND ")
+    ))?;
+
+    let events = stmt.query_map(params_from_iter(values), |row| {
+        Ok(EventRecord {
+            id: row.get(0)?,
+            event_id: row.get(1)?,
+            event_json: row.get(2)?,
+            pubkey: row.get(3)?,
+            kind: row.get(4)?,
+            content: row.get(5)?,
+            created_at: row.get(6)?,
+            received_at: row.get(7)?,
+            language: row.get(8)?,
+        })
+    })?
+    .collect::<Result<Vec<_>>>()?;
+
+    Ok(events)
+}
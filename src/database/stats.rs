@@ -39,8 +39,9 @@ pub fn get_dashboard_stats(conn: &Connection) -> Result<DashboardStats> {
         |row| row.get(0)
     ).unwrap_or(0);
     
-    // RAG統計（embedding無効化のため常に0）
-    let vectorized_events: u32 = 0;
+    // RAG統計（ベクトル化の対象はBotとの会話のテキスト投稿のみ）
+    let vectorized_events = super::embeddings::count_embeddings(conn, super::embeddings::EmbeddingSource::Event)
+        .unwrap_or(0);
     
     let total_events: u32 = conn.query_row(
         "SELECT COUNT(*) FROM events",
@@ -48,7 +49,18 @@ pub fn get_dashboard_stats(conn: &Connection) -> Result<DashboardStats> {
         |row| row.get(0)
     ).unwrap_or(0);
     
-    let pending_vectorization = total_events.saturating_sub(vectorized_events);
+    let vectorizable_events: u32 = conn.query_row(
+        "SELECT COUNT(*) FROM events e
+         WHERE e.kind IN (1, 42) AND e.content != ''
+           AND EXISTS (
+               SELECT 1 FROM conversation_logs cl
+               WHERE cl.event_ref_id = e.id AND cl.is_direct_message = 0
+           )",
+        [],
+        |row| row.get(0)
+    ).unwrap_or(0);
+    
+    let pending_vectorization = vectorizable_events.saturating_sub(vectorized_events);
     
     // レート制限されたユーザー数（過去N分間でM回以上会話したユーザー）
     // 簡易実装: 過去3分間で5回以上会話したユーザー数
@@ -75,6 +87,10 @@ pub fn get_dashboard_stats(conn: &Connection) -> Result<DashboardStats> {
     // 実装するには検索履歴テーブルが必要
     let average_similarity: f64 = 0.0;
     
+    // 今日Botに届いたリアクション・Zap
+    let engagements_today = super::engagement::count_engagements_since(conn, None, today_start)
+        .unwrap_or_default();
+    
     Ok(DashboardStats {
         replies_today,
         replies_week,
@@ -87,6 +103,9 @@ pub fn get_dashboard_stats(conn: &Connection) -> Result<DashboardStats> {
         pending_vectorization,
         total_searches,
         average_similarity,
+        reactions_today: engagements_today.reactions,
+        zaps_today: engagements_today.zaps,
+        zap_sats_today: engagements_today.zap_sats,
     })
 }
 
@@ -103,4 +122,7 @@ pub struct DashboardStats {
     pub pending_vectorization: u32,
     pub total_searches: u32,
     pub average_similarity: f64,
+    pub reactions_today: u32,
+    pub zaps_today: u32,
+    pub zap_sats_today: i64,
 }
@@ -1,4 +1,5 @@
 use rusqlite::{params, Connection, Result};
+use tracing::info;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(i32)]
@@ -9,9 +10,17 @@ pub enum TokenCategory {
     SearchInitialReply = 4,
     SearchKeywordExtraction = 5,
     SearchFinalReply = 6,
+    DirectMessage = 7,
+    ZapThanks = 8,
+    ToolCall = 9,
+    ScheduledPost = 10,
+    Reminder = 11,
+    /// ダッシュボードでのプロンプト試行
+    Playground = 12,
 }
 
 impl TokenCategory {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s {
             "reply" => Some(Self::Reply),
@@ -20,6 +29,12 @@ impl TokenCategory {
             "search_initial_reply" => Some(Self::SearchInitialReply),
             "search_keyword_extraction" => Some(Self::SearchKeywordExtraction),
             "search_final_reply" => Some(Self::SearchFinalReply),
+            "direct_message" => Some(Self::DirectMessage),
+            "zap_thanks" => Some(Self::ZapThanks),
+            "tool_call" => Some(Self::ToolCall),
+            "scheduled_post" => Some(Self::ScheduledPost),
+            "reminder" => Some(Self::Reminder),
+            "playground" => Some(Self::Playground),
             _ => None,
         }
     }
@@ -32,6 +47,12 @@ impl TokenCategory {
             Self::SearchInitialReply => "search_initial_reply",
             Self::SearchKeywordExtraction => "search_keyword_extraction",
             Self::SearchFinalReply => "search_final_reply",
+            Self::DirectMessage => "direct_message",
+            Self::ZapThanks => "zap_thanks",
+            Self::ToolCall => "tool_call",
+            Self::ScheduledPost => "scheduled_post",
+            Self::Reminder => "reminder",
+            Self::Playground => "playground",
         }
     }
     
@@ -43,6 +64,12 @@ impl TokenCategory {
             Self::SearchInitialReply => "検索一次回答",
             Self::SearchKeywordExtraction => "キーワード抽出",
             Self::SearchFinalReply => "検索最終回答",
+            Self::DirectMessage => "DM返信",
+            Self::ZapThanks => "Zapのお礼",
+            Self::ToolCall => "ツール呼び出し",
+            Self::ScheduledPost => "定期投稿",
+            Self::Reminder => "リマインド",
+            Self::Playground => "プロンプト試行",
         }
     }
     
@@ -56,6 +83,12 @@ impl TokenCategory {
             Self::SearchInitialReply,
             Self::SearchKeywordExtraction,
             Self::SearchFinalReply,
+            Self::DirectMessage,
+            Self::ZapThanks,
+            Self::ToolCall,
+            Self::ScheduledPost,
+            Self::Reminder,
+            Self::Playground,
         ]
     }
 }
@@ -75,13 +108,14 @@ pub fn record_token_usage(
     let now = chrono::Utc::now().timestamp();
     let total_tokens = prompt_tokens + completion_tokens;
     
+    // その時点のプロンプトの版も記録する（版同士で返信を比べるため）
     conn.execute(
-        "INSERT INTO token_usage (bot_pubkey, category_id, prompt_tokens, completion_tokens, total_tokens, prompt_text, completion_text, created_at)
-         VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        "INSERT INTO token_usage (bot_pubkey, category_id, prompt_tokens, completion_tokens, total_tokens, prompt_text, completion_text, created_at, persona_version_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, (SELECT MAX(id) FROM persona_versions WHERE bot_pubkey = ?1))",
         params![bot_pubkey, category_enum as i32, prompt_tokens as i64, completion_tokens as i64, total_tokens as i64, prompt_text, completion_text, now],
     )?;
     
-    println!("[Token] Bot: {}, カテゴリ: {} ({}), プロンプト: {}トークン, 完了: {}トークン, 合計: {}トークン", 
+    info!("[Token] Bot: {}, カテゴリ: {} ({}), プロンプト: {}トークン, 完了: {}トークン, 合計: {}トークン", 
              &bot_pubkey[..8], category_enum.display_name(), category_enum.name(), prompt_tokens, completion_tokens, total_tokens);
     
     Ok(())
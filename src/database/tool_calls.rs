@@ -0,0 +1,71 @@
+use rusqlite::{params, Connection, Result};
+use chrono::Utc;
+use serde::Serialize;
+
+/// ツール呼び出しの記録
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolCallLog {
+    pub id: i64,
+    pub bot_pubkey: String,
+    /// 話しかけてきたユーザー
+    pub user_pubkey: Option<String>,
+    /// きっかけになったイベント
+    pub event_id: Option<String>,
+    pub tool_name: String,
+    /// 引数（JSON文字列）
+    pub arguments: String,
+    /// LLMに返した結果
+    pub result: String,
+    pub is_error: bool,
+    pub duration_ms: i64,
+    pub created_at: i64,
+}
+
+/// ツール呼び出しを記録
+#[allow(clippy::too_many_arguments)]
+pub fn insert_tool_call_log(
+    conn: &Connection,
+    bot_pubkey: &str,
+    user_pubkey: Option<&str>,
+    event_id: Option<&str>,
+    tool_name: &str,
+    arguments: &str,
+    result: &str,
+    is_error: bool,
+    duration_ms: i64,
+) -> Result<i64> {
+    let now = Utc::now().timestamp();
+    conn.execute(
+        "INSERT INTO tool_call_logs
+            (bot_pubkey, user_pubkey, event_id, tool_name, arguments, result, is_error, duration_ms, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        params![bot_pubkey, user_pubkey, event_id, tool_name, arguments, result, is_error, duration_ms, now],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Botのツール呼び出しを新しい順に取得
+pub fn get_tool_call_logs(conn: &Connection, bot_pubkey: &str, limit: usize) -> Result<Vec<ToolCallLog>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, bot_pubkey, user_pubkey, event_id, tool_name, arguments, result, is_error, duration_ms, created_at
+         FROM tool_call_logs
+         WHERE bot_pubkey = ?
+         ORDER BY id DESC
+         LIMIT ?",
+    )?;
+    let logs = stmt.query_map(params![bot_pubkey, limit as i64], |row| {
+        Ok(ToolCallLog {
+            id: row.get(0)?,
+            bot_pubkey: row.get(1)?,
+            user_pubkey: row.get(2)?,
+            event_id: row.get(3)?,
+            tool_name: row.get(4)?,
+            arguments: row.get(5)?,
+            result: row.get(6)?,
+            is_error: row.get(7)?,
+            duration_ms: row.get(8)?,
+            created_at: row.get(9)?,
+        })
+    })?;
+    logs.collect()
+}
@@ -0,0 +1,177 @@
+// ダイレクトメッセージ（NIP-17 / NIP-04）の復号と返信
+// 受信したDMを宛先Botの秘密鍵で復号し、同じ方式で返信する
+
+use crate::config::AppConfig;
+use crate::database as db;
+use crate::live;
+use crate::relay_pool;
+use nostr_sdk::prelude::*;
+use std::error::Error;
+use std::time::Duration;
+use tracing::{error, info};
+
+/// DMの方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DmScheme {
+    /// NIP-17（kind 1059のGift Wrapに包まれたkind 14）
+    Nip17,
+    /// NIP-04（kind 4、レガシー）
+    Nip04,
+}
+
+/// 復号済みのDM
+#[derive(Debug, Clone)]
+pub struct DirectMessage {
+    pub bot: db::Person,
+    pub sender: PublicKey,
+    pub content: String,
+    /// 本文のイベントID（NIP-17はrumor、NIP-04はkind 4のID）
+    pub message_id: EventId,
+    pub created_at: Timestamp,
+    pub scheme: DmScheme,
+    /// DB保存用のJSON（NIP-17はrumor、NIP-04は受信したイベント）
+    pub event_json: String,
+}
+
+/// Botが送信したDM（DB保存用）
+#[derive(Debug, Clone)]
+pub struct SentDirectMessage {
+    pub message_id: EventId,
+    pub kind: Kind,
+    pub created_at: Timestamp,
+    pub event_json: String,
+}
+
+/// DMとして扱うkindか
+pub fn is_direct_message_kind(kind: Kind) -> bool {
+    kind == Kind::GiftWrap || kind == Kind::EncryptedDirectMessage
+}
+
+/// 宛先（pタグ）のBotを探す
+fn find_recipient<'a>(persons: &'a [db::Person], event: &Event) -> Option<&'a db::Person> {
+    event.tags.public_keys().find_map(|pubkey| {
+        let hex = pubkey.to_hex();
+        persons.iter().find(|p| p.pubkey == hex)
+    })
+}
+
+/// DMイベントを宛先Botの鍵で復号（Bot宛てでなければNone）
+pub async fn open(persons: &[db::Person], event: &Event) -> Result<Option<DirectMessage>, Box<dyn Error>> {
+    let bot = match find_recipient(persons, event) {
+        Some(bot) => bot.clone(),
+        None => return Ok(None),
+    };
+    let keys = Keys::parse(&bot.secretkey)?;
+
+    if event.kind == Kind::GiftWrap {
+        let unwrapped = UnwrappedGift::from_gift_wrap(&keys, event).await?;
+        let mut rumor = unwrapped.rumor;
+        // sealの署名者とrumorの作成者が異なるものはなりすましとして捨てる
+        if rumor.pubkey != unwrapped.sender {
+            return Err("rumorの作成者がsealの署名者と一致しません".into());
+        }
+        if rumor.kind != Kind::PrivateDirectMessage {
+            return Ok(None);
+        }
+        rumor.ensure_id();
+        let message_id = rumor.id.ok_or("rumorのIDを計算できませんでした")?;
+        let event_json = rumor.as_json();
+        Ok(Some(DirectMessage {
+            bot,
+            sender: unwrapped.sender,
+            content: rumor.content,
+            message_id,
+            created_at: rumor.created_at,
+            scheme: DmScheme::Nip17,
+            event_json,
+        }))
+    } else if event.kind == Kind::EncryptedDirectMessage {
+        let content = nip04::decrypt(keys.secret_key(), &event.pubkey, &event.content)?;
+        Ok(Some(DirectMessage {
+            bot,
+            sender: event.pubkey,
+            content,
+            message_id: event.id,
+            created_at: event.created_at,
+            scheme: DmScheme::Nip04,
+            event_json: event.as_json(),
+        }))
+    } else {
+        Ok(None)
+    }
+}
+
+/// 受信者のDM用リレー（NIP-17のkind 10050）を取得。なければwriteリレー
+async fn inbox_relays(config: &AppConfig, receiver: PublicKey) -> Vec<String> {
+    let filter = Filter::new().author(receiver).kind(Kind::InboxRelays).limit(1);
+    let latest = relay_pool::shared()
+        .fetch_latest(filter, &config.relay_servers.read, Duration::from_secs(5))
+        .await;
+
+    let relays: Vec<String> = match latest {
+        Ok(Some(event)) => event
+            .tags
+            .iter()
+            .filter_map(|tag| match tag.as_standardized() {
+                Some(TagStandard::Relay(url)) => Some(url.to_string()),
+                _ => None,
+            })
+            .collect(),
+        Ok(None) => Vec::new(),
+        Err(e) => {
+            error!("[DM] DM用リレーの取得エラー: {}", e);
+            Vec::new()
+        }
+    };
+
+    if relays.is_empty() {
+        config.relay_servers.write.clone()
+    } else {
+        relays
+    }
+}
+
+/// 受信したDMと同じ方式で返信
+pub async fn send_reply(config: &AppConfig, dm: &DirectMessage, text: &str) -> Result<SentDirectMessage, Box<dyn Error>> {
+    let keys = Keys::parse(&dm.bot.secretkey)?;
+    let pool = relay_pool::shared();
+
+    match dm.scheme {
+        DmScheme::Nip17 => {
+            let mut rumor = EventBuilder::private_msg_rumor(dm.sender, text)
+                .tag(Tag::event(dm.message_id))
+                .build(keys.public_key());
+            rumor.ensure_id();
+            let message_id = rumor.id.ok_or("rumorのIDを計算できませんでした")?;
+            let gift_wrap = EventBuilder::gift_wrap(&keys, &dm.sender, rumor.clone(), []).await?;
+
+            let relays = inbox_relays(config, dm.sender).await;
+            let result = pool.publish(&gift_wrap, &relays).await?;
+            info!("[DM] NIP-17で返信しました accepted:{:?}", result.accepted);
+            live::outgoing(&dm.bot.pubkey, message_id, rumor.kind, Some(dm.message_id), None);
+
+            Ok(SentDirectMessage {
+                message_id,
+                kind: rumor.kind,
+                created_at: rumor.created_at,
+                event_json: rumor.as_json(),
+            })
+        }
+        DmScheme::Nip04 => {
+            let encrypted = nip04::encrypt(keys.secret_key(), &dm.sender, text)?;
+            let builder = EventBuilder::new(Kind::EncryptedDirectMessage, encrypted)
+                .tag(Tag::public_key(dm.sender))
+                .tag(Tag::event(dm.message_id));
+            let (event, result) = pool.publish_as(&keys, builder, &config.relay_servers.write).await?;
+            info!("[DM] NIP-04で返信しました accepted:{:?}", result.accepted);
+            live::outgoing(&dm.bot.pubkey, event.id, event.kind, Some(dm.message_id), None);
+
+            Ok(SentDirectMessage {
+                message_id: event.id,
+                kind: event.kind,
+                created_at: event.created_at,
+                event_json: event.as_json(),
+            })
+        }
+    }
+}
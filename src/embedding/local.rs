@@ -0,0 +1,61 @@
+use super::{EmbeddingFuture, EmbeddingProvider};
+use crate::llm::LlmError;
+use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use tracing::info;
+
+/// 読み込み済みのモデル（読み込みに時間がかかるのでプロセスで1つだけ持つ）
+static MODEL: OnceLock<(String, Arc<Mutex<TextEmbedding>>)> = OnceLock::new();
+
+/// CPUで動くローカルモデル（fastembed / ONNX Runtime）
+pub struct LocalEmbeddingProvider {
+    model_name: String,
+    model: Arc<Mutex<TextEmbedding>>,
+}
+
+impl LocalEmbeddingProvider {
+    /// 共有のモデルを使うプロバイダー（初回はモデルをダウンロードして読み込む）
+    pub fn shared(model_name: &str, cache_dir: Option<&str>) -> Result<Self, LlmError> {
+        if let Some((loaded_name, model)) = MODEL.get() {
+            if loaded_name != model_name {
+                return Err(format!("ローカルモデル{}を読み込み済みのため{}は使えません", loaded_name, model_name).into());
+            }
+            return Ok(Self { model_name: model_name.to_string(), model: Arc::clone(model) });
+        }
+
+        let kind: EmbeddingModel = model_name.parse()?;
+        let mut options = InitOptions::new(kind).with_show_download_progress(false);
+        if let Some(dir) = cache_dir {
+            options = options.with_cache_dir(PathBuf::from(dir));
+        }
+        info!("[Embedding] ローカルモデル{}を読み込みます", model_name);
+        let model = TextEmbedding::try_new(options).map_err(|e| e.to_string())?;
+        let (_, model) = MODEL.get_or_init(|| (model_name.to_string(), Arc::new(Mutex::new(model))));
+        Ok(Self { model_name: model_name.to_string(), model: Arc::clone(model) })
+    }
+}
+
+impl EmbeddingProvider for LocalEmbeddingProvider {
+    fn name(&self) -> &str {
+        "local"
+    }
+
+    fn model(&self) -> &str {
+        &self.model_name
+    }
+
+    fn embed<'a>(&'a self, texts: &'a [String]) -> EmbeddingFuture<'a> {
+        let model = Arc::clone(&self.model);
+        let texts = texts.to_vec();
+        Box::pin(async move {
+            // CPUで重い処理なので非同期ランタイムを塞がないようにする
+            tokio::task::spawn_blocking(move || {
+                let mut model = model.lock().map_err(|e| e.to_string())?;
+                model.embed(&texts, None).map_err(|e| LlmError::from(e.to_string()))
+            })
+            .await
+            .map_err(|e| e.to_string())?
+        })
+    }
+}
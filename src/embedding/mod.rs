@@ -0,0 +1,210 @@
+// 埋め込み（ベクトル化）プロバイダーモジュール
+// 過去の会話・要約の類似検索に使う。OpenAI / OpenAI互換API / ローカルモデル / テスト用を同じインターフェースで扱う
+
+pub mod openai;
+#[cfg(feature = "local-embedding")]
+pub mod local;
+pub mod scripted;
+
+pub use openai::OpenAiEmbeddingProvider;
+#[cfg(feature = "local-embedding")]
+pub use local::LocalEmbeddingProvider;
+pub use scripted::ScriptedEmbeddingProvider;
+
+use crate::config::{AppConfig, EmbeddingConfig, EmbeddingProviderKind};
+use crate::database as db;
+use crate::llm::{read_api_key, LlmError, BASE_URL_REQUIRED};
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+use tracing::{error, info};
+
+// プロバイダーが返すFuture（入力と同じ順のベクトル）
+pub type EmbeddingFuture<'a> = Pin<Box<dyn Future<Output = Result<Vec<Vec<f32>>, LlmError>> + Send + 'a>>;
+
+/// 埋め込みプロバイダーの共通インターフェース
+pub trait EmbeddingProvider: Send + Sync {
+    /// プロバイダー名（ログ用）
+    fn name(&self) -> &str;
+
+    /// 使用するモデル名（embeddingsテーブルにはモデル毎に保存する）
+    fn model(&self) -> &str;
+
+    /// テキストをまとめてベクトル化
+    fn embed<'a>(&'a self, texts: &'a [String]) -> EmbeddingFuture<'a>;
+}
+
+/// 設定からプロバイダーを生成（無効ならNone）
+pub fn create_provider(config: &EmbeddingConfig) -> Result<Option<Box<dyn EmbeddingProvider>>, LlmError> {
+    match config.provider {
+        EmbeddingProviderKind::Disabled => Ok(None),
+        EmbeddingProviderKind::OpenAi => {
+            let env_name = config.api_key_env.as_deref().unwrap_or("OPEN_AI_API_KEY");
+            let api_key = read_api_key(Some(env_name))
+                .ok_or_else(|| format!("{} is not set", env_name))?;
+            Ok(Some(Box::new(OpenAiEmbeddingProvider::new(None, Some(api_key), config.model.clone()))))
+        }
+        EmbeddingProviderKind::OpenAiCompatible => {
+            let base_url = config.base_url.clone()
+                .ok_or(BASE_URL_REQUIRED)?;
+            let api_key = read_api_key(config.api_key_env.as_deref());
+            Ok(Some(Box::new(OpenAiEmbeddingProvider::new(Some(base_url), api_key, config.model.clone()))))
+        }
+        #[cfg(feature = "local-embedding")]
+        EmbeddingProviderKind::Local => {
+            Ok(Some(Box::new(LocalEmbeddingProvider::shared(&config.model, config.cache_dir.as_deref())?)))
+        }
+        #[cfg(not(feature = "local-embedding"))]
+        EmbeddingProviderKind::Local => {
+            Err("embedding.provider: local を使うには --features local-embedding でビルドしてください".into())
+        }
+        EmbeddingProviderKind::Scripted => {
+            Ok(Some(Box::new(ScriptedEmbeddingProvider::new(config.model.clone()))))
+        }
+    }
+}
+
+/// コサイン類似度（長さが違う・ゼロベクトルなら0）
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let mut dot = 0.0f64;
+    let mut norm_a = 0.0f64;
+    let mut norm_b = 0.0f64;
+    for (x, y) in a.iter().zip(b) {
+        dot += (*x as f64) * (*y as f64);
+        norm_a += (*x as f64) * (*x as f64);
+        norm_b += (*y as f64) * (*y as f64);
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+/// 類似検索の問い合わせ（ベクトルと、比較するときのモデル・閾値）
+#[derive(Debug, Clone)]
+pub struct SimilarityQuery {
+    pub model: String,
+    pub vector: Vec<f32>,
+    pub threshold: f64,
+}
+
+impl SimilarityQuery {
+    /// 保存済みのベクトルとの類似度
+    pub fn similarity(&self, vector: &[f32]) -> f64 {
+        cosine_similarity(&self.vector, vector)
+    }
+}
+
+/// 入力文をベクトル化して問い合わせを作る（無効・失敗時はNoneで、呼び出し側は時系列のみで動く）
+pub async fn similarity_query(config: &AppConfig, text: &str) -> Option<SimilarityQuery> {
+    if text.trim().is_empty() {
+        return None;
+    }
+    let provider = match create_provider(&config.embedding) {
+        Ok(Some(provider)) => provider,
+        Ok(None) => return None,
+        Err(e) => {
+            error!("[Embedding] プロバイダー生成エラー: {}", e);
+            return None;
+        }
+    };
+    match provider.embed(&[text.to_string()]).await {
+        Ok(mut vectors) if !vectors.is_empty() => Some(SimilarityQuery {
+            model: provider.model().to_string(),
+            vector: vectors.swap_remove(0),
+            threshold: config.get_f64_setting("rag_similarity_threshold"),
+        }),
+        Ok(_) => None,
+        Err(e) => {
+            error!("[Embedding] ベクトル化エラー ({}): {}", provider.name(), e);
+            None
+        }
+    }
+}
+
+/// まだベクトル化していない投稿・要約を1バッチ分ベクトル化（ベクトル化した件数を返す）
+///
+/// 失敗した行は失敗回数を記録し、上限に達したら以後は対象から外す（同じ行で止まり続けないように）
+pub async fn vectorize_pending(config: &AppConfig, conn: &rusqlite::Connection) -> Result<usize, Box<dyn std::error::Error>> {
+    let provider = match create_provider(&config.embedding).map_err(|e| e.to_string())? {
+        Some(provider) => provider,
+        None => return Ok(0),
+    };
+    let batch_size = config.embedding.batch_size.max(1);
+    let mut vectorized = 0;
+
+    // 要約は件数が少なく検索に直接使うので先に処理する（失敗しても投稿の処理は続ける）
+    let batches = [
+        (db::EmbeddingSource::Summary, db::get_summaries_without_embedding(conn, provider.model(), batch_size)?),
+        (db::EmbeddingSource::Event, db::get_events_without_embedding(conn, provider.model(), batch_size)?),
+    ];
+    for (source, rows) in batches {
+        if rows.is_empty() {
+            continue;
+        }
+        let texts: Vec<String> = rows.iter().map(|(_, text)| text.clone()).collect();
+        match embed_batch(provider.as_ref(), &texts).await {
+            Ok(vectors) => {
+                for ((id, _), vector) in rows.iter().zip(vectors) {
+                    db::insert_embedding(conn, source, *id, provider.model(), &vector)?;
+                    vectorized += 1;
+                }
+            }
+            Err(e) => {
+                // どの行が原因か分からないので1件ずつやり直す
+                error!("[Embedding] {}のバッチのベクトル化エラー、1件ずつ再試行します: {}", source.as_str(), e);
+                for (id, text) in &rows {
+                    match embed_batch(provider.as_ref(), std::slice::from_ref(text)).await {
+                        Ok(mut vectors) => {
+                            db::insert_embedding(conn, source, *id, provider.model(), &vectors.swap_remove(0))?;
+                            vectorized += 1;
+                        }
+                        Err(e) => {
+                            error!("[Embedding] {} {} のベクトル化エラー: {}", source.as_str(), id, e);
+                            db::record_embedding_failure(conn, source, *id, provider.model(), &e)?;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Ok(vectorized)
+}
+
+/// テキストをまとめてベクトル化（入力と同じ数のベクトルが返らなければエラー）
+async fn embed_batch(provider: &dyn EmbeddingProvider, texts: &[String]) -> Result<Vec<Vec<f32>>, String> {
+    let vectors = provider.embed(texts).await.map_err(|e| e.to_string())?;
+    if vectors.len() != texts.len() {
+        return Err(format!("ベクトルの数が一致しません（{}件に対して{}件）", texts.len(), vectors.len()));
+    }
+    Ok(vectors)
+}
+
+/// バックグラウンドで投稿・要約をベクトル化し続ける（溜まっている間は続けて処理）
+pub async fn run_vectorizer(config: AppConfig) {
+    let interval = Duration::from_secs(config.embedding.interval_secs.max(1));
+    loop {
+        let vectorized = match db::connect() {
+            Ok(conn) => match vectorize_pending(&config, &conn).await {
+                Ok(count) => count,
+                Err(e) => {
+                    error!("[Embedding] バックグラウンドのベクトル化エラー: {}", e);
+                    0
+                }
+            },
+            Err(e) => {
+                error!("[Embedding] DB接続エラー: {}", e);
+                0
+            }
+        };
+        if vectorized > 0 {
+            info!("[Embedding] {}件をベクトル化しました", vectorized);
+        }
+        if vectorized < config.embedding.batch_size.max(1) {
+            tokio::time::sleep(interval).await;
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use super::{EmbeddingFuture, EmbeddingProvider};
+use crate::llm::LlmError;
+use openai_api_rs::v1::api::OpenAIClient;
+use openai_api_rs::v1::embedding::EmbeddingRequest;
+
+/// OpenAI / OpenAI互換API（Ollama / llama.cpp / vLLMなど）の/embeddings
+pub struct OpenAiEmbeddingProvider {
+    endpoint: Option<String>,
+    api_key: Option<String>,
+    model: String,
+}
+
+impl OpenAiEmbeddingProvider {
+    pub fn new(endpoint: Option<String>, api_key: Option<String>, model: String) -> Self {
+        // 末尾のスラッシュは除去（"/embeddings"と連結されるため）
+        let endpoint = endpoint.map(|url| url.trim_end_matches('/').to_string());
+        Self { endpoint, api_key, model }
+    }
+
+    async fn request(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, LlmError> {
+        let mut builder = OpenAIClient::builder().with_api_key(self.api_key.clone().unwrap_or_default());
+        if let Some(endpoint) = &self.endpoint {
+            builder = builder.with_endpoint(endpoint);
+        }
+        // Box<dyn Error>はSendではないのでここで文字列化する
+        let mut client = builder.build().map_err(|e| e.to_string())?;
+
+        let response = client.embedding(EmbeddingRequest::new(self.model.clone(), texts.to_vec())).await?;
+        let mut data = response.data;
+        data.sort_by_key(|d| d.index);
+        Ok(data.into_iter().map(|d| d.embedding).collect())
+    }
+}
+
+impl EmbeddingProvider for OpenAiEmbeddingProvider {
+    fn name(&self) -> &str {
+        if self.endpoint.is_some() {
+            "openai_compatible"
+        } else {
+            "openai"
+        }
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn embed<'a>(&'a self, texts: &'a [String]) -> EmbeddingFuture<'a> {
+        Box::pin(self.request(texts))
+    }
+}
@@ -0,0 +1,61 @@
+use super::{EmbeddingFuture, EmbeddingProvider};
+
+/// ベクトルの次元数
+const DIMENSIONS: usize = 256;
+
+/// 1件あたりの入力の上限（文字数）。超えるとバッチ全体がエラーになる（実際のAPIの入力長の上限を模す）
+pub const MAX_INPUT_CHARS: usize = 2000;
+
+/// テスト用の埋め込みプロバイダー（ネットワーク不要）
+///
+/// 文字bigramをハッシュして数える簡易なベクトルで、同じ言葉を含む文ほど類似度が高くなる
+pub struct ScriptedEmbeddingProvider {
+    model: String,
+}
+
+impl ScriptedEmbeddingProvider {
+    pub fn new(model: String) -> Self {
+        Self { model }
+    }
+
+    /// 1つのテキストをベクトル化（L2正規化済み）
+    pub fn vectorize(text: &str) -> Vec<f32> {
+        let chars: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+        let mut vector = vec![0.0f32; DIMENSIONS];
+        for pair in chars.windows(2) {
+            // FNV-1aで次元を決める（実行毎に変わらないように）
+            let mut hash: u64 = 0xcbf29ce484222325;
+            for c in pair {
+                for byte in (*c as u32).to_le_bytes() {
+                    hash ^= byte as u64;
+                    hash = hash.wrapping_mul(0x100000001b3);
+                }
+            }
+            vector[(hash % DIMENSIONS as u64) as usize] += 1.0;
+        }
+        let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|v| *v /= norm);
+        }
+        vector
+    }
+}
+
+impl EmbeddingProvider for ScriptedEmbeddingProvider {
+    fn name(&self) -> &str {
+        "scripted"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn embed<'a>(&'a self, texts: &'a [String]) -> EmbeddingFuture<'a> {
+        Box::pin(async move {
+            if let Some(text) = texts.iter().find(|text| text.chars().count() > MAX_INPUT_CHARS) {
+                return Err(format!("入力が長すぎます（{}文字）", text.chars().count()).into());
+            }
+            Ok(texts.iter().map(|text| Self::vectorize(text)).collect())
+        })
+    }
+}
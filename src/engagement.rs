@@ -0,0 +1,266 @@
+// Botの投稿へのリアクション（NIP-25 kind 7）とZap（NIP-57 kind 9735）
+// 受信したものを記録し、心境の更新やZapへのお礼に使う
+
+use crate::config::AppConfig;
+use crate::database as db;
+use crate::{relay_pool, util};
+use nostr_sdk::prelude::*;
+use std::collections::HashMap;
+use std::error::Error;
+use tracing::{error, info};
+
+/// 心境の更新時に振り返る期間（秒）
+const RECENT_WINDOW_SECS: i64 = 24 * 60 * 60;
+
+/// LNURLサーバーのZap用公開鍵をキャッシュする期間（秒）
+const ZAPPER_CACHE_TTL_SECS: i64 = 24 * 60 * 60;
+
+/// LNURLサーバーへの問い合わせのタイムアウト（秒）
+const LNURL_TIMEOUT_SECS: u64 = 10;
+
+/// リアクションかZapか
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngagementKind {
+    Reaction,
+    Zap,
+}
+
+impl EngagementKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Reaction => "reaction",
+            Self::Zap => "zap",
+        }
+    }
+}
+
+/// Bot宛てのリアクション・Zap
+#[derive(Debug, Clone)]
+pub struct Engagement {
+    pub bot: db::Person,
+    pub kind: EngagementKind,
+    /// リアクションした人・Zapを送った人（Zapはzap requestの作成者）
+    pub from: PublicKey,
+    /// 対象のBotの投稿
+    pub target_event_id: Option<EventId>,
+    /// リアクションの内容、またはZapに添えられたメッセージ
+    pub content: String,
+    pub amount_sats: u64,
+}
+
+/// リアクション・Zapとして扱うkindか
+pub fn is_engagement_kind(kind: Kind) -> bool {
+    kind == Kind::Reaction || kind == Kind::ZapReceipt
+}
+
+/// イベントからBot宛てのリアクション・Zapを取り出す（Bot宛てでなければNone）
+pub fn parse(persons: &[db::Person], event: &Event) -> Option<Engagement> {
+    // NIP-25では最後のpタグが対象投稿の作者
+    let bot = event
+        .tags
+        .public_keys()
+        .filter_map(|pubkey| {
+            let hex = pubkey.to_hex();
+            persons.iter().find(|p| p.pubkey == hex)
+        })
+        .last()?
+        .clone();
+    let target_event_id = event.tags.event_ids().last().copied();
+
+    if event.kind == Kind::Reaction {
+        if event.pubkey.to_hex() == bot.pubkey {
+            return None;
+        }
+        Some(Engagement {
+            bot,
+            kind: EngagementKind::Reaction,
+            from: event.pubkey,
+            target_event_id,
+            content: event.content.clone(),
+            amount_sats: 0,
+        })
+    } else if event.kind == Kind::ZapReceipt {
+        let mut bolt11 = None;
+        let mut zap_request = None;
+        for tag in event.tags.iter() {
+            match tag.as_standardized() {
+                Some(TagStandard::Bolt11(invoice)) => bolt11 = Some(invoice.clone()),
+                Some(TagStandard::Description(description)) => {
+                    zap_request = Event::from_json(description).ok();
+                }
+                _ => {}
+            }
+        }
+        // zap requestはZapした本人の署名があり、Botに宛てたものに限る
+        let zap_request = zap_request?;
+        if zap_request.kind != Kind::ZapRequest || zap_request.verify().is_err() {
+            return None;
+        }
+        if zap_request.tags.public_keys().next().map(|pubkey| pubkey.to_hex()).as_deref() != Some(bot.pubkey.as_str()) {
+            return None;
+        }
+        let amount_msats = bolt11
+            .and_then(|invoice| util::decode_bolt11_invoice(&invoice).ok())
+            .and_then(|invoice| invoice.amount_milli_satoshis())?;
+        // zap requestに金額があれば請求書の金額と一致すること
+        if let Some(TagStandard::Amount { millisats, .. }) = zap_request.tags.find_standardized(TagKind::Amount) {
+            if *millisats != amount_msats {
+                return None;
+            }
+        }
+        Some(Engagement {
+            bot,
+            kind: EngagementKind::Zap,
+            from: zap_request.pubkey,
+            target_event_id,
+            content: zap_request.content,
+            amount_sats: amount_msats / 1000,
+        })
+    } else {
+        None
+    }
+}
+
+/// Botのライトニングアドレス（lud16）のLNURLサーバーがZapレシートの署名に使う公開鍵（NIP-57のnostrPubkey）
+///
+/// lud16ごとにキャッシュする。lud16がない・Zapに対応していない場合はNone
+pub async fn zapper_pubkey(conn: &rusqlite::Connection, bot: &db::Person) -> Result<Option<PublicKey>, Box<dyn Error>> {
+    let lud16 = match serde_json::from_str::<serde_json::Value>(&bot.content)
+        .ok()
+        .and_then(|metadata| metadata["lud16"].as_str().map(|s| s.trim().to_string()))
+        .filter(|s| !s.is_empty())
+    {
+        Some(lud16) => lud16,
+        None => return Ok(None),
+    };
+    if let Some(cached) = db::get_zapper_cache(conn, &bot.pubkey, &lud16, ZAPPER_CACHE_TTL_SECS)? {
+        return Ok(cached.and_then(|hex| PublicKey::from_hex(&hex).ok()));
+    }
+
+    let (name, domain) = lud16.split_once('@').ok_or_else(|| format!("不正なlud16です: {}", lud16))?;
+    let response: serde_json::Value = reqwest::Client::new()
+        .get(format!("https://{}/.well-known/lnurlp/{}", domain, name))
+        .timeout(std::time::Duration::from_secs(LNURL_TIMEOUT_SECS))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let zapper = if response["allowsNostr"].as_bool() == Some(true) {
+        response["nostrPubkey"].as_str().and_then(|hex| PublicKey::from_hex(hex).ok())
+    } else {
+        None
+    };
+    db::set_zapper_cache(conn, &bot.pubkey, &lud16, zapper.map(|pubkey| pubkey.to_hex()).as_deref())?;
+    Ok(zapper)
+}
+
+/// Bot宛てのリアクション・Zapを記録（新規に記録した場合のみSome）
+///
+/// ZapレシートはBotのLNURLサーバーが署名したものだけを記録する
+pub async fn record(conn: &rusqlite::Connection, persons: &[db::Person], event: &Event) -> Result<Option<Engagement>, Box<dyn Error>> {
+    let engagement = match parse(persons, event) {
+        Some(engagement) => engagement,
+        None => return Ok(None),
+    };
+    if engagement.kind == EngagementKind::Zap && zapper_pubkey(conn, &engagement.bot).await? != Some(event.pubkey) {
+        info!("[Engagement] BotのLNURLサーバー以外が署名したZapレシートを無視: {}", event.id);
+        return Ok(None);
+    }
+    let target_event_id = engagement.target_event_id.map(|id| id.to_hex());
+    let inserted = db::insert_engagement(
+        conn,
+        &engagement.bot.pubkey,
+        engagement.kind.as_str(),
+        &event.id.to_hex(),
+        &engagement.from.to_hex(),
+        target_event_id.as_deref(),
+        &engagement.content,
+        engagement.amount_sats as i64,
+        event.created_at.as_u64() as i64,
+    )?;
+    if !inserted {
+        return Ok(None);
+    }
+    info!(
+        "[Engagement] {} -> {} ({}{})",
+        engagement.from.to_hex(),
+        engagement.bot.pubkey,
+        engagement.kind.as_str(),
+        if engagement.kind == EngagementKind::Zap { format!(" {} sats", engagement.amount_sats) } else { String::new() }
+    );
+    Ok(Some(engagement))
+}
+
+/// お礼のリプライを送るZapか
+pub fn should_thank(config: &AppConfig, engagement: &Engagement) -> bool {
+    let min_sats = config.get_i64_setting("zap_thanks_min_sats");
+    engagement.kind == EngagementKind::Zap && min_sats > 0 && engagement.amount_sats as i64 >= min_sats
+}
+
+/// 最近届いたリアクション・Zapを心境更新用のテキストにまとめる（なければ空文字）
+pub async fn recent_summary(conn: &rusqlite::Connection, bot_pubkey: &str) -> Result<String, Box<dyn Error>> {
+    let since = chrono::Utc::now().timestamp() - RECENT_WINDOW_SECS;
+    let records = db::get_recent_engagements(conn, bot_pubkey, since, 100)?;
+    if records.is_empty() {
+        return Ok(String::new());
+    }
+
+    // 人ごとに集計（多い順）
+    let mut reactions: HashMap<String, usize> = HashMap::new();
+    let mut zaps: HashMap<String, (i64, usize)> = HashMap::new();
+    for record in &records {
+        if record.kind == EngagementKind::Zap.as_str() {
+            let entry = zaps.entry(record.from_pubkey.clone()).or_default();
+            entry.0 += record.amount_sats;
+            entry.1 += 1;
+        } else {
+            *reactions.entry(record.from_pubkey.clone()).or_default() += 1;
+        }
+    }
+
+    let mut lines = Vec::new();
+    if !reactions.is_empty() {
+        let mut sorted: Vec<(String, usize)> = reactions.into_iter().collect();
+        sorted.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        let mut names = Vec::new();
+        for (pubkey, count) in sorted.iter().take(10) {
+            let name = util::get_user_name(pubkey).await?;
+            names.push(format!("{}さん({}回)", name, count));
+        }
+        lines.push(format!("- いいねしてくれた人: {}", names.join("、")));
+    }
+    if !zaps.is_empty() {
+        let mut sorted: Vec<(String, (i64, usize))> = zaps.into_iter().collect();
+        sorted.sort_by_key(|(_, (sats, _))| std::cmp::Reverse(*sats));
+        let mut names = Vec::new();
+        for (pubkey, (sats, count)) in sorted.iter().take(10) {
+            let name = util::get_user_name(pubkey).await?;
+            names.push(format!("{}さん({}回, {} sats)", name, count, util::format_with_commas(*sats as u64)));
+        }
+        lines.push(format!("- Zapしてくれた人: {}", names.join("、")));
+    }
+    Ok(lines.join("\n"))
+}
+
+/// Zapへのお礼をZapした人へのリプライとして投稿
+pub async fn send_zap_thanks(config: &AppConfig, zap: &Engagement, text: &str) -> Result<Event, Box<dyn Error>> {
+    let keys = Keys::parse(&zap.bot.secretkey)?;
+    let mut builder = EventBuilder::text_note(text).tag(Tag::public_key(zap.from));
+    if let Some(target) = zap.target_event_id {
+        builder = builder.tag(Tag::from_standardized(TagStandard::Event {
+            event_id: target,
+            relay_url: None,
+            marker: Some(Marker::Root),
+            public_key: None,
+            uppercase: false,
+        }));
+    }
+    let (event, result) = relay_pool::shared()
+        .publish_as(&keys, builder, &config.relay_servers.write)
+        .await?;
+    if !result.is_accepted() {
+        error!("[Engagement] Zapのお礼を受理したリレーがありません: {:?}", result.rejected);
+    }
+    Ok(event)
+}
@@ -1,8 +1,10 @@
-use crate::{config, database as db, gpt, util, conversation, dashboard};
+use crate::{commands, config, database as db, gpt, util, conversation, dashboard, direct_message, engagement, live, metrics, reaction};
 use nostr_sdk::prelude::*;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::{Duration, Instant};
+use tokio::sync::{Notify, RwLock};
 use chrono::Utc;
+use tracing::{error, info, info_span, Instrument, Span};
 
 /// イベント処理のメイン関数
 pub async fn process_event(
@@ -13,6 +15,16 @@ pub async fn process_event(
     use whatlang::{detect, Lang};
     use chrono::TimeZone;
     
+    // DMは専用の流れで処理
+    if direct_message::is_direct_message_kind(event.kind) {
+        return process_direct_message(config, bot_info, event).await;
+    }
+    
+    // キューに入るZapはお礼対象のもののみ
+    if event.kind == Kind::ZapReceipt {
+        return process_zap_thanks(config, bot_info, event).await;
+    }
+    
     // DB接続
     let conn = db::connect()?;
     
@@ -32,7 +44,7 @@ pub async fn process_event(
             }
             Err(e) => {
                 if !e.to_string().contains("UNIQUE constraint failed") {
-                    eprintln!("[Worker] Failed to save event: {}", e);
+                    error!("[Worker] Failed to save event: {}", e);
                 }
             }
         }
@@ -52,16 +64,16 @@ pub async fn process_event(
         .cloned()
         .collect();
     
-    println!("[EventProcessor] Total bots: {}, Active bots: {}", persons.len(), active_persons.len());
+    info!("[EventProcessor] Total bots: {}, Active bots: {}", persons.len(), active_persons.len());
     
     if active_persons.is_empty() {
-        println!("[EventProcessor] No active bots, skipping event");
+        info!("[EventProcessor] No active bots, skipping event");
         return Ok(());
     }
     
     // ブラックリストチェック
     if is_blacklisted(&conn, &event.pubkey.to_string())? {
-        println!("[Worker] ブラックリストのユーザーからの投稿をスキップ: {}", event.pubkey);
+        info!("[Worker] ブラックリストのユーザーからの投稿をスキップ: {}", event.pubkey);
         return Ok(());
     }
     
@@ -74,25 +86,17 @@ pub async fn process_event(
         return Ok(());
     }
     
-    // 確率判定（有効なBotのみ）
-    let (mut should_post, _) = util::judge_post(&config, active_persons.clone(), &event)?;
-    
-    // Personを決定
-    let person = if let Some(p) = person_op {
-        p
-    } else {
-        // ランダム選択も有効なBotから
-        if active_persons.is_empty() {
-            return Ok(());
-        }
-        use rand::seq::SliceRandom;
-        let mut rng = rand::thread_rng();
-        active_persons.choose(&mut rng).unwrap().clone()
+    // 確率判定とPersonの決定（メンション先、なければ有効なBotからランダム）
+    let (mut should_post, judged_person) = util::judge_post(&config, active_persons.clone(), &event)?;
+    let person = match person_op.or(judged_person) {
+        Some(p) => p,
+        None => return Ok(()),
     };
+    Span::current().record("bot", person.pubkey.as_str());
     
     // Botのステータスチェック（無効化されていたらスキップ）
     if person.status != 0 {
-        println!("🚫 Bot無効化中のため、返信をスキップ: {} ({})", person.pubkey, event.id);
+        info!("🚫 Bot無効化中のため、返信をスキップ: {} ({})", person.pubkey, event.id);
         return Ok(());
     }
     
@@ -107,14 +111,14 @@ pub async fn process_event(
     
     // グローバル一時停止チェック（返信処理の直前）
     if db::is_global_pause(&conn)? {
-        println!("⏸️ グローバル一時停止中のため、返信をスキップ: {}", event.id);
+        info!("⏸️ グローバル一時停止中のため、返信をスキップ: {}", event.id);
         return Ok(());
     }
     
     // フォロワーチェック
     let is_follower = util::is_follower(&event.pubkey.to_string(), &person.secretkey).await?;
     if !is_follower {
-        println!("[Worker] Not a follower, skipping");
+        info!("[Worker] Not a follower, skipping");
         return Ok(());
     }
     
@@ -128,10 +132,11 @@ pub async fn process_event(
             &person.pubkey,
             &event.pubkey.to_string(),
             limit_minutes,
-        )?;
+        )?
+        .saturating_sub(logged_by_previous_attempt(&conn, &person.pubkey, &event.id.to_hex())? as usize);
         
         if conversation_count >= limit_count {
-            println!(
+            info!(
                 "[Worker] 会話回数制限: {}分間で{}回 (制限: {}回)",
                 limit_minutes,
                 conversation_count,
@@ -141,6 +146,19 @@ pub async fn process_event(
         }
     }
     
+    // Bot毎の確率で応じ方を決める（無視・リアクションも選べる・返信）
+    let response_mode = reaction::choose_response_mode(&person.overrides);
+    if response_mode == reaction::ResponseMode::Ignore {
+        info!("[Worker] 応答モード: 無視 ({})", event.id);
+        return Ok(());
+    }
+    let emojis = reaction::available_emojis(&person, &event);
+    let reaction_instruction = if response_mode == reaction::ResponseMode::Reaction {
+        Some(reaction::reaction_instruction(&emojis))
+    } else {
+        None
+    };
+    
     // 会話ログに記録（メンション時のみ）
     let conversation_log_id = if has_mention {
         let event_record = db::get_event_by_event_id(&conn, &event.id.to_string())?;
@@ -167,13 +185,13 @@ pub async fn process_event(
             &person.pubkey,
             event_ref_id,
             thread_root_id.as_deref(),
-            mentioned_pubkeys.as_ref().map(|v| v.as_slice()),
+            mentioned_pubkeys.as_deref(),
             false,
             is_bot_conversation,
         ) {
             Ok(log_id) => Some(log_id),
             Err(e) => {
-                eprintln!("[Worker] 会話ログ記録エラー: {}", e);
+                error!("[Worker] 会話ログ記録エラー: {}", e);
                 None
             }
         }
@@ -219,7 +237,7 @@ pub async fn process_event(
                 }
             },
             Err(e) => {
-                eprintln!("[Worker] コンテキスト準備エラー: {}", e);
+                error!("[Worker] コンテキスト準備エラー: {}", e);
                 None
             }
         }
@@ -243,7 +261,7 @@ pub async fn process_event(
                         let dt = chrono::Local.timestamp_opt(selected_event.created_at, 0).single().unwrap();
                         let time_str = dt.format("%m/%d %H:%M").to_string();
                         let display_name = selected_event.display_name(&conn);
-                        println!("[Worker] エアリプモード: 単一投稿 ({}%)", person.air_reply_single_ratio);
+                        info!("[Worker] エアリプモード: 単一投稿 ({}%)", person.air_reply_single_ratio);
                         Some(format!("【投稿】[{}] {}: {}", time_str, display_name, selected_event.content))
                     } else {
                         None
@@ -259,49 +277,85 @@ pub async fn process_event(
                             format!("{}. [{}] {}: {}", i + 1, time_str, display_name, ev.content)
                         })
                         .collect();
-                    println!("[Worker] エアリプモード: タイムライン全体 ({}%)", 100 - person.air_reply_single_ratio);
+                    info!("[Worker] エアリプモード: タイムライン全体 ({}%)", 100 - person.air_reply_single_ratio);
                     Some(format!("【タイムライン】\n{}", timeline_lines.join("\n")))
                 }
             }
             Err(e) => {
-                eprintln!("[Worker] タイムライン取得エラー: {}", e);
+                error!("[Worker] タイムライン取得エラー: {}", e);
                 None
             }
         }
     };
     
+    // ツールが許可されたBotは、メンションへの返信に必要な情報をツールで集めておく
+    let context = if has_mention {
+        let user_text = context.as_deref().unwrap_or(&event.content);
+        match gpt::gather_tool_results_for_reply(&person, &event, user_text, &config).await {
+            Some(tool_results) => Some(format!("{}\n\n{}", tool_results, context.unwrap_or_else(|| event.content.clone()))),
+            None => context,
+        }
+    } else {
+        context
+    };
+
     // GPT応答生成（メンションの場合は印象＋心境付き、エアリプの場合は心境のみ）
     // 注意: この時点ではDBに保存しない（送信成功後に保存）
     let (reply, gpt_response) = if has_mention {
-        match gpt::get_reply_with_mental_diary(&person.pubkey, &event.pubkey.to_string(), &prompt, &event.content, context, user_name.as_deref(), &config).await {
+        match gpt::get_reply_with_mental_diary(&person, &event.pubkey.to_string(), &prompt, &event.content, context, user_name.as_deref(), reaction_instruction.as_deref(), &config).await {
             Ok(response) => {
                 let reply = response.reply.clone();
                 (reply, Some(response))
             },
-            Err(e) => {
-                eprintln!("[GPT Error] {}", e);
-                return Ok(());
-            }
+            // キューの再試行に回す（返信はまだ送っていない）
+            Err(e) => return Err(format!("LLM呼び出しエラー: {}", e).into()),
         }
     } else {
         // エアリプ時も心境を参照・更新
-        match gpt::get_air_reply_with_mental_diary(&person.pubkey, &prompt, &event.content, has_mention, context, &config).await {
+        match gpt::get_air_reply_with_mental_diary(&person, &prompt, &event.content, has_mention, context, reaction_instruction.as_deref(), &config).await {
             Ok(response) => {
                 let reply = response.reply.clone();
                 (reply, Some(response))
             },
-            Err(e) => {
-                eprintln!("[GPT Error] {}", e);
-                return Ok(());
-            }
+            Err(e) => return Err(format!("LLM呼び出しエラー: {}", e).into()),
         }
     };
     
+    // リアクションが選ばれた場合は返信の代わりにkind 7を送信
+    let chosen_reaction = if response_mode == reaction::ResponseMode::Reaction {
+        gpt_response.as_ref()
+            .and_then(|r| r.reaction.as_deref())
+            .and_then(|r| reaction::resolve_reaction(r, &emojis))
+    } else {
+        None
+    };
+    if let Some(chosen) = chosen_reaction {
+        info!("[Worker] Reacting: {}", chosen.content);
+        match reaction::send_reaction(&config, &person, &event, &chosen).await {
+            Ok(reaction_event) => {
+                if let Some(ref response) = gpt_response {
+                    let user_pubkey = if has_mention { Some(user_pubkey.as_str()) } else { None };
+                    if let Err(e) = gpt::save_mental_diary_response(&person.pubkey, user_pubkey, response) {
+                        error!("[Worker] GPTレスポンス保存エラー: {}", e);
+                    }
+                }
+                // 会話回数制限に数えるため会話ログに記録
+                if let Err(e) = util::log_event_to_conversation(&reaction_event, &person.pubkey, true) {
+                    error!("[Worker] リアクションの会話ログ記録エラー: {}", e);
+                }
+                let mut info = bot_info.write().await;
+                info.last_reply_timestamp = Utc::now().timestamp();
+            }
+            Err(e) => return Err(format!("リアクションの送信エラー: {}", e).into()),
+        }
+        return Ok(());
+    }
+    
     if reply.is_empty() {
         return Ok(());
     }
     
-    println!("[Worker] Replying: {}", reply);
+    info!("[Worker] Replying: {}", reply);
     
     // 返信送信
     let sent_event = if has_mention {
@@ -310,7 +364,7 @@ pub async fn process_event(
                 // 送信成功！GPTレスポンスをDBに保存
                 if let Some(ref response) = gpt_response {
                     if let Err(e) = gpt::save_mental_diary_response(&person.pubkey, Some(&event.pubkey.to_string()), response) {
-                        eprintln!("[Worker] GPTレスポンス保存エラー: {}", e);
+                        error!("[Worker] GPTレスポンス保存エラー: {}", e);
                     }
                 }
                 
@@ -321,31 +375,25 @@ pub async fn process_event(
                 }
                 Some(evt)
             }
-            Err(e) => {
-                eprintln!("[Worker] Failed to reply: {}", e);
-                // 送信失敗時はDBに保存しない
-                None
-            }
+            // 送信失敗時はDBに保存せず、キューの再試行に回す
+            Err(e) => return Err(format!("返信の送信エラー: {}", e).into()),
         }
     } else if event.kind == Kind::TextNote {
-        let send_ok = util::send_to(&config, event.clone(), person.clone(), &reply).await.is_ok();
-        if send_ok {
-            // 送信成功！GPTレスポンスをDBに保存
-            if let Some(ref response) = gpt_response {
-                if let Err(e) = gpt::save_mental_diary_response(&person.pubkey, None, response) {
-                    eprintln!("[Worker] GPTレスポンス保存エラー: {}", e);
-                }
-            }
-            
-            if let Ok(bot_keys) = Keys::parse(&person.secretkey) {
-                let event_builder = EventBuilder::text_note(&reply);
-                event_builder.sign(&bot_keys).await.ok()
-            } else {
-                None
+        if let Err(e) = util::send_to(&config, event.clone(), person.clone(), &reply).await {
+            // 送信失敗時はDBに保存せず、キューの再試行に回す
+            return Err(format!("エアリプの送信エラー: {}", e).into());
+        }
+        // 送信成功！GPTレスポンスをDBに保存
+        if let Some(ref response) = gpt_response {
+            if let Err(e) = gpt::save_mental_diary_response(&person.pubkey, None, response) {
+                error!("[Worker] GPTレスポンス保存エラー: {}", e);
             }
+        }
+        
+        if let Ok(bot_keys) = Keys::parse(&person.secretkey) {
+            let event_builder = EventBuilder::text_note(&reply);
+            event_builder.sign(&bot_keys).await.ok()
         } else {
-            eprintln!("[Worker] Failed to send");
-            // 送信失敗時はDBに保存しない
             None
         }
     } else {
@@ -356,13 +404,13 @@ pub async fn process_event(
     if let Some(bot_event) = sent_event {
         if has_conversation_log {
             if let Err(e) = util::log_event_to_conversation(&bot_event, &person.pubkey, true) {
-                eprintln!("[Worker] bot発言の会話ログ記録エラー: {}", e);
+                error!("[Worker] bot発言の会話ログ記録エラー: {}", e);
             }
         } else {
             // bot_postとして保存（会話ログには記録しない）
             if let Err(e) = db::insert_event(&conn, &bot_event, None) {
                 if !e.to_string().contains("UNIQUE constraint failed") {
-                    eprintln!("[Worker] bot発言の保存エラー: {}", e);
+                    error!("[Worker] bot発言の保存エラー: {}", e);
                 }
             }
         }
@@ -375,7 +423,7 @@ pub async fn process_event(
             &reply,
             Utc::now().timestamp()
         ) {
-            eprintln!("[Worker] Failed to save bot timeline post: {}", e);
+            error!("[Worker] Failed to save bot timeline post: {}", e);
         }
         
         let timeline_size = config.get_usize_setting("timeline_size");
@@ -385,6 +433,623 @@ pub async fn process_event(
     Ok(())
 }
 
+/// DMの処理（復号 → DM履歴をコンテキストに返信 → 同じ方式で送信 → DM履歴に記録）
+async fn process_direct_message(
+    config: config::AppConfig,
+    bot_info: Arc<RwLock<dashboard::BotInfo>>,
+    event: Event,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = db::connect()?;
+    let persons = db::get_all_persons(&conn)?;
+    
+    let dm = match direct_message::open(&persons, &event).await? {
+        Some(dm) => dm,
+        None => return Ok(()),
+    };
+    let person = dm.bot.clone();
+    let user_pubkey = dm.sender.to_hex();
+    Span::current().record("bot", person.pubkey.as_str()).record("user", user_pubkey.as_str());
+    info!("[DM] {} -> {} ({:?})", user_pubkey, person.pubkey, dm.scheme);
+    
+    if dm.content.trim().is_empty() {
+        return Ok(());
+    }
+    
+    // ブラックリストチェック（Gift Wrapの作成者はランダムなので復号後に判定）
+    if config.get_blacklist().contains(&user_pubkey) {
+        info!("[DM] ブラックリストのユーザーからのDMをスキップ: {}", user_pubkey);
+        return Ok(());
+    }
+    
+    if person.status != 0 {
+        info!("🚫 Bot無効化中のため、DMへの返信をスキップ: {}", person.pubkey);
+        return Ok(());
+    }
+    
+    if db::is_global_pause(&conn)? {
+        info!("⏸️ グローバル一時停止中のため、DMへの返信をスキップ");
+        return Ok(());
+    }
+    
+    // フォロワーチェック（メンションと同じ条件）
+    if !util::is_follower(&user_pubkey, &person.secretkey).await? {
+        info!("[DM] Not a follower, skipping");
+        return Ok(());
+    }
+    
+    // 会話回数制限チェック
+    let limit_minutes = config.get_i64_setting("conversation_limit_minutes");
+    let limit_count = config.get_usize_setting("conversation_limit_count");
+    let conversation_count = db::get_conversation_count_with_user(&conn, &person.pubkey, &user_pubkey, limit_minutes)?
+        .saturating_sub(logged_by_previous_attempt(&conn, &person.pubkey, &dm.message_id.to_hex())? as usize);
+    if conversation_count >= limit_count {
+        info!("[DM] 会話回数制限: {}分間で{}回 (制限: {}回)", limit_minutes, conversation_count, limit_count);
+        return Ok(());
+    }
+    
+    // 受信したDMを記録（エアリプのタイムラインには載らない。再試行で記録し直しても重複しない）
+    let message_ref_id = db::insert_direct_message_event(
+        &conn,
+        &dm.message_id.to_hex(),
+        &user_pubkey,
+        if dm.scheme == direct_message::DmScheme::Nip17 { Kind::PrivateDirectMessage.as_u16() } else { Kind::EncryptedDirectMessage.as_u16() },
+        &dm.content,
+        dm.created_at.as_u64() as i64,
+        &dm.event_json,
+    )?;
+    db::insert_direct_message_log(&conn, &person.pubkey, message_ref_id, &user_pubkey, false)?;
+    
+    let user_name = util::get_user_name(&user_pubkey).await.ok()
+        .filter(|name| !name.ends_with("..."));
+    let context = conversation::prepare_context_for_direct_message(
+        &conn,
+        &person.pubkey,
+        &user_pubkey,
+        &dm.content,
+        50,
+        user_name.as_deref(),
+    ).ok();
+    
+    // 失敗したらキューの再試行に回す（受信したDMの記録は再試行しても重複しない）
+    let response = gpt::get_dm_reply_with_mental_diary(&person, &user_pubkey, &person.prompt, &dm.content, context, user_name.as_deref(), &config)
+        .await
+        .map_err(|e| format!("LLM呼び出しエラー: {}", e))?;
+    if response.reply.is_empty() {
+        return Ok(());
+    }
+    
+    info!("[DM] Replying: {}", response.reply);
+    let sent = direct_message::send_reply(&config, &dm, &response.reply)
+        .await
+        .map_err(|e| format!("DMの送信エラー: {}", e))?;
+    
+    // 送信成功後にBotの発言を保存（失敗してもキューの再試行でDMを再送しないよう、ログに残すだけ）
+    // 心境・ユーザー属性は公開の返信や定期投稿のプロンプトにも入るため、DMでは更新しない
+    let logged = db::insert_direct_message_event(
+        &conn,
+        &sent.message_id.to_hex(),
+        &person.pubkey,
+        sent.kind.as_u16(),
+        &response.reply,
+        sent.created_at.as_u64() as i64,
+        &sent.event_json,
+    )
+    .and_then(|reply_ref_id| db::insert_direct_message_log(&conn, &person.pubkey, reply_ref_id, &user_pubkey, true));
+    if let Err(e) = logged {
+        error!("[DM] bot発言の記録エラー: {}", e);
+    }
+    
+    {
+        let mut info = bot_info.write().await;
+        info.last_reply_timestamp = Utc::now().timestamp();
+    }
+    
+    Ok(())
+}
+
+/// Zapへのお礼（ペルソナでお礼を生成 → Zapした人へリプライ → 会話ログに記録）
+async fn process_zap_thanks(
+    config: config::AppConfig,
+    bot_info: Arc<RwLock<dashboard::BotInfo>>,
+    event: Event,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = db::connect()?;
+    let persons = db::get_all_persons(&conn)?;
+    
+    let zap = match engagement::parse(&persons, &event) {
+        Some(zap) => zap,
+        None => return Ok(()),
+    };
+    let person = zap.bot.clone();
+    let user_pubkey = zap.from.to_hex();
+    Span::current().record("bot", person.pubkey.as_str()).record("user", user_pubkey.as_str());
+    
+    if config.get_blacklist().contains(&user_pubkey) {
+        info!("[Zap] ブラックリストのユーザーからのZapにはお礼をしない: {}", user_pubkey);
+        return Ok(());
+    }
+    
+    if person.status != 0 {
+        info!("🚫 Bot無効化中のため、Zapのお礼をスキップ: {}", person.pubkey);
+        return Ok(());
+    }
+    
+    if db::is_global_pause(&conn)? {
+        info!("⏸️ グローバル一時停止中のため、Zapのお礼をスキップ");
+        return Ok(());
+    }
+    
+    // 会話回数制限チェック（メンションと同じ条件）
+    let limit_minutes = config.get_i64_setting("conversation_limit_minutes");
+    let limit_count = config.get_usize_setting("conversation_limit_count");
+    let conversation_count = db::get_conversation_count_with_user(&conn, &person.pubkey, &user_pubkey, limit_minutes)?;
+    if conversation_count >= limit_count {
+        info!("[Zap] 会話回数制限: {}分間で{}回 (制限: {}回)", limit_minutes, conversation_count, limit_count);
+        return Ok(());
+    }
+    
+    let user_name = util::get_user_name(&user_pubkey).await.ok()
+        .filter(|name| !name.ends_with("..."));
+    
+    // 失敗したらキューの再試行に回す（お礼はまだ送っていない）
+    let response = gpt::get_zap_thanks_with_mental_diary(&person, &user_pubkey, &person.prompt, zap.amount_sats, &zap.content, user_name.as_deref(), &config)
+        .await
+        .map_err(|e| format!("LLM呼び出しエラー: {}", e))?;
+    if response.reply.is_empty() {
+        return Ok(());
+    }
+    
+    info!("[Zap] Thanking {} for {} sats: {}", user_pubkey, zap.amount_sats, response.reply);
+    let sent = engagement::send_zap_thanks(&config, &zap, &response.reply)
+        .await
+        .map_err(|e| format!("Zapのお礼の送信エラー: {}", e))?;
+    
+    // 送信成功後にGPTレスポンスとBotの発言を保存
+    if let Err(e) = gpt::save_mental_diary_response(&person.pubkey, Some(&user_pubkey), &response) {
+        error!("[Zap] GPTレスポンス保存エラー: {}", e);
+    }
+    if let Err(e) = util::log_event_to_conversation(&sent, &person.pubkey, true) {
+        error!("[Zap] bot発言の会話ログ記録エラー: {}", e);
+    }
+    
+    {
+        let mut info = bot_info.write().await;
+        info.last_reply_timestamp = Utc::now().timestamp();
+    }
+    
+    Ok(())
+}
+
+/// リレーから受信したイベントの振り分け（kind 0保存・コマンド即時実行・キュー投入）
+#[tracing::instrument(name = "incoming", skip_all, fields(event_id = %event.id, user = %event.pubkey, kind = event.kind.as_u16()))]
+pub async fn handle_incoming_event(
+    config: &config::AppConfig,
+    conn: &rusqlite::Connection,
+    event: &Event,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // ブラックリストチェック（DB優先）
+    let blacklist = config.get_blacklist();
+    if blacklist.iter().any(|s| s == &event.pubkey.to_string()) {
+        return Ok(());
+    }
+    
+    let kind = event.kind;
+    
+    // kind 0 (Metadata) の処理
+    if kind == Kind::Metadata {
+        // eventsテーブルに保存（upsert処理で最新のみ保持）
+        if let Err(e) = db::insert_event(conn, event, None) {
+            error!("[Kind0] DB保存エラー: {}", e);
+        }
+        return Ok(()); // kind 0はキューに入れない
+    }
+    
+    // DMは復号せずにそのままキューへ（復号・処理はワーカーで行う）
+    if direct_message::is_direct_message_kind(kind) {
+        // NIP-17のgift wrapは送信者が復号するまで分からない
+        let lane = db::QueueLane {
+            bot_pubkey: event.tags.public_keys().next().map(|pk| pk.to_hex()),
+            user_pubkey: (kind == Kind::EncryptedDirectMessage).then(|| event.pubkey.to_hex()),
+        };
+        enqueue(conn, event, db::QueuePriority::DirectMessage, lane);
+        return Ok(());
+    }
+    
+    // Bot宛てのリアクション・Zapは記録のみ（お礼をするZapだけキューへ）
+    if engagement::is_engagement_kind(kind) {
+        let persons = match db::get_all_persons(conn) {
+            Ok(p) => p,
+            Err(e) => {
+                error!("Failed to get persons: {}", e);
+                return Ok(());
+            }
+        };
+        match engagement::record(conn, &persons, event).await {
+            Ok(Some(recorded)) => {
+                if engagement::should_thank(config, &recorded) {
+                    let lane = db::QueueLane {
+                        bot_pubkey: Some(recorded.bot.pubkey.clone()),
+                        user_pubkey: Some(recorded.from.to_hex()),
+                    };
+                    enqueue(conn, event, db::QueuePriority::Mention, lane);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => error!("[Engagement] 記録エラー: {}", e),
+        }
+        return Ok(());
+    }
+    
+    if kind != Kind::TextNote && kind != Kind::ChannelMessage {
+        // Kind::TextNoteでもKind::ChannelMessageでもない
+        info!("Skipping event kind: {:?}", event.kind);
+        return Ok(());
+    }
+    
+    // NIP-36(コンテンツ警告)をスキップ
+    let detect_nip36 = event.tags.iter().any(|tag| {
+        matches!(tag.as_standardized(), Some(TagStandard::ContentWarning { reason: _ }))
+    });
+    if detect_nip36 {
+        return Ok(());
+    }
+    
+    // コマンド処理（即座に実行）
+    let persons = match db::get_all_persons(conn) {
+        Ok(p) => p,
+        Err(e) => {
+            error!("Failed to get persons: {}", e);
+            return Ok(());
+        }
+    };
+    let handled = commands::command_handler(config, conn, &persons, event).await?;
+    
+    if handled {
+        // コマンドとして処理済み: キューに入れない（キャッチアップで再実行しないよう記録）
+        if let Err(e) = db::mark_event_processed(conn, &event.id.to_hex()) {
+            error!("Failed to mark event as processed: {}", e);
+        }
+        return Ok(());
+    }
+    
+    // イベントをキューに追加（永続化）。メンションはエアリプ候補より先に処理する
+    let active_persons: Vec<db::Person> = persons.into_iter().filter(|p| p.status == 0).collect();
+    let mentioned = util::extract_mention(active_persons, event).ok().flatten();
+    let priority = if mentioned.is_some() {
+        db::QueuePriority::Mention
+    } else {
+        db::QueuePriority::AirReply
+    };
+    // エアリプ候補はどのBotが反応するか処理するまで決まらない
+    let lane = db::QueueLane {
+        bot_pubkey: mentioned.map(|person| person.pubkey),
+        user_pubkey: Some(event.pubkey.to_hex()),
+    };
+    enqueue(conn, event, priority, lane);
+    
+    Ok(())
+}
+
+/// イベントをキューに追加（処理済みのイベントは追加しない）
+fn enqueue(conn: &rusqlite::Connection, event: &Event, priority: db::QueuePriority, lane: db::QueueLane) {
+    let event_json = match serde_json::to_string(event) {
+        Ok(json) => json,
+        Err(e) => {
+            error!("Failed to serialize event: {}", e);
+            return;
+        }
+    };
+    
+    match db::enqueue_event(conn, &event_json, priority, &lane) {
+        Ok(Some(queue_id)) => {
+            info!("Enqueued event {} (queue_id: {}, priority: {:?})", event.id, queue_id, priority);
+            live::queue(Some(queue_id), &event.id.to_hex(), live::QueueStatus::Enqueued, lane.bot_pubkey.as_deref(), None);
+            notify_queue_workers();
+        }
+        Ok(None) => {
+            info!("Skipping already processed event {}", event.id);
+        }
+        Err(e) => {
+            error!("Failed to enqueue event: {}", e);
+        }
+    }
+}
+
+/// タイムライン購読の購読ID（チェックポイント名を兼ねる）
+pub const TIMELINE_SUBSCRIPTION: &str = "timeline";
+
+/// タイムライン購読で受信した投稿のcreated_atをリレー毎のチェックポイントとして記録
+///
+/// DM・リアクション・Zapなど他の購読のイベントやkind 0では進めない
+pub fn record_checkpoint(conn: &rusqlite::Connection, relay_url: &RelayUrl, subscription_id: &SubscriptionId, event: &Event) {
+    if subscription_id.as_str() != TIMELINE_SUBSCRIPTION {
+        return;
+    }
+    if event.kind != Kind::TextNote && event.kind != Kind::ChannelMessage {
+        return;
+    }
+    if let Err(e) = db::update_relay_checkpoint(
+        conn,
+        relay_url.as_str(),
+        TIMELINE_SUBSCRIPTION,
+        event.created_at.as_u64() as i64,
+    ) {
+        error!("[CatchUp] チェックポイント保存エラー ({}): {}", relay_url, e);
+    }
+}
+
+/// Bot宛てDM（NIP-17 Gift Wrap / NIP-04）の購読ID
+pub const DIRECT_MESSAGE_SUBSCRIPTION: &str = "direct_messages";
+
+/// Botの投稿へのリアクション・Zapの購読ID
+pub const ENGAGEMENT_SUBSCRIPTION: &str = "engagements";
+
+/// Gift Wrapのcreated_atは最大2日前までランダムにずらされるため、その分遡って購読する
+const GIFT_WRAP_LOOKBACK_SECS: u64 = 2 * 24 * 60 * 60;
+
+/// Botの公開鍵で絞り込む購読（DM・リアクション・Zap）
+///
+/// Botの追加・削除に合わせて同じ購読IDで張り直す
+#[derive(Debug, Default)]
+pub struct BotSubscriptions {
+    pubkeys: Vec<PublicKey>,
+}
+
+impl BotSubscriptions {
+    /// Personsの変化を購読に反映する（張り直した場合はtrue）
+    pub async fn refresh(&mut self, client: &Client, conn: &rusqlite::Connection) -> Result<bool, Box<dyn std::error::Error>> {
+        let mut pubkeys: Vec<PublicKey> = db::get_all_persons(conn)?
+            .iter()
+            .filter_map(|p| PublicKey::from_hex(&p.pubkey).ok())
+            .collect();
+        pubkeys.sort();
+        pubkeys.dedup();
+        if pubkeys == self.pubkeys {
+            return Ok(false);
+        }
+        
+        let dm_id = SubscriptionId::new(DIRECT_MESSAGE_SUBSCRIPTION);
+        let engagement_id = SubscriptionId::new(ENGAGEMENT_SUBSCRIPTION);
+        if pubkeys.is_empty() {
+            client.unsubscribe(&dm_id).await;
+            client.unsubscribe(&engagement_id).await;
+        } else {
+            let dm_subscription = Filter::new()
+                .kinds([Kind::GiftWrap, Kind::EncryptedDirectMessage])
+                .pubkeys(pubkeys.clone())
+                .since(Timestamp::now() - GIFT_WRAP_LOOKBACK_SECS);
+            client.subscribe_with_id(dm_id, dm_subscription, None).await?;
+            
+            let engagement_subscription = Filter::new()
+                .kinds([Kind::Reaction, Kind::ZapReceipt])
+                .pubkeys(pubkeys.clone())
+                .since(Timestamp::now());
+            client.subscribe_with_id(engagement_id, engagement_subscription, None).await?;
+        }
+        info!("subscribe (GiftWrap, EncryptedDirectMessage, Reaction, ZapReceipt) for {} bots", pubkeys.len());
+        self.pubkeys = pubkeys;
+        Ok(true)
+    }
+}
+
+/// 停止中に取りこぼしたBot宛てメンションをチェックポイント以降から取得して受信処理に流す
+///
+/// 遡るのは最大`catch_up_max_lookback`秒まで。処理済みのイベントはスキップされる。
+/// 戻り値は受信処理に渡したイベント数
+pub async fn catch_up_missed_mentions(
+    config: &config::AppConfig,
+    conn: &rusqlite::Connection,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let max_lookback = config.get_i64_setting("catch_up_max_lookback");
+    if max_lookback <= 0 {
+        return Ok(0);
+    }
+    
+    let bot_pubkeys: Vec<PublicKey> = db::get_all_persons(conn)?
+        .iter()
+        .filter(|p| p.status == 0)
+        .filter_map(|p| PublicKey::from_hex(&p.pubkey).ok())
+        .collect();
+    if bot_pubkeys.is_empty() {
+        return Ok(0);
+    }
+    
+    let floor = Utc::now().timestamp() - max_lookback;
+    let pool = crate::relay_pool::shared();
+    let mut seen = std::collections::HashSet::new();
+    let mut missed: Vec<Event> = Vec::new();
+    
+    for relay in &config.relay_servers.read {
+        let relay_url = match RelayUrl::parse(relay) {
+            Ok(url) => url,
+            Err(_) => continue,
+        };
+        // チェックポイントがない（初回起動）リレーは遡らない
+        let checkpoint = match db::get_relay_checkpoint(conn, relay_url.as_str(), TIMELINE_SUBSCRIPTION)? {
+            Some(checkpoint) => checkpoint,
+            None => continue,
+        };
+        let since = checkpoint.max(floor);
+        
+        let filter = Filter::new()
+            .kinds([Kind::TextNote, Kind::ChannelMessage])
+            .pubkeys(bot_pubkeys.clone())
+            .since(Timestamp::from(since as u64));
+        match pool.fetch(filter, std::slice::from_ref(relay), std::time::Duration::from_secs(10)).await {
+            Ok(events) => {
+                info!("[CatchUp] {}: {}件 (since {})", relay, events.len(), since);
+                missed.extend(events.into_iter().filter(|e| seen.insert(e.id)));
+            }
+            Err(e) => error!("[CatchUp] {} からの取得エラー: {}", relay, e),
+        }
+    }
+    
+    // 古い順に処理
+    missed.sort_by_key(|e| e.created_at);
+    
+    let mut replayed = 0;
+    for event in &missed {
+        if db::is_event_processed(conn, &event.id.to_hex())? {
+            continue;
+        }
+        handle_incoming_event(config, conn, event).await?;
+        replayed += 1;
+    }
+    
+    if replayed > 0 {
+        info!("[CatchUp] 取りこぼしたメンション{}件を処理キューに渡しました", replayed);
+    }
+    Ok(replayed)
+}
+
+/// キューにイベントが追加されたことを待機中のワーカーに知らせる通知
+static QUEUE_NOTIFY: Notify = Notify::const_new();
+
+/// 待機中のワーカーが通知なしで起きてキューを確認する間隔（他プロセスからの追加に備える）
+const WORKER_IDLE_WAKEUP: Duration = Duration::from_secs(60);
+
+/// 待機中のワーカーを1つ起こす
+pub fn notify_queue_workers() {
+    QUEUE_NOTIFY.notify_one();
+}
+
+/// キューのイベントを処理し続けるワーカー（複数起動すると並行して処理する）
+/// 取り出せるイベントがなくなったら、追加の通知か次の再試行時刻まで待つ
+pub async fn run_queue_worker(config: config::AppConfig, bot_info: Arc<RwLock<dashboard::BotInfo>>) {
+    loop {
+        while process_next_queued_event(&config, Arc::clone(&bot_info)).await {
+            // 処理が終わって同じBot・ユーザーの次のイベントを取り出せるようになったかもしれない
+            notify_queue_workers();
+        }
+        
+        let wait = db::connect()
+            .and_then(|conn| db::next_queue_attempt_at(&conn))
+            .ok()
+            .flatten()
+            .map(|at| Duration::from_secs((at - Utc::now().timestamp()).max(0) as u64))
+            .map_or(WORKER_IDLE_WAKEUP, |until_retry| until_retry.min(WORKER_IDLE_WAKEUP));
+        tokio::select! {
+            _ = QUEUE_NOTIFY.notified() => {}
+            _ = tokio::time::sleep(wait) => {}
+        }
+    }
+}
+
+/// キューから次のイベントを1件取り出して処理する（処理した場合はtrue）
+pub async fn process_next_queued_event(
+    config: &config::AppConfig,
+    bot_info: Arc<RwLock<dashboard::BotInfo>>,
+) -> bool {
+    // キューから次のイベントを取得
+    let conn_worker = match db::connect() {
+        Ok(c) => c,
+        Err(e) => {
+            error!("[Worker] DB接続エラー: {}", e);
+            return false;
+        }
+    };
+    
+    let queue_item = match db::dequeue_event(&conn_worker) {
+        Ok(Some(item)) => item,
+        Ok(None) => return false, // 処理できるイベントがない
+        Err(e) => {
+            error!("[Worker] キュー取得エラー: {}", e);
+            return false;
+        }
+    };
+    
+    // このイベントの処理中に出たログにはevent_id・bot・userを付ける（botとuserは分かった時点で記録）
+    let span = info_span!(
+        "event",
+        queue_id = queue_item.id,
+        event_id = queue_item.event_id.as_deref().unwrap_or_default(),
+        bot = tracing::field::Empty,
+        user = tracing::field::Empty
+    );
+    if let Some(bot) = &queue_item.bot_pubkey {
+        span.record("bot", bot.as_str());
+    }
+    if let Some(user) = &queue_item.user_pubkey {
+        span.record("user", user.as_str());
+    }
+    process_queue_item(config, bot_info, &conn_worker, queue_item).instrument(span).await;
+    true
+}
+
+/// キューから取り出したイベントを処理し、結果に応じて完了・再試行・dead_letterにする
+async fn process_queue_item(
+    config: &config::AppConfig,
+    bot_info: Arc<RwLock<dashboard::BotInfo>>,
+    conn_worker: &rusqlite::Connection,
+    queue_item: db::QueueItem,
+) {
+    let queue_id = queue_item.id;
+    let queue_event_id = queue_item.event_id.clone().unwrap_or_default();
+    let queue_bot = queue_item.bot_pubkey.clone();
+    let publish_status = |status: live::QueueStatus, error: Option<String>| {
+        live::queue(Some(queue_id), &queue_event_id, status, queue_bot.as_deref(), error);
+    };
+    publish_status(live::QueueStatus::Processing, None);
+    
+    // JSONからEventを復元（復元できないものは再試行しても無駄なのでdead_letterへ）
+    let event: Event = match serde_json::from_str(&queue_item.event_json) {
+        Ok(e) => e,
+        Err(e) => {
+            error!("[Worker] イベント復元エラー: {}", e);
+            let error = format!("イベント復元エラー: {}", e);
+            if let Err(e) = db::dead_letter_queue_event(conn_worker, queue_id, &error) {
+                error!("[Worker] dead_letter移動エラー: {}", e);
+            }
+            publish_status(live::QueueStatus::DeadLettered, Some(error));
+            return;
+        }
+    };
+    
+    // イベント処理を実行
+    let started = Instant::now();
+    match process_event(config.clone(), bot_info, event).await {
+        Ok(_) => {
+            metrics::event_processed(started.elapsed(), "ok");
+            // 処理成功: キューから削除
+            if let Err(e) = db::complete_queue_event(conn_worker, queue_id) {
+                error!("[Worker] キュー削除エラー: {}", e);
+            }
+            publish_status(live::QueueStatus::Completed, None);
+        }
+        Err(e) => {
+            // 処理失敗: 間隔を空けて再試行（上限に達したらdead_letterへ）
+            match db::retry_queue_event(conn_worker, queue_id, &e.to_string()) {
+                Ok(db::RetryOutcome::Retrying { attempts, next_attempt_at }) => {
+                    error!(
+                        "[Worker] イベント処理エラー: {} - {}回目の失敗、{}秒後に再試行",
+                        e,
+                        attempts,
+                        next_attempt_at - Utc::now().timestamp()
+                    );
+                    publish_status(live::QueueStatus::Retrying, Some(e.to_string()));
+                    metrics::event_processed(started.elapsed(), "retrying");
+                }
+                Ok(db::RetryOutcome::DeadLettered) => {
+                    error!("[Worker] イベント処理エラー: {} - 再試行の上限に達したためdead_letterへ移動", e);
+                    publish_status(live::QueueStatus::DeadLettered, Some(e.to_string()));
+                    metrics::event_processed(started.elapsed(), "dead_lettered");
+                }
+                Err(retry_error) => {
+                    error!("[Worker] イベント処理エラー: {} - 再試行の登録に失敗: {}", e, retry_error);
+                }
+            }
+        }
+    }
+}
+
+/// 前の試行で会話ログに記録済みのイベントか（キューの再試行で自分の投稿を会話回数に数えないため）
+fn logged_by_previous_attempt(conn: &rusqlite::Connection, bot_pubkey: &str, event_id: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    match db::get_event_by_event_id(conn, event_id)? {
+        Some(record) => Ok(db::find_conversation_log(conn, bot_pubkey, record.id)?.is_some()),
+        None => Ok(false),
+    }
+}
+
 /// ブラックリストチェック
 fn is_blacklisted(conn: &rusqlite::Connection, pubkey: &str) -> Result<bool, Box<dyn std::error::Error>> {
     if let Some(blacklist_str) = db::get_system_setting(conn, "blacklist")? {
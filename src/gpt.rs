@@ -1,20 +1,21 @@
 use crate::config::AppConfig;
 use crate::TimelinePost;
 use crate::database as db;
+use crate::live;
+use crate::metrics;
+use crate::llm;
 use dotenv::dotenv;
 use std::error::Error;
 use std::fs::File;
-use std::time::Duration;
-use std::env;
+use std::time::{Duration, Instant};
 use tokio::time::timeout;
-use openai_api_rs::v1::api::OpenAIClient;
-use openai_api_rs::v1::chat_completion::{self, chat_completion::ChatCompletionRequest};
 use chrono::{Local, TimeZone};
-use tiktoken_rs::o200k_base;
 use serde::{Deserialize, Serialize};
 use partial_json_fixer::fix_json;
+use tracing::{debug, error, info, warn};
 
 /// GPTの応答（返信＋印象）
+#[allow(dead_code)]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GptResponseWithImpression {
     pub reply: String,
@@ -25,212 +26,135 @@ pub struct GptResponseWithImpression {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GptResponseWithMentalDiary {
     pub reply: String,
+    /// 返信の代わりに送るリアクション（リアクションを選べる場合のみ）
+    #[serde(default)]
+    pub reaction: Option<String>,
     pub user_attributes: db::UserAttributes,
     pub mental_diary: db::MentalDiary,
 }
 
-/// トークン数を計算
-fn count_tokens(text: &str) -> usize {
-    let bpe = o200k_base().expect("[Token] tiktoken (o200k_base) 初期化に失敗しました");
-    let tokens = bpe.encode_with_special_tokens(text);
-    tokens.len()
-}
-
 #[allow(dead_code)]
 pub async fn call_gpt(prompt: &str, user_text: &str, config: &AppConfig) -> Result<String, Box<dyn Error>> {
-    call_gpt_with_category(prompt, user_text, "unknown", "general", config).await
+    call_llm(prompt, user_text, "unknown", &db::PersonOverrides::default(), "general", false, config).await
 }
 
-pub async fn call_gpt_with_category(prompt: &str, user_text: &str, bot_pubkey: &str, category: &str, config: &AppConfig) -> Result<String, Box<dyn Error>> {
-    const MAX_RETRIES: u32 = 3;
-    const RETRY_DELAY_SECS: u64 = 3;
-    
-    dotenv().ok();
-    let api_key = env::var("OPEN_AI_API_KEY").expect("OPEN_AI_API_KEY is not set");
+pub async fn call_gpt_with_category(prompt: &str, user_text: &str, bot: &db::Person, category: &str, config: &AppConfig) -> Result<String, Box<dyn Error>> {
+    call_llm(prompt, user_text, &bot.pubkey, &bot.overrides, category, false, config).await
+}
+
+/// GPT呼び出し（JSON mode、印象付き返信用）
+pub async fn call_gpt_with_json_mode(prompt: &str, user_text: &str, bot: &db::Person, category: &str, config: &AppConfig) -> Result<String, Box<dyn Error>> {
+    call_llm(prompt, user_text, &bot.pubkey, &bot.overrides, category, true, config).await
+}
+
+/// LLM呼び出しの共通処理（プロバイダー選択・トークン数の計算）
+async fn call_llm(prompt: &str, user_text: &str, bot_pubkey: &str, overrides: &db::PersonOverrides, category: &str, json_mode: bool, config: &AppConfig) -> Result<String, Box<dyn Error>> {
+    let log_tag = if json_mode { "[GPT JSON]" } else { "[GPT]" };
     
-    // タイムアウト設定を取得
-    let timeout_secs = config.get_u64_setting("gpt_timeout");
+    // Bot毎の設定からプロバイダーを選択
+    let provider = llm::provider_for_bot(config, bot_pubkey, overrides).map_err(|e| e.to_string())?;
+    let provider = provider.as_ref();
     
     // トークン数を計算
-    let prompt_tokens = count_tokens(prompt);
-    let user_tokens = count_tokens(user_text);
+    let prompt_tokens = provider.count_tokens(prompt);
+    let user_tokens = provider.count_tokens(user_text);
     let total_prompt_tokens = prompt_tokens + user_tokens;
     
-    let req = ChatCompletionRequest::new(
-        "gpt-5-nano".to_string(),
-        vec![
-            chat_completion::ChatCompletionMessage {
-                role: chat_completion::MessageRole::system,
-                content: chat_completion::Content::Text(String::from(prompt)),
-                name: None,
-                tool_calls: None,
-                tool_call_id: None,
-            },
-            chat_completion::ChatCompletionMessage {
-                role: chat_completion::MessageRole::user,
-                content: chat_completion::Content::Text(String::from(user_text)),
-                name: None,
-                tool_calls: None,
-                tool_call_id: None,
-            },
-        ]
-    );
-    
-    let mut last_error: Option<String> = None;
-    
-    for attempt in 1..=MAX_RETRIES {
-        let mut client = OpenAIClient::builder()
-            .with_api_key(api_key.clone())
-            .build()?;
-        
-        let chat_completion_future = async {
-            client.chat_completion(req.clone()).await
-        };
+    call_with_retry(
+        provider,
+        bot_pubkey,
+        category,
+        log_tag,
+        total_prompt_tokens,
+        config,
+        || if json_mode { provider.chat_json(prompt, user_text) } else { provider.chat(prompt, user_text) },
+        // プロンプト全体を作成（システムプロンプト + ユーザー入力）
+        |content| (format!("{}\n\nユーザー入力:\n{}", prompt, user_text), content.clone()),
+    ).await
+}
 
-        // タイムアウトを設定
-        match timeout(Duration::from_secs(timeout_secs), chat_completion_future).await {
-            Ok(result) => match result {
-                Ok(response) => {
-                    // 正常なレスポンスの処理
-                    match &response.choices[0].message.content {
-                        Some(content) => {
-                            if attempt > 1 {
-                                println!("[GPT] リトライ成功 (試行 {}/{})", attempt, MAX_RETRIES);
-                            }
-                            
-                            // 完了トークン数を計算
-                            let completion_tokens = count_tokens(content);
-                            
-                            // プロンプト全体を作成（システムプロンプト + ユーザー入力）
-                            let full_prompt = format!("{}\n\nユーザー入力:\n{}", prompt, user_text);
-                            
-                            // トークン使用量を記録
-                            println!("[Token] 記録開始: bot_pubkey={}, category={}", bot_pubkey, category);
-                            if let Ok(conn) = db::connect() {
-                                if let Err(e) = db::record_token_usage(&conn, bot_pubkey, category, total_prompt_tokens, completion_tokens, &full_prompt, content) {
-                                    eprintln!("[Token] 記録エラー: {:?}", e);
-                                }
-                            } else {
-                                eprintln!("[Token] DB接続エラー");
-                            }
-                            
-                            return Ok(content.to_string());
-                        },
-                        None => {
-                            last_error = Some("No content found in response".to_string());
-                        }
-                    }            
-                },
-                Err(e) => {
-                    last_error = Some(format!("{}", e));
-                }
-            },
-            Err(_) => {
-                last_error = Some(format!("Timeout after {} seconds", timeout_secs));
-            }
-        }
-        
-        // 最後の試行でなければリトライ
-        if attempt < MAX_RETRIES {
-            eprintln!("[GPT] エラー発生 (試行 {}/{}): {:?} - {}秒後にリトライ", 
-                      attempt, MAX_RETRIES, last_error, RETRY_DELAY_SECS);
-            tokio::time::sleep(Duration::from_secs(RETRY_DELAY_SECS)).await;
-        }
-    }
+/// ツール（Function Calling）付きのLLM呼び出し
+pub async fn call_gpt_with_tools(
+    prompt: &str,
+    messages: &[llm::ToolMessage],
+    tools: &[llm::ToolDefinition],
+    bot: &db::Person,
+    category: &str,
+    config: &AppConfig,
+) -> Result<llm::ToolChatResponse, Box<dyn Error>> {
+    let bot_pubkey = bot.pubkey.as_str();
+    let provider = llm::provider_for_bot(config, bot_pubkey, &bot.overrides).map_err(|e| e.to_string())?;
+    let provider = provider.as_ref();
     
-    // 全てのリトライが失敗
-    Err(last_error.unwrap_or_else(|| "Unknown error after retries".to_string()).into())
+    // ツール定義もプロンプトの一部として数える
+    let tools_text = tools
+        .iter()
+        .map(|tool| format!("{}: {} {}", tool.name, tool.description, tool.parameters))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let messages_text = messages.iter().map(|m| m.text()).collect::<Vec<_>>().join("\n");
+    let total_prompt_tokens = provider.count_tokens(prompt) + provider.count_tokens(&tools_text) + provider.count_tokens(&messages_text);
+    
+    call_with_retry(
+        provider,
+        bot_pubkey,
+        category,
+        "[GPT Tools]",
+        total_prompt_tokens,
+        config,
+        || provider.chat_with_tools(prompt, messages, tools),
+        |response| {
+            let completion_text = match response {
+                llm::ToolChatResponse::Text(text) => text.clone(),
+                llm::ToolChatResponse::ToolCalls(calls) => llm::ToolMessage::Assistant(calls.clone()).text(),
+            };
+            let full_prompt = format!("{}\n\n利用可能なツール:\n{}\n\n会話:\n{}", prompt, tools_text, messages_text);
+            (full_prompt, completion_text)
+        },
+    ).await
 }
 
-/// GPT呼び出し（JSON mode、印象付き返信用）
-pub async fn call_gpt_with_json_mode(prompt: &str, user_text: &str, bot_pubkey: &str, category: &str, config: &AppConfig) -> Result<String, Box<dyn Error>> {
+/// LLM呼び出しのリトライ・タイムアウト・トークン記録（通常・JSON mode・ツール付きで共通）
+///
+/// requestは試行のたびに呼び出しを作り直す。成功したらdescribeで記録用の（プロンプト全体, 応答）を作る
+#[allow(clippy::too_many_arguments)]
+async fn call_with_retry<T, Fut>(
+    provider: &dyn llm::LlmProvider,
+    bot_pubkey: &str,
+    category: &str,
+    log_tag: &str,
+    prompt_tokens: usize,
+    config: &AppConfig,
+    request: impl Fn() -> Fut,
+    describe: impl FnOnce(&T) -> (String, String),
+) -> Result<T, Box<dyn Error>>
+where
+    Fut: std::future::Future<Output = Result<T, llm::LlmError>>,
+{
     const MAX_RETRIES: u32 = 3;
     const RETRY_DELAY_SECS: u64 = 3;
     
-    dotenv().ok();
-    let api_key = env::var("OPEN_AI_API_KEY").expect("OPEN_AI_API_KEY is not set");
-    
     // タイムアウト設定を取得
     let timeout_secs = config.get_u64_setting("gpt_timeout");
     
-    // トークン数を計算
-    let prompt_tokens = count_tokens(prompt);
-    let user_tokens = count_tokens(user_text);
-    let total_prompt_tokens = prompt_tokens + user_tokens;
-    
-    let mut req = ChatCompletionRequest::new(
-        "gpt-5-nano".to_string(),
-        vec![
-            chat_completion::ChatCompletionMessage {
-                role: chat_completion::MessageRole::system,
-                content: chat_completion::Content::Text(String::from(prompt)),
-                name: None,
-                tool_calls: None,
-                tool_call_id: None,
-            },
-            chat_completion::ChatCompletionMessage {
-                role: chat_completion::MessageRole::user,
-                content: chat_completion::Content::Text(String::from(user_text)),
-                name: None,
-                tool_calls: None,
-                tool_call_id: None,
-            },
-        ]
-    );
-    
-    // JSON modeを有効化（serde_json::Valueとして指定）
-    req.response_format = Some(serde_json::json!({
-        "type": "json_object"
-    }));
-    
     let mut last_error: Option<String> = None;
+    let started = Instant::now();
     
     for attempt in 1..=MAX_RETRIES {
-        let mut client = OpenAIClient::builder()
-            .with_api_key(api_key.clone())
-            .build()?;
-        
-        let chat_completion_future = async {
-            client.chat_completion(req.clone()).await
-        };
-
         // タイムアウトを設定
-        match timeout(Duration::from_secs(timeout_secs), chat_completion_future).await {
-            Ok(result) => match result {
-                Ok(response) => {
-                    // 正常なレスポンスの処理
-                    match &response.choices[0].message.content {
-                        Some(content) => {
-                            if attempt > 1 {
-                                println!("[GPT JSON] リトライ成功 (試行 {}/{})", attempt, MAX_RETRIES);
-                            }
-                            
-                            // 完了トークン数を計算
-                            let completion_tokens = count_tokens(content);
-                            
-                            // プロンプト全体を作成（システムプロンプト + ユーザー入力）
-                            let full_prompt = format!("{}\n\nユーザー入力:\n{}", prompt, user_text);
-                            
-                            // トークン使用量を記録
-                            println!("[Token] 記録開始: bot_pubkey={}, category={}", bot_pubkey, category);
-                            if let Ok(conn) = db::connect() {
-                                if let Err(e) = db::record_token_usage(&conn, bot_pubkey, category, total_prompt_tokens, completion_tokens, &full_prompt, content) {
-                                    eprintln!("[Token] 記録エラー: {:?}", e);
-                                }
-                            } else {
-                                eprintln!("[Token] DB接続エラー");
-                            }
-                            
-                            return Ok(content.to_string());
-                        },
-                        None => {
-                            last_error = Some("No content found in response".to_string());
-                        }
-                    }            
-                },
-                Err(e) => {
-                    last_error = Some(format!("{}", e));
+        match timeout(Duration::from_secs(timeout_secs), request()).await {
+            Ok(Ok(response)) => {
+                if attempt > 1 {
+                    info!("{} リトライ成功 (試行 {}/{})", log_tag, attempt, MAX_RETRIES);
                 }
+                
+                let (full_prompt, completion) = describe(&response);
+                record_llm_usage(provider, bot_pubkey, category, prompt_tokens, &full_prompt, &completion, started);
+                
+                return Ok(response);
+            },
+            Ok(Err(e)) => {
+                last_error = Some(format!("{}", e));
             },
             Err(_) => {
                 last_error = Some(format!("Timeout after {} seconds", timeout_secs));
@@ -239,20 +163,156 @@ pub async fn call_gpt_with_json_mode(prompt: &str, user_text: &str, bot_pubkey:
         
         // 最後の試行でなければリトライ
         if attempt < MAX_RETRIES {
-            eprintln!("[GPT JSON] エラー発生 (試行 {}/{}): {:?} - {}秒後にリトライ", 
-                      attempt, MAX_RETRIES, last_error, RETRY_DELAY_SECS);
+            metrics::llm_retry(provider.name(), category);
+            warn!("{} エラー発生 (試行 {}/{}): {:?} - {}秒後にリトライ", 
+                      log_tag, attempt, MAX_RETRIES, last_error, RETRY_DELAY_SECS);
             tokio::time::sleep(Duration::from_secs(RETRY_DELAY_SECS)).await;
         }
     }
     
     // 全てのリトライが失敗
-    Err(last_error.unwrap_or_else(|| "Unknown error after retries".to_string()).into())
+    let error = last_error.unwrap_or_else(|| "Unknown error after retries".to_string());
+    publish_llm_call(provider, bot_pubkey, category, started, prompt_tokens, 0, Some(error.clone()));
+    Err(error.into())
+}
+
+/// ツールを使ったやり取りの結果
+pub struct ToolLoopOutcome {
+    /// 最終的な返答
+    pub answer: String,
+    /// 実行したツールの結果（呼び出し順）
+    pub results: Vec<llm::tools::ToolResult>,
+}
+
+/// ツールを実行しながらLLMに答えさせる
+/// 最初の呼び出しはcategories.0、ツールの結果を受け取った後はcategories.1で記録し、
+/// max_rounds回ツールを実行したら最後はツールなしで答えさせる
+pub async fn call_gpt_with_tool_loop(
+    prompt: &str,
+    user_text: &str,
+    tools: &llm::tools::ToolRegistry,
+    ctx: &llm::tools::ToolContext<'_>,
+    categories: (&str, &str),
+    max_rounds: usize,
+) -> Result<ToolLoopOutcome, Box<dyn Error>> {
+    let definitions = tools.definitions();
+    let mut messages = vec![llm::ToolMessage::User(user_text.to_string())];
+    let mut results = Vec::new();
+
+    for round in 0..=max_rounds {
+        let category = if round == 0 { categories.0 } else { categories.1 };
+        // 上限に達したらツールなしで回答させる
+        let round_tools: &[llm::ToolDefinition] = if round < max_rounds { &definitions } else { &[] };
+
+        let response = call_gpt_with_tools(prompt, &messages, round_tools, ctx.bot, category, ctx.config)
+            .await
+            .map_err(|e| e.to_string())?;
+        let calls = match response {
+            llm::ToolChatResponse::Text(answer) => return Ok(ToolLoopOutcome { answer, results }),
+            llm::ToolChatResponse::ToolCalls(calls) if round < max_rounds => calls,
+            llm::ToolChatResponse::ToolCalls(_) => break,
+        };
+
+        messages.push(llm::ToolMessage::Assistant(calls.clone()));
+        for call in calls {
+            let result = tools.execute(ctx, &call).await;
+            messages.push(llm::ToolMessage::Tool { call_id: call.id.clone(), content: result.content.clone() });
+            results.push(result);
+        }
+    }
+
+    error!("[GPT Tools] ツール呼び出しの上限（{}回）に達しました", max_rounds);
+    Ok(ToolLoopOutcome { answer: String::new(), results })
+}
+
+/// メンション返信の前に、Botに許可されたツールで返信に必要な情報を集める
+/// 返信のコンテキストに加える文字列を返す（ツールを使わなかった場合はNone）
+pub async fn gather_tool_results_for_reply(
+    person: &db::Person,
+    event: &nostr_sdk::Event,
+    user_text: &str,
+    config: &AppConfig,
+) -> Option<String> {
+    let allowed = person.overrides.allowed_tools.as_deref().unwrap_or_default();
+    let tools = llm::tools::ToolRegistry::builtin().only(allowed);
+    if tools.is_empty() {
+        return None;
+    }
+
+    let prompt = format!(
+        "あなたはNostrのBotで、これからユーザーの投稿に返信します。\n\
+        返信に必要な情報があれば、ツールを呼び出して調べてください。\n\
+        ・話しかけてきたユーザーの公開鍵は {} です\n\
+        ・調べる必要がなければ、ツールを呼び出さずに「なし」とだけ答えてください",
+        event.pubkey.to_hex()
+    );
+    let ctx = llm::tools::ToolContext {
+        config,
+        bot: person,
+        event,
+    };
+    let max_rounds = config.get_usize_setting("tool_max_rounds");
+
+    let outcome = match call_gpt_with_tool_loop(&prompt, user_text, &tools, &ctx, ("tool_call", "tool_call"), max_rounds).await {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            error!("[GPT Tools] ツール呼び出しエラー: {}", e);
+            return None;
+        }
+    };
+    if outcome.results.is_empty() {
+        return None;
+    }
+
+    let lines: Vec<String> = outcome
+        .results
+        .iter()
+        .map(|result| format!("- {}({}): {}", result.call.name, result.call.arguments, result.content))
+        .collect();
+    Some(format!("【ツールの実行結果】\n{}", lines.join("\n")))
+}
+
+/// トークン使用量を記録
+fn record_llm_usage(provider: &dyn llm::LlmProvider, bot_pubkey: &str, category: &str, prompt_tokens: usize, full_prompt: &str, completion: &str, started: Instant) {
+    // 完了トークン数を計算
+    let completion_tokens = provider.count_tokens(completion);
+    publish_llm_call(provider, bot_pubkey, category, started, prompt_tokens, completion_tokens, None);
+    metrics::tokens_used(bot_pubkey, prompt_tokens, completion_tokens);
+    
+    debug!("[Token] 記録開始: bot_pubkey={}, category={}, provider={}, model={}", bot_pubkey, category, provider.name(), provider.model());
+    if let Ok(conn) = db::connect() {
+        if let Err(e) = db::record_token_usage(&conn, bot_pubkey, category, prompt_tokens, completion_tokens, full_prompt, completion) {
+            error!("[Token] 記録エラー: {:?}", e);
+        }
+    } else {
+        error!("[Token] DB接続エラー");
+    }
+}
+
+/// LLM呼び出しの所要時間とトークン数をメトリクスに記録し、ダッシュボードにライブ配信
+fn publish_llm_call(provider: &dyn llm::LlmProvider, bot_pubkey: &str, category: &str, started: Instant, prompt_tokens: usize, completion_tokens: usize, error: Option<String>) {
+    metrics::llm_request(provider.name(), category, started.elapsed(), error.is_none());
+    live::publish(live::LiveEvent::Llm {
+        bot_pubkey: bot_pubkey.to_string(),
+        category: category.to_string(),
+        provider: provider.name().to_string(),
+        model: provider.model().to_string(),
+        latency_ms: started.elapsed().as_millis() as u64,
+        prompt_tokens,
+        completion_tokens,
+        error,
+    });
+}
+
+/// 返信の文字数目安を取得（Bot個別設定があれば優先）
+fn answer_length_for_bot(bot: &db::Person, config: &AppConfig) -> i32 {
+    bot.overrides.answer_length.unwrap_or_else(|| config.get_i32_setting("gpt_answer_length"))
 }
 
 /// 新しいインターフェース: 会話コンテキスト文字列を受け取る
 #[allow(dead_code)]
 pub async fn get_reply_with_context<'a>(
-    bot_pubkey: &'a str,
+    bot: &'a db::Person,
     personality: &'a str,
     user_text: &'a str,
     has_mention: bool,
@@ -262,7 +322,7 @@ pub async fn get_reply_with_context<'a>(
     dotenv().ok();
     
     // 回答長設定を取得
-    let answer_length = config.get_i32_setting("gpt_answer_length");
+    let answer_length = answer_length_for_bot(bot, config);
 
     let start_delimiter = "<<";
     let end_delimiter = ">>";
@@ -281,13 +341,11 @@ pub async fn get_reply_with_context<'a>(
     }
 
     let prompt;
-    let prompt_temp;
-
-    if modified_personality.len() > 0 && extracted_prompt.len() > 0 {
-        prompt_temp = format!("これはあなたの人格です。'{personality}'\n{extracted_prompt}");
+    let prompt_temp = if !modified_personality.is_empty() && !extracted_prompt.is_empty() {
+        format!("これはあなたの人格です。'{personality}'\n{extracted_prompt}")
     } else {
-        prompt_temp = format!("これはあなたの人格です。'{personality}'\nこの人格を演じて次の行の文章に対して{answer_length}文字程度で返信してください。ユーザーから文字数指定があった場合はそちらを優先してください。");
-    }
+        format!("これはあなたの人格です。'{personality}'\nこの人格を演じて次の行の文章に対して{answer_length}文字程度で返信してください。ユーザーから文字数指定があった場合はそちらを優先してください。")
+    };
     
     // コンテキストがある場合
     let user_input = if let Some(ctx) = context {
@@ -318,17 +376,17 @@ pub async fn get_reply_with_context<'a>(
     };
     
     // config.ymlを読み込み
-    let file = File::open("../config.yml")?;
+    let file = File::open(crate::config::config_path())?;
     let config: AppConfig = serde_yaml::from_reader(file)?;
     
-    match call_gpt_with_category(&prompt, &user_input, bot_pubkey, category, &config).await {
+    match call_gpt_with_category(&prompt, &user_input, bot, category, &config).await {
         Ok(reply) => {
-            println!("Reply: {}", reply);
+            info!("Reply: {}", reply);
             Ok(reply)
         },
         Err(e) => {
-            eprintln!("Error calling GPT API: {:?}", e);
-            eprintln!("Error details: {}", e);
+            error!("Error calling GPT API: {:?}", e);
+            error!("Error details: {}", e);
             Ok("".to_string())
         },
     }
@@ -336,7 +394,7 @@ pub async fn get_reply_with_context<'a>(
 
 /// 旧インターフェース: 互換性のため残す
 pub async fn get_reply<'a>(
-    bot_pubkey: &'a str,
+    bot: &'a db::Person,
     personality: &'a str, 
     user_text: &'a str, 
     _has_mention: bool,
@@ -346,7 +404,7 @@ pub async fn get_reply<'a>(
     dotenv().ok();
     
     // 回答長設定を取得
-    let answer_length = config.get_i32_setting("gpt_answer_length");
+    let answer_length = answer_length_for_bot(bot, config);
 
     let start_delimiter = "<<";
     let end_delimiter = ">>";
@@ -365,13 +423,11 @@ pub async fn get_reply<'a>(
     }
 
     let prompt;
-    let prompt_temp;
-
-    if modified_personality.len() > 0 && extracted_prompt.len() > 0 {
-        prompt_temp = format!("これはあなたの人格です。'{personality}'\n{extracted_prompt}");
+    let prompt_temp = if !modified_personality.is_empty() && !extracted_prompt.is_empty() {
+        format!("これはあなたの人格です。'{personality}'\n{extracted_prompt}")
     } else {
-        prompt_temp = format!("これはあなたの人格です。'{personality}'\nこの人格を演じて次の行の文章に対して{answer_length}文字程度で返信してください。ユーザーから文字数指定があった場合はそちらを優先してください。");
-    }
+        format!("これはあなたの人格です。'{personality}'\nこの人格を演じて次の行の文章に対して{answer_length}文字程度で返信してください。ユーザーから文字数指定があった場合はそちらを優先してください。")
+    };
     
     // タイムラインがある場合（エアリプ）
     // カテゴリを先に決定（moveの前に）
@@ -412,10 +468,10 @@ pub async fn get_reply<'a>(
             let user_input_text = format!("【タイムライン】\n{}", timeline_text);
             
             // デバッグ: エアリプ時のLLM入力内容をログ出力
-            println!("=== Air-reply LLM Input ===");
-            println!("Prompt:\n{}", prompt);
-            println!("User input:\n{}", user_input_text);
-            println!("===========================");
+            info!("=== Air-reply LLM Input ===");
+            info!("Prompt:\n{}", prompt);
+            info!("User input:\n{}", user_input_text);
+            info!("===========================");
             
             user_input_text
         } else {
@@ -429,55 +485,151 @@ pub async fn get_reply<'a>(
     };
 
     // config.ymlを読み込み
-    let file = File::open("../config.yml")?;
+    let file = File::open(crate::config::config_path())?;
     let config: AppConfig = serde_yaml::from_reader(file)?;
 
-    match call_gpt_with_category(&prompt, &user_input, bot_pubkey, category, &config).await {
+    match call_gpt_with_category(&prompt, &user_input, bot, category, &config).await {
         Ok(reply) => {
-            println!("Reply: {}", reply);
+            info!("Reply: {}", reply);
             Ok(reply)
         },
         Err(e) => {
-            eprintln!("Error calling GPT API: {:?}", e);
-            eprintln!("Error details: {}", e);
+            error!("Error calling GPT API: {:?}", e);
+            error!("Error details: {}", e);
             Ok("".to_string())
         },
     }
 }
 
+/// エアリプ用の追加指示（メンションなら空）
+fn air_reply_instruction(has_mention: bool) -> &'static str {
+    if !has_mention {
+        "\n\n以下は最近のタイムラインです。この流れを見て、あなたが気になった投稿に自然に反応してください。\
+         あなた宛ではないので、独り言のように自然に反応してください。"
+    } else {
+        ""
+    }
+}
+
 /// エアリプ時の心境付き返信を生成（印象なし、心境のみ）
+/// reaction_instructionがある場合は返信の代わりにリアクションを選べる
 pub async fn get_air_reply_with_mental_diary<'a>(
-    bot_pubkey: &'a str,
+    bot: &'a db::Person,
     personality: &'a str,
     user_text: &'a str,
     has_mention: bool,
     context: Option<String>,
+    reaction_instruction: Option<&'a str>,
     config: &'a AppConfig,
 ) -> Result<GptResponseWithMentalDiary, Box<dyn Error>> {
-    // エアリプ用の追加指示
-    let air_reply_instruction = if !has_mention {
-        "\n\n以下は最近のタイムラインです。この流れを見て、あなたが気になった投稿に自然に反応してください。\
-         あなた宛ではないので、独り言のように自然に反応してください。"
-    } else {
-        ""
-    };
+    let instruction = format!("{}{}", air_reply_instruction(has_mention), reaction_instruction.unwrap_or(""));
     
     call_gpt_with_mental_diary_internal(
-        bot_pubkey,
+        bot,
         None, // user_pubkey なし（エアリプなので印象不要）
         personality,
         user_text,
         context,
-        Some(air_reply_instruction),
+        Some(&instruction),
         "air_reply",
         None, // user_name なし（エアリプなので不要）
         config,
     ).await
 }
 
+/// DMへの返信を生成（印象＋心境付き）
+pub async fn get_dm_reply_with_mental_diary<'a>(
+    bot: &'a db::Person,
+    user_pubkey: &'a str,
+    personality: &'a str,
+    user_text: &'a str,
+    context: Option<String>,
+    user_name: Option<&'a str>,
+    config: &'a AppConfig,
+) -> Result<GptResponseWithMentalDiary, Box<dyn Error>> {
+    // DM用の追加指示
+    let dm_instruction = "\n\nこれは相手とあなただけの非公開のダイレクトメッセージです。\
+         1対1の会話として親密に返信してください。DMの内容を公開の場で話題にしないでください。";
+    
+    call_gpt_with_mental_diary_internal(
+        bot,
+        Some(user_pubkey),
+        personality,
+        user_text,
+        context,
+        Some(dm_instruction),
+        "direct_message",
+        user_name,
+        config,
+    ).await
+}
+
+/// Zapへのお礼を生成（印象＋心境付き）
+pub async fn get_zap_thanks_with_mental_diary<'a>(
+    bot: &'a db::Person,
+    user_pubkey: &'a str,
+    personality: &'a str,
+    amount_sats: u64,
+    message: &'a str,
+    user_name: Option<&'a str>,
+    config: &'a AppConfig,
+) -> Result<GptResponseWithMentalDiary, Box<dyn Error>> {
+    // Zapのお礼用の追加指示
+    let zap_instruction = format!(
+        "\n\n相手があなたに{amount_sats} satsのZap（投げ銭）を送ってくれました。\
+         あなたらしく感謝の気持ちを伝えてください。メッセージが添えられていればそれにも触れてください。"
+    );
+    let user_text = if message.is_empty() {
+        format!("（{} satsのZap）", amount_sats)
+    } else {
+        format!("（{} satsのZap）{}", amount_sats, message)
+    };
+    
+    call_gpt_with_mental_diary_internal(
+        bot,
+        Some(user_pubkey),
+        personality,
+        &user_text,
+        None,
+        Some(&zap_instruction),
+        "zap_thanks",
+        user_name,
+        config,
+    ).await
+}
+
+/// 定期投稿を生成（印象なし、心境のみ）
+/// instructionには投稿の種類ごとの指示、materialには投稿の材料（最近の会話など）を渡す
+pub async fn get_scheduled_post_with_mental_diary<'a>(
+    bot: &'a db::Person,
+    personality: &'a str,
+    instruction: &'a str,
+    material: &'a str,
+    config: &'a AppConfig,
+) -> Result<GptResponseWithMentalDiary, Box<dyn Error>> {
+    // 定期投稿用の追加指示
+    let schedule_instruction = format!(
+        "\n\nこれは誰かへの返信ではなく、あなたが自分からタイムラインに投稿する文章です。\
+         replyには投稿する文章を入れてください。\n{}",
+        instruction
+    );
+
+    call_gpt_with_mental_diary_internal(
+        bot,
+        None, // user_pubkey なし（特定の相手はいない）
+        personality,
+        material,
+        None,
+        Some(&schedule_instruction),
+        "scheduled_post",
+        None,
+        config,
+    ).await
+}
+
 /// 心境・印象付きプロンプトを構築する共通関数
 async fn build_mental_diary_prompt<'a>(
-    bot_pubkey: &'a str,
+    bot: &'a db::Person,
     user_pubkey: Option<&'a str>, // Noneの場合は印象を含めない
     personality: &'a str,
     _user_text: &'a str,
@@ -487,8 +639,10 @@ async fn build_mental_diary_prompt<'a>(
 ) -> Result<(String, rusqlite::Connection), Box<dyn Error>> {
     dotenv().ok();
     
+    let bot_pubkey = bot.pubkey.as_str();
+    
     // 設定を取得
-    let answer_length = config.get_i32_setting("gpt_answer_length");
+    let answer_length = answer_length_for_bot(bot, config);
     let _max_impression_length = config.get_usize_setting("max_impression_length");
     
     // DB接続
@@ -522,6 +676,18 @@ async fn build_mental_diary_prompt<'a>(
         String::new()
     };
 
+    // 最近届いたいいね・Zap（心境の更新に使う）
+    let engagement_context = match crate::engagement::recent_summary(&conn, bot_pubkey).await {
+        Ok(summary) if !summary.is_empty() => {
+            format!("\n\n【最近24時間にあなたの投稿へ届いた反応】\n{}", summary)
+        }
+        Ok(_) => String::new(),
+        Err(e) => {
+            error!("[Engagement] 反応の取得エラー: {}", e);
+            String::new()
+        }
+    };
+
     // パーソナリティのパース
     let start_delimiter = "<<";
     let end_delimiter = ">>";
@@ -547,7 +713,7 @@ async fn build_mental_diary_prompt<'a>(
     };
     
     // ベースプロンプトの構築
-    let base_prompt = if modified_personality.len() > 0 && extracted_prompt.len() > 0 {
+    let base_prompt = if !modified_personality.is_empty() && !extracted_prompt.is_empty() {
         format!(
             "これはあなたの人格です。'{modified_personality}'{user_name_section}\n{extracted_prompt}"
         )
@@ -642,7 +808,8 @@ async fn build_mental_diary_prompt<'a>(
         "# あなたの役割\n\
          {base_prompt}{additional_inst}\
          {user_attributes_context}\
-         {mental_state_context}\n\n\
+         {mental_state_context}\
+         {engagement_context}\n\n\
          # 出力形式\n\
          重要: あなたは必ずJSON形式で応答してください。他の形式は一切使用しないでください。\n\n\
          ```json\n\
@@ -657,22 +824,25 @@ async fn build_mental_diary_prompt<'a>(
 }
 
 /// ユーザーへの印象と心境を含む返信を生成（メンション返信のみ）
+/// reaction_instructionがある場合は返信の代わりにリアクションを選べる
+#[allow(clippy::too_many_arguments)]
 pub async fn get_reply_with_mental_diary<'a>(
-    bot_pubkey: &'a str,
+    bot: &'a db::Person,
     user_pubkey: &'a str,
     personality: &'a str,
     user_text: &'a str,
     context: Option<String>,
     user_name: Option<&'a str>,
+    reaction_instruction: Option<&'a str>,
     config: &'a AppConfig,
 ) -> Result<GptResponseWithMentalDiary, Box<dyn Error>> {
     call_gpt_with_mental_diary_internal(
-        bot_pubkey,
+        bot,
         Some(user_pubkey), // user_pubkey あり（メンション返信なので印象必要）
         personality,
         user_text,
         context,
-        None, // 追加指示なし
+        reaction_instruction, // リアクションを選べる場合のみ追加指示
         "reply",
         user_name,
         config,
@@ -680,8 +850,9 @@ pub async fn get_reply_with_mental_diary<'a>(
 }
 
 /// 心境・印象付き返信の内部共通関数
+#[allow(clippy::too_many_arguments)]
 async fn call_gpt_with_mental_diary_internal<'a>(
-    bot_pubkey: &'a str,
+    bot: &'a db::Person,
     user_pubkey: Option<&'a str>,
     personality: &'a str,
     user_text: &'a str,
@@ -691,9 +862,81 @@ async fn call_gpt_with_mental_diary_internal<'a>(
     user_name: Option<&'a str>,
     config: &'a AppConfig,
 ) -> Result<GptResponseWithMentalDiary, Box<dyn Error>> {
+    let trace = run_mental_diary_call(
+        bot,
+        user_pubkey,
+        personality,
+        user_text,
+        context,
+        additional_instruction,
+        category,
+        user_name,
+        config,
+    ).await?;
+    
+    // 注意: ここではDBに保存しない！
+    // 送信成功後に呼び出し元で save_mental_diary_response を呼ぶこと
+    match (trace.response, trace.parse_error) {
+        (Some(response), _) => Ok(response),
+        (None, error) => Err(error.unwrap_or_default().into()),
+    }
+}
+
+/// 心境付き返信を生成する過程（組み立てたプロンプト・LLMの生の出力・トークン数）
+#[derive(Debug, Serialize)]
+pub struct MentalDiaryTrace {
+    pub system_prompt: String,
+    /// LLMにユーザー入力として渡した文章（コンテキストがあればコンテキスト）
+    pub user_input: String,
+    pub raw_output: String,
+    pub prompt_tokens: usize,
+    pub completion_tokens: usize,
+    /// パースできた場合の応答
+    pub response: Option<GptResponseWithMentalDiary>,
+    pub parse_error: Option<String>,
+}
+
+/// 返信を送らずに生成だけ行う（ダッシュボードのプロンプト試行用、カテゴリ: playground）
+/// user_pubkeyがあればメンションへの返信、なければエアリプとして生成する
+pub async fn dry_run_reply_with_mental_diary<'a>(
+    bot: &'a db::Person,
+    user_pubkey: Option<&'a str>,
+    personality: &'a str,
+    user_text: &'a str,
+    context: Option<String>,
+    user_name: Option<&'a str>,
+    config: &'a AppConfig,
+) -> Result<MentalDiaryTrace, Box<dyn Error>> {
+    let instruction = air_reply_instruction(user_pubkey.is_some());
+    run_mental_diary_call(
+        bot,
+        user_pubkey,
+        personality,
+        user_text,
+        context,
+        Some(instruction),
+        "playground",
+        user_name,
+        config,
+    ).await
+}
+
+/// プロンプトを組み立ててLLMを呼び出し、応答をパースする（パースの失敗はparse_errorに入れる）
+#[allow(clippy::too_many_arguments)]
+async fn run_mental_diary_call<'a>(
+    bot: &'a db::Person,
+    user_pubkey: Option<&'a str>,
+    personality: &'a str,
+    user_text: &'a str,
+    context: Option<String>,
+    additional_instruction: Option<&'a str>,
+    category: &'a str,
+    user_name: Option<&'a str>,
+    config: &'a AppConfig,
+) -> Result<MentalDiaryTrace, Box<dyn Error>> {
     // 共通のプロンプト構築関数を使用
     let (system_prompt, _conn) = build_mental_diary_prompt(
-        bot_pubkey,
+        bot,
         user_pubkey,
         personality,
         user_text,
@@ -709,61 +952,60 @@ async fn call_gpt_with_mental_diary_internal<'a>(
     };
 
     // GPTを呼び出し（JSON mode使用）
-    match call_gpt_with_json_mode(&system_prompt, &user_input, bot_pubkey, category, config).await {
-        Ok(response_text) => {
-            // 不完全なJSONを修正（partial-json-fixer 0.5.3は直接Stringを返す）
-            let fixed_json = fix_json(&response_text);
-            
-            // user_pubkeyがある場合は印象あり、ない場合は印象なし
-            if user_pubkey.is_some() {
-                // ユーザー属性ありのパース（保存はせず、パース結果とconnを返す）
-                match serde_json::from_str::<GptResponseWithMentalDiary>(&fixed_json) {
-                    Ok(parsed) => {
-                        // 注意: ここではDBに保存しない！
-                        // 送信成功後に呼び出し元で save_mental_diary_response を呼ぶこと
-                        Ok(parsed)
-                    },
-                    Err(e) => {
-                        eprintln!("[JSON Parse] エラー: {}", e);
-                        eprintln!("[JSON Parse] 修正後のJSON: {}", fixed_json);
-                        eprintln!("[JSON Parse] 元の応答: {}", response_text);
-                        Err(format!("JSONパースエラー: {} (応答: {})", e, response_text).into())
-                    }
-                }
-            } else {
-                // 印象なしのパース（エアリプ用）
-                #[derive(Debug, serde::Deserialize)]
-                struct AirReplyResponse {
-                    reply: String,
-                    mental_diary: db::MentalDiary,
-                }
-                
-                match serde_json::from_str::<AirReplyResponse>(&fixed_json) {
-                    Ok(parsed) => {
-                        // 注意: ここではDBに保存しない！
-                        // 送信成功後に呼び出し元で save_mental_diary_response を呼ぶこと
-                        
-                        // 印象なしのレスポンスをユーザー属性ありの形式に変換
-                        Ok(GptResponseWithMentalDiary {
-                            reply: parsed.reply,
-                            user_attributes: db::UserAttributes::empty(),
-                            mental_diary: parsed.mental_diary,
-                        })
-                    },
-                    Err(e) => {
-                        eprintln!("[JSON Parse] エラー: {}", e);
-                        eprintln!("[JSON Parse] 修正後のJSON: {}", fixed_json);
-                        eprintln!("[JSON Parse] 元の応答: {}", response_text);
-                        Err(format!("JSONパースエラー: {} (応答: {})", e, response_text).into())
-                    }
-                }
-            }
-        },
+    let response_text = match call_gpt_with_json_mode(&system_prompt, &user_input, bot, category, config).await {
+        Ok(response_text) => response_text,
         Err(e) => {
-            eprintln!("[GPT API] エラー: {:?}", e);
-            Err(e)
+            error!("[GPT API] エラー: {:?}", e);
+            return Err(e);
         }
-    }
+    };
+    let provider = llm::provider_for_bot(config, &bot.pubkey, &bot.overrides).map_err(|e| e.to_string())?;
+    let prompt_tokens = provider.count_tokens(&system_prompt) + provider.count_tokens(&user_input);
+    let completion_tokens = provider.count_tokens(&response_text);
+    
+    // 不完全なJSONを修正（partial-json-fixer 0.5.3は直接Stringを返す）
+    let fixed_json = fix_json(&response_text);
+    
+    // user_pubkeyがある場合は印象あり、ない場合は印象なし
+    let parsed = if user_pubkey.is_some() {
+        serde_json::from_str::<GptResponseWithMentalDiary>(&fixed_json)
+    } else {
+        // 印象なしのパース（エアリプ用）
+        #[derive(Debug, serde::Deserialize)]
+        struct AirReplyResponse {
+            reply: String,
+            #[serde(default)]
+            reaction: Option<String>,
+            mental_diary: db::MentalDiary,
+        }
+        
+        // 印象なしのレスポンスをユーザー属性ありの形式に変換
+        serde_json::from_str::<AirReplyResponse>(&fixed_json).map(|parsed| GptResponseWithMentalDiary {
+            reply: parsed.reply,
+            reaction: parsed.reaction,
+            user_attributes: db::UserAttributes::empty(),
+            mental_diary: parsed.mental_diary,
+        })
+    };
+    let (response, parse_error) = match parsed {
+        Ok(response) => (Some(response), None),
+        Err(e) => {
+            error!("[JSON Parse] エラー: {}", e);
+            error!("[JSON Parse] 修正後のJSON: {}", fixed_json);
+            error!("[JSON Parse] 元の応答: {}", response_text);
+            (None, Some(format!("JSONパースエラー: {} (応答: {})", e, response_text)))
+        }
+    };
+    
+    Ok(MentalDiaryTrace {
+        system_prompt,
+        user_input,
+        raw_output: response_text,
+        prompt_tokens,
+        completion_tokens,
+        response,
+        parse_error,
+    })
 }
 
 /// GPTレスポンス（印象・心境）をDBに保存するヘルパー関数
@@ -780,18 +1022,18 @@ pub fn save_mental_diary_response(
         match response.user_attributes.to_json() {
             Ok(json_str) => {
                 if let Err(e) = db::save_user_impression(&conn, bot_pubkey, upk, &json_str) {
-                    eprintln!("[UserAttributes] 保存エラー: {}", e);
+                    error!("[UserAttributes] 保存エラー: {}", e);
                 }
             }
             Err(e) => {
-                eprintln!("[UserAttributes] JSON変換エラー: {}", e);
+                error!("[UserAttributes] JSON変換エラー: {}", e);
             }
         }
     }
     
     // 心境をDBに保存
     if let Err(e) = db::save_bot_mental_state(&conn, bot_pubkey, &response.mental_diary) {
-        eprintln!("[MentalDiary] 保存エラー: {}", e);
+        error!("[MentalDiary] 保存エラー: {}", e);
     }
     
     Ok(())
@@ -2,12 +2,24 @@ pub mod config;
 pub mod database;
 pub use database as db;  // 外部クレートからdb::でアクセス可能に
 pub mod gpt;
+pub mod llm;
+pub mod embedding;
+pub mod search;
+pub mod web_search;
+pub mod scheduler;
 pub mod commands;
 pub mod util;
 pub mod conversation;
 pub mod dashboard;
 pub mod init;
 pub mod event_processor;
+pub mod relay_pool;
+pub mod direct_message;
+pub mod engagement;
+pub mod reaction;
+pub mod live;
+pub mod metrics;
+pub mod logging;
 
 // main.rs 内の公開構造体
 #[derive(Clone, Debug)]
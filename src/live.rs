@@ -0,0 +1,168 @@
+// ダッシュボードへのライブ配信
+// 受信・キューの状態変化・LLM呼び出し・送信をプロセス内のbroadcastチャンネルに流し、
+// ダッシュボードのSSE（/api/live）から購読する
+
+use chrono::Utc;
+use nostr_sdk::prelude::*;
+use serde::Serialize;
+use std::sync::OnceLock;
+use tokio::sync::broadcast;
+
+/// 購読者が受け取りきれない場合に保持する件数（超えた分は古いものから捨てる）
+const CHANNEL_CAPACITY: usize = 1024;
+
+/// 配信する本文の最大文字数
+const MAX_CONTENT_CHARS: usize = 280;
+
+static CHANNEL: OnceLock<broadcast::Sender<LiveMessage>> = OnceLock::new();
+
+fn sender() -> &'static broadcast::Sender<LiveMessage> {
+    CHANNEL.get_or_init(|| broadcast::channel(CHANNEL_CAPACITY).0)
+}
+
+/// 配信するメッセージ（発生時刻つき）
+#[derive(Debug, Clone, Serialize)]
+pub struct LiveMessage {
+    pub at: i64,
+    #[serde(flatten)]
+    pub event: LiveEvent,
+}
+
+impl LiveMessage {
+    /// SSEのイベント名
+    pub fn name(&self) -> &'static str {
+        match self.event {
+            LiveEvent::Incoming { .. } => "incoming",
+            LiveEvent::Queue { .. } => "queue",
+            LiveEvent::Llm { .. } => "llm",
+            LiveEvent::Outgoing { .. } => "outgoing",
+        }
+    }
+
+    /// 関係するBot（決まっていない場合はNone）
+    pub fn bot_pubkey(&self) -> Option<&str> {
+        match &self.event {
+            LiveEvent::Incoming { .. } => None,
+            LiveEvent::Queue { bot_pubkey, .. } => bot_pubkey.as_deref(),
+            LiveEvent::Llm { bot_pubkey, .. } => Some(bot_pubkey),
+            LiveEvent::Outgoing { bot_pubkey, .. } => Some(bot_pubkey),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LiveEvent {
+    /// リレーから受信したイベント
+    Incoming {
+        event_id: String,
+        kind: u16,
+        pubkey: String,
+        relay: String,
+        /// DMは本文を流さない
+        content: Option<String>,
+    },
+    /// キューの状態変化
+    Queue {
+        queue_id: Option<i64>,
+        event_id: String,
+        status: QueueStatus,
+        bot_pubkey: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    /// LLMの呼び出し（リトライを含めた1回分）
+    Llm {
+        bot_pubkey: String,
+        category: String,
+        provider: String,
+        model: String,
+        latency_ms: u64,
+        prompt_tokens: usize,
+        completion_tokens: usize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    /// Botが送信したイベント（返信・リアクション・DM）
+    Outgoing {
+        bot_pubkey: String,
+        event_id: String,
+        kind: u16,
+        reply_to: Option<String>,
+        /// DMは本文を流さない
+        content: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueStatus {
+    /// キューに追加
+    Enqueued,
+    /// ワーカーが取り出した
+    Processing,
+    /// 処理完了
+    Completed,
+    /// 失敗して再試行待ち
+    Retrying,
+    /// 再試行の上限に達した
+    DeadLettered,
+}
+
+/// メッセージを配信（購読者がいなければ何もしない）
+pub fn publish(event: LiveEvent) {
+    let _ = sender().send(LiveMessage {
+        at: Utc::now().timestamp_millis(),
+        event,
+    });
+}
+
+/// 配信を購読
+pub fn subscribe() -> broadcast::Receiver<LiveMessage> {
+    sender().subscribe()
+}
+
+/// 長い本文を切り詰める
+pub fn truncate(content: &str) -> String {
+    if content.chars().count() <= MAX_CONTENT_CHARS {
+        content.to_string()
+    } else {
+        let mut truncated: String = content.chars().take(MAX_CONTENT_CHARS).collect();
+        truncated.push('…');
+        truncated
+    }
+}
+
+/// リレーから受信したイベント
+pub fn incoming(relay_url: &RelayUrl, event: &Event) {
+    let encrypted = matches!(event.kind, Kind::GiftWrap | Kind::EncryptedDirectMessage);
+    publish(LiveEvent::Incoming {
+        event_id: event.id.to_hex(),
+        kind: event.kind.as_u16(),
+        pubkey: event.pubkey.to_hex(),
+        relay: relay_url.to_string(),
+        content: (!encrypted).then(|| truncate(&event.content)),
+    });
+}
+
+/// キューの状態変化
+pub fn queue(queue_id: Option<i64>, event_id: &str, status: QueueStatus, bot_pubkey: Option<&str>, error: Option<String>) {
+    publish(LiveEvent::Queue {
+        queue_id,
+        event_id: event_id.to_string(),
+        status,
+        bot_pubkey: bot_pubkey.map(str::to_string),
+        error,
+    });
+}
+
+/// Botが送信したイベント（DMは本文にNoneを渡す）
+pub fn outgoing(bot_pubkey: &str, event_id: EventId, kind: Kind, reply_to: Option<EventId>, content: Option<&str>) {
+    publish(LiveEvent::Outgoing {
+        bot_pubkey: bot_pubkey.to_string(),
+        event_id: event_id.to_hex(),
+        kind: kind.as_u16(),
+        reply_to: reply_to.map(|id| id.to_hex()),
+        content: content.map(truncate),
+    });
+}
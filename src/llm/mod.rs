@@ -0,0 +1,173 @@
+// LLMプロバイダーモジュール
+// OpenAI / OpenAI互換API / テスト用スクリプトを同じインターフェースで扱う
+
+pub mod openai;
+pub mod openai_compatible;
+pub mod scripted;
+pub mod tools;
+
+pub use openai::OpenAiProvider;
+pub use openai_compatible::OpenAiCompatibleProvider;
+pub use scripted::ScriptedProvider;
+
+use crate::config::{AppConfig, LlmProviderKind, LlmSettings};
+use crate::database as db;
+use dotenv::dotenv;
+use std::env;
+use std::future::Future;
+use std::pin::Pin;
+use tiktoken_rs::o200k_base;
+
+pub type LlmError = Box<dyn std::error::Error + Send + Sync>;
+
+/// openai_compatibleでbase_urlがないときのエラー（埋め込みの設定でも使う）
+pub(crate) const BASE_URL_REQUIRED: &str = "openai_compatibleにはbase_urlの指定が必要です";
+
+/// リクエストに載せるモデル・生成パラメータ
+#[derive(Debug, Clone)]
+pub struct ChatParams {
+    pub model: String,
+    pub temperature: Option<f64>,
+    pub max_tokens: Option<i64>,
+}
+
+impl ChatParams {
+    pub fn from_settings(settings: &LlmSettings) -> Self {
+        Self {
+            model: settings.model.clone(),
+            temperature: settings.temperature,
+            max_tokens: settings.max_tokens,
+        }
+    }
+}
+
+// プロバイダーが返すFuture
+pub type LlmFuture<'a> = Pin<Box<dyn Future<Output = Result<String, LlmError>> + Send + 'a>>;
+
+// ツール付きチャットのFuture
+pub type ToolChatFuture<'a> = Pin<Box<dyn Future<Output = Result<ToolChatResponse, LlmError>> + Send + 'a>>;
+
+/// LLMに渡すツール（関数）の定義
+#[derive(Debug, Clone)]
+pub struct ToolDefinition {
+    pub name: String,
+    pub description: String,
+    /// 引数のJSON Schema（type: object）
+    pub parameters: serde_json::Value,
+}
+
+/// LLMが要求したツール呼び出し
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    /// 引数（JSON文字列）
+    pub arguments: String,
+}
+
+/// ツール付きチャットの会話の1件
+#[derive(Debug, Clone)]
+pub enum ToolMessage {
+    User(String),
+    /// LLMのツール呼び出し（次の呼び出しで履歴として渡す）
+    Assistant(Vec<ToolCall>),
+    /// ツールの実行結果
+    Tool { call_id: String, content: String },
+}
+
+impl ToolMessage {
+    /// トークン数の計算・記録用のテキスト
+    pub fn text(&self) -> String {
+        match self {
+            Self::User(text) => text.clone(),
+            Self::Assistant(calls) => calls
+                .iter()
+                .map(|call| format!("{}({})", call.name, call.arguments))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Self::Tool { content, .. } => content.clone(),
+        }
+    }
+}
+
+/// ツール付きチャットの応答
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToolChatResponse {
+    /// 最終的な返答
+    Text(String),
+    /// ツールの実行要求（結果を渡して再度呼び出す）
+    ToolCalls(Vec<ToolCall>),
+}
+
+/// LLMプロバイダーの共通インターフェース
+///
+/// 1回の呼び出しのみを担当し、リトライ・タイムアウト・トークン記録は呼び出し側（gpt.rs）で行う
+pub trait LlmProvider: Send + Sync {
+    /// プロバイダー名（ログ用）
+    fn name(&self) -> &str;
+
+    /// 使用するモデル名
+    fn model(&self) -> &str;
+
+    /// 通常のチャット（システムプロンプト + ユーザー入力）
+    fn chat<'a>(&'a self, system_prompt: &'a str, user_text: &'a str) -> LlmFuture<'a>;
+
+    /// JSON modeでのチャット
+    fn chat_json<'a>(&'a self, system_prompt: &'a str, user_text: &'a str) -> LlmFuture<'a>;
+
+    /// ツール（Function Calling）付きのチャット
+    fn chat_with_tools<'a>(&'a self, system_prompt: &'a str, messages: &'a [ToolMessage], tools: &'a [ToolDefinition]) -> ToolChatFuture<'a>;
+
+    /// トークン数を計算（デフォルトはo200k_base）
+    fn count_tokens(&self, text: &str) -> usize {
+        count_tokens_o200k(text)
+    }
+}
+
+/// o200k_base（GPT-4o, GPT-5用）でトークン数を計算
+pub fn count_tokens_o200k(text: &str) -> usize {
+    let bpe = o200k_base().expect("[Token] tiktoken (o200k_base) 初期化に失敗しました");
+    bpe.encode_with_special_tokens(text).len()
+}
+
+/// 解決済み設定からプロバイダーを生成
+pub fn create_provider(settings: &LlmSettings) -> Result<Box<dyn LlmProvider>, LlmError> {
+    match settings.provider {
+        LlmProviderKind::OpenAi => {
+            let env_name = settings.api_key_env.as_deref().unwrap_or("OPEN_AI_API_KEY");
+            let api_key = read_api_key(Some(env_name))
+                .ok_or_else(|| format!("{} is not set", env_name))?;
+            Ok(Box::new(OpenAiProvider::new(api_key, ChatParams::from_settings(settings))))
+        }
+        LlmProviderKind::OpenAiCompatible => {
+            let base_url = settings.base_url.clone()
+                .ok_or(BASE_URL_REQUIRED)?;
+            // ローカルLLMではAPIキー不要なことが多いので未設定を許容
+            let api_key = read_api_key(settings.api_key_env.as_deref());
+            Ok(Box::new(OpenAiCompatibleProvider::new(base_url, api_key, ChatParams::from_settings(settings))))
+        }
+        LlmProviderKind::Scripted => {
+            Ok(Box::new(ScriptedProvider::from_config(&settings.scripted, settings.model.clone())))
+        }
+    }
+}
+
+/// Bot用のLLM設定を解決（config.ymlの共通設定 < config.ymlのBot個別設定 < Personsテーブルの個別設定）
+///
+/// Personsの個別設定は呼び出し側が読み込み済みのものを渡す（LLM呼び出しのたびにDBを読まない）
+pub fn settings_for_bot(config: &AppConfig, bot_pubkey: &str, overrides: &db::PersonOverrides) -> LlmSettings {
+    let mut settings = config.llm.for_bot(bot_pubkey);
+    settings.apply_person_overrides(overrides);
+    settings
+}
+
+/// Bot用のプロバイダーを生成
+pub fn provider_for_bot(config: &AppConfig, bot_pubkey: &str, overrides: &db::PersonOverrides) -> Result<Box<dyn LlmProvider>, LlmError> {
+    create_provider(&settings_for_bot(config, bot_pubkey, overrides))
+}
+
+/// 環境変数からAPIキーを読み込む（変数名未指定・未設定ならNone）
+pub(crate) fn read_api_key(env_name: Option<&str>) -> Option<String> {
+    dotenv().ok();
+    env_name.and_then(|name| env::var(name).ok())
+}
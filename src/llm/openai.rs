@@ -0,0 +1,186 @@
+use super::{ChatParams, LlmError, LlmFuture, LlmProvider, ToolCall, ToolChatFuture, ToolChatResponse, ToolDefinition, ToolMessage};
+use openai_api_rs::v1::api::OpenAIClient;
+use openai_api_rs::v1::chat_completion::{self, chat_completion::ChatCompletionRequest};
+use openai_api_rs::v1::types::{Function, FunctionParameters};
+
+/// OpenAI API（api.openai.com）のプロバイダー
+pub struct OpenAiProvider {
+    api_key: String,
+    params: ChatParams,
+}
+
+impl OpenAiProvider {
+    pub fn new(api_key: String, params: ChatParams) -> Self {
+        Self { api_key, params }
+    }
+}
+
+impl LlmProvider for OpenAiProvider {
+    fn name(&self) -> &str {
+        "openai"
+    }
+
+    fn model(&self) -> &str {
+        &self.params.model
+    }
+
+    fn chat<'a>(&'a self, system_prompt: &'a str, user_text: &'a str) -> LlmFuture<'a> {
+        Box::pin(chat_completion(None, Some(&self.api_key), &self.params, system_prompt, user_text, false))
+    }
+
+    fn chat_json<'a>(&'a self, system_prompt: &'a str, user_text: &'a str) -> LlmFuture<'a> {
+        Box::pin(chat_completion(None, Some(&self.api_key), &self.params, system_prompt, user_text, true))
+    }
+
+    fn chat_with_tools<'a>(&'a self, system_prompt: &'a str, messages: &'a [ToolMessage], tools: &'a [ToolDefinition]) -> ToolChatFuture<'a> {
+        Box::pin(chat_completion_with_tools(None, Some(&self.api_key), &self.params, system_prompt, messages, tools))
+    }
+}
+
+/// Chat Completions APIを1回呼び出す（OpenAI / OpenAI互換で共通）
+pub(crate) async fn chat_completion(
+    endpoint: Option<&str>,
+    api_key: Option<&str>,
+    params: &ChatParams,
+    system_prompt: &str,
+    user_text: &str,
+    json_mode: bool,
+) -> Result<String, LlmError> {
+    let mut req = ChatCompletionRequest::new(
+        params.model.clone(),
+        vec![
+            chat_completion::ChatCompletionMessage {
+                role: chat_completion::MessageRole::system,
+                content: chat_completion::Content::Text(String::from(system_prompt)),
+                name: None,
+                tool_calls: None,
+                tool_call_id: None,
+            },
+            chat_completion::ChatCompletionMessage {
+                role: chat_completion::MessageRole::user,
+                content: chat_completion::Content::Text(String::from(user_text)),
+                name: None,
+                tool_calls: None,
+                tool_call_id: None,
+            },
+        ]
+    );
+
+    req.temperature = params.temperature;
+    req.max_tokens = params.max_tokens;
+
+    if json_mode {
+        // JSON modeを有効化（serde_json::Valueとして指定）
+        req.response_format = Some(serde_json::json!({
+            "type": "json_object"
+        }));
+    }
+
+    let mut client = build_client(endpoint, api_key)?;
+
+    let response = client.chat_completion(req).await?;
+    match response.choices.first().and_then(|choice| choice.message.content.clone()) {
+        Some(content) => Ok(content),
+        None => Err("No content found in response".into()),
+    }
+}
+
+/// ツール付きでChat Completions APIを1回呼び出す（OpenAI / OpenAI互換で共通）
+pub(crate) async fn chat_completion_with_tools(
+    endpoint: Option<&str>,
+    api_key: Option<&str>,
+    params: &ChatParams,
+    system_prompt: &str,
+    messages: &[ToolMessage],
+    tools: &[ToolDefinition],
+) -> Result<ToolChatResponse, LlmError> {
+    let mut request_messages = vec![text_message(chat_completion::MessageRole::system, system_prompt)];
+    for message in messages {
+        request_messages.push(match message {
+            ToolMessage::User(text) => text_message(chat_completion::MessageRole::user, text),
+            ToolMessage::Assistant(calls) => chat_completion::ChatCompletionMessage {
+                role: chat_completion::MessageRole::assistant,
+                content: chat_completion::Content::Text(String::new()),
+                name: None,
+                tool_calls: Some(calls.iter().map(|call| chat_completion::ToolCall {
+                    id: call.id.clone(),
+                    r#type: "function".to_string(),
+                    function: chat_completion::ToolCallFunction {
+                        name: Some(call.name.clone()),
+                        arguments: Some(call.arguments.clone()),
+                    },
+                }).collect()),
+                tool_call_id: None,
+            },
+            ToolMessage::Tool { call_id, content } => chat_completion::ChatCompletionMessage {
+                role: chat_completion::MessageRole::tool,
+                content: chat_completion::Content::Text(content.clone()),
+                name: None,
+                tool_calls: None,
+                tool_call_id: Some(call_id.clone()),
+            },
+        });
+    }
+
+    let mut req = ChatCompletionRequest::new(params.model.clone(), request_messages);
+    req.temperature = params.temperature;
+    req.max_tokens = params.max_tokens;
+    if !tools.is_empty() {
+        let mut request_tools = Vec::new();
+        for tool in tools {
+            let parameters: FunctionParameters = serde_json::from_value(tool.parameters.clone())?;
+            request_tools.push(chat_completion::Tool {
+                r#type: chat_completion::ToolType::Function,
+                function: Function {
+                    name: tool.name.clone(),
+                    description: Some(tool.description.clone()),
+                    parameters,
+                },
+            });
+        }
+        req.tools = Some(request_tools);
+        req.tool_choice = Some(chat_completion::ToolChoiceType::Auto);
+    }
+
+    let mut client = build_client(endpoint, api_key)?;
+
+    let response = client.chat_completion(req).await?;
+    let message = match response.choices.into_iter().next() {
+        Some(choice) => choice.message,
+        None => return Err("No choice found in response".into()),
+    };
+    let calls: Vec<ToolCall> = message.tool_calls.unwrap_or_default()
+        .into_iter()
+        .filter_map(|call| Some(ToolCall {
+            id: call.id,
+            name: call.function.name?,
+            arguments: call.function.arguments.unwrap_or_else(|| "{}".to_string()),
+        }))
+        .collect();
+    if !calls.is_empty() {
+        return Ok(ToolChatResponse::ToolCalls(calls));
+    }
+    match message.content {
+        Some(content) => Ok(ToolChatResponse::Text(content)),
+        None => Err("No content found in response".into()),
+    }
+}
+
+fn text_message(role: chat_completion::MessageRole, text: &str) -> chat_completion::ChatCompletionMessage {
+    chat_completion::ChatCompletionMessage {
+        role,
+        content: chat_completion::Content::Text(String::from(text)),
+        name: None,
+        tool_calls: None,
+        tool_call_id: None,
+    }
+}
+
+fn build_client(endpoint: Option<&str>, api_key: Option<&str>) -> Result<OpenAIClient, LlmError> {
+    let mut builder = OpenAIClient::builder().with_api_key(api_key.unwrap_or_default());
+    if let Some(endpoint) = endpoint {
+        builder = builder.with_endpoint(endpoint);
+    }
+    // Box<dyn Error>はSendではないのでここで文字列化する
+    Ok(builder.build().map_err(|e| e.to_string())?)
+}
@@ -0,0 +1,39 @@
+use super::openai::{chat_completion, chat_completion_with_tools};
+use super::{ChatParams, LlmFuture, LlmProvider, ToolChatFuture, ToolDefinition, ToolMessage};
+
+/// OpenAI互換API（Ollama / llama.cpp / vLLMなど）のプロバイダー
+pub struct OpenAiCompatibleProvider {
+    base_url: String,
+    api_key: Option<String>,
+    params: ChatParams,
+}
+
+impl OpenAiCompatibleProvider {
+    pub fn new(base_url: String, api_key: Option<String>, params: ChatParams) -> Self {
+        // 末尾のスラッシュは除去（"/chat/completions"と連結されるため）
+        let base_url = base_url.trim_end_matches('/').to_string();
+        Self { base_url, api_key, params }
+    }
+}
+
+impl LlmProvider for OpenAiCompatibleProvider {
+    fn name(&self) -> &str {
+        "openai_compatible"
+    }
+
+    fn model(&self) -> &str {
+        &self.params.model
+    }
+
+    fn chat<'a>(&'a self, system_prompt: &'a str, user_text: &'a str) -> LlmFuture<'a> {
+        Box::pin(chat_completion(Some(&self.base_url), self.api_key.as_deref(), &self.params, system_prompt, user_text, false))
+    }
+
+    fn chat_json<'a>(&'a self, system_prompt: &'a str, user_text: &'a str) -> LlmFuture<'a> {
+        Box::pin(chat_completion(Some(&self.base_url), self.api_key.as_deref(), &self.params, system_prompt, user_text, true))
+    }
+
+    fn chat_with_tools<'a>(&'a self, system_prompt: &'a str, messages: &'a [ToolMessage], tools: &'a [ToolDefinition]) -> ToolChatFuture<'a> {
+        Box::pin(chat_completion_with_tools(Some(&self.base_url), self.api_key.as_deref(), &self.params, system_prompt, messages, tools))
+    }
+}
@@ -0,0 +1,138 @@
+use super::{LlmFuture, LlmProvider, ToolCall, ToolChatFuture, ToolChatResponse, ToolDefinition, ToolMessage};
+use crate::config::{ScriptedConfig, ScriptedRule};
+use std::sync::Mutex;
+
+/// テスト用の決定的なプロバイダー
+///
+/// 入力に含まれる文字列でルールを選び、固定の応答を返す（ネットワーク不要）
+pub struct ScriptedProvider {
+    model: String,
+    default_reply: String,
+    default_json: String,
+    rules: Vec<ScriptedRule>,
+    calls: Mutex<Vec<ScriptedCall>>,
+}
+
+/// 受け付けた呼び出しの記録
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub struct ScriptedCall {
+    pub system_prompt: String,
+    pub user_text: String,
+    pub json_mode: bool,
+}
+
+impl ScriptedProvider {
+    #[allow(dead_code)]
+    pub fn new(default_reply: &str, default_json: &str) -> Self {
+        Self {
+            model: "scripted".to_string(),
+            default_reply: default_reply.to_string(),
+            default_json: default_json.to_string(),
+            rules: Vec::new(),
+            calls: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn from_config(config: &ScriptedConfig, model: String) -> Self {
+        Self {
+            model,
+            default_reply: config.default_reply.clone(),
+            default_json: config.default_json.clone(),
+            rules: config.rules.clone(),
+            calls: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// ルールを追加（先に追加したものが優先）
+    #[allow(dead_code)]
+    pub fn with_rule(mut self, contains: &str, response: &str) -> Self {
+        self.rules.push(ScriptedRule {
+            contains: Some(contains.to_string()),
+            json_mode: None,
+            response: response.to_string(),
+            tool_call: None,
+        });
+        self
+    }
+
+    /// これまでの呼び出し履歴
+    #[allow(dead_code)]
+    pub fn calls(&self) -> Vec<ScriptedCall> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    fn respond(&self, system_prompt: &str, user_text: &str, json_mode: bool) -> String {
+        self.calls.lock().unwrap().push(ScriptedCall {
+            system_prompt: system_prompt.to_string(),
+            user_text: user_text.to_string(),
+            json_mode,
+        });
+
+        let matched = self.rules.iter().filter(|rule| rule.tool_call.is_none()).find(|rule| {
+            let mode_ok = rule.json_mode.is_none_or(|mode| mode == json_mode);
+            let text_ok = rule.contains.as_deref().is_none_or(|needle| {
+                user_text.contains(needle) || system_prompt.contains(needle)
+            });
+            mode_ok && text_ok
+        });
+
+        match matched {
+            Some(rule) => rule.response.clone(),
+            None if json_mode => self.default_json.clone(),
+            None => self.default_reply.clone(),
+        }
+    }
+}
+
+impl LlmProvider for ScriptedProvider {
+    fn name(&self) -> &str {
+        "scripted"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn chat<'a>(&'a self, system_prompt: &'a str, user_text: &'a str) -> LlmFuture<'a> {
+        let response = self.respond(system_prompt, user_text, false);
+        Box::pin(async move { Ok(response) })
+    }
+
+    fn chat_json<'a>(&'a self, system_prompt: &'a str, user_text: &'a str) -> LlmFuture<'a> {
+        let response = self.respond(system_prompt, user_text, true);
+        Box::pin(async move { Ok(response) })
+    }
+
+    /// ユーザー入力への最初の呼び出しではtool_callのルールを、ツールの結果を受け取った後は通常のルールを使う
+    /// （repeatのルールは結果を受け取った後も呼び出し続ける）
+    fn chat_with_tools<'a>(&'a self, system_prompt: &'a str, messages: &'a [ToolMessage], tools: &'a [ToolDefinition]) -> ToolChatFuture<'a> {
+        let user_text = messages.iter().map(|m| m.text()).collect::<Vec<_>>().join("\n");
+        let after_user = matches!(messages.last(), Some(ToolMessage::User(_)));
+        let tool_call = self.rules.iter().find_map(|rule| {
+            let call = rule.tool_call.as_ref().filter(|call| after_user || call.repeat)?;
+            let offered = tools.iter().any(|tool| tool.name == call.name);
+            let text_ok = rule.contains.as_deref().is_none_or(|needle| {
+                user_text.contains(needle) || system_prompt.contains(needle)
+            });
+            (offered && text_ok).then(|| call.clone())
+        });
+
+        let response = match tool_call {
+            Some(call) => {
+                self.calls.lock().unwrap().push(ScriptedCall {
+                    system_prompt: system_prompt.to_string(),
+                    user_text,
+                    json_mode: false,
+                });
+                ToolChatResponse::ToolCalls(vec![ToolCall {
+                    id: format!("call_{}", messages.len()),
+                    name: call.name,
+                    arguments: call.arguments,
+                }])
+            }
+            None => ToolChatResponse::Text(self.respond(system_prompt, &user_text, false)),
+        };
+        Box::pin(async move { Ok(response) })
+    }
+}
@@ -0,0 +1,36 @@
+use super::{Tool, ToolContext, ToolDefinition, ToolFuture};
+use crate::util;
+use chrono::Utc;
+
+/// 現在日時（日本時間）を返すツール
+pub struct CurrentTimeTool;
+
+impl Tool for CurrentTimeTool {
+    fn name(&self) -> &'static str {
+        "get_current_time"
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: self.name().to_string(),
+            description: "現在の日時（日本時間）と曜日を取得します".to_string(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {}
+            }),
+        }
+    }
+
+    fn call<'a>(&'a self, _ctx: &'a ToolContext<'a>, _arguments: &'a str) -> ToolFuture<'a> {
+        Box::pin(async move {
+            let weekdays = ["日", "月", "火", "水", "木", "金", "土"];
+            let now = Utc::now().with_timezone(&util::jst());
+            let weekday = weekdays[now.format("%w").to_string().parse::<usize>().unwrap_or(0)];
+            Ok(serde_json::json!({
+                "datetime": now.format("%Y-%m-%d %H:%M:%S").to_string(),
+                "weekday": weekday,
+                "timezone": "JST",
+            }))
+        })
+    }
+}
@@ -0,0 +1,204 @@
+// ツール（Function Calling）モジュール
+// LLMが返信の前に呼び出せる機能をToolとして登録し、Bot毎に許可したものだけを渡す
+
+mod current_time;
+mod search_events;
+mod thread;
+mod user_profile;
+mod zap_total;
+
+pub use current_time::CurrentTimeTool;
+pub use search_events::SearchEventsTool;
+pub use thread::ThreadTool;
+pub use user_profile::UserProfileTool;
+pub use zap_total::ZapTotalTool;
+
+use super::{LlmError, ToolCall, ToolDefinition};
+use crate::config::AppConfig;
+use crate::database as db;
+use crate::util;
+use chrono::TimeZone;
+use nostr_sdk::prelude::*;
+use serde::de::DeserializeOwned;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{error, info};
+
+// ツールが返すFuture（結果はLLMにJSONで渡す）
+pub type ToolFuture<'a> = Pin<Box<dyn Future<Output = Result<serde_json::Value, LlmError>> + Send + 'a>>;
+
+/// ツールを実行するときの状況
+pub struct ToolContext<'a> {
+    pub config: &'a AppConfig,
+    /// 返信するBot
+    pub bot: &'a db::Person,
+    /// 話しかけてきたユーザーの投稿
+    pub event: &'a Event,
+}
+
+/// LLMが呼び出せるツール
+pub trait Tool: Send + Sync {
+    /// ツール名（LLMからの呼び出し・許可リストで使う）
+    fn name(&self) -> &'static str;
+
+    /// LLMに渡す定義
+    fn definition(&self) -> ToolDefinition;
+
+    /// 実行（argumentsはLLMが指定した引数のJSON文字列）
+    fn call<'a>(&'a self, ctx: &'a ToolContext<'a>, arguments: &'a str) -> ToolFuture<'a>;
+}
+
+/// ツールの実行結果
+#[derive(Debug, Clone)]
+pub struct ToolResult {
+    pub call: ToolCall,
+    /// LLMに返す内容（JSON文字列）
+    pub content: String,
+    pub is_error: bool,
+}
+
+/// 利用できるツールの一覧
+#[derive(Clone, Default)]
+pub struct ToolRegistry {
+    tools: Vec<Arc<dyn Tool>>,
+}
+
+impl ToolRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 組み込みのツールを全て登録したもの
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        registry.register(CurrentTimeTool);
+        registry.register(UserProfileTool);
+        registry.register(ThreadTool);
+        registry.register(ZapTotalTool);
+        registry.register(SearchEventsTool);
+        registry
+    }
+
+    /// ツールを登録（同じ名前のものは置き換える）
+    pub fn register(&mut self, tool: impl Tool + 'static) {
+        self.tools.retain(|t| t.name() != tool.name());
+        self.tools.push(Arc::new(tool));
+    }
+
+    /// 許可されたツールだけに絞る（存在しない名前は無視する）
+    pub fn only<S: AsRef<str>>(&self, names: &[S]) -> Self {
+        for name in names {
+            if self.get(name.as_ref()).is_none() {
+                error!("[Tool] 不明なツールは無視します: {}", name.as_ref());
+            }
+        }
+        Self {
+            tools: self
+                .tools
+                .iter()
+                .filter(|tool| names.iter().any(|name| name.as_ref() == tool.name()))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Tool> {
+        self.tools.iter().find(|tool| tool.name() == name).map(|tool| tool.as_ref())
+    }
+
+    /// LLMに渡す定義
+    pub fn definitions(&self) -> Vec<ToolDefinition> {
+        self.tools.iter().map(|tool| tool.definition()).collect()
+    }
+
+    /// ツール呼び出しを実行してtool_call_logsに記録（失敗した場合もエラー内容をLLMに返す）
+    pub async fn execute(&self, ctx: &ToolContext<'_>, call: &ToolCall) -> ToolResult {
+        let started = Instant::now();
+        let outcome = match self.get(&call.name) {
+            Some(tool) => tool.call(ctx, &call.arguments).await.map_err(|e| e.to_string()),
+            None => Err(format!("利用できないツールです: {}", call.name)),
+        };
+        let duration_ms = started.elapsed().as_millis() as i64;
+
+        let (content, is_error) = match outcome {
+            Ok(value) => (value.to_string(), false),
+            Err(e) => {
+                error!("[Tool] {}の実行エラー: {} ({})", call.name, e, call.arguments);
+                (serde_json::json!({ "error": e }).to_string(), true)
+            }
+        };
+        info!("[Tool] {}({}) {}ms", call.name, call.arguments, duration_ms);
+
+        let user_pubkey = ctx.event.pubkey.to_hex();
+        let event_id = ctx.event.id.to_hex();
+        let logged = db::connect().and_then(|conn| {
+            db::insert_tool_call_log(
+                &conn,
+                &ctx.bot.pubkey,
+                Some(&user_pubkey),
+                Some(&event_id),
+                &call.name,
+                &call.arguments,
+                &content,
+                is_error,
+                duration_ms,
+            )
+        });
+        if let Err(e) = logged {
+            error!("[Tool] 呼び出しの記録エラー: {}", e);
+        }
+
+        ToolResult {
+            call: call.clone(),
+            content,
+            is_error,
+        }
+    }
+}
+
+/// 引数のJSONをパース（空なら全て省略とみなす）
+fn parse_arguments<T: DeserializeOwned + Default>(arguments: &str) -> Result<T, LlmError> {
+    if arguments.trim().is_empty() {
+        return Ok(T::default());
+    }
+    serde_json::from_str(arguments).map_err(|e| format!("引数が不正です: {}", e).into())
+}
+
+/// 公開鍵をパース（npub1...・hex・nostr:・@付きに対応）
+fn parse_pubkey(pubkey: &str) -> Result<PublicKey, LlmError> {
+    let pubkey = pubkey.trim().trim_start_matches('@');
+    PublicKey::parse(pubkey).map_err(|_| format!("公開鍵の形式が不正です: {}", pubkey).into())
+}
+
+/// 引数で指定された公開鍵（省略時は話しかけてきたユーザー）
+fn target_pubkey(ctx: &ToolContext<'_>, pubkey: Option<&str>) -> Result<PublicKey, LlmError> {
+    match pubkey.map(str::trim) {
+        Some(pubkey) if !pubkey.is_empty() => parse_pubkey(pubkey),
+        _ => Ok(ctx.event.pubkey),
+    }
+}
+
+/// 投稿のリンク（nostr:note1...）
+fn note_link(event_id: &EventId) -> String {
+    format!("nostr:{}", event_id.to_bech32().unwrap_or_default())
+}
+
+/// UNIX秒を日本時間の日時に変換
+fn format_jst(timestamp: i64) -> String {
+    util::jst()
+        .timestamp_opt(timestamp, 0)
+        .single()
+        .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+/// 文字数で切り詰める
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    text.chars().take(max_chars).collect()
+}
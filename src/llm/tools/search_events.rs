@@ -0,0 +1,139 @@
+use super::{format_jst, note_link, parse_arguments, parse_pubkey, truncate_chars, Tool, ToolContext, ToolDefinition, ToolFuture};
+use crate::search::{self, SearchParams, SearchSource};
+use crate::util;
+use chrono::{NaiveDate, TimeZone};
+use serde::Deserialize;
+use tracing::info;
+
+/// 取得件数（デフォルト・上限）
+const DEFAULT_LIMIT: usize = 10;
+const MAX_LIMIT: usize = 20;
+
+/// LLMに渡す投稿本文の最大文字数
+const MAX_CONTENT_CHARS: usize = 200;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct SearchEventsArgs {
+    keywords: Vec<String>,
+    start_date: Option<String>,
+    end_date: Option<String>,
+    author: Option<String>,
+    limit: Option<f64>,
+    source: Option<String>,
+}
+
+/// 投稿を検索するツール（保存済みの投稿を優先し、足りなければ検索リレー）
+pub struct SearchEventsTool;
+
+impl Tool for SearchEventsTool {
+    fn name(&self) -> &'static str {
+        "search_events"
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: self.name().to_string(),
+            description: "Nostrの投稿を検索します".to_string(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "keywords": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "検索キーワード（全てを含む投稿を探す）"
+                    },
+                    "start_date": {
+                        "type": "string",
+                        "description": "開始日（YYYY-MM-DD、日本時間、省略可）"
+                    },
+                    "end_date": {
+                        "type": "string",
+                        "description": "終了日（YYYY-MM-DD、日本時間、この日を含む、省略可）"
+                    },
+                    "author": {
+                        "type": "string",
+                        "description": "投稿者（npub1...またはhex、省略可）"
+                    },
+                    "limit": {
+                        "type": "number",
+                        "description": "取得件数（デフォルト10、最大20）"
+                    },
+                    "source": {
+                        "type": "string",
+                        "description": "検索先。local: 保存済みの投稿のみ、relay: 検索リレーのみ。省略時は保存済みの投稿を優先し、足りなければ検索リレー"
+                    }
+                },
+                "required": ["keywords"]
+            }),
+        }
+    }
+
+    fn call<'a>(&'a self, ctx: &'a ToolContext<'a>, arguments: &'a str) -> ToolFuture<'a> {
+        Box::pin(async move {
+            let args: SearchEventsArgs = parse_arguments(arguments)?;
+            let params = to_search_params(args)?;
+            info!("[Search] search_events: {:?}", params);
+
+            // 話しかけてきた投稿自体は除外
+            let found = search::search_events(ctx.config, &params, Some(ctx.event.id)).await?;
+            if found.is_empty() {
+                return Ok(serde_json::json!({ "results": [], "message": "該当する投稿が見つかりませんでした" }));
+            }
+
+            let mut results = Vec::new();
+            for hit in &found {
+                let author = util::get_user_name(&hit.pubkey).await.unwrap_or_else(|_| hit.pubkey.clone());
+                results.push(serde_json::json!({
+                    "note": note_link(&hit.event_id),
+                    "author": author,
+                    "date": format_jst(hit.created_at.as_u64() as i64),
+                    "content": truncate_chars(&hit.content, MAX_CONTENT_CHARS),
+                }));
+            }
+            Ok(serde_json::json!({ "results": results }))
+        })
+    }
+}
+
+/// ツール引数を検索条件に変換
+fn to_search_params(args: SearchEventsArgs) -> Result<SearchParams, String> {
+    let keywords: Vec<String> = args.keywords
+        .iter()
+        .map(|k| k.trim().to_string())
+        .filter(|k| !k.is_empty())
+        .collect();
+    if keywords.is_empty() {
+        return Err("keywordsが空です".to_string());
+    }
+
+    let author = match args.author.as_deref().map(str::trim) {
+        Some(author) if !author.is_empty() => Some(parse_pubkey(author).map_err(|e| e.to_string())?.to_hex()),
+        _ => None,
+    };
+    let source = match args.source.as_deref() {
+        Some("local") => SearchSource::Local,
+        Some("relay") => SearchSource::Relay,
+        _ => SearchSource::Auto,
+    };
+    let limit = args.limit.map(|l| l as usize).unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    Ok(SearchParams {
+        keywords,
+        author,
+        since: args.start_date.as_deref().map(|d| jst_date_to_timestamp(d, false)).transpose()?,
+        until: args.end_date.as_deref().map(|d| jst_date_to_timestamp(d, true)).transpose()?,
+        limit,
+        source,
+    })
+}
+
+/// 日付（日本時間）をUNIX秒に変換（end_of_dayなら23:59:59）
+fn jst_date_to_timestamp(date: &str, end_of_day: bool) -> Result<i64, String> {
+    let date = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").map_err(|_| format!("日付の形式が不正です: {}", date))?;
+    let time = if end_of_day { date.and_hms_opt(23, 59, 59) } else { date.and_hms_opt(0, 0, 0) };
+    let jst = util::jst();
+    time.and_then(|t| jst.from_local_datetime(&t).single())
+        .map(|dt| dt.timestamp())
+        .ok_or_else(|| format!("日付の形式が不正です: {}", date))
+}
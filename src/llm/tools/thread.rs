@@ -0,0 +1,110 @@
+use super::{format_jst, note_link, parse_arguments, truncate_chars, Tool, ToolContext, ToolDefinition, ToolFuture};
+use crate::database as db;
+use crate::util;
+use nostr_sdk::prelude::*;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::time::Duration;
+
+/// 返す投稿の最大件数（新しいものを残す）
+const MAX_THREAD_EVENTS: usize = 20;
+
+/// LLMに渡す投稿本文の最大文字数
+const MAX_CONTENT_CHARS: usize = 200;
+
+/// リレーのタイムアウト
+const RELAY_TIMEOUT_SECS: u64 = 10;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ThreadArgs {
+    event_id: Option<String>,
+}
+
+/// スレッドの投稿を返すツール
+pub struct ThreadTool;
+
+impl Tool for ThreadTool {
+    fn name(&self) -> &'static str {
+        "get_thread"
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: self.name().to_string(),
+            description: "投稿が含まれるスレッドの投稿を古い順に取得します".to_string(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "event_id": {
+                        "type": "string",
+                        "description": "スレッド内の投稿（note1...またはhex）。省略時は今話しかけられている投稿"
+                    }
+                }
+            }),
+        }
+    }
+
+    fn call<'a>(&'a self, ctx: &'a ToolContext<'a>, arguments: &'a str) -> ToolFuture<'a> {
+        Box::pin(async move {
+            let args: ThreadArgs = parse_arguments(arguments)?;
+            let target = match args.event_id.as_deref().map(|id| id.trim().trim_start_matches("nostr:")) {
+                Some(id) if !id.is_empty() => {
+                    let event_id = EventId::parse(id).map_err(|_| format!("投稿IDの形式が不正です: {}", id))?;
+                    find_event(ctx, event_id).await?
+                }
+                _ => ctx.event.clone(),
+            };
+
+            // ルートの投稿と、それを参照する返信を集める
+            let root_id = db::extract_thread_root_id(&target.as_json())?
+                .and_then(|id| EventId::from_hex(&id).ok())
+                .unwrap_or(target.id);
+            let relays = &ctx.config.relay_servers.read;
+            let timeout = Duration::from_secs(RELAY_TIMEOUT_SECS);
+            let pool = crate::relay_pool::shared();
+            let mut events = pool.fetch(Filter::new().id(root_id), relays, timeout).await?;
+            events.extend(
+                pool.fetch(Filter::new().kind(Kind::TextNote).event(root_id).limit(MAX_THREAD_EVENTS * 2), relays, timeout)
+                    .await?,
+            );
+            events.push(target);
+
+            let mut seen = HashSet::new();
+            events.retain(|event| seen.insert(event.id));
+            events.sort_by_key(|event| event.created_at);
+            let skip = events.len().saturating_sub(MAX_THREAD_EVENTS);
+
+            let mut posts = Vec::new();
+            for event in events.into_iter().skip(skip) {
+                let author = util::get_user_name(&event.pubkey.to_hex()).await.unwrap_or_else(|_| event.pubkey.to_hex());
+                posts.push(serde_json::json!({
+                    "note": note_link(&event.id),
+                    "author": author,
+                    "is_you": event.pubkey.to_hex() == ctx.bot.pubkey,
+                    "date": format_jst(event.created_at.as_u64() as i64),
+                    "content": truncate_chars(&event.content, MAX_CONTENT_CHARS),
+                }));
+            }
+            Ok(serde_json::json!({ "root": note_link(&root_id), "posts": posts }))
+        })
+    }
+}
+
+/// 投稿を取得（保存済みのものを優先し、なければリレーから）
+async fn find_event(ctx: &ToolContext<'_>, event_id: EventId) -> Result<Event, super::LlmError> {
+    if event_id == ctx.event.id {
+        return Ok(ctx.event.clone());
+    }
+    let stored = {
+        let conn = db::connect()?;
+        db::get_event_by_event_id(&conn, &event_id.to_hex())?
+    };
+    if let Some(record) = stored.and_then(|record| Event::from_json(&record.event_json).ok()) {
+        return Ok(record);
+    }
+    crate::relay_pool::shared()
+        .fetch_latest(Filter::new().id(event_id), &ctx.config.relay_servers.read, Duration::from_secs(RELAY_TIMEOUT_SECS))
+        .await?
+        .ok_or_else(|| format!("投稿が見つかりませんでした: {}", event_id).into())
+}
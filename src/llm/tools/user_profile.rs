@@ -0,0 +1,69 @@
+use super::{target_pubkey, parse_arguments, truncate_chars, Tool, ToolContext, ToolDefinition, ToolFuture};
+use crate::database as db;
+use crate::util;
+use nostr_sdk::prelude::*;
+use serde::Deserialize;
+
+/// 自己紹介の最大文字数
+const MAX_ABOUT_CHARS: usize = 300;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct UserProfileArgs {
+    pubkey: Option<String>,
+}
+
+/// ユーザーのプロフィール（kind 0）を返すツール
+pub struct UserProfileTool;
+
+impl Tool for UserProfileTool {
+    fn name(&self) -> &'static str {
+        "get_user_profile"
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: self.name().to_string(),
+            description: "Nostrユーザーのプロフィール（名前・自己紹介など）を取得します".to_string(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "pubkey": {
+                        "type": "string",
+                        "description": "ユーザー（npub1...またはhex）。省略時は話しかけてきたユーザー"
+                    }
+                }
+            }),
+        }
+    }
+
+    fn call<'a>(&'a self, ctx: &'a ToolContext<'a>, arguments: &'a str) -> ToolFuture<'a> {
+        Box::pin(async move {
+            let args: UserProfileArgs = parse_arguments(arguments)?;
+            let pubkey = target_pubkey(ctx, args.pubkey.as_deref())?;
+            let hex = pubkey.to_hex();
+
+            // 保存済みのkind 0を優先し、なければリレーから取得する
+            let cached = {
+                let conn = db::connect()?;
+                util::get_kind0_metadata(&conn, &hex)
+            };
+            let content = match cached {
+                Some(content) => content,
+                None => util::get_kind0(&hex, "").await.map_err(|e| e.to_string())?.content,
+            };
+            let metadata: serde_json::Value = serde_json::from_str(&content)?;
+            let field = |key: &str| metadata[key].as_str().unwrap_or_default().to_string();
+
+            Ok(serde_json::json!({
+                "npub": pubkey.to_bech32()?,
+                "name": field("name"),
+                "display_name": field("display_name"),
+                "about": truncate_chars(&field("about"), MAX_ABOUT_CHARS),
+                "nip05": field("nip05"),
+                "lud16": field("lud16"),
+                "website": field("website"),
+            }))
+        })
+    }
+}
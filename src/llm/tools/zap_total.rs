@@ -0,0 +1,61 @@
+use super::{target_pubkey, parse_arguments, Tool, ToolContext, ToolDefinition, ToolFuture};
+use crate::util;
+use nostr_sdk::prelude::*;
+use serde::Deserialize;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ZapTotalArgs {
+    pubkey: Option<String>,
+}
+
+/// ユーザーが受け取ったZapの合計を返すツール
+pub struct ZapTotalTool;
+
+impl Tool for ZapTotalTool {
+    fn name(&self) -> &'static str {
+        "get_zap_total"
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: self.name().to_string(),
+            description: "Nostrユーザーが過去1年間に受け取ったZapの回数と合計金額（sats）を取得します".to_string(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "pubkey": {
+                        "type": "string",
+                        "description": "ユーザー（npub1...またはhex）。省略時は話しかけてきたユーザー"
+                    }
+                }
+            }),
+        }
+    }
+
+    fn call<'a>(&'a self, ctx: &'a ToolContext<'a>, arguments: &'a str) -> ToolFuture<'a> {
+        Box::pin(async move {
+            let args: ZapTotalArgs = parse_arguments(arguments)?;
+            let pubkey = target_pubkey(ctx, args.pubkey.as_deref())?;
+
+            let receipts = util::get_zap_received(&pubkey.to_hex()).await.map_err(|e| e.to_string())?;
+            let amounts: Vec<u64> = receipts
+                .iter()
+                .filter_map(|receipt| {
+                    receipt.tags.iter().find_map(|tag| match tag.as_standardized() {
+                        Some(TagStandard::Bolt11(invoice)) => util::decode_bolt11_invoice(invoice).ok(),
+                        _ => None,
+                    })
+                })
+                .filter_map(|invoice| invoice.amount_milli_satoshis())
+                .collect();
+
+            Ok(serde_json::json!({
+                "npub": pubkey.to_bech32()?,
+                "period": "過去1年間",
+                "zap_count": amounts.len(),
+                "total_sats": amounts.iter().sum::<u64>() / 1000,
+            }))
+        })
+    }
+}
@@ -0,0 +1,197 @@
+// ログの初期化とerror_logテーブルへの記録
+// tracingのspanに付けたevent_id・bot・userを、WARN/ERRORのログと一緒にDBへ残す
+
+use crate::config::{LogFormat, LoggingConfig};
+use crate::database as db;
+use chrono::Utc;
+use std::cell::Cell;
+use std::fmt::{self, Write as _};
+use std::str::FromStr;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Record};
+use tracing::{Event, Id, Level, Subscriber};
+use tracing_subscriber::filter::{filter_fn, EnvFilter};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt as subscriber_fmt, Layer};
+
+/// ログを初期化（RUST_LOGがあればconfigのlevelより優先）
+pub fn init(config: &LoggingConfig) {
+    let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(&config.level));
+    let output = match config.format {
+        LogFormat::Text => subscriber_fmt::layer().with_filter(env_filter).boxed(),
+        LogFormat::Json => subscriber_fmt::layer()
+            .json()
+            .with_current_span(true)
+            .with_span_list(false)
+            .with_filter(env_filter)
+            .boxed(),
+    };
+
+    let error_log = ErrorLogLayer::from_config(config);
+    let persist_level = error_log.persist_level;
+    // spanは文脈を拾うためにINFOまで、イベントは記録するレベルだけ受け取る
+    let error_log = error_log.with_filter(filter_fn(move |metadata| {
+        if metadata.is_span() {
+            *metadata.level() <= Level::INFO
+        } else {
+            *metadata.level() <= persist_level
+        }
+    }));
+
+    if let Err(e) = tracing_subscriber::registry().with(output).with(error_log).try_init() {
+        eprintln!("[Logging] 初期化エラー: {}", e);
+    }
+}
+
+/// spanから拾う処理中のイベントの情報
+#[derive(Debug, Default, Clone)]
+struct EventContext {
+    event_id: Option<String>,
+    bot: Option<String>,
+    user: Option<String>,
+}
+
+impl EventContext {
+    /// 足りない項目を外側のspanの値で埋める
+    fn fill_from(&mut self, outer: &EventContext) {
+        if self.event_id.is_none() {
+            self.event_id = outer.event_id.clone();
+        }
+        if self.bot.is_none() {
+            self.bot = outer.bot.clone();
+        }
+        if self.user.is_none() {
+            self.user = outer.user.clone();
+        }
+    }
+}
+
+impl Visit for EventContext {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        let slot = match field.name() {
+            "event_id" => &mut self.event_id,
+            "bot" => &mut self.bot,
+            "user" => &mut self.user,
+            _ => return,
+        };
+        *slot = Some(value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.record_str(field, &format!("{:?}", value));
+    }
+}
+
+/// ログのメッセージとその他のフィールド
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.fields, " {}={}", field.name(), value);
+        }
+    }
+}
+
+thread_local! {
+    /// 記録中に出たログで再び記録しないように
+    static RECORDING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// WARN/ERRORのログをerror_logテーブルに記録するレイヤー
+pub struct ErrorLogLayer {
+    persist_level: Level,
+    max_persisted: usize,
+}
+
+impl ErrorLogLayer {
+    pub fn new(persist_level: Level, max_persisted: usize) -> Self {
+        Self {
+            persist_level,
+            max_persisted,
+        }
+    }
+
+    pub fn from_config(config: &LoggingConfig) -> Self {
+        let persist_level = Level::from_str(&config.persist_level).unwrap_or_else(|_| {
+            eprintln!("[Logging] persist_levelが不正です（warnを使います）: {}", config.persist_level);
+            Level::WARN
+        });
+        Self::new(persist_level, config.max_persisted)
+    }
+
+    fn persist(&self, entry: &db::ErrorLogEntry) {
+        let result = db::connect().and_then(|conn| db::insert_error_log(&conn, entry, self.max_persisted));
+        if let Err(e) = result {
+            eprintln!("[Logging] error_logへの記録に失敗: {}", e);
+        }
+    }
+}
+
+impl<S> Layer<S> for ErrorLogLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let mut context = EventContext::default();
+        attrs.record(&mut context);
+        span.extensions_mut().insert(context);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let mut extensions = span.extensions_mut();
+        if let Some(context) = extensions.get_mut::<EventContext>() {
+            values.record(context);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        if *metadata.level() > self.persist_level || RECORDING.with(Cell::get) {
+            return;
+        }
+        RECORDING.with(|recording| recording.set(true));
+
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let mut context = EventContext::default();
+        event.record(&mut context);
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope {
+                if let Some(outer) = span.extensions().get::<EventContext>() {
+                    context.fill_from(outer);
+                }
+            }
+        }
+
+        self.persist(&db::ErrorLogEntry {
+            id: 0,
+            timestamp: Utc::now().timestamp(),
+            level: metadata.level().to_string(),
+            target: metadata.target().to_string(),
+            message: format!("{}{}", visitor.message, visitor.fields),
+            event_id: context.event_id,
+            bot_pubkey: context.bot,
+            user_pubkey: context.user,
+        });
+        RECORDING.with(|recording| recording.set(false));
+    }
+}
@@ -1,12 +1,24 @@
 mod config;
 mod database;
 mod gpt;
+mod llm;
+mod embedding;
+mod search;
+mod web_search;
+mod scheduler;
 mod commands;
 mod util;
 mod conversation;
 mod dashboard;
 mod init;
 mod event_processor;
+mod relay_pool;
+mod direct_message;
+mod engagement;
+mod reaction;
+mod live;
+mod metrics;
+mod logging;
 use database as db;
 use chrono::Utc;
 use dotenv::dotenv;
@@ -15,6 +27,10 @@ use std::{fs::File, str::FromStr};
 use std::env;
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tracing::{error, info};
+
+/// Botの追加・削除をDM・リアクションの購読に反映する間隔（秒）
+const BOT_SUBSCRIPTION_REFRESH_SECS: u64 = 30;
 
 // タイムライン投稿の構造体
 #[derive(Clone, Debug)]
@@ -30,9 +46,10 @@ pub struct TimelinePost {
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     dotenv().ok();
-    println!("start");
-    let file = File::open("../config.yml")?;
+    let file = File::open(config::config_path())?;
     let config: config::AppConfig = serde_yaml::from_reader(file)?;
+    logging::init(&config.logging);
+    info!("start");
     let conn = db::connect()?;
     
     // データベース初期化（テーブル作成とマイグレーション）
@@ -50,12 +67,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }));
     
     // ダッシュボードサーバーを起動（バックグラウンド）
+    let dashboard_bind = config.dashboard.bind.clone();
     let dashboard_port = config.dashboard.port;
-    let dashboard_db_path = "../nostrchan.db".to_string();
+    let dashboard_db_path = db::db_path();
     let bot_info_clone = Arc::clone(&bot_info);
+    let dashboard_auth = Arc::new(dashboard::AuthState::new(&config));
     tokio::spawn(async move {
-        if let Err(e) = dashboard::start_dashboard(dashboard_port, dashboard_db_path, bot_info_clone).await {
-            eprintln!("ダッシュボードエラー: {}", e);
+        if let Err(e) = dashboard::start_dashboard(dashboard_bind, dashboard_port, dashboard_db_path, bot_info_clone, dashboard_auth).await {
+            error!("ダッシュボードエラー: {}", e);
         }
     });
     
@@ -68,12 +87,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     for item in config.relay_servers.read.iter() {
         client.add_relay(item.clone()).await?;
     }
-    println!("add_relay");
+    info!("add_relay");
 
     // Connect to relays
     client.connect().await;
-    println!("client.connect");
-    
+    info!("client.connect");
+
+    // 送信用の共有リレープールを先に接続しておく（最初の返信で接続待ちしないように）
+    let write_relays = relay_pool::shared().ensure_relays(&config.relay_servers.write).await;
+    info!("relay pool ready ({} write relays)", write_relays.len());
+
     // ダッシュボードに接続リレー情報を更新
     {
         let mut info = bot_info.write().await;
@@ -85,157 +108,94 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .kinds([nostr_sdk::Kind::TextNote, nostr_sdk::Kind::ChannelMessage, nostr_sdk::Kind::Metadata].to_vec())
         .since(Timestamp::now());
 
-    let _ = client.subscribe(subscription, None).await;
-    println!("subscribe (TextNote, ChannelMessage, Metadata)");
+    // チェックポイントはこの購読で受信した投稿でのみ進める
+    let _ = client
+        .subscribe_with_id(SubscriptionId::new(event_processor::TIMELINE_SUBSCRIPTION), subscription, None)
+        .await;
+    info!("subscribe (TextNote, ChannelMessage, Metadata)");
+
+    // Bot宛てのDM・Botの投稿へのリアクション・Zapをsubscribe（Botの追加に合わせて張り直す）
+    let mut bot_subscriptions = event_processor::BotSubscriptions::default();
+    bot_subscriptions.refresh(&client, &conn).await?;
     
     // DBから既存のタイムラインを読み込み（起動時のみ）
-    println!("Loading timeline from DB...");
+    info!("Loading timeline from DB...");
     let timeline_size = config.get_usize_setting("timeline_size");
     let timeline_posts = db::get_latest_timeline_posts(&conn, timeline_size).unwrap_or_else(|e| {
-        eprintln!("Failed to load timeline: {}", e);
+        error!("Failed to load timeline: {}", e);
         Vec::new()
     });
-    println!("Loaded {} timeline posts", timeline_posts.len());
+    info!("Loaded {} timeline posts", timeline_posts.len());
     
     // 起動時に処理中だったイベントをpendingに戻す
     match db::reset_processing_events(&conn) {
         Ok(count) => {
             if count > 0 {
-                println!("Reset {} processing events to pending", count);
+                info!("Reset {} processing events to pending", count);
             }
         }
-        Err(e) => eprintln!("Failed to reset processing events: {}", e),
+        Err(e) => error!("Failed to reset processing events: {}", e),
+    }
+    
+    // イベント処理ワーカーを起動（ワーカー毎に別スレッドで実行し、並行して処理する）
+    let worker_count = config.get_usize_setting("event_workers").max(1);
+    info!("Starting {} event queue workers...", worker_count);
+    for _ in 0..worker_count {
+        let config_for_worker = config.clone();
+        let bot_info_for_worker = Arc::clone(&bot_info);
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+            rt.block_on(event_processor::run_queue_worker(config_for_worker, bot_info_for_worker));
+        });
     }
     
-    // イベント処理ワーカーを起動（別スレッドで実行）
-    let config_for_worker = config.clone();
-    let bot_info_for_worker = Arc::clone(&bot_info);
+    // 埋め込みが有効なら投稿・要約のベクトル化をバックグラウンドで行う
+    if config.embedding.provider != config::EmbeddingProviderKind::Disabled {
+        info!("Starting embedding vectorizer ({:?})...", config.embedding.provider);
+        let config_for_vectorizer = config.clone();
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+            rt.block_on(embedding::run_vectorizer(config_for_vectorizer));
+        });
+    }
+    
+    // スケジュールに従った定期投稿をバックグラウンドで行う
+    info!("Starting scheduler...");
+    let config_for_scheduler = config.clone();
     std::thread::spawn(move || {
-        let rt = tokio::runtime::Runtime::new().unwrap();
-        rt.block_on(async move {
-            println!("Starting event queue worker...");
-            loop {
-                // 0.5秒ごとにキューをチェック
-                tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-                
-                // キューから次のイベントを取得
-                let conn_worker = match db::connect() {
-                    Ok(c) => c,
-                    Err(e) => {
-                        eprintln!("[Worker] DB接続エラー: {}", e);
-                        continue;
-                    }
-                };
-                
-                let queue_item = match db::dequeue_event(&conn_worker) {
-                    Ok(Some(item)) => item,
-                    Ok(None) => continue, // キューが空
-                    Err(e) => {
-                        eprintln!("[Worker] キュー取得エラー: {}", e);
-                        continue;
-                    }
-                };
-                
-                let (queue_id, event_json) = queue_item;
-                
-                // JSONからEventを復元
-                let event: nostr_sdk::Event = match serde_json::from_str(&event_json) {
-                    Ok(e) => e,
-                    Err(e) => {
-                        eprintln!("[Worker] イベント復元エラー: {}", e);
-                        let _ = db::complete_queue_event(&conn_worker, queue_id);
-                        continue;
-                    }
-                };
-                
-                // イベント処理を実行
-                match event_processor::process_event(config_for_worker.clone(), Arc::clone(&bot_info_for_worker), event).await {
-                    Ok(_) => {
-                        // 処理成功: キューから削除
-                        if let Err(e) = db::complete_queue_event(&conn_worker, queue_id) {
-                            eprintln!("[Worker] キュー削除エラー: {}", e);
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("[Worker] イベント処理エラー: {} - キューから削除", e);
-                        // エラーでも削除（無限ループ防止）
-                        let _ = db::complete_queue_event(&conn_worker, queue_id);
-                    }
-                }
-            }
-        })
+        let rt = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        rt.block_on(scheduler::run_scheduler(config_for_scheduler));
     });
     
     let mut notifications = client.notifications();
-    println!("Listening for events...");
     
-    while let Ok(notification) = notifications.recv().await {
-        if let RelayPoolNotification::Event{relay_url: _, subscription_id: _, event} = notification {
-            // ブラックリストチェック（DB優先）
-            let blacklist = config.get_blacklist();
-            if blacklist.iter().any(|s| s == &event.pubkey.to_string()) {
-                continue;
-            }
-            
-            let kind = event.kind;
-            
-            // kind 0 (Metadata) の処理
-            if kind == Kind::Metadata {
-                // eventsテーブルに保存（upsert処理で最新のみ保持）
-                if let Err(e) = db::insert_event(&conn, &event, None) {
-                    eprintln!("[Kind0] DB保存エラー: {}", e);
-                }
-                continue; // kind 0はキューに入れない
-            }
-            
-            // NIP-36(コンテンツ警告)をスキップ
-            if kind == Kind::TextNote || kind == Kind::ChannelMessage {
-                let mut detect_nip36 = false;
-                for tag in event.tags.clone().into_iter() {
-                    if let Some(TagStandard::ContentWarning { reason: _ }) = tag.as_standardized() {
-                        detect_nip36 = true;
-                        break;
-                    }
-                }
-                if detect_nip36 {
-                    continue;
-                }
-                
-                // コマンド処理（即座に実行）
-                let persons = match db::get_all_persons(&conn) {
-                    Ok(p) => p,
-                    Err(e) => {
-                        eprintln!("Failed to get persons: {}", e);
-                        continue;
+    // 停止中に取りこぼしたメンションを処理（購読開始後に行い、隙間をなくす）
+    match event_processor::catch_up_missed_mentions(&config, &conn).await {
+        Ok(count) => info!("Catch-up finished ({} events)", count),
+        Err(e) => error!("Catch-up failed: {}", e),
+    }
+    
+    info!("Listening for events...");
+    
+    let mut subscription_refresh = tokio::time::interval(std::time::Duration::from_secs(BOT_SUBSCRIPTION_REFRESH_SECS));
+    loop {
+        tokio::select! {
+            notification = notifications.recv() => {
+                let Ok(notification) = notification else { break };
+                if let RelayPoolNotification::Event{relay_url, subscription_id, event} = notification {
+                    metrics::event_received(&relay_url, &event);
+                    if event.kind != Kind::Metadata {
+                        live::incoming(&relay_url, &event);
                     }
-                };
-                let handled = commands::command_handler(&config, &conn, &persons, &event).await?;
-                
-                if handled {
-                    // コマンドとして処理済み: キューに入れない
-                    continue;
+                    event_processor::handle_incoming_event(&config, &conn, &event).await?;
+                    event_processor::record_checkpoint(&conn, &relay_url, &subscription_id, &event);
                 }
-                
-                // イベントをキューに追加（永続化）
-                let event_json = match serde_json::to_string(&*event) {
-                    Ok(json) => json,
-                    Err(e) => {
-                        eprintln!("Failed to serialize event: {}", e);
-                        continue;
-                    }
-                };
-                
-                match db::enqueue_event(&conn, &event_json) {
-                    Ok(queue_id) => {
-                        println!("Enqueued event {} (queue_id: {})", event.id, queue_id);
-                    }
-                    Err(e) => {
-                        eprintln!("Failed to enqueue event: {}", e);
-                    }
+            }
+            _ = subscription_refresh.tick() => {
+                // ダッシュボードで追加されたBotのDM・リアクションも受け取れるようにする
+                if let Err(e) = bot_subscriptions.refresh(&client, &conn).await {
+                    error!("Failed to refresh bot subscriptions: {}", e);
                 }
-            } else {
-                // Kind::TextNoteでもKind::ChannelMessageでもない
-                println!("Skipping event kind: {:?}", event.kind);
             }
         }
     }
@@ -0,0 +1,189 @@
+// Prometheusのメトリクス
+// 各処理からカウンター・ヒストグラムを更新し、ダッシュボードの /metrics でテキスト形式で公開する
+
+use crate::relay_pool::PublishResult;
+use chrono::Utc;
+use nostr_sdk::prelude::*;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry,
+    TextEncoder,
+};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::error;
+
+/// イベント処理の所要時間のバケット（秒）
+const PROCESSING_BUCKETS: &[f64] = &[0.1, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0];
+
+/// LLM呼び出しの所要時間のバケット（秒）
+const LLM_BUCKETS: &[f64] = &[0.25, 0.5, 1.0, 2.0, 5.0, 10.0, 20.0, 30.0, 60.0, 120.0];
+
+struct Metrics {
+    registry: Registry,
+    events_received: IntCounterVec,
+    queue_depth: IntGauge,
+    dead_letters: IntGauge,
+    event_processing: HistogramVec,
+    llm_duration: HistogramVec,
+    llm_errors: IntCounterVec,
+    llm_retries: IntCounterVec,
+    tokens: IntCounterVec,
+    publish: IntCounterVec,
+    reply_errors: IntCounter,
+    last_reply: IntGaugeVec,
+    follower_cache: IntCounterVec,
+    commands: IntCounterVec,
+    start_time: IntGauge,
+}
+
+static METRICS: OnceLock<Metrics> = OnceLock::new();
+
+fn metrics() -> &'static Metrics {
+    METRICS.get_or_init(|| Metrics::new().expect("メトリクスの登録に失敗しました"))
+}
+
+impl Metrics {
+    fn new() -> prometheus::Result<Self> {
+        let registry = Registry::new_custom(Some("nostrchan".to_string()), None)?;
+        let counter = |name: &str, help: &str, labels: &[&str]| -> prometheus::Result<IntCounterVec> {
+            let counter = IntCounterVec::new(Opts::new(name, help), labels)?;
+            registry.register(Box::new(counter.clone()))?;
+            Ok(counter)
+        };
+        let histogram = |name: &str, help: &str, labels: &[&str], buckets: &[f64]| -> prometheus::Result<HistogramVec> {
+            let histogram = HistogramVec::new(HistogramOpts::new(name, help).buckets(buckets.to_vec()), labels)?;
+            registry.register(Box::new(histogram.clone()))?;
+            Ok(histogram)
+        };
+        let gauge = |name: &str, help: &str| -> prometheus::Result<IntGauge> {
+            let gauge = IntGauge::new(name, help)?;
+            registry.register(Box::new(gauge.clone()))?;
+            Ok(gauge)
+        };
+
+        let reply_errors = IntCounter::new("reply_errors_total", "どのリレーにも送信できなかった返信の数")?;
+        registry.register(Box::new(reply_errors.clone()))?;
+        let last_reply = IntGaugeVec::new(
+            Opts::new("bot_last_reply_timestamp_seconds", "Bot毎の最後に返信した時刻（UNIX秒）"),
+            &["bot"],
+        )?;
+        registry.register(Box::new(last_reply.clone()))?;
+
+        let metrics = Self {
+            events_received: counter("events_received_total", "リレーから受信したイベントの数", &["kind", "relay"])?,
+            queue_depth: gauge("queue_depth", "キューで待っているイベントの数")?,
+            dead_letters: gauge("dead_letters", "dead_letterに移動したイベントの数")?,
+            event_processing: histogram(
+                "event_processing_seconds",
+                "キューのイベント1件の処理時間",
+                &["result"],
+                PROCESSING_BUCKETS,
+            )?,
+            llm_duration: histogram(
+                "llm_request_seconds",
+                "LLM呼び出しの所要時間（リトライを含む）",
+                &["provider", "category"],
+                LLM_BUCKETS,
+            )?,
+            llm_errors: counter("llm_errors_total", "リトライしても失敗したLLM呼び出しの数", &["provider", "category"])?,
+            llm_retries: counter("llm_retries_total", "LLM呼び出しのリトライ回数", &["provider", "category"])?,
+            tokens: counter("llm_tokens_total", "Bot毎のLLMトークン数", &["bot", "type"])?,
+            publish: counter("replies_published_total", "返信の送信結果（リレー毎）", &["relay", "result"])?,
+            reply_errors,
+            last_reply,
+            follower_cache: counter("follower_cache_requests_total", "フォロワーキャッシュの参照結果", &["result"])?,
+            commands: counter("commands_total", "コマンドの実行回数", &["command", "result"])?,
+            start_time: gauge("start_time_seconds", "起動した時刻（UNIX秒）")?,
+            registry,
+        };
+        metrics.start_time.set(Utc::now().timestamp());
+        Ok(metrics)
+    }
+}
+
+/// リレーからイベントを受信した
+pub fn event_received(relay_url: &RelayUrl, event: &Event) {
+    metrics()
+        .events_received
+        .with_label_values(&[&event.kind.as_u16().to_string(), relay_url.as_str()])
+        .inc();
+}
+
+/// キューのイベントを1件処理した（result: ok / retrying / dead_lettered）
+pub fn event_processed(elapsed: Duration, result: &str) {
+    metrics()
+        .event_processing
+        .with_label_values(&[result])
+        .observe(elapsed.as_secs_f64());
+}
+
+/// LLM呼び出しが終わった
+pub fn llm_request(provider: &str, category: &str, elapsed: Duration, success: bool) {
+    let metrics = metrics();
+    metrics
+        .llm_duration
+        .with_label_values(&[provider, category])
+        .observe(elapsed.as_secs_f64());
+    if !success {
+        metrics.llm_errors.with_label_values(&[provider, category]).inc();
+    }
+}
+
+/// LLM呼び出しをリトライする
+pub fn llm_retry(provider: &str, category: &str) {
+    metrics().llm_retries.with_label_values(&[provider, category]).inc();
+}
+
+/// Botが使ったトークン数
+pub fn tokens_used(bot_pubkey: &str, prompt_tokens: usize, completion_tokens: usize) {
+    let tokens = &metrics().tokens;
+    tokens.with_label_values(&[bot_pubkey, "prompt"]).inc_by(prompt_tokens as u64);
+    tokens.with_label_values(&[bot_pubkey, "completion"]).inc_by(completion_tokens as u64);
+}
+
+/// 返信を送信した（リレー毎の受理・拒否と、Bot毎の最終返信時刻）
+pub fn reply_published(bot_pubkey: &str, result: &PublishResult) {
+    let metrics = metrics();
+    for relay in &result.accepted {
+        metrics.publish.with_label_values(&[relay, "accepted"]).inc();
+    }
+    for rejection in &result.rejected {
+        metrics.publish.with_label_values(&[&rejection.relay, "rejected"]).inc();
+    }
+    if result.is_accepted() {
+        metrics.last_reply.with_label_values(&[bot_pubkey]).set(Utc::now().timestamp());
+    } else {
+        metrics.reply_errors.inc();
+    }
+}
+
+/// 返信を送信できなかった（署名・接続のエラー）
+pub fn reply_failed() {
+    metrics().reply_errors.inc();
+}
+
+/// フォロワーキャッシュを参照した
+pub fn follower_cache(hit: bool) {
+    metrics()
+        .follower_cache
+        .with_label_values(&[if hit { "hit" } else { "miss" }])
+        .inc();
+}
+
+/// コマンドを実行した（result: ok / usage_error / error）
+pub fn command_invoked(command: &str, result: &str) {
+    metrics().commands.with_label_values(&[command, result]).inc();
+}
+
+/// テキスト形式で出力（キューの件数は出力時に更新する）
+pub fn render(queue_depth: i64, dead_letters: i64) -> String {
+    let metrics = metrics();
+    metrics.queue_depth.set(queue_depth);
+    metrics.dead_letters.set(dead_letters);
+
+    let mut buffer = Vec::new();
+    if let Err(e) = TextEncoder::new().encode(&metrics.registry.gather(), &mut buffer) {
+        error!("[Metrics] 出力エラー: {}", e);
+    }
+    String::from_utf8(buffer).unwrap_or_default()
+}
@@ -0,0 +1,125 @@
+// 返信の代わりの絵文字リアクション（NIP-25、カスタム絵文字はNIP-30）
+// Bot毎の確率で「返信」「リアクションも選べる」「無視」を振り分ける
+
+use crate::config::AppConfig;
+use crate::database as db;
+use crate::live;
+use crate::relay_pool;
+use nostr_sdk::prelude::*;
+use rand::Rng;
+use std::error::Error;
+use tracing::error;
+
+/// 投稿への応じ方
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseMode {
+    /// 文章で返信する
+    Reply,
+    /// LLMが返信かリアクションかを選ぶ
+    Reaction,
+    /// 反応しない
+    Ignore,
+}
+
+/// Bot個別設定の確率で応じ方を決める（未設定なら常に返信）
+pub fn choose_response_mode(overrides: &db::PersonOverrides) -> ResponseMode {
+    let roll = rand::thread_rng().gen_range(0..100);
+    response_mode_for_roll(overrides, roll)
+}
+
+/// 0〜99の乱数値から応じ方を決める（無視 → リアクション → 返信の順に割り当て）
+pub fn response_mode_for_roll(overrides: &db::PersonOverrides, roll: i64) -> ResponseMode {
+    let ignore = overrides.ignore_percent.unwrap_or(0).clamp(0, 100);
+    let reaction = overrides.emoji_reaction_percent.unwrap_or(0).clamp(0, 100);
+    if roll < ignore {
+        ResponseMode::Ignore
+    } else if roll < ignore + reaction {
+        ResponseMode::Reaction
+    } else {
+        ResponseMode::Reply
+    }
+}
+
+/// リアクションに使えるカスタム絵文字（Botの設定＋相手の投稿に含まれるもの）
+pub fn available_emojis(person: &db::Person, event: &Event) -> Vec<db::CustomEmoji> {
+    let mut emojis: Vec<db::CustomEmoji> = person.overrides.custom_emojis.clone().unwrap_or_default();
+    for tag in event.tags.iter() {
+        if let Some(TagStandard::Emoji { shortcode, url }) = tag.as_standardized() {
+            if !emojis.iter().any(|e| &e.shortcode == shortcode) {
+                emojis.push(db::CustomEmoji {
+                    shortcode: shortcode.clone(),
+                    url: url.to_string(),
+                });
+            }
+        }
+    }
+    emojis
+}
+
+/// リアクションを選べるときにプロンプトへ加える指示
+pub fn reaction_instruction(emojis: &[db::CustomEmoji]) -> String {
+    let custom = if emojis.is_empty() {
+        String::new()
+    } else {
+        let shortcodes: Vec<String> = emojis.iter().map(|e| format!(":{}:", e.shortcode)).collect();
+        format!("\nカスタム絵文字を使う場合は次の中から`:shortcode:`の形式で1つ選んでください: {}", shortcodes.join(" "))
+    };
+    format!(
+        "\n\n文章で返信するほどでもない投稿には、返信の代わりに絵文字1つのリアクションで応えても構いません。\
+         その場合は\"reply\"を空文字にして、\"reaction\"に絵文字を1つだけ入れてください（例: \"❤️\"、\"😂\"、\"🙏\"）。\
+         文章で返信する場合は\"reaction\"を含めないでください。{custom}"
+    )
+}
+
+/// 送信するリアクション
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChosenReaction {
+    /// kind 7のcontent（絵文字、または:shortcode:）
+    pub content: String,
+    /// カスタム絵文字の場合のemojiタグ用
+    pub emoji: Option<db::CustomEmoji>,
+}
+
+/// LLMが選んだリアクションを送信できる形に整える（空ならNone、知らないカスタム絵文字は"+"）
+pub fn resolve_reaction(reaction: &str, emojis: &[db::CustomEmoji]) -> Option<ChosenReaction> {
+    let reaction = reaction.trim();
+    if reaction.is_empty() {
+        return None;
+    }
+    if let Some(shortcode) = reaction.strip_prefix(':').and_then(|s| s.strip_suffix(':')) {
+        return Some(match emojis.iter().find(|e| e.shortcode == shortcode) {
+            Some(emoji) => ChosenReaction {
+                content: format!(":{}:", emoji.shortcode),
+                emoji: Some(emoji.clone()),
+            },
+            None => ChosenReaction {
+                content: "+".to_string(),
+                emoji: None,
+            },
+        });
+    }
+    Some(ChosenReaction {
+        content: reaction.to_string(),
+        emoji: None,
+    })
+}
+
+/// 投稿にリアクション（kind 7）を送信
+pub async fn send_reaction(config: &AppConfig, person: &db::Person, event: &Event, reaction: &ChosenReaction) -> Result<Event, Box<dyn Error>> {
+    let keys = Keys::parse(&person.secretkey)?;
+    let mut builder = EventBuilder::reaction(event, &reaction.content);
+    if let Some(emoji) = &reaction.emoji {
+        builder = builder.tag(Tag::from_standardized(TagStandard::Emoji {
+            shortcode: emoji.shortcode.clone(),
+            url: Url::parse(&emoji.url)?,
+        }));
+    }
+    let (sent, result) = relay_pool::shared()
+        .publish_as(&keys, builder, &config.relay_servers.write)
+        .await?;
+    if !result.is_accepted() {
+        error!("[Reaction] リアクションを受理したリレーがありません: {:?}", result.rejected);
+    }
+    live::outgoing(&person.pubkey, sent.id, sent.kind, Some(event.id), Some(&sent.content));
+    Ok(sent)
+}
@@ -0,0 +1,228 @@
+// 共有リレープール
+// プロセス全体で1つのClientを持ち、リレーとの接続を維持したまま
+// Bot毎の鍵で署名したイベントの公開・取得を行う
+
+use nostr_sdk::prelude::*;
+use serde::Serialize;
+use std::future::Future;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::runtime::Handle;
+use tokio::sync::Mutex;
+use tracing::{error, info};
+
+/// リレープールのエラー（呼び出し元のResult型へそのまま`?`で変換できるように具体型にする）
+#[derive(Debug)]
+pub struct PoolError(String);
+
+impl std::fmt::Display for PoolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for PoolError {}
+
+impl PoolError {
+    fn new(message: impl std::fmt::Display) -> Self {
+        Self(message.to_string())
+    }
+}
+
+/// 新規リレーへの接続待ちの上限
+const CONNECT_TIMEOUT_SECS: u64 = 5;
+
+static POOL: OnceLock<RelayPool> = OnceLock::new();
+
+pub struct RelayPool {
+    client: Client,
+    // 接続を維持する専用ランタイム（呼び出し元のランタイムが終了しても接続が切れないように）
+    handle: Handle,
+    // 同じリレーの追加・接続が並行して走らないようにする
+    connect_lock: Mutex<()>,
+}
+
+/// 公開結果（リレー毎の受理/拒否）
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PublishResult {
+    pub event_id: String,
+    pub accepted: Vec<String>,
+    pub rejected: Vec<RelayRejection>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RelayRejection {
+    pub relay: String,
+    pub reason: String,
+}
+
+impl PublishResult {
+    fn from_output(output: Output<EventId>) -> Self {
+        let mut accepted: Vec<String> = output.success.iter().map(|url| url.to_string()).collect();
+        accepted.sort();
+        let mut rejected: Vec<RelayRejection> = output
+            .failed
+            .iter()
+            .map(|(url, reason)| RelayRejection {
+                relay: url.to_string(),
+                reason: reason.clone(),
+            })
+            .collect();
+        rejected.sort_by(|a, b| a.relay.cmp(&b.relay));
+        Self {
+            event_id: output.val.to_hex(),
+            accepted,
+            rejected,
+        }
+    }
+
+    /// 1つ以上のリレーが受理したか
+    pub fn is_accepted(&self) -> bool {
+        !self.accepted.is_empty()
+    }
+}
+
+/// プロセス共有のリレープールを取得（初回呼び出しで起動）
+pub fn shared() -> &'static RelayPool {
+    POOL.get_or_init(RelayPool::start)
+}
+
+impl RelayPool {
+    fn start() -> Self {
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::Builder::new()
+            .name("relay-pool".to_string())
+            .spawn(move || {
+                let runtime = tokio::runtime::Builder::new_multi_thread()
+                    .worker_threads(2)
+                    .enable_all()
+                    .build()
+                    .expect("[RelayPool] ランタイムの起動に失敗しました");
+                let client = {
+                    let _guard = runtime.enter();
+                    // 署名はBot毎の鍵で行うため、Client自体はSignerを持たない
+                    Client::default()
+                };
+                tx.send((client, runtime.handle().clone()))
+                    .expect("[RelayPool] 初期化結果の送信に失敗しました");
+                runtime.block_on(std::future::pending::<()>());
+            })
+            .expect("[RelayPool] スレッドの起動に失敗しました");
+        let (client, handle) = rx.recv().expect("[RelayPool] 初期化に失敗しました");
+        info!("[RelayPool] 共有リレープールを起動しました");
+
+        Self {
+            client,
+            handle,
+            connect_lock: Mutex::new(()),
+        }
+    }
+
+    /// プールのランタイム上で実行する
+    async fn run<F, T>(&self, future: F) -> Result<T, PoolError>
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        self.handle.spawn(future).await.map_err(PoolError::new)
+    }
+
+    /// 未接続のリレーを追加・接続し、利用できるURLを返す
+    pub async fn ensure_relays(&self, relays: &[String]) -> Vec<RelayUrl> {
+        let _lock = self.connect_lock.lock().await;
+        let client = self.client.clone();
+        let relays = relays.to_vec();
+        let result = self
+            .run(async move {
+                let mut urls = Vec::new();
+                for relay in relays {
+                    let url = match RelayUrl::parse(&relay) {
+                        Ok(url) => url,
+                        Err(e) => {
+                            error!("[RelayPool] リレーURLが不正です ({}): {}", relay, e);
+                            continue;
+                        }
+                    };
+                    match client.add_relay(url.clone()).await {
+                        Ok(true) => {
+                            if let Err(e) = client
+                                .try_connect_relay(url.clone(), Duration::from_secs(CONNECT_TIMEOUT_SECS))
+                                .await
+                            {
+                                error!("[RelayPool] 接続エラー ({}): {}", url, e);
+                            }
+                        }
+                        Ok(false) => {}
+                        Err(e) => {
+                            error!("[RelayPool] リレー追加エラー ({}): {}", url, e);
+                            continue;
+                        }
+                    }
+                    urls.push(url);
+                }
+                urls
+            })
+            .await;
+
+        match result {
+            Ok(urls) => urls,
+            Err(e) => {
+                error!("[RelayPool] リレー接続処理エラー: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    /// 署名済みイベントを指定リレーへ公開
+    pub async fn publish(&self, event: &Event, relays: &[String]) -> Result<PublishResult, PoolError> {
+        let urls = self.ensure_relays(relays).await;
+        if urls.is_empty() {
+            return Err(PoolError::new("公開先のリレーがありません"));
+        }
+
+        let client = self.client.clone();
+        let event = event.clone();
+        let output = self
+            .run(async move { client.send_event_to(urls, &event).await })
+            .await?
+            .map_err(PoolError::new)?;
+        let result = PublishResult::from_output(output);
+        for rejection in &result.rejected {
+            error!("[RelayPool] {} が拒否: {}", rejection.relay, rejection.reason);
+        }
+        Ok(result)
+    }
+
+    /// Botの鍵で署名して公開
+    pub async fn publish_as(
+        &self,
+        keys: &Keys,
+        builder: EventBuilder,
+        relays: &[String],
+    ) -> Result<(Event, PublishResult), PoolError> {
+        let event = builder.sign_with_keys(keys).map_err(PoolError::new)?;
+        let result = self.publish(&event, relays).await?;
+        Ok((event, result))
+    }
+
+    /// 指定リレーからイベントを取得
+    pub async fn fetch(&self, filter: Filter, relays: &[String], timeout: Duration) -> Result<Vec<Event>, PoolError> {
+        let urls = self.ensure_relays(relays).await;
+        if urls.is_empty() {
+            return Err(PoolError::new("取得先のリレーがありません"));
+        }
+
+        let client = self.client.clone();
+        let events = self
+            .run(async move { client.fetch_events_from(urls, filter, timeout).await })
+            .await?
+            .map_err(PoolError::new)?;
+        Ok(events.into_iter().collect())
+    }
+
+    /// 指定リレーから最新のイベントを1件取得
+    pub async fn fetch_latest(&self, filter: Filter, relays: &[String], timeout: Duration) -> Result<Option<Event>, PoolError> {
+        let events = self.fetch(filter, relays, timeout).await?;
+        Ok(events.into_iter().max_by_key(|event| event.created_at))
+    }
+}
@@ -0,0 +1,121 @@
+use crate::util;
+use chrono::{DateTime, Datelike, NaiveDate, TimeZone, Timelike};
+
+/// 次回の実行時刻を探す最大日数（2月29日だけのスケジュールも見つかるよう5年分）
+const MAX_SEARCH_DAYS: u32 = 366 * 5;
+
+/// cron形式のスケジュール（分 時 日 月 曜日）
+///
+/// 各フィールドは `*`、数値、範囲（`1-5`）、リスト（`1,15`）、間隔（`*/15`、`9-18/3`）に対応する。
+/// 曜日は0と7が日曜日。日と曜日の両方を指定した場合はどちらかに当てはまれば実行する（一般的なcronと同じ）。
+/// 時刻はJSTで評価する。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!("cronは「分 時 日 月 曜日」の5項目で指定してください: {}", expression));
+        }
+
+        let mut weekdays = parse_field(fields[4], 0, 7, "曜日")?;
+        // 7（日曜日）は0として扱う
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays & !(1 << 7)) | 1;
+        }
+
+        Ok(Self {
+            minutes: parse_field(fields[0], 0, 59, "分")?,
+            hours: parse_field(fields[1], 0, 23, "時")?,
+            days: parse_field(fields[2], 1, 31, "日")?,
+            months: parse_field(fields[3], 1, 12, "月")?,
+            weekdays,
+            days_restricted: !fields[2].starts_with('*'),
+            weekdays_restricted: !fields[4].starts_with('*'),
+        })
+    }
+
+    /// afterより後（分単位）で最初に実行する時刻（UNIX秒）
+    pub fn next_after(&self, after: i64) -> Option<i64> {
+        let jst = util::jst();
+        let start = DateTime::from_timestamp(after - after.rem_euclid(60) + 60, 0)?.with_timezone(&jst);
+        let start_date = start.date_naive();
+
+        let mut date = start_date;
+        for _ in 0..MAX_SEARCH_DAYS {
+            if self.matches_date(date) {
+                let (first_hour, first_minute) = if date == start_date {
+                    (start.hour(), start.minute())
+                } else {
+                    (0, 0)
+                };
+                for hour in first_hour..24 {
+                    if self.hours & (1 << hour) == 0 {
+                        continue;
+                    }
+                    let from_minute = if hour == first_hour { first_minute } else { 0 };
+                    if let Some(minute) = (from_minute..60).find(|m| self.minutes & (1 << m) != 0) {
+                        let time = date.and_hms_opt(hour, minute, 0)?;
+                        return jst.from_local_datetime(&time).single().map(|t| t.timestamp());
+                    }
+                }
+            }
+            date = date.succ_opt()?;
+        }
+        None
+    }
+
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        if self.months & (1 << date.month()) == 0 {
+            return false;
+        }
+        let day = self.days & (1 << date.day()) != 0;
+        let weekday = self.weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+        match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => day || weekday,
+            (true, false) => day,
+            (false, true) => weekday,
+            (false, false) => true,
+        }
+    }
+}
+
+/// 1項目を読み取り、該当する値のビットを立てる
+fn parse_field(field: &str, min: u32, max: u32, label: &str) -> Result<u64, String> {
+    let invalid = || format!("cronの{}が不正です: {}", label, field);
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, Some(step.parse::<u32>().map_err(|_| invalid())?)),
+            None => (part, None),
+        };
+        if step == Some(0) {
+            return Err(invalid());
+        }
+        let (from, to) = if range == "*" {
+            (min, max)
+        } else if let Some((from, to)) = range.split_once('-') {
+            (from.parse().map_err(|_| invalid())?, to.parse().map_err(|_| invalid())?)
+        } else {
+            let value: u32 = range.parse().map_err(|_| invalid())?;
+            // "5/10" は5から最大値まで10おき
+            (value, if step.is_some() { max } else { value })
+        };
+        if from < min || to > max || from > to {
+            return Err(invalid());
+        }
+        for value in (from..=to).step_by(step.unwrap_or(1) as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
@@ -0,0 +1,232 @@
+// 定期投稿モジュール
+// Botごとのスケジュール（bot_schedules）に従い、返信ではなく自分から投稿する
+// ユーザーから頼まれたリマインド（reminders）の送信も同じワーカーで行う
+
+pub mod cron;
+pub mod reminder;
+
+pub use cron::CronSchedule;
+
+use crate::config::AppConfig;
+use crate::database as db;
+use crate::gpt;
+use crate::relay_pool;
+use crate::util;
+use chrono::Utc;
+use nostr_sdk::prelude::*;
+use std::error::Error;
+use std::time::Duration;
+use tracing::{error, info};
+
+/// スケジュールを確認する間隔（秒）
+const SCHEDULER_INTERVAL_SECS: u64 = 30;
+
+/// 1週間の振り返りに使う会話の最大件数
+const WEEKLY_SUMMARY_MAX_EVENTS: usize = 100;
+
+/// 定期投稿の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleKind {
+    Greeting,
+    Mood,
+    WeeklySummary,
+}
+
+impl ScheduleKind {
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "greeting" => Some(Self::Greeting),
+            "mood" => Some(Self::Mood),
+            "weekly_summary" => Some(Self::WeeklySummary),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Greeting => "greeting",
+            Self::Mood => "mood",
+            Self::WeeklySummary => "weekly_summary",
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Self::Greeting => "朝の挨拶",
+            Self::Mood => "今日の気分",
+            Self::WeeklySummary => "1週間の振り返り",
+        }
+    }
+
+    pub fn all() -> Vec<Self> {
+        vec![Self::Greeting, Self::Mood, Self::WeeklySummary]
+    }
+
+    /// 投稿の種類ごとの指示
+    fn instruction(&self) -> &'static str {
+        match self {
+            Self::Greeting => "朝の挨拶を投稿してください。日付や曜日、季節に触れても構いません。",
+            Self::Mood => "【あなたの現在の心境】をもとに、今日の気分を日記のようにつぶやいてください。",
+            Self::WeeklySummary => {
+                "次の行以降はこの1週間にあなたがした会話です。\
+                 誰かの発言をそのまま引用せず、1週間を振り返る投稿をしてください。"
+            }
+        }
+    }
+}
+
+/// cronから次回の実行時刻を求める
+pub fn next_run_at(cron: &str, after: i64) -> Result<Option<i64>, String> {
+    Ok(CronSchedule::parse(cron)?.next_after(after))
+}
+
+/// スケジュールとリマインドを定期的に確認して投稿するワーカー
+pub async fn run_scheduler(config: AppConfig) {
+    loop {
+        let now = Utc::now().timestamp();
+        match run_due_schedules(&config, now).await {
+            Ok(count) if count > 0 => info!("[Scheduler] {}件の定期投稿を行いました", count),
+            Ok(_) => {}
+            Err(e) => error!("[Scheduler] エラー: {}", e),
+        }
+        match reminder::deliver_due_reminders(&config, now).await {
+            Ok(count) if count > 0 => info!("[Reminder] {}件のリマインドを送りました", count),
+            Ok(_) => {}
+            Err(e) => error!("[Reminder] エラー: {}", e),
+        }
+        tokio::time::sleep(Duration::from_secs(SCHEDULER_INTERVAL_SECS)).await;
+    }
+}
+
+/// 実行時刻を過ぎたスケジュールを実行し、投稿した件数を返す
+///
+/// 停止中に過ぎた分はまとめて1回だけ実行する。
+/// グローバル一時停止中・Bot無効化中は投稿せず、次回の実行時刻だけ進める。
+pub async fn run_due_schedules(config: &AppConfig, now: i64) -> Result<usize, Box<dyn Error>> {
+    let conn = db::connect()?;
+    let schedules = db::get_due_schedules(&conn, now)?;
+    if schedules.is_empty() {
+        return Ok(0);
+    }
+    let paused = db::is_global_pause(&conn)?;
+
+    let mut posted = 0;
+    for schedule in schedules {
+        // 投稿に失敗しても同じスケジュールを繰り返さないよう、先に次回の時刻を記録する
+        let next = next_run_at(&schedule.cron, now).unwrap_or_else(|e| {
+            error!("[Scheduler] スケジュール{}を停止: {}", schedule.id, e);
+            None
+        });
+        db::mark_schedule_run(&conn, schedule.id, now, next)?;
+
+        if paused {
+            info!("⏸️ グローバル一時停止中のため、定期投稿をスキップ: {}", schedule.id);
+            continue;
+        }
+        let person = match db::find_person(&conn, &schedule.bot_pubkey)? {
+            Some(person) => person,
+            None => continue,
+        };
+        if person.status != 0 {
+            info!("🚫 Bot無効化中のため、定期投稿をスキップ: {}", person.pubkey);
+            continue;
+        }
+
+        match post_schedule(config, &conn, &person, &schedule, now).await {
+            Ok(event) => {
+                info!("[Scheduler] {} ({}) を投稿: {}", schedule.kind, person.pubkey, event.id);
+                posted += 1;
+            }
+            Err(e) => error!(bot = %person.pubkey, "[Scheduler] スケジュール{}の投稿に失敗: {}", schedule.id, e),
+        }
+    }
+    Ok(posted)
+}
+
+/// スケジュールに従って投稿する（ペルソナで文章を生成 → 投稿 → events・timelineに記録）
+#[tracing::instrument(name = "schedule", skip_all, fields(schedule_id = schedule.id, bot = %person.pubkey))]
+async fn post_schedule(
+    config: &AppConfig,
+    conn: &rusqlite::Connection,
+    person: &db::Person,
+    schedule: &db::BotSchedule,
+    now: i64,
+) -> Result<Event, Box<dyn Error>> {
+    let kind = ScheduleKind::from_str(&schedule.kind)
+        .ok_or_else(|| format!("不明な投稿の種類です: {}", schedule.kind))?;
+
+    let mut instruction = kind.instruction().to_string();
+    if let Some(extra) = schedule.instruction.as_deref().filter(|s| !s.trim().is_empty()) {
+        instruction.push_str(&format!("\n{}", extra));
+    }
+    let material = build_material(kind, conn, person, now)?;
+
+    let response = gpt::get_scheduled_post_with_mental_diary(person, &person.prompt, &instruction, &material, config).await?;
+    if response.reply.trim().is_empty() {
+        return Err("投稿する文章が空でした".into());
+    }
+
+    let keys = Keys::parse(&person.secretkey)?;
+    let (event, result) = relay_pool::shared()
+        .publish_as(&keys, EventBuilder::text_note(&response.reply), &config.relay_servers.write)
+        .await?;
+    if !result.is_accepted() {
+        error!("[Scheduler] どのリレーにも受理されませんでした: {}", event.id);
+    }
+
+    // 送信成功後に心境とBotの発言を保存
+    if let Err(e) = gpt::save_mental_diary_response(&person.pubkey, None, &response) {
+        error!("[Scheduler] GPTレスポンス保存エラー: {}", e);
+    }
+    if let Err(e) = db::insert_event(conn, &event, None) {
+        error!("[Scheduler] bot発言の保存エラー: {}", e);
+    }
+    if let Err(e) = db::add_timeline_post(conn, &person.pubkey, Some("Bot"), &response.reply, now) {
+        error!("[Scheduler] Failed to save bot timeline post: {}", e);
+    }
+    let timeline_size = config.get_usize_setting("timeline_size");
+    let _ = db::cleanup_old_timeline_posts(conn, timeline_size);
+
+    Ok(event)
+}
+
+/// 投稿の材料（LLMへの入力）
+fn build_material(
+    kind: ScheduleKind,
+    conn: &rusqlite::Connection,
+    person: &db::Person,
+    now: i64,
+) -> Result<String, Box<dyn Error>> {
+    let jst = util::jst();
+    let weekdays = ["日", "月", "火", "水", "木", "金", "土"];
+    let format_time = |timestamp: i64, format: &str| {
+        chrono::DateTime::from_timestamp(timestamp, 0)
+            .map(|t| t.with_timezone(&jst).format(format).to_string())
+            .unwrap_or_default()
+    };
+    let weekday = weekdays[format_time(now, "%w").parse::<usize>().unwrap_or(0)];
+    let current = format!("現在日時: {}({}) JST", format_time(now, "%Y-%m-%d %H:%M"), weekday);
+
+    match kind {
+        ScheduleKind::Greeting | ScheduleKind::Mood => Ok(current),
+        ScheduleKind::WeeklySummary => {
+            let events = db::get_conversation_timeline_since(conn, &person.pubkey, now - 7 * 24 * 60 * 60, WEEKLY_SUMMARY_MAX_EVENTS)?;
+            if events.is_empty() {
+                return Ok(format!("{}\n\nこの1週間は誰とも会話しませんでした。", current));
+            }
+            let lines: Vec<String> = events
+                .iter()
+                .map(|event| {
+                    let name = if event.pubkey == person.pubkey {
+                        "あなた".to_string()
+                    } else {
+                        event.display_name(conn)
+                    };
+                    format!("[{}] {}: {}", format_time(event.created_at, "%m/%d %H:%M"), name, event.content)
+                })
+                .collect();
+            Ok(format!("{}\n\n{}", current, lines.join("\n")))
+        }
+    }
+}
@@ -0,0 +1,324 @@
+use crate::config::AppConfig;
+use crate::database as db;
+use crate::gpt;
+use crate::util;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone};
+use nostr_sdk::prelude::*;
+use regex::Regex;
+use std::error::Error;
+use std::sync::OnceLock;
+use tracing::{error, info};
+
+/// 日付だけ指定された場合の時刻
+const DEFAULT_HOUR: u32 = 9;
+
+/// リマインドの依頼を読み取った結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedReminder {
+    /// リマインドする時刻（UNIX秒）
+    pub remind_at: i64,
+    /// リマインドする内容（時刻や「リマインドして」を除いた部分）
+    pub message: String,
+}
+
+/// 文章からリマインドの時刻と内容を読み取る（時刻はJST）
+///
+/// - 相対: 「30分後」「1時間半後」「2日後」「in 2h」「in 1h30m」「in 10 minutes」
+/// - 絶対: 「明日9時」「午後3時半」「10月20日 18:00」「2025-10-20 9:00」「tomorrow at 9am」「at 21:30」
+///
+/// 時刻だけで今日のその時刻を過ぎていれば翌日、日付だけなら9時とする。読み取れなければNone
+pub fn parse_reminder(text: &str, now: i64) -> Option<ParsedReminder> {
+    let text = normalize_digits(text);
+    let (remind_at, spans) = parse_relative(&text, now).or_else(|| parse_absolute(&text, now))?;
+    Some(ParsedReminder {
+        remind_at,
+        message: extract_message(&text, &spans),
+    })
+}
+
+/// 全角数字・記号を半角に
+fn normalize_digits(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '０'..='９' => char::from_u32(c as u32 - '０' as u32 + '0' as u32).unwrap_or(c),
+            '：' => ':',
+            '／' => '/',
+            _ => c,
+        })
+        .collect()
+}
+
+/// 「30分後」「in 2h」などの相対時刻
+fn parse_relative(text: &str, now: i64) -> Option<(i64, Vec<(usize, usize)>)> {
+    static JA: OnceLock<Regex> = OnceLock::new();
+    let ja = JA.get_or_init(|| Regex::new(r"((?:\d+\s*(?:日|時間|分|秒)\s*)+)(半)?\s*後").unwrap());
+    static JA_UNIT: OnceLock<Regex> = OnceLock::new();
+    let ja_unit = JA_UNIT.get_or_init(|| Regex::new(r"(\d+)\s*(日|時間|分|秒)").unwrap());
+    if let Some(caps) = ja.captures(text) {
+        let mut seconds: i64 = ja_unit
+            .captures_iter(&caps[1])
+            .map(|unit| unit_seconds(&unit[1], &unit[2]))
+            .sum();
+        if caps.get(2).is_some() {
+            // 「1時間半後」「2日半後」などの半分
+            let last_unit = ja_unit.captures_iter(&caps[1]).last()?;
+            seconds += unit_seconds("1", &last_unit[2]) / 2;
+        }
+        let whole = caps.get(0)?;
+        return (seconds > 0).then(|| (now + seconds, vec![(whole.start(), whole.end())]));
+    }
+
+    static EN: OnceLock<Regex> = OnceLock::new();
+    let en = EN.get_or_init(|| {
+        Regex::new(r"(?i)\bin\s+((?:\d+\s*(?:days|day|d|hours|hour|hrs|hr|h|minutes|minute|mins|min|m|seconds|second|secs|sec|s)\s*)+)").unwrap()
+    });
+    static EN_UNIT: OnceLock<Regex> = OnceLock::new();
+    let en_unit = EN_UNIT.get_or_init(|| {
+        Regex::new(r"(?i)(\d+)\s*(days|day|d|hours|hour|hrs|hr|h|minutes|minute|mins|min|m|seconds|second|secs|sec|s)").unwrap()
+    });
+    let caps = en.captures(text)?;
+    let seconds: i64 = en_unit
+        .captures_iter(&caps[1])
+        .map(|unit| unit_seconds(&unit[1], &unit[2].to_lowercase()))
+        .sum();
+    let whole = caps.get(0)?;
+    (seconds > 0).then(|| (now + seconds, vec![(whole.start(), whole.end())]))
+}
+
+fn unit_seconds(value: &str, unit: &str) -> i64 {
+    let value: i64 = value.parse().unwrap_or(0);
+    let unit_secs = match unit {
+        "日" | "days" | "day" | "d" => 24 * 60 * 60,
+        "時間" | "hours" | "hour" | "hrs" | "hr" | "h" => 60 * 60,
+        "分" | "minutes" | "minute" | "mins" | "min" | "m" => 60,
+        _ => 1,
+    };
+    value.saturating_mul(unit_secs)
+}
+
+/// 「明日9時」「10月20日 18:00」「tomorrow at 9am」などの絶対時刻
+fn parse_absolute(text: &str, now: i64) -> Option<(i64, Vec<(usize, usize)>)> {
+    let jst = util::jst();
+    let today = DateTime::from_timestamp(now, 0)?.with_timezone(&jst).date_naive();
+    let mut spans = Vec::new();
+
+    // 日付
+    let mut date: Option<NaiveDate> = None;
+    let mut explicit_year = false;
+    static DAY_WORDS: OnceLock<Regex> = OnceLock::new();
+    let day_words = DAY_WORDS.get_or_init(|| {
+        Regex::new(r"(?i)明後日|あさって|明日|あした|今日|きょう|day after tomorrow|tomorrow|today").unwrap()
+    });
+    static FULL_DATE: OnceLock<Regex> = OnceLock::new();
+    let full_date = FULL_DATE.get_or_init(|| {
+        Regex::new(r"(\d{4})[-/](\d{1,2})[-/](\d{1,2})").unwrap()
+    });
+    static MONTH_DAY: OnceLock<Regex> = OnceLock::new();
+    let month_day = MONTH_DAY.get_or_init(|| {
+        Regex::new(r"(\d{1,2})月\s*(\d{1,2})日|(\d{1,2})/(\d{1,2})").unwrap()
+    });
+    if let Some(caps) = full_date.captures(text) {
+        date = NaiveDate::from_ymd_opt(caps[1].parse().ok()?, caps[2].parse().ok()?, caps[3].parse().ok()?);
+        explicit_year = true;
+        let whole = caps.get(0)?;
+        spans.push((whole.start(), whole.end()));
+    } else if let Some(caps) = month_day.captures(text) {
+        let month = caps.get(1).or(caps.get(3))?.as_str().parse().ok()?;
+        let day = caps.get(2).or(caps.get(4))?.as_str().parse().ok()?;
+        date = NaiveDate::from_ymd_opt(today.year(), month, day);
+        let whole = caps.get(0)?;
+        spans.push((whole.start(), whole.end()));
+    } else if let Some(found) = day_words.find(text) {
+        let days = match found.as_str().to_lowercase().as_str() {
+            "明後日" | "あさって" | "day after tomorrow" => 2,
+            "明日" | "あした" | "tomorrow" => 1,
+            _ => 0,
+        };
+        date = Some(today + Duration::days(days));
+        spans.push((found.start(), found.end()));
+    }
+
+    // 時刻
+    static JA_TIME: OnceLock<Regex> = OnceLock::new();
+    let ja_time = JA_TIME.get_or_init(|| {
+        Regex::new(r"(午前|午後)?\s*(\d{1,2})時\s*(?:(半)|(\d{1,2})分)?").unwrap()
+    });
+    static COLON_TIME: OnceLock<Regex> = OnceLock::new();
+    let colon_time = COLON_TIME.get_or_init(|| {
+        Regex::new(r"(?i)(?:\bat\s+)?(\d{1,2}):(\d{2})\s*(am|pm)?").unwrap()
+    });
+    static AMPM_TIME: OnceLock<Regex> = OnceLock::new();
+    let ampm_time = AMPM_TIME.get_or_init(|| {
+        Regex::new(r"(?i)(?:\bat\s+)?(\d{1,2})\s*(am|pm)\b").unwrap()
+    });
+    let mut time: Option<(u32, u32)> = None;
+    let span_free = |start: usize, end: usize, spans: &[(usize, usize)]| spans.iter().all(|&(s, e)| end <= s || start >= e);
+    if let Some(caps) = ja_time.captures(text) {
+        let mut hour: u32 = caps[2].parse().ok()?;
+        let minute: u32 = if caps.get(3).is_some() { 30 } else { caps.get(4).map_or(Some(0), |m| m.as_str().parse().ok())? };
+        if caps.get(1).map(|m| m.as_str()) == Some("午後") && hour < 12 {
+            hour += 12;
+        }
+        time = Some((hour, minute));
+        let whole = caps.get(0)?;
+        spans.push((whole.start(), whole.end()));
+    } else if let Some(caps) = colon_time.captures_iter(text).find(|c| {
+        let whole = c.get(0).unwrap();
+        span_free(whole.start(), whole.end(), &spans)
+    }) {
+        let hour = to_24h(caps[1].parse().ok()?, caps.get(3).map(|m| m.as_str()))?;
+        time = Some((hour, caps[2].parse().ok()?));
+        let whole = caps.get(0)?;
+        spans.push((whole.start(), whole.end()));
+    } else if let Some(caps) = ampm_time.captures(text) {
+        let hour = to_24h(caps[1].parse().ok()?, caps.get(2).map(|m| m.as_str()))?;
+        time = Some((hour, 0));
+        let whole = caps.get(0)?;
+        spans.push((whole.start(), whole.end()));
+    }
+
+    if date.is_none() && time.is_none() {
+        return None;
+    }
+    let (hour, minute) = time.unwrap_or((DEFAULT_HOUR, 0));
+    if hour >= 24 || minute >= 60 {
+        return None;
+    }
+
+    let to_timestamp = |date: NaiveDate| {
+        jst.from_local_datetime(&date.and_hms_opt(hour, minute, 0)?)
+            .single()
+            .map(|t| t.timestamp())
+    };
+    let mut remind_at = to_timestamp(date.unwrap_or(today))?;
+    if remind_at <= now {
+        match date {
+            // 時刻だけなら翌日
+            None => remind_at = to_timestamp(today + Duration::days(1))?,
+            // 年の指定がない日付なら翌年
+            Some(d) if !explicit_year && d != today => {
+                remind_at = to_timestamp(d.with_year(d.year() + 1)?)?;
+            }
+            _ => {}
+        }
+    }
+    Some((remind_at, spans))
+}
+
+/// am/pmを24時間制に
+fn to_24h(hour: u32, ampm: Option<&str>) -> Option<u32> {
+    match ampm.map(|s| s.to_lowercase()) {
+        None => Some(hour),
+        Some(_) if hour == 0 || hour > 12 => None,
+        Some(s) if s == "am" => Some(hour % 12),
+        Some(_) => Some(hour % 12 + 12),
+    }
+}
+
+/// 時刻の部分と依頼の言葉を除いてリマインドの内容を取り出す
+fn extract_message(text: &str, spans: &[(usize, usize)]) -> String {
+    let mut spans = spans.to_vec();
+    spans.sort();
+    let mut rest = String::new();
+    let mut pos = 0;
+    for (start, end) in spans {
+        if start >= pos {
+            rest.push_str(&text[pos..start]);
+            rest.push(' ');
+            pos = end;
+        }
+    }
+    rest.push_str(&text[pos..]);
+
+    static REQUEST: OnceLock<Regex> = OnceLock::new();
+    let request = REQUEST.get_or_init(|| {
+        Regex::new(r"(?i)(って|と)?\s*(リマインド(して|を?お願い(します)?)?(ください|下さい|ね|ー)?|remind\s+me|remind)").unwrap()
+    });
+    let rest = request.replace_all(&rest, " ");
+    static LEADING: OnceLock<Regex> = OnceLock::new();
+    let leading = LEADING.get_or_init(|| {
+        Regex::new(r"(?i)^[\s、,。]*(?:には|に|(?:to|about)(?:\s+|$))?[\s、,。]*").unwrap()
+    });
+    static TRAILING: OnceLock<Regex> = OnceLock::new();
+    let trailing = TRAILING.get_or_init(|| {
+        Regex::new(r"[\s、,。!！?？]*(に|を|って|と)?[\s、,。!！?？]*$").unwrap()
+    });
+    let rest = leading.replace(&rest, "");
+    let rest = trailing.replace(&rest, "");
+    static SPACES: OnceLock<Regex> = OnceLock::new();
+    let spaces = SPACES.get_or_init(|| Regex::new(r"\s+").unwrap());
+    spaces.replace_all(rest.trim(), " ").to_string()
+}
+
+/// JSTの「MM/DD HH:MM」
+pub fn format_remind_at(timestamp: i64) -> String {
+    DateTime::from_timestamp(timestamp, 0)
+        .map(|t| t.with_timezone(&util::jst()).format("%m/%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+/// 時刻を過ぎたリマインドを送り、送った件数を返す
+///
+/// グローバル一時停止中・Bot無効化中は送らずに残し、再開後に送る。
+pub async fn deliver_due_reminders(config: &AppConfig, now: i64) -> Result<usize, Box<dyn Error>> {
+    let conn = db::connect()?;
+    let reminders = db::get_due_reminders(&conn, now)?;
+    if reminders.is_empty() || db::is_global_pause(&conn)? {
+        return Ok(0);
+    }
+
+    let mut sent = 0;
+    for reminder in reminders {
+        let person = match db::find_person(&conn, &reminder.bot_pubkey)? {
+            Some(person) => person,
+            None => {
+                db::finish_reminder(&conn, reminder.id, "failed")?;
+                continue;
+            }
+        };
+        if person.status != 0 {
+            continue;
+        }
+
+        // 送信に失敗しても繰り返さないよう、先に結果を記録する
+        db::finish_reminder(&conn, reminder.id, "sent")?;
+        match send_reminder(config, &person, &reminder).await {
+            Ok(event) => {
+                info!("[Reminder] #{} を送信: {}", reminder.id, event.id);
+                sent += 1;
+            }
+            Err(e) => {
+                error!("[Reminder] #{} の送信に失敗: {}", reminder.id, e);
+                db::finish_reminder(&conn, reminder.id, "failed")?;
+            }
+        }
+    }
+    Ok(sent)
+}
+
+/// ペルソナでリマインドの文章を作り、依頼された投稿へ返信する
+async fn send_reminder(config: &AppConfig, person: &db::Person, reminder: &db::Reminder) -> Result<Event, Box<dyn Error>> {
+    let request = Event::from_json(&reminder.event_json)?;
+    let message = if reminder.message.is_empty() { "（内容の指定なし）" } else { reminder.message.as_str() };
+
+    let prompt = format!(
+        "{}\n\nあなたは以前ユーザーからリマインドを頼まれていて、今がその時間です。\
+         リマインドの内容をあなたらしく短く伝えてください。返答のみを出力してください。",
+        person.prompt
+    );
+    let user_text = format!("リマインドの内容: {}\n依頼した投稿: {}", message, request.content);
+    let text = match gpt::call_gpt_with_category(&prompt, &user_text, person, "reminder", config).await {
+        Ok(text) if !text.trim().is_empty() => text,
+        Ok(_) => format!("⏰ リマインド: {}", message),
+        Err(e) => {
+            error!("[Reminder] 文章の生成に失敗: {}", e);
+            format!("⏰ リマインド: {}", message)
+        }
+    };
+
+    let event = util::reply_to(config, request, person.clone(), &text).await?;
+    if let Err(e) = util::log_event_to_conversation(&event, &person.pubkey, true) {
+        error!("[Reminder] 会話ログ記録エラー: {}", e);
+    }
+    Ok(event)
+}
@@ -0,0 +1,133 @@
+// 投稿検索モジュール
+// ローカルに保存した投稿（FTS5）を優先し、足りない分だけNIP-50検索リレーに問い合わせる
+
+use crate::config::AppConfig;
+use crate::database as db;
+use nostr_sdk::prelude::*;
+use std::time::Duration;
+use tracing::{error, info};
+
+pub type SearchError = Box<dyn std::error::Error + Send + Sync>;
+
+/// 検索リレーのタイムアウト
+const RELAY_TIMEOUT_SECS: u64 = 10;
+
+/// 検索先
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchSource {
+    /// ローカル優先（足りなければリレー）
+    #[default]
+    Auto,
+    Local,
+    Relay,
+}
+
+/// 投稿検索の条件
+#[derive(Debug, Clone, Default)]
+pub struct SearchParams {
+    /// 検索キーワード（全てを含む投稿）
+    pub keywords: Vec<String>,
+    /// 投稿者（hex）
+    pub author: Option<String>,
+    /// この時刻以降（UNIX秒）
+    pub since: Option<i64>,
+    /// この時刻以前（UNIX秒）
+    pub until: Option<i64>,
+    pub limit: usize,
+    pub source: SearchSource,
+}
+
+/// 検索で見つかった投稿
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub event_id: EventId,
+    pub pubkey: String,
+    pub content: String,
+    pub created_at: Timestamp,
+}
+
+/// 投稿を検索（新しい順、excludeの投稿は除く）
+pub async fn search_events(config: &AppConfig, params: &SearchParams, exclude: Option<EventId>) -> Result<Vec<SearchHit>, SearchError> {
+    let mut hits = Vec::new();
+
+    if params.source != SearchSource::Relay {
+        let query = db::LocalSearchQuery {
+            keyword: params.keywords.join(" "),
+            author: params.author.clone(),
+            since: params.since,
+            until: params.until,
+            limit: params.limit + 1,
+        };
+        let local_events = {
+            let conn = db::connect()?;
+            db::search_events_local(&conn, &query)?
+        };
+        hits.extend(local_events.into_iter().filter_map(|e| {
+            Some(SearchHit {
+                event_id: EventId::from_hex(&e.event_id).ok()?,
+                pubkey: e.pubkey,
+                content: e.content,
+                created_at: Timestamp::from(e.created_at as u64),
+            })
+        }));
+        hits.retain(|hit| Some(hit.event_id) != exclude);
+        info!("[Search] ローカルで{}件見つかりました", hits.len());
+    }
+
+    // 足りない場合のみ検索リレーに問い合わせる
+    if params.source == SearchSource::Relay || (params.source == SearchSource::Auto && hits.len() < params.limit) {
+        match search_relays(config, params).await {
+            Ok(events) => {
+                info!("[Search] 検索リレーから{}件取得しました", events.len());
+                for e in events {
+                    if Some(e.id) == exclude || hits.iter().any(|hit| hit.event_id == e.id) {
+                        continue;
+                    }
+                    // 検索リレーが期間指定を無視することがあるので確認する
+                    let timestamp = e.created_at.as_u64() as i64;
+                    if params.since.is_some_and(|since| timestamp < since) || params.until.is_some_and(|until| timestamp > until) {
+                        continue;
+                    }
+                    hits.push(SearchHit {
+                        event_id: e.id,
+                        pubkey: e.pubkey.to_hex(),
+                        content: e.content,
+                        created_at: e.created_at,
+                    });
+                }
+            }
+            // ローカルで見つかっていればリレーの失敗は無視する
+            Err(e) if !hits.is_empty() => {
+                error!("[Search] 検索リレーからの取得に失敗: {}", e);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    hits.sort_by_key(|hit| std::cmp::Reverse(hit.created_at));
+    hits.truncate(params.limit);
+    Ok(hits)
+}
+
+/// NIP-50検索リレーから取得
+async fn search_relays(config: &AppConfig, params: &SearchParams) -> Result<Vec<Event>, SearchError> {
+    let mut filter = Filter::new()
+        .kind(Kind::TextNote)
+        .search(params.keywords.join(" "))
+        .limit(params.limit.max(10));
+    if let Some(author) = &params.author {
+        filter = filter.author(PublicKey::from_hex(author)?);
+    }
+    if let Some(since) = params.since {
+        filter = filter.since(Timestamp::from(since as u64));
+    }
+    if let Some(until) = params.until {
+        filter = filter.until(Timestamp::from(until as u64));
+    }
+    info!("[Search] 検索リレーに問い合わせ: {:?}", filter);
+
+    let events = crate::relay_pool::shared()
+        .fetch(filter, &config.relay_servers.search, Duration::from_secs(RELAY_TIMEOUT_SECS))
+        .await?;
+    Ok(events)
+}
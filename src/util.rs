@@ -1,14 +1,23 @@
 use crate::config;
 use crate::config::AppConfig;
 use crate::database as db;
+use crate::live;
+use crate::metrics;
+use crate::relay_pool;
+use chrono::FixedOffset;
 use lightning_invoice::Bolt11Invoice;
 use nostr_sdk::prelude::*;
 use rand::Rng;
 use std::fs::File;
 use std::str::FromStr;
-use std::thread;
 use std::time::Duration;
 use serde_json::Value;
+use tracing::{debug, error, info};
+
+/// 日本時間（UTC+9）
+pub fn jst() -> FixedOffset {
+    FixedOffset::east_opt(9 * 3600).unwrap()
+}
 
 /// イベントを会話履歴に記録するヘルパー関数
 pub fn log_event_to_conversation(
@@ -34,9 +43,9 @@ pub fn log_event_to_conversation(
 }
 
 pub async fn is_follower(user_pubkey: &str, bot_secret_key: &str) -> Result<bool> {
-  let file = File::open("../config.yml")?;
+  let file = File::open(config::config_path())?;
   let config: config::AppConfig = serde_yaml::from_reader(file)?;
-  let my_keys = Keys::parse(&bot_secret_key)?;
+  let my_keys = Keys::parse(bot_secret_key)?;
   let bot_pubkey = my_keys.public_key();
   let bot_pubkey_str = bot_pubkey.to_string();
   
@@ -48,12 +57,14 @@ pub async fn is_follower(user_pubkey: &str, bot_secret_key: &str) -> Result<bool
     None => config.bot.follower_cache_ttl,
   };
   if let Some((cached_result, remaining)) = db::get_follower_cache(&conn, user_pubkey, &bot_pubkey_str, ttl)? {
-    println!("Follower cache hit: remaining {}s ({}h {}m)", 
+    debug!("Follower cache hit: remaining {}s ({}h {}m)", 
       remaining, remaining / 3600, (remaining % 3600) / 60);
+    metrics::follower_cache(true);
     return Ok(cached_result);
   }
+  metrics::follower_cache(false);
   
-  println!("Follower cache miss, fetching from relay...");
+  debug!("Follower cache miss, fetching from relay...");
   
   // Cache miss, fetch from relay
   let detect = fetch_follower_status(user_pubkey, bot_secret_key).await?;
@@ -65,28 +76,22 @@ pub async fn is_follower(user_pubkey: &str, bot_secret_key: &str) -> Result<bool
 }
 
 pub async fn fetch_follower_status(user_pubkey: &str, bot_secret_key: &str) -> Result<bool> {
-  let file = File::open("../config.yml")?;
+  let file = File::open(config::config_path())?;
   let config: config::AppConfig = serde_yaml::from_reader(file)?;
-  let my_keys = Keys::parse(&bot_secret_key)?;
+  let my_keys = Keys::parse(bot_secret_key)?;
   let bot_pubkey = my_keys.public_key();
-  
-  let client = Client::new(my_keys);
-  for item in config.relay_servers.read.iter() {
-      client.add_relay(item.clone()).await?;
-  }
-  client.connect().await;
-  let publickey = PublicKey::from_hex(user_pubkey).unwrap();
+  let publickey = PublicKey::from_hex(user_pubkey)?;
 
   let filter = Filter::new()
     .authors([publickey].to_vec())
     .kinds([nostr_sdk::Kind::ContactList].to_vec())
     .limit(1);
 
-  let events = client
-    .fetch_events(filter, Duration::from_secs(30))
+  let latest = relay_pool::shared()
+    .fetch_latest(filter, &config.relay_servers.read, Duration::from_secs(30))
     .await?;
 
-  let detect = events.first().map_or(false, |first_event: &Event| {
+  let detect = latest.is_some_and(|first_event: Event| {
     first_event.tags.iter().any(|tag| {
       match tag.as_standardized() {
         Some(TagStandard::PublicKey { public_key, .. }) => {
@@ -96,66 +101,36 @@ pub async fn fetch_follower_status(user_pubkey: &str, bot_secret_key: &str) -> R
       }
     })
   });
-
-  client.shutdown().await;
   
   Ok(detect)
 }
 
-pub async fn get_kind0(target_pubkey: &str, bot_secret_key: &str) -> Result<Event> {
-  let file = File::open("../config.yml")?;
+pub async fn get_kind0(target_pubkey: &str, _bot_secret_key: &str) -> Result<Event> {
+  let file = File::open(config::config_path())?;
   let config: config::AppConfig = serde_yaml::from_reader(file)?;
-  let my_keys = Keys::parse(&bot_secret_key)?;
-  let client = Client::new(my_keys);
-  for item in config.relay_servers.read.iter() {
-      client.add_relay(item.clone()).await?;
-  }
-  client.connect().await;
-  let public_key = PublicKey::from_hex(target_pubkey).unwrap();
-  let subscription = Filter::new()
+  let public_key = PublicKey::from_hex(target_pubkey)?;
+  let filter = Filter::new()
       .authors([public_key])
       .kinds([nostr_sdk::Kind::Metadata].to_vec())
       .limit(1);
 
-  let _ = client.subscribe(subscription, None).await;
-  println!("subscribe");
-
-  let mut events = vec![];
-  let mut count = 0;
-  let mut notifications = client.notifications();
-  while let Ok(notification) = notifications.recv().await {
-    if let RelayPoolNotification::Event { relay_url: _, subscription_id: _, event } = notification {
-      if event.kind == Kind::Metadata {
-        println!("event {:?}", event);
-        events.push(event);
-        break;
-      }
-    }
-    count += 1;
-    println!("count:{:?}", count);
-    if events.len() >= (config.relay_servers.read.len() / 2) || count >= 3 {
-      break;
-    }
-  }
-  client.shutdown().await;
-  events.sort_by_key(|event| std::cmp::Reverse(event.created_at));
+  let event = relay_pool::shared()
+    .fetch_latest(filter, &config.relay_servers.read, Duration::from_secs(10))
+    .await?
+    .ok_or("kind 0が見つかりませんでした")?;
 
-  Ok(*events.first().unwrap().clone())
+  Ok(event)
 }
 
 pub async fn send_kind0(bot_secret_key: &str, meta_json: &str) -> Result<()> {
-  let file = File::open("../config.yml")?;
+  let file = File::open(config::config_path())?;
   let config: config::AppConfig = serde_yaml::from_reader(file)?;
-  let my_keys = Keys::parse(&bot_secret_key)?;
-  let client = Client::new(my_keys);
-  for item in config.relay_servers.write.iter() {
-    client.add_relay(item.clone()).await?;
-  }
-  client.connect().await;
-  let metadata = Metadata::from_json(meta_json).unwrap();
-  client.set_metadata(&metadata).await?;
-  thread::sleep(Duration::from_secs(10));
-  client.shutdown().await;
+  let my_keys = Keys::parse(bot_secret_key)?;
+  let metadata = Metadata::from_json(meta_json)?;
+  let (_, result) = relay_pool::shared()
+    .publish_as(&my_keys, EventBuilder::metadata(&metadata), &config.relay_servers.write)
+    .await?;
+  info!("[kind 0] accepted:{:?} rejected:{}", result.accepted, result.rejected.len());
 
   Ok(())
 }
@@ -172,12 +147,12 @@ pub fn extract_mention(persons: Vec<db::Person>, event: &Event) -> Result<Option
     let name = &content["name"].to_string().replace('"', "");
     let display_name = &content["display_name"].to_string().replace('"', "");
 
-    if words.len() > 0
+    if !words.is_empty()
       && (&words[0] == name
           || &words[0] == display_name
           || event.content.contains(display_name))
     {
-      println!("name:{} display_name:{}", name, display_name);
+      info!("name:{} display_name:{}", name, display_name);
       person = Some(_person.clone());
       break;
     }
@@ -186,18 +161,16 @@ pub fn extract_mention(persons: Vec<db::Person>, event: &Event) -> Result<Option
   if person.is_none() {
     for _tag in event.tags.iter() {
       let tag_vec = _tag.clone().to_vec();
-      if tag_vec.len() > 1 {
-        if tag_vec[0].len() == 1 {
-          if tag_vec[0].starts_with('p') {
+      if tag_vec.len() > 1
+        && tag_vec[0].len() == 1
+          && tag_vec[0].starts_with('p') {
             for _person in &persons {
-              if tag_vec[1].to_string() == _person.pubkey.to_string() {
+              if tag_vec[1] == _person.pubkey {
                 person = Some(_person.clone());
                 break;
               }
             }
           }
-        }
-      }
       if person.is_some() {
         break;
       }
@@ -206,22 +179,30 @@ pub fn extract_mention(persons: Vec<db::Person>, event: &Event) -> Result<Option
   Ok(person)
 }
 
-#[allow(dead_code)]
+/// 返信するかを確率で判定し、判定対象のBot（メンション先、なければランダムに選んだBot）を返す
 #[allow(dead_code)]
 pub fn judge_post(
   config: &AppConfig,
   persons: Vec<db::Person>,
   event: &Event,
 ) -> Result<(bool, Option<db::Person>)> {
+  use rand::seq::SliceRandom;
+
   let mut post = false;
-  println!("{:?}", event);
+  info!("{:?}", event);
   let random_number = rand::thread_rng().gen_range(0..100);
-  let person = extract_mention(persons, &event).unwrap();
-  let mut base_percent = config.get_i64_setting("reaction_percent");
-  if person.is_some() {
+  let mention = extract_mention(persons.clone(), event).unwrap();
+  let has_mention = mention.is_some();
+  let person = mention.or_else(|| persons.choose(&mut rand::thread_rng()).cloned());
+  // Bot個別の反応確率があれば優先
+  let mut base_percent = person
+    .as_ref()
+    .and_then(|p| p.overrides.reaction_percent)
+    .unwrap_or_else(|| config.get_i64_setting("reaction_percent"));
+  if has_mention {
     base_percent += 10;
   }
-  println!(
+  info!(
     "random_number:{:?} base_percent:{:?}",
     random_number, base_percent
   );
@@ -234,29 +215,22 @@ pub fn judge_post(
 #[allow(dead_code)]
 pub async fn send_to(config: &config::AppConfig, event: Event, person: db::Person, text: &str) -> Result<()> {
   let bot_keys = Keys::parse(&person.secretkey)?;
-  let client_temp = Client::new(bot_keys.clone());
-  for item in config.relay_servers.write.iter() {
-    client_temp.add_relay(item.clone()).await.unwrap();
-  }
-  client_temp.connect().await;
+  let pool = relay_pool::shared();
   let kind = event.kind;
   if kind == Kind::TextNote {
     let event_builder = EventBuilder::text_note(text);
-    let event = event_builder.sign(&bot_keys).await?;
-    let event_id = client_temp.send_event(&event).await?;
-    println!("publish_text_note! eventId:{:?}", event_id);
+    let (_, result) = pool.publish_as(&bot_keys, event_builder, &config.relay_servers.write).await?;
+    info!("publish_text_note! eventId:{} accepted:{:?}", result.event_id, result.accepted);
   } else if kind == Kind::ChannelMessage {
     let tags_vec: Vec<Tag> = event.tags.iter().cloned().collect();
     if let Some((event_id, relay_url)) = extract_root_tag_info(&tags_vec) {
       let _id = event_id.clone();
-      let relay_url_obj = RelayUrl::parse(&relay_url).unwrap();
-      let event_builder = EventBuilder::channel_msg(EventId::parse(&event_id).unwrap(), relay_url_obj, text);
-      let event = event_builder.sign(&bot_keys).await?;
-      client_temp.send_event(&event).await?;
-      println!("eventId:{} relay_url:{} text:{}", _id, relay_url, text);
+      let relay_url_obj = RelayUrl::parse(&relay_url)?;
+      let event_builder = EventBuilder::channel_msg(EventId::parse(&event_id)?, relay_url_obj, text);
+      pool.publish_as(&bot_keys, event_builder, &config.relay_servers.write).await?;
+      info!("eventId:{} relay_url:{} text:{}", _id, relay_url, text);
     }
   }
-  client_temp.shutdown().await;
   Ok(())
 }
 
@@ -278,11 +252,7 @@ pub async fn reply_to(
   text: &str,
 ) -> Result<Event> {
   let bot_keys = Keys::parse(&person.secretkey)?;
-  let client_temp = Client::new(bot_keys.clone());
-  for item in config.relay_servers.write.iter() {
-    client_temp.add_relay(item.clone()).await.unwrap();
-  }
-  client_temp.connect().await;
+  let pool = relay_pool::shared();
   let kind = event.kind;
   let mut event_copy: Option<Event> = None;
 
@@ -347,48 +317,53 @@ pub async fn reply_to(
     }
     
     let event_builder = EventBuilder::text_note(text).tags(tags);
-    let event = event_builder.sign(&bot_keys).await?;
-    event_copy = Some(event.clone());
-    let send_result = client_temp.send_event(&event).await?;
-    println!("publish_text_note! relay responses: {:?}", send_result);
-    println!("Event ID: {}", event.id);
+    let (event, result) = pool
+      .publish_as(&bot_keys, event_builder, &config.relay_servers.write)
+      .await
+      .inspect_err(|_| metrics::reply_failed())?;
+    metrics::reply_published(&person.pubkey, &result);
+    info!("publish_text_note! accepted:{:?} rejected:{:?}", result.accepted, result.rejected);
+    if !result.is_accepted() {
+      error!("[Reply] どのリレーにも受理されませんでした: {}", event.id);
+    }
+    info!("Event ID: {}", event.id);
+    event_copy = Some(event);
   } else if kind == Kind::ChannelMessage {
     let tags_vec: Vec<Tag> = event.tags.iter().cloned().collect();
     if let Some((event_id, relay_url)) = extract_root_tag_info(&tags_vec) {
       let _id = event_id.clone();
-      let relay_url_obj = RelayUrl::parse(&relay_url).unwrap();
-      let event_builder = EventBuilder::channel_msg(EventId::parse(&event_id).unwrap(), relay_url_obj, text);
-      let event = event_builder.sign(&bot_keys).await?;
-      event_copy = Some(event.clone());
-      client_temp.send_event(&event).await?;
-      println!("eventId:{} relay_url:{} text:{}", _id, relay_url, text);
+      let relay_url_obj = RelayUrl::parse(&relay_url)?;
+      let event_builder = EventBuilder::channel_msg(EventId::parse(&event_id)?, relay_url_obj, text);
+      let (event, result) = pool
+        .publish_as(&bot_keys, event_builder, &config.relay_servers.write)
+        .await
+        .inspect_err(|_| metrics::reply_failed())?;
+      metrics::reply_published(&person.pubkey, &result);
+      event_copy = Some(event);
+      info!("eventId:{} relay_url:{} text:{}", _id, relay_url, text);
       let result = event_copy.clone().unwrap();
-      println!("publish_public_message! eventId:{}, text:{}", result.id.to_hex(), result.content);
+      info!("publish_public_message! eventId:{}, text:{}", result.id.to_hex(), result.content);
     }
   }
 
-  client_temp.shutdown().await;
   let event_copy = event_copy.ok_or("Failed to create event")?;
+  live::outgoing(&person.pubkey, event_copy.id, event_copy.kind, Some(event.id), Some(&event_copy.content));
   Ok(event_copy)
 }
 
 #[allow(dead_code)]
 pub async fn get_zap_received(target_pubkey: &str) -> Result<Vec<Event>> {
-  let file = File::open("../config.yml")?;
+  let file = File::open(config::config_path())?;
   let config: config::AppConfig = serde_yaml::from_reader(file)?;
-  let client = Client::default();
   // 日本リレーだけだと少ないのでwriteのリレーから取得する
-  for item in config.relay_servers.write.iter() {
-    client.add_relay(item.clone()).await?;
-  }
-  client.connect().await;
+  let pool = relay_pool::shared();
   let now = Timestamp::now();
 
   let one_year_ago = now.as_u64() - 60 * 60 * 24 * 30 * 12; // 約1年前
 
   let mut all_events: Vec<Event> = Vec::new();
   for month_offset in 0..12 {
-    println!("month_offset:{:?}", month_offset);
+    info!("month_offset:{:?}", month_offset);
     // sinceは1年前からスタートし、毎回30日分進める
     let since = Timestamp::from(one_year_ago + 60 * 60 * 24 * 30 * month_offset);
     // untilはsinceから30日後
@@ -401,22 +376,21 @@ pub async fn get_zap_received(target_pubkey: &str) -> Result<Vec<Event>> {
       .until(until)
       .since(since);
 
-    match client
-      .fetch_events(filter, Duration::from_secs(30))
+    match pool
+      .fetch(filter, &config.relay_servers.write, Duration::from_secs(30))
       .await
     {
       Ok(events) => {
-        println!("counts:{:?}", events.len());
+        info!("counts:{:?}", events.len());
         all_events.extend(events);
       }
       Err(e) => {
-        println!("Error fetching events: {:?}", e);
+        info!("Error fetching events: {:?}", e);
       }
     }
   }
 
-  client.shutdown().await;
-  println!("{all_events:#?}");
+  info!("{all_events:#?}");
   let results = all_events;
 
   Ok(results)
@@ -458,7 +432,7 @@ pub async fn fetch_kind0_from_relay_async(conn: &rusqlite::Connection, pubkey: &
     use std::time::Duration;
     
     // config.ymlから読み込みリレーを取得
-    let config_result = std::fs::File::open("../config.yml")
+    let config_result = std::fs::File::open(config::config_path())
         .ok()
         .and_then(|file| serde_yaml::from_reader::<_, config::AppConfig>(file).ok());
     
@@ -466,16 +440,7 @@ pub async fn fetch_kind0_from_relay_async(conn: &rusqlite::Connection, pubkey: &
         .map(|c| c.relay_servers.read)
         .unwrap_or_else(|| vec!["wss://relay.damus.io".to_string()]);
     
-    let keys = Keys::generate();
-    let client = Client::new(keys);
-    
-    for relay in relays.iter().take(3) {
-        if client.add_relay(relay).await.is_err() {
-            continue;
-        }
-    }
-    
-    client.connect().await;
+    let relays: Vec<String> = relays.into_iter().take(3).collect();
     
     let public_key = PublicKey::from_hex(pubkey).ok()?;
     let filter = Filter::new()
@@ -483,16 +448,18 @@ pub async fn fetch_kind0_from_relay_async(conn: &rusqlite::Connection, pubkey: &
         .kind(Kind::Metadata)
         .limit(1);
     
-    let events = client.fetch_events(filter, Duration::from_secs(5)).await.ok()?;
-    client.shutdown().await;
+    let latest = relay_pool::shared()
+        .fetch_latest(filter, &relays, Duration::from_secs(5))
+        .await
+        .ok()?;
     
-    if let Some(event) = events.first() {
+    if let Some(event) = latest {
         let content = event.content.clone();
         
         // eventsテーブルに保存
         let _ = conn.execute(
-            "INSERT OR IGNORE INTO events (event_id, event_json, pubkey, kind, content, created_at, received_at, language, embedding) 
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            "INSERT OR IGNORE INTO events (event_id, event_json, pubkey, kind, content, created_at, received_at, language) 
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
             rusqlite::params![
                 event.id.to_hex(),
                 serde_json::to_string(&event).ok()?,
@@ -502,7 +469,6 @@ pub async fn fetch_kind0_from_relay_async(conn: &rusqlite::Connection, pubkey: &
                 event.created_at.as_u64() as i64,
                 chrono::Utc::now().timestamp(),
                 rusqlite::types::Null,
-                rusqlite::types::Null,
             ],
         );
         
@@ -537,37 +503,3 @@ pub async fn get_user_name(pubkey: &str) -> Result<String> {
     };
     Ok(format!("{}...", short_pubkey))
 }
-
-// Gemini CLIでWeb検索を実行
-pub async fn gemini_search(query: &str, timeout_seconds: i32) -> Result<String, String> {
-    use std::process::Command;
-    use tokio::time::{timeout, Duration};
-    
-    println!("Executing gemini search: {} (timeout: {}s)", query, timeout_seconds);
-    
-    let search_future = tokio::task::spawn_blocking({
-        let query = query.to_string();
-        move || {
-            Command::new("gemini")
-                .arg("search")
-                .arg(&query)
-                .output()
-        }
-    });
-    
-    let output = match timeout(Duration::from_secs(timeout_seconds as u64), search_future).await {
-        Ok(Ok(Ok(output))) => output,
-        Ok(Ok(Err(e))) => return Err(format!("Failed to execute gemini command: {}", e)),
-        Ok(Err(e)) => return Err(format!("Task join error: {}", e)),
-        Err(_) => return Err(format!("Gemini search timed out after {} seconds", timeout_seconds)),
-    };
-    
-    if !output.status.success() {
-        let error = String::from_utf8_lossy(&output.stderr);
-        eprintln!("Gemini search failed: {}", error);
-        return Err(format!("Gemini search failed: {}", error));
-    }
-    
-    let result = String::from_utf8_lossy(&output.stdout).to_string();
-    Ok(result)
-}
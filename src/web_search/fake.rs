@@ -0,0 +1,46 @@
+use super::{WebSearchFuture, WebSearchProvider, WebSearchResult};
+use crate::llm::LlmError;
+use serde::Deserialize;
+
+/// fixtureの1件（queryを含む検索にresultsを返す）
+#[derive(Debug, Clone, Deserialize)]
+pub struct FakeSearchEntry {
+    pub query: String,
+    pub results: Vec<WebSearchResult>,
+}
+
+/// テスト用のWeb検索プロバイダー（ネットワーク不要）
+///
+/// 検索語にfixtureのqueryが含まれていればその結果を返し、どれにも当たらなければ0件
+pub struct FakeWebSearchProvider {
+    entries: Vec<FakeSearchEntry>,
+}
+
+impl FakeWebSearchProvider {
+    pub fn new(entries: Vec<FakeSearchEntry>) -> Self {
+        Self { entries }
+    }
+
+    /// JSONファイル（FakeSearchEntryの配列）から読み込む
+    pub fn from_file(path: &str) -> Result<Self, LlmError> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| format!("fixtureを読み込めません: {} ({})", path, e))?;
+        Ok(Self::new(serde_json::from_str(&json)?))
+    }
+}
+
+impl WebSearchProvider for FakeWebSearchProvider {
+    fn name(&self) -> &str {
+        "fake"
+    }
+
+    fn search<'a>(&'a self, query: &'a str, limit: usize) -> WebSearchFuture<'a> {
+        let results = self
+            .entries
+            .iter()
+            .find(|entry| query.contains(&entry.query))
+            .map(|entry| entry.results.iter().take(limit).cloned().collect())
+            .unwrap_or_default();
+        Box::pin(async move { Ok(results) })
+    }
+}
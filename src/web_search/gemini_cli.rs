@@ -0,0 +1,96 @@
+use super::{WebSearchFuture, WebSearchProvider, WebSearchResult};
+use crate::llm::LlmError;
+use regex::Regex;
+use std::sync::OnceLock;
+use tokio::process::Command;
+use tracing::error;
+
+/// 結果をJSONで読み取れなかったときに使う出力の最大文字数
+const MAX_FALLBACK_CHARS: usize = 2000;
+
+/// Gemini CLIでWeb検索する（Google検索の結果をJSONで出力させる）
+pub struct GeminiCliProvider {
+    command: String,
+}
+
+impl GeminiCliProvider {
+    pub fn new(command: String) -> Self {
+        Self { command }
+    }
+
+    async fn run(&self, query: &str, limit: usize) -> Result<Vec<WebSearchResult>, LlmError> {
+        let prompt = format!(
+            "次の内容をGoogle検索で調べ、参考になったページを最大{}件、JSON配列だけで出力してください。\n\
+            各要素は {{\"title\": \"ページのタイトル\", \"url\": \"ページのURL\", \"snippet\": \"調べた内容に関係する部分の要約\"}} とします。\n\n\
+            検索内容: {}",
+            limit, query
+        );
+        // タイムアウトで打ち切られたらプロセスも終了させる
+        let output = Command::new(&self.command)
+            .arg("-p")
+            .arg(&prompt)
+            .kill_on_drop(true)
+            .output()
+            .await
+            .map_err(|e| format!("{}コマンドを実行できません: {}", self.command, e))?;
+
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            error!("[WebSearch] Gemini CLIのエラー: {}", error);
+            return Err(format!("Gemini CLIの実行に失敗しました: {}", error.trim()).into());
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut results = parse_output(&stdout);
+        results.truncate(limit);
+        Ok(results)
+    }
+}
+
+impl WebSearchProvider for GeminiCliProvider {
+    fn name(&self) -> &str {
+        "gemini_cli"
+    }
+
+    fn search<'a>(&'a self, query: &'a str, limit: usize) -> WebSearchFuture<'a> {
+        Box::pin(self.run(query, limit))
+    }
+}
+
+/// Gemini CLIの出力から検索結果を読み取る
+/// JSON配列を優先し、なければMarkdownのリンク、それもなければ出力全体を1件として扱う
+fn parse_output(output: &str) -> Vec<WebSearchResult> {
+    if let (Some(start), Some(end)) = (output.find('['), output.rfind(']')) {
+        if start < end {
+            if let Ok(results) = serde_json::from_str::<Vec<WebSearchResult>>(&output[start..=end]) {
+                return results;
+            }
+        }
+    }
+
+    static LINK: OnceLock<Regex> = OnceLock::new();
+    let link = LINK.get_or_init(|| Regex::new(r"\[([^\]]+)\]\((https?://[^)\s]+)\)").unwrap());
+    let linked: Vec<WebSearchResult> = output
+        .lines()
+        .flat_map(|line| {
+            link.captures_iter(line).map(move |caps| WebSearchResult {
+                title: caps[1].to_string(),
+                url: caps[2].to_string(),
+                snippet: link.replace_all(line, "$1").trim().to_string(),
+            })
+        })
+        .collect();
+    if !linked.is_empty() {
+        return linked;
+    }
+
+    let text = output.trim();
+    if text.is_empty() {
+        return Vec::new();
+    }
+    vec![WebSearchResult {
+        title: "Gemini CLIの検索結果".to_string(),
+        url: String::new(),
+        snippet: text.chars().take(MAX_FALLBACK_CHARS).collect(),
+    }]
+}
@@ -0,0 +1,114 @@
+// Web検索プロバイダーモジュール
+// 「調べて」コマンドで使う。Gemini CLI / SearxNG互換API / テスト用fixtureを同じインターフェースで扱う
+
+pub mod fake;
+pub mod gemini_cli;
+pub mod searxng;
+
+pub use fake::FakeWebSearchProvider;
+pub use gemini_cli::GeminiCliProvider;
+pub use searxng::SearxngProvider;
+
+use crate::config::{AppConfig, WebSearchConfig, WebSearchProviderKind};
+use crate::llm::LlmError;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+use tracing::info;
+
+/// 出典として並べる件数（回答で番号が引用されなかった場合）
+const DEFAULT_SOURCE_COUNT: usize = 3;
+
+/// Web検索の結果1件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebSearchResult {
+    pub title: String,
+    pub url: String,
+    #[serde(default)]
+    pub snippet: String,
+}
+
+// プロバイダーが返すFuture
+pub type WebSearchFuture<'a> = Pin<Box<dyn Future<Output = Result<Vec<WebSearchResult>, LlmError>> + Send + 'a>>;
+
+/// Web検索プロバイダーの共通インターフェース
+pub trait WebSearchProvider: Send + Sync {
+    /// プロバイダー名（ログ用）
+    fn name(&self) -> &str;
+
+    /// 検索（最大limit件、関連度の高い順）
+    fn search<'a>(&'a self, query: &'a str, limit: usize) -> WebSearchFuture<'a>;
+}
+
+/// 設定からプロバイダーを生成
+pub fn create_provider(config: &WebSearchConfig) -> Result<Box<dyn WebSearchProvider>, LlmError> {
+    match config.provider {
+        WebSearchProviderKind::GeminiCli => Ok(Box::new(GeminiCliProvider::new(config.command.clone()))),
+        WebSearchProviderKind::Searxng => {
+            let base_url = config.base_url.clone()
+                .ok_or("searxngにはbase_urlの指定が必要です")?;
+            Ok(Box::new(SearxngProvider::new(base_url)))
+        }
+        WebSearchProviderKind::Fake => {
+            let fixture = config.fixture.as_deref()
+                .ok_or("fakeにはfixtureの指定が必要です")?;
+            Ok(Box::new(FakeWebSearchProvider::from_file(fixture)?))
+        }
+    }
+}
+
+/// 設定されたプロバイダーで検索（タイムアウトはgemini_search_timeoutの設定値）
+pub async fn search(config: &AppConfig, query: &str) -> Result<Vec<WebSearchResult>, LlmError> {
+    let provider = create_provider(&config.web_search)?;
+    let timeout_secs = config.get_i32_setting("gemini_search_timeout").max(1) as u64;
+    info!("[WebSearch] {}で検索: {} (timeout: {}s)", provider.name(), query, timeout_secs);
+
+    let results = tokio::time::timeout(
+        Duration::from_secs(timeout_secs),
+        provider.search(query, config.web_search.max_results),
+    )
+    .await
+    .map_err(|_| format!("Web検索が{}秒でタイムアウトしました", timeout_secs))??;
+    info!("[WebSearch] {}件の結果", results.len());
+    Ok(results)
+}
+
+/// LLMに渡す検索結果（番号付き）
+pub fn format_results_for_prompt(results: &[WebSearchResult]) -> String {
+    results
+        .iter()
+        .enumerate()
+        .map(|(i, result)| format!("[{}] {}\nURL: {}\n{}", i + 1, result.title, result.url, result.snippet))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// 回答の末尾に出典を付ける
+/// 回答中で[番号]として引用された結果を並べ、引用がなければ上位の結果を並べる
+pub fn append_sources(answer: &str, results: &[WebSearchResult]) -> String {
+    let with_url: Vec<(usize, &WebSearchResult)> = results
+        .iter()
+        .enumerate()
+        .map(|(i, result)| (i + 1, result))
+        .filter(|(_, result)| !result.url.is_empty())
+        .collect();
+    let cited: Vec<&(usize, &WebSearchResult)> = with_url
+        .iter()
+        .filter(|(number, _)| answer.contains(&format!("[{}]", number)))
+        .collect();
+    let sources: Vec<&(usize, &WebSearchResult)> = if cited.is_empty() {
+        with_url.iter().take(DEFAULT_SOURCE_COUNT).collect()
+    } else {
+        cited
+    };
+    if sources.is_empty() {
+        return answer.to_string();
+    }
+
+    let lines: Vec<String> = sources
+        .iter()
+        .map(|(number, result)| format!("[{}] {} {}", number, result.title, result.url))
+        .collect();
+    format!("{}\n\n出典:\n{}", answer, lines.join("\n"))
+}
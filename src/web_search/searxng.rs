@@ -0,0 +1,63 @@
+use super::{WebSearchFuture, WebSearchProvider, WebSearchResult};
+use crate::llm::LlmError;
+use serde::Deserialize;
+
+/// SearxNG互換のHTTP API（/search?format=json）で検索する
+pub struct SearxngProvider {
+    base_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearxngResponse {
+    #[serde(default)]
+    results: Vec<SearxngResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearxngResult {
+    #[serde(default)]
+    title: String,
+    url: String,
+    #[serde(default)]
+    content: String,
+}
+
+impl SearxngProvider {
+    pub fn new(base_url: String) -> Self {
+        // 末尾のスラッシュは除去（"/search"と連結されるため）
+        let base_url = base_url.trim_end_matches('/').to_string();
+        Self { base_url }
+    }
+
+    async fn request(&self, query: &str, limit: usize) -> Result<Vec<WebSearchResult>, LlmError> {
+        let response: SearxngResponse = reqwest::Client::new()
+            .get(format!("{}/search", self.base_url))
+            .query(&[("q", query), ("format", "json")])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(response
+            .results
+            .into_iter()
+            .take(limit)
+            .map(|result| WebSearchResult {
+                title: result.title,
+                url: result.url,
+                snippet: result.content,
+            })
+            .collect())
+    }
+}
+
+impl WebSearchProvider for SearxngProvider {
+    fn name(&self) -> &str {
+        "searxng"
+    }
+
+    fn search<'a>(&'a self, query: &'a str, limit: usize) -> WebSearchFuture<'a> {
+        Box::pin(self.request(query, limit))
+    }
+}
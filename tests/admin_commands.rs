@@ -0,0 +1,132 @@
+// 管理者コマンド（一時停止・Bot有効化・ブラックリスト・設定・キュー・トークン）のテスト
+
+mod common;
+
+use bot::db;
+use common::TestEnv;
+use nostr_sdk::prelude::*;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Botにメンションしてコマンドを送り、その返信の本文を返す
+async fn command(env: &TestEnv, sender: &Keys, bot: &Keys, text: &str) -> String {
+    // 同じ秒に同じコマンドを送ってもイベントIDが重複しないよう、作成日時をずらす
+    static SEQ: AtomicU64 = AtomicU64::new(0);
+    let created_at = Timestamp::now() + SEQ.fetch_add(1, Ordering::Relaxed);
+    let event = EventBuilder::text_note(format!("nostr:{} {}", bot.public_key().to_bech32().unwrap(), text))
+        .tag(Tag::public_key(bot.public_key()))
+        .custom_created_at(created_at)
+        .sign_with_keys(sender)
+        .unwrap();
+    env.deliver(&event).await;
+
+    for _ in 0..50 {
+        let replies = env
+            .fetch(Filter::new().author(bot.public_key()).kind(Kind::TextNote).event(event.id))
+            .await;
+        if let Some(reply) = replies.first() {
+            return reply.content.clone();
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    panic!("コマンド「{}」の返信がありません", text);
+}
+
+fn setting(env: &TestEnv, key: &str) -> Option<String> {
+    db::get_system_setting(&env.conn(), key).unwrap()
+}
+
+#[tokio::test]
+async fn admin_controls_pause_bots_and_settings() {
+    let mut env = TestEnv::new().await;
+    let bot = env.add_bot("かんりちゃん");
+    let other_bot = env.add_bot("べつのこ");
+    let admin = Keys::generate();
+    env.config.bot.admin_pubkeys = vec![admin.public_key().to_hex()];
+
+    assert!(command(&env, &admin, &bot, "pause").await.contains("一時停止しました"));
+    assert!(db::is_global_pause(&env.conn()).unwrap());
+    assert!(command(&env, &admin, &bot, "queue status").await.contains("一時停止中"));
+    assert!(command(&env, &admin, &bot, "resume").await.contains("解除しました"));
+    assert!(!db::is_global_pause(&env.conn()).unwrap());
+
+    // 省略時はメンションしたBot、指定すればそのBot
+    assert_eq!(command(&env, &admin, &bot, "disable").await, "🚫 かんりちゃんを無効にしました");
+    let npub = other_bot.public_key().to_bech32().unwrap();
+    assert_eq!(command(&env, &admin, &bot, &format!("disable {}", npub)).await, "🚫 べつのこを無効にしました");
+    let status = |keys: &Keys| db::find_person(&env.conn(), &keys.public_key().to_hex()).unwrap().unwrap().status;
+    assert_eq!((status(&bot), status(&other_bot)), (1, 1));
+    // 無効化したBotにも管理者コマンドは届く
+    assert_eq!(command(&env, &admin, &bot, "enable").await, "✅ かんりちゃんを有効にしました");
+    assert_eq!((status(&bot), status(&other_bot)), (0, 1));
+    let unknown = Keys::generate().public_key().to_bech32().unwrap();
+    assert!(command(&env, &admin, &bot, &format!("enable {}", unknown)).await.starts_with("Botが見つかりません"));
+    assert!(command(&env, &admin, &bot, "enable abc").await.starts_with("公開鍵の形式が正しくありません: abc\n使い方: enable"));
+
+    let spammer = Keys::generate();
+    let spammer_npub = spammer.public_key().to_bech32().unwrap();
+    assert!(command(&env, &admin, &bot, &format!("blacklist add {}", spammer_npub)).await.contains("追加しました"));
+    assert!(command(&env, &admin, &bot, &format!("blacklist add {}", spammer.public_key().to_hex())).await.contains("すでに"));
+    assert_eq!(setting(&env, "blacklist"), Some(spammer.public_key().to_hex()));
+    assert!(command(&env, &admin, &bot, "blacklist").await.contains(&spammer_npub));
+    assert!(command(&env, &admin, &bot, &format!("blacklist remove nostr:{}", spammer_npub)).await.contains("削除しました"));
+    assert_eq!(setting(&env, "blacklist"), Some(String::new()));
+
+    assert_eq!(
+        command(&env, &admin, &bot, "setting timeline_size 50").await,
+        "timeline_size を 30 → 50 に変更しました"
+    );
+    assert_eq!(setting(&env, "timeline_size"), Some("50".to_string()));
+    assert_eq!(env.config.get_usize_setting("timeline_size"), 50);
+    assert_eq!(command(&env, &admin, &bot, "setting timeline_size").await, "timeline_size = 50");
+    assert!(command(&env, &admin, &bot, "setting max_impression_length").await.contains("未設定"));
+
+    // 範囲外の値・知らないキーは変更しない
+    assert!(command(&env, &admin, &bot, "setting timeline_size 0").await.starts_with("timeline_size は1〜1000の整数で指定してください"));
+    assert!(command(&env, &admin, &bot, "setting timeline_size abc").await.contains("整数で指定"));
+    assert!(command(&env, &admin, &bot, "setting timelien_size 50").await.starts_with("timelien_size は変更できる設定ではありません"));
+    assert_eq!(setting(&env, "timeline_size"), Some("50".to_string()));
+    assert_eq!(setting(&env, "timelien_size"), None);
+
+    // 一覧や個別表示にブラックリストなど変更できない設定は出さない
+    command(&env, &admin, &bot, &format!("blacklist add {}", spammer_npub)).await;
+    let list = command(&env, &admin, &bot, "setting").await;
+    assert!(list.contains("timeline_size = 50"));
+    assert!(!list.contains("blacklist") && !list.contains(&spammer.public_key().to_hex()));
+    assert!(command(&env, &admin, &bot, "setting blacklist").await.starts_with("blacklist は変更できる設定ではありません"));
+}
+
+#[tokio::test]
+async fn token_report_sums_todays_usage() {
+    let mut env = TestEnv::new().await;
+    let bot = env.add_bot("かんりちゃん");
+    let admin = Keys::generate();
+    env.config.bot.admin_pubkeys = vec![admin.public_key().to_hex()];
+
+    let bot_hex = bot.public_key().to_hex();
+    db::record_token_usage(&env.conn(), &bot_hex, "reply", 100, 20, "", "").unwrap();
+    db::record_token_usage(&env.conn(), &bot_hex, "reply", 50, 10, "", "").unwrap();
+    db::record_token_usage(&env.conn(), &bot_hex, "summary", 30, 0, "", "").unwrap();
+
+    let report = command(&env, &admin, &bot, "token report").await;
+    assert!(report.contains("・メンション返信: 180トークン（2回）"), "{}", report);
+    assert!(report.contains("合計: 210トークン（3回）"), "{}", report);
+    assert!(command(&env, &admin, &bot, "token report 7").await.contains("〜"));
+    assert!(command(&env, &admin, &bot, "token report 0").await.starts_with("日数は1以上"));
+}
+
+#[tokio::test]
+async fn admin_commands_are_ignored_for_other_users() {
+    let env = TestEnv::new().await;
+    let bot = env.add_bot("かんりちゃん");
+    let user = Keys::generate();
+
+    let event = EventBuilder::text_note(format!("nostr:{} pause", bot.public_key().to_bech32().unwrap()))
+        .tag(Tag::public_key(bot.public_key()))
+        .sign_with_keys(&user)
+        .unwrap();
+    env.deliver(&event).await;
+
+    assert!(!db::is_global_pause(&env.conn()).unwrap());
+    // コマンドではなく通常のメンションとしてキューに入る
+    assert_eq!(env.count("SELECT COUNT(*) FROM event_queue", []), 1);
+}
@@ -0,0 +1,144 @@
+// 停止中に取りこぼしたメンションのキャッチアップのテスト
+
+mod common;
+
+use bot::db;
+use bot::event_processor::{catch_up_missed_mentions, record_checkpoint, DIRECT_MESSAGE_SUBSCRIPTION, TIMELINE_SUBSCRIPTION};
+use common::TestEnv;
+use nostr_sdk::prelude::*;
+
+/// 停止前に最後に処理したイベントのチェックポイントを置く
+fn set_checkpoint(env: &TestEnv, created_at: i64) {
+    let relay_url = RelayUrl::parse(&env.relay_url).unwrap();
+    db::update_relay_checkpoint(&env.conn(), relay_url.as_str(), TIMELINE_SUBSCRIPTION, created_at).unwrap();
+}
+
+fn mention(user: &Keys, bot: &Keys, created_at: Timestamp) -> Event {
+    EventBuilder::text_note("キャッチアップちゃん 起きてる？")
+        .tag(Tag::public_key(bot.public_key()))
+        .custom_created_at(created_at)
+        .sign_with_keys(user)
+        .unwrap()
+}
+
+#[tokio::test]
+async fn missed_mention_is_replayed_exactly_once() {
+    let env = TestEnv::new().await;
+    let bot = env.add_bot("キャッチアップちゃん");
+    let user = Keys::generate();
+    env.follow(&user, &bot).await;
+
+    let now = Timestamp::now().as_u64() as i64;
+    set_checkpoint(&env, now - 60);
+
+    // 停止中に届いたメンション
+    let missed = mention(&user, &bot, Timestamp::now());
+    env.publish(&missed).await;
+
+    assert_eq!(catch_up_missed_mentions(&env.config, &env.conn()).await.unwrap(), 1);
+    assert_eq!(env.count("SELECT COUNT(*) FROM event_queue", []), 1);
+
+    // 処理前に再起動してもキューには重複しない
+    catch_up_missed_mentions(&env.config, &env.conn()).await.unwrap();
+    assert_eq!(env.count("SELECT COUNT(*) FROM event_queue", []), 1);
+
+    assert_eq!(env.drain_queue().await, 1);
+    let replies = env
+        .fetch(Filter::new().author(bot.public_key()).kind(Kind::TextNote))
+        .await;
+    assert_eq!(replies.len(), 1);
+
+    // 処理済みのメンションは次の起動で再投入されない
+    assert_eq!(catch_up_missed_mentions(&env.config, &env.conn()).await.unwrap(), 0);
+    assert_eq!(env.count("SELECT COUNT(*) FROM event_queue", []), 0);
+}
+
+#[tokio::test]
+async fn first_start_without_checkpoint_does_not_replay() {
+    let env = TestEnv::new().await;
+    let bot = env.add_bot("キャッチアップちゃん");
+    let user = Keys::generate();
+
+    env.publish(&mention(&user, &bot, Timestamp::now())).await;
+
+    assert_eq!(catch_up_missed_mentions(&env.config, &env.conn()).await.unwrap(), 0);
+    assert_eq!(env.count("SELECT COUNT(*) FROM event_queue", []), 0);
+}
+
+#[tokio::test]
+async fn catch_up_is_limited_to_max_lookback() {
+    let env = TestEnv::new().await;
+    let bot = env.add_bot("キャッチアップちゃん");
+    let user = Keys::generate();
+
+    let now = Timestamp::now().as_u64();
+    set_checkpoint(&env, (now - 10 * 24 * 60 * 60) as i64);
+
+    // 遡りの上限（デフォルト6時間）より古いメンションは対象外
+    let too_old = mention(&user, &bot, Timestamp::from(now - 2 * 24 * 60 * 60));
+    env.publish(&too_old).await;
+    let recent = mention(&user, &bot, Timestamp::from(now - 60 * 60));
+    env.publish(&recent).await;
+
+    assert_eq!(catch_up_missed_mentions(&env.config, &env.conn()).await.unwrap(), 1);
+    let queued: String = env
+        .conn()
+        .query_row("SELECT event_json FROM event_queue", [], |row| row.get(0))
+        .unwrap();
+    assert!(queued.contains(&recent.id.to_hex()));
+}
+
+#[tokio::test]
+async fn checkpoint_ignores_future_and_non_timeline_events() {
+    let env = TestEnv::new().await;
+    let relay_url = RelayUrl::parse(&env.relay_url).unwrap();
+    let timeline = SubscriptionId::new(TIMELINE_SUBSCRIPTION);
+    let user = Keys::generate();
+    let checkpoint = || db::get_relay_checkpoint(&env.conn(), relay_url.as_str(), TIMELINE_SUBSCRIPTION).unwrap();
+
+    let now = Timestamp::now().as_u64();
+    let note = EventBuilder::text_note("ふつうの投稿")
+        .custom_created_at(Timestamp::from(now - 60))
+        .sign_with_keys(&user)
+        .unwrap();
+    record_checkpoint(&env.conn(), &relay_url, &timeline, &note);
+    assert_eq!(checkpoint(), Some((now - 60) as i64));
+
+    // 他の購読のイベントやkind 0では進まない
+    let reaction = EventBuilder::reaction(&note, "+").sign_with_keys(&user).unwrap();
+    record_checkpoint(&env.conn(), &relay_url, &SubscriptionId::new(DIRECT_MESSAGE_SUBSCRIPTION), &reaction);
+    let metadata = EventBuilder::metadata(&Metadata::new().name("だれか")).sign_with_keys(&user).unwrap();
+    record_checkpoint(&env.conn(), &relay_url, &timeline, &metadata);
+    assert_eq!(checkpoint(), Some((now - 60) as i64));
+
+    // 未来の日時の投稿では現在時刻までしか進まない
+    let future = EventBuilder::text_note("未来からの投稿")
+        .custom_created_at(Timestamp::from(now + 365 * 24 * 60 * 60))
+        .sign_with_keys(&user)
+        .unwrap();
+    record_checkpoint(&env.conn(), &relay_url, &timeline, &future);
+    assert!(checkpoint().unwrap() <= Timestamp::now().as_u64() as i64);
+}
+
+#[tokio::test]
+async fn missed_channel_message_mention_is_replayed() {
+    let env = TestEnv::new().await;
+    let bot = env.add_bot("チャンネルちゃん");
+    let user = Keys::generate();
+
+    let now = Timestamp::now().as_u64() as i64;
+    set_checkpoint(&env, now - 60);
+
+    let channel_message = EventBuilder::new(Kind::ChannelMessage, "チャンネルちゃん いる？")
+        .tag(Tag::public_key(bot.public_key()))
+        .sign_with_keys(&user)
+        .unwrap();
+    env.publish(&channel_message).await;
+
+    assert_eq!(catch_up_missed_mentions(&env.config, &env.conn()).await.unwrap(), 1);
+    let queued: String = env
+        .conn()
+        .query_row("SELECT event_json FROM event_queue", [], |row| row.get(0))
+        .unwrap();
+    assert!(queued.contains(&channel_message.id.to_hex()));
+}
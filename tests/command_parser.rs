@@ -0,0 +1,142 @@
+// コマンドの構文解析（メンション除去・コマンド語の照合・引数）のテスト
+
+mod common;
+
+use bot::commands::parser::{self, CommandArgs, UsageError};
+use common::TestEnv;
+use nostr_sdk::prelude::*;
+
+fn npub() -> String {
+    Keys::generate().public_key().to_bech32().unwrap()
+}
+
+fn args(content: &str, patterns: &[&str], require_start: bool) -> Option<String> {
+    parser::match_command(content, patterns, require_start).map(|args| args.text().to_string())
+}
+
+#[test]
+fn strips_leading_mentions_and_references() {
+    let mention = npub();
+    assert_eq!(parser::strip_leading_mentions(&format!("nostr:{} help", mention)), "help");
+    assert_eq!(parser::strip_leading_mentions(&format!("@{}\u{3000}検索 Nostr", mention)), "検索 Nostr");
+    assert_eq!(
+        parser::strip_leading_mentions(&format!("nostr:{} nostr:{}\nhelp", mention, npub())),
+        "help"
+    );
+    let note = EventId::all_zeros().to_bech32().unwrap();
+    assert_eq!(parser::strip_leading_mentions(&format!("nostr:{} 占って", note)), "占って");
+    // 文中の参照は残す
+    assert_eq!(
+        parser::strip_leading_mentions(&format!("help nostr:{}", mention)),
+        format!("help nostr:{}", mention)
+    );
+}
+
+#[test]
+fn command_words_match_only_whole_words_at_the_start() {
+    let mention = format!("nostr:{} ", npub());
+
+    // 管理者の new は news・renew では反応しない
+    assert_eq!(args("new\nprompt\n{}", &["new"], true), Some("prompt\n{}".to_string()));
+    assert_eq!(args("news\nprompt", &["new"], true), None);
+    assert_eq!(args("今日のnewsだよ", &["new"], true), None);
+    assert_eq!(args("renew", &["new"], true), None);
+
+    // 文中の help・search では反応しない
+    assert_eq!(args(&format!("{}help", mention), &["help"], true), Some(String::new()));
+    assert_eq!(args(&format!("{}HELP search", mention), &["help"], true), Some("search".to_string()));
+    assert_eq!(args("this is helpful", &["help"], true), None);
+    assert_eq!(args("I need help", &["help"], true), None);
+    assert_eq!(args("research notes", &["search"], true), None);
+    assert_eq!(args("ねえ 検索 Nostr", &["search", "検索"], true), None);
+
+    // 日本語のコマンド語は直後に空白がなくてもよい
+    assert_eq!(args(&format!("{}検索 Nostr 7d", mention), &["search", "検索"], true), Some("Nostr 7d".to_string()));
+    assert_eq!(args("検索Nostr", &["search", "検索"], true), Some("Nostr".to_string()));
+
+    // 複数語のコマンドは空白の数と大文字小文字を問わない
+    assert_eq!(args("Zap   Ranking", &["zap ranking"], true), Some(String::new()));
+    assert_eq!(args("zap rankings", &["zap ranking"], true), None);
+    assert_eq!(args("zapranking", &["zap ranking"], true), None);
+    assert_eq!(args("get kind 0", &["get kind 0"], true), Some(String::new()));
+    assert_eq!(args("get kind 01", &["get kind 0"], true), None);
+}
+
+#[test]
+fn phrases_match_anywhere_and_keep_the_whole_body() {
+    let mention = format!("nostr:{} ", npub());
+    assert_eq!(
+        args(&format!("{}Rustの最新バージョンを調べて", mention), &["調べて"], false),
+        Some("Rustの最新バージョンを調べて".to_string())
+    );
+    assert_eq!(
+        args("30分後に会議ってリマインドして", &["リマインド", "remind"], false),
+        Some("30分後に会議ってリマインドして".to_string())
+    );
+    assert_eq!(args("please Remind me in 2h", &["リマインド", "remind"], false), Some("please Remind me in 2h".to_string()));
+    assert_eq!(args("reminders are nice", &["リマインド", "remind"], false), None);
+
+    // 「silent」は単語として含まれるときだけ
+    assert!(parser::contains_phrase("silent 検索 Nostr", "silent"));
+    assert!(parser::contains_phrase("(silent)", "silent"));
+    assert!(!parser::contains_phrase("silently working", "silent"));
+}
+
+#[test]
+fn arguments_are_tokenized_and_typed() {
+    let mut tokens = CommandArgs::new(r#" 7 "two words"  rest of it "#).tokens().unwrap();
+    assert_eq!(tokens.required::<u32>("日数"), Ok(7));
+    assert_eq!(tokens.required::<String>("名前"), Ok("two words".to_string()));
+    assert_eq!(tokens.rest(), "rest of it");
+    assert!(tokens.is_empty());
+    assert_eq!(tokens.optional::<u32>("件数"), Ok(None));
+    assert_eq!(tokens.required::<u32>("件数"), Err(UsageError::new("件数を指定してください")));
+
+    let mut tokens = CommandArgs::new("abc extra").tokens().unwrap();
+    assert_eq!(
+        tokens.required::<u32>("日数"),
+        Err(UsageError::new("日数の形式が正しくありません: abc"))
+    );
+    assert_eq!(tokens.finish(), Err(UsageError::new("余分な引数があります: extra")));
+
+    assert_eq!(
+        CommandArgs::new(r#"say "hello"#).tokens(),
+        Err(UsageError::new("引用符 \" が閉じられていません"))
+    );
+
+    // 行ごとの引数（0行目はコマンド語と同じ行の残り）
+    let lines = parser::match_command("new\nやさしいBot\n{\"name\":\"bot\"}", &["new"], true).unwrap();
+    assert_eq!(lines.line(1, "プロンプト"), Ok("やさしいBot"));
+    assert_eq!(lines.line(2, "kind 0のJSON"), Ok("{\"name\":\"bot\"}"));
+    assert_eq!(lines.line(3, "その他"), Err(UsageError::new("その他を指定してください")));
+}
+
+#[tokio::test]
+async fn usage_errors_are_replied_with_usage() {
+    let mut env = TestEnv::new().await;
+    let bot = env.add_bot("こまんどちゃん");
+    let admin = Keys::generate();
+    env.config.bot.admin_pubkeys = vec![admin.public_key().to_hex()];
+
+    // kind 0のJSONがない new は、Botを作らずに使い方を返す
+    let command = EventBuilder::text_note(format!("nostr:{} new\nやさしいBot", bot.public_key().to_bech32().unwrap()))
+        .tag(Tag::public_key(bot.public_key()))
+        .sign_with_keys(&admin)
+        .unwrap();
+    env.deliver(&command).await;
+
+    let mut replies = Vec::new();
+    for _ in 0..50 {
+        replies = env
+            .fetch(Filter::new().author(bot.public_key()).kind(Kind::TextNote))
+            .await;
+        if !replies.is_empty() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    assert_eq!(replies.len(), 1);
+    assert!(replies[0].content.starts_with("kind 0のJSONを指定してください\n使い方: new"));
+    assert!(common::event_tags(&replies[0]).iter().any(|(id, _)| *id == command.id));
+    assert_eq!(env.count("SELECT COUNT(*) FROM Persons", []), 1);
+}
@@ -0,0 +1,273 @@
+// 統合テスト用のハーネス
+// インプロセスのモックリレー + スクリプトLLM + 一時ディレクトリのDB/config.ymlで
+// リレーやOpenAI APIなしにイベント処理の流れを動かす
+
+#![allow(dead_code)]
+
+use bot::config::AppConfig;
+use bot::dashboard::BotInfo;
+use bot::db;
+use nostr_relay_builder::MockRelay;
+use nostr_sdk::prelude::*;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, MutexGuard, RwLock};
+
+/// スクリプトLLMがJSON modeで返す応答
+pub const SCRIPTED_REPLY: &str = "テストの返信だよ！";
+
+// DB/config.ymlのパスは環境変数で切り替えるため、テストは1つずつ実行する
+static ENV_LOCK: Mutex<()> = Mutex::const_new(());
+static ENV_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+pub struct TestEnv {
+    pub relay: MockRelay,
+    pub relay_url: String,
+    pub config: AppConfig,
+    pub dir: PathBuf,
+    pub bot_info: Arc<RwLock<BotInfo>>,
+    _guard: MutexGuard<'static, ()>,
+}
+
+impl TestEnv {
+    pub async fn new() -> Self {
+        let guard = ENV_LOCK.lock().await;
+
+        let relay = MockRelay::run().await.expect("モックリレーの起動に失敗");
+        let relay_url = relay.url();
+
+        let dir = std::env::temp_dir().join(format!(
+            "nostrchan-test-{}-{}",
+            std::process::id(),
+            ENV_COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let config_yaml = format!(
+            r#"relay_servers:
+  write: ["{relay}"]
+  read: ["{relay}"]
+  search: ["{relay}"]
+bot:
+  admin_pubkeys: []
+  root_bot_pubkey: ""
+  prompt: テスト用のBotです
+  picture: ""
+  about: ""
+  reaction_percent: 0
+  reaction_freq: 600
+  follower_cache_ttl: 86400
+  timeline_size: 30
+  conversation_limit_count: 5
+  conversation_limit_minutes: 3
+  blacklist: []
+gpt:
+  answer_length: 100
+  timeout: 10
+  search_answer_length: 500
+  gemini_search_timeout: 10
+  recent_context_count: 10
+  summary_threshold: 5000
+  max_summary_tokens: 8000
+  max_impression_length: 500
+  max_mental_diary_length: 1000
+dashboard:
+  port: 0
+llm:
+  provider: scripted
+  model: scripted-model
+  scripted:
+    default_reply: スクリプト応答
+    default_json: '{json}'
+"#,
+            relay = relay_url,
+            json = scripted_json_response(),
+        );
+        let config_path = dir.join("config.yml");
+        std::fs::write(&config_path, &config_yaml).unwrap();
+        let config: AppConfig = serde_yaml::from_str(&config_yaml).unwrap();
+
+        let db_path = dir.join("nostrchan.db");
+        std::env::set_var("NOSTRCHAN_DB_PATH", &db_path);
+        std::env::set_var("NOSTRCHAN_CONFIG_PATH", &config_path);
+
+        let conn = db::connect_at_path(db_path.to_str().unwrap()).unwrap();
+        db::initialize_db(&conn).unwrap();
+        bot::init::initialize_system_settings(&conn, &config).unwrap();
+
+        let bot_info = Arc::new(RwLock::new(BotInfo {
+            online: true,
+            last_reply_timestamp: 0,
+            connected_relays: vec![relay_url.clone()],
+        }));
+
+        Self {
+            relay,
+            relay_url,
+            config,
+            dir,
+            bot_info,
+            _guard: guard,
+        }
+    }
+
+    /// テスト用DBへの接続
+    pub fn conn(&self) -> rusqlite::Connection {
+        db::connect_at_path(self.dir.join("nostrchan.db").to_str().unwrap()).unwrap()
+    }
+
+    /// Botを登録して鍵を返す
+    pub fn add_bot(&self, name: &str) -> Keys {
+        let keys = Keys::generate();
+        let content = serde_json::json!({ "name": name, "display_name": name }).to_string();
+        db::add_person(
+            &self.conn(),
+            &keys.public_key().to_hex(),
+            &keys.secret_key().to_secret_hex(),
+            &format!("あなたは{}です。", name),
+            &content,
+            None,
+        )
+        .unwrap();
+        keys
+    }
+
+    /// リレーに接続したクライアントを作成
+    pub async fn client(&self, keys: &Keys) -> Client {
+        let client = Client::new(keys.clone());
+        client.add_relay(&self.relay_url).await.unwrap();
+        client.connect().await;
+        client
+    }
+
+    /// イベントをリレーに公開
+    pub async fn publish(&self, event: &Event) {
+        let client = self.client(&Keys::generate()).await;
+        let output = client.send_event(event).await.expect("イベントの公開に失敗");
+        assert!(!output.success.is_empty(), "リレーがイベントを受理しませんでした");
+        client.shutdown().await;
+    }
+
+    /// ユーザーがBotをフォローしているkind 3を公開
+    pub async fn follow(&self, user: &Keys, bot: &Keys) {
+        let event = EventBuilder::contact_list([Contact::new(bot.public_key())])
+            .sign_with_keys(user)
+            .unwrap();
+        self.publish(&event).await;
+    }
+
+    /// リレーからイベントを取得
+    pub async fn fetch(&self, filter: Filter) -> Vec<Event> {
+        let client = self.client(&Keys::generate()).await;
+        let events = client
+            .fetch_events(filter, Duration::from_secs(5))
+            .await
+            .expect("イベントの取得に失敗");
+        client.shutdown().await;
+        events.into_iter().collect()
+    }
+
+    /// main.rsと同じ購読でリレーから受信し、受信処理に渡す
+    pub async fn deliver(&self, event: &Event) {
+        let listener = self.client(&Keys::generate()).await;
+        let subscription = Filter::new()
+            .kinds([
+                Kind::TextNote,
+                Kind::ChannelMessage,
+                Kind::Metadata,
+                Kind::GiftWrap,
+                Kind::EncryptedDirectMessage,
+                Kind::Reaction,
+                Kind::ZapReceipt,
+            ])
+            .since(event.created_at);
+        listener.subscribe(subscription, None).await.unwrap();
+        let mut notifications = listener.notifications();
+
+        self.publish(event).await;
+
+        let received = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Ok(RelayPoolNotification::Event { event: received, .. }) = notifications.recv().await {
+                    if received.id == event.id {
+                        return received;
+                    }
+                }
+            }
+        })
+        .await
+        .expect("リレーからイベントが配信されませんでした");
+        listener.shutdown().await;
+
+        bot::event_processor::handle_incoming_event(&self.config, &self.conn(), &received)
+            .await
+            .unwrap();
+    }
+
+    /// キューに溜まったイベントを全て処理
+    pub async fn drain_queue(&self) -> usize {
+        let mut processed = 0;
+        while bot::event_processor::process_next_queued_event(&self.config, Arc::clone(&self.bot_info)).await {
+            processed += 1;
+        }
+        processed
+    }
+
+    /// 任意のSQLで件数を数える
+    pub fn count(&self, sql: &str, params: impl rusqlite::Params) -> i64 {
+        self.conn().query_row(sql, params, |row| row.get(0)).unwrap()
+    }
+}
+
+impl Drop for TestEnv {
+    fn drop(&mut self) {
+        self.relay.shutdown();
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// 返信＋ユーザー属性＋心境のJSON（GptResponseWithMentalDiary形式）
+pub fn scripted_json_response() -> String {
+    serde_json::json!({
+        "reply": SCRIPTED_REPLY,
+        "user_attributes": {
+            "nickname": "テストさん",
+            "likes": ["テスト"],
+            "dislikes": [],
+            "hobbies": [],
+            "frequent_topics": [],
+            "impression": "テストに熱心な人"
+        },
+        "mental_diary": {
+            "mood": "上機嫌",
+            "current_interests": ["テスト"]
+        }
+    })
+    .to_string()
+}
+
+/// e タグ（marker付き）の一覧
+pub fn event_tags(event: &Event) -> Vec<(EventId, Option<Marker>)> {
+    event
+        .tags
+        .iter()
+        .filter_map(|tag| match tag.as_standardized() {
+            Some(TagStandard::Event { event_id, marker, .. }) => Some((*event_id, *marker)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// p タグの一覧
+pub fn pubkey_tags(event: &Event) -> Vec<PublicKey> {
+    event
+        .tags
+        .iter()
+        .filter_map(|tag| match tag.as_standardized() {
+            Some(TagStandard::PublicKey { public_key, .. }) => Some(*public_key),
+            _ => None,
+        })
+        .collect()
+}
@@ -0,0 +1,292 @@
+// ダッシュボードのログイン（固定トークン・NIP-98）と権限のテスト
+
+mod common;
+
+use bot::dashboard::{self, AuthState, DashboardState};
+use common::TestEnv;
+use nostr_sdk::prelude::*;
+use reqwest::{header, StatusCode};
+use std::sync::Arc;
+use std::time::Instant;
+
+const ADMIN_TOKEN: &str = "admin-token-for-test";
+const VIEWER_TOKEN: &str = "viewer-token-for-test";
+
+struct Dashboard {
+    base: String,
+    http: reqwest::Client,
+}
+
+impl Dashboard {
+    /// ローカルの空いているポートでダッシュボードを起動（public_urlが未設定ならそのアドレスにする）
+    async fn start(env: &TestEnv) -> Self {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let mut config = env.config.clone();
+        config.dashboard.auth.public_url.get_or_insert_with(|| base.clone());
+        let state = DashboardState {
+            db_path: env.dir.join("test.db").display().to_string(),
+            start_time: Arc::new(Instant::now()),
+            bot_info: env.bot_info.clone(),
+            auth: Arc::new(AuthState::new(&config)),
+        };
+        tokio::spawn(async move {
+            axum::serve(listener, dashboard::build_router(state)).await.unwrap();
+        });
+        Self {
+            base,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base, path)
+    }
+
+    /// ログインしてセッションCookieを返す
+    async fn login_with_token(&self, token: &str) -> reqwest::Response {
+        self.http
+            .post(self.url("/api/auth/login"))
+            .json(&serde_json::json!({ "token": token }))
+            .send()
+            .await
+            .unwrap()
+    }
+
+    async fn login_with_nip98(&self, keys: &Keys, url: &str) -> reqwest::Response {
+        self.login_with_authorization(&nip98_authorization(keys, url).await).await
+    }
+
+    async fn login_with_authorization(&self, authorization: &str) -> reqwest::Response {
+        self.http
+            .post(self.url("/api/auth/login"))
+            .header(header::AUTHORIZATION, authorization)
+            .send()
+            .await
+            .unwrap()
+    }
+
+    async fn get(&self, path: &str, cookie: &str) -> reqwest::Response {
+        self.http.get(self.url(path)).header(header::COOKIE, cookie).send().await.unwrap()
+    }
+
+    async fn post(&self, path: &str, cookie: &str, body: serde_json::Value) -> reqwest::Response {
+        self.http
+            .post(self.url(path))
+            .header(header::COOKIE, cookie)
+            .json(&body)
+            .send()
+            .await
+            .unwrap()
+    }
+}
+
+async fn nip98_authorization(keys: &Keys, url: &str) -> String {
+    nip98::HttpData::new(Url::parse(url).unwrap(), nip98::HttpMethod::POST)
+        .to_authorization(keys)
+        .await
+        .unwrap()
+}
+
+/// レスポンスのSet-CookieからCookieヘッダーの値を作る
+fn session_cookie(response: &reqwest::Response) -> String {
+    let set_cookie = response.headers()[header::SET_COOKIE].to_str().unwrap();
+    set_cookie.split(';').next().unwrap().to_string()
+}
+
+fn configure(env: &mut TestEnv, admin: &Keys, viewer: &Keys) {
+    env.config.bot.admin_pubkeys = vec![admin.public_key().to_hex()];
+    env.config.dashboard.auth.admin_token = Some(ADMIN_TOKEN.to_string());
+    env.config.dashboard.auth.viewer_token = Some(VIEWER_TOKEN.to_string());
+    env.config.dashboard.auth.viewer_pubkeys = vec![viewer.public_key().to_hex()];
+}
+
+#[tokio::test]
+async fn viewer_can_read_but_not_change_or_see_secrets() {
+    let mut env = TestEnv::new().await;
+    let bot = env.add_bot("だっしゅちゃん");
+    configure(&mut env, &Keys::generate(), &Keys::generate());
+    let dashboard = Dashboard::start(&env).await;
+
+    // ログインしていなければAPIは使えない（画面は表示できる）
+    assert_eq!(dashboard.get("/api/bots", "").await.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(dashboard.get("/api/auth/me", "").await.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(dashboard.login_with_token("wrong").await.status(), StatusCode::UNAUTHORIZED);
+
+    let login = dashboard.login_with_token(VIEWER_TOKEN).await;
+    assert_eq!(login.status(), StatusCode::OK);
+    let cookie = session_cookie(&login);
+    let me: serde_json::Value = dashboard.get("/api/auth/me", &cookie).await.json().await.unwrap();
+    assert_eq!(me["role"], "viewer");
+
+    let bots: serde_json::Value = dashboard.get("/api/bots", &cookie).await.json().await.unwrap();
+    assert_eq!(bots[0]["pubkey"], bot.public_key().to_hex());
+    assert!(bots[0]["secretkey"].is_null());
+    assert_eq!(dashboard.get("/api/bots?include_secret=true", &cookie).await.status(), StatusCode::FORBIDDEN);
+    assert_eq!(dashboard.get("/api/bots/generate-key", &cookie).await.status(), StatusCode::FORBIDDEN);
+    assert_eq!(
+        dashboard.post("/api/global-pause", &cookie, serde_json::json!({ "paused": true })).await.status(),
+        StatusCode::FORBIDDEN
+    );
+
+    // ログアウトするとセッションは使えない
+    let logout = dashboard
+        .http
+        .post(dashboard.url("/api/auth/logout"))
+        .header(header::COOKIE, &cookie)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(logout.status(), StatusCode::NO_CONTENT);
+    assert_eq!(dashboard.get("/api/bots", &cookie).await.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn admin_can_change_settings_and_request_secrets() {
+    let mut env = TestEnv::new().await;
+    let bot = env.add_bot("だっしゅちゃん");
+    configure(&mut env, &Keys::generate(), &Keys::generate());
+    let dashboard = Dashboard::start(&env).await;
+
+    let login = dashboard.login_with_token(ADMIN_TOKEN).await;
+    // httpで公開しているときはSecureを付けない
+    assert!(!login.headers()[header::SET_COOKIE].to_str().unwrap().contains("Secure"));
+    let cookie = session_cookie(&login);
+    let bots: serde_json::Value = dashboard.get("/api/bots", &cookie).await.json().await.unwrap();
+    assert!(bots[0]["secretkey"].is_null());
+    let bots: serde_json::Value = dashboard.get("/api/bots?include_secret=true", &cookie).await.json().await.unwrap();
+    assert_eq!(bots[0]["secretkey"], bot.secret_key().to_secret_hex());
+
+    let pause = dashboard.post("/api/global-pause", &cookie, serde_json::json!({ "paused": true })).await;
+    assert_eq!(pause.status(), StatusCode::OK);
+    assert!(bot::db::is_global_pause(&env.conn()).unwrap());
+
+    // 秘密鍵を省略した更新では秘密鍵を変えない
+    let update = dashboard
+        .http
+        .put(dashboard.url(&format!("/api/bots/{}", bot.public_key().to_hex())))
+        .header(header::COOKIE, &cookie)
+        .json(&serde_json::json!({ "prompt": "新しいプロンプト", "content": "{}" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(update.status(), StatusCode::OK);
+    let updated: serde_json::Value = update.json().await.unwrap();
+    assert!(updated["secretkey"].is_null());
+    let person = bot::db::find_person(&env.conn(), &bot.public_key().to_hex()).unwrap().unwrap();
+    assert_eq!(person.prompt, "新しいプロンプト");
+    assert_eq!(person.secretkey, bot.secret_key().to_secret_hex());
+
+    // Bot個別設定は範囲外の値や、無視と絵文字リアクションの合計が100%を超えると保存しない
+    let put_overrides = |overrides: serde_json::Value| {
+        dashboard
+            .http
+            .put(dashboard.url(&format!("/api/bots/{}", bot.public_key().to_hex())))
+            .header(header::COOKIE, &cookie)
+            .json(&serde_json::json!({ "prompt": "新しいプロンプト", "content": "{}", "overrides": overrides }))
+            .send()
+    };
+    for invalid in [
+        serde_json::json!({ "temperature": 5.0 }),
+        serde_json::json!({ "max_tokens": 0 }),
+        serde_json::json!({ "answer_length": 5 }),
+        serde_json::json!({ "reaction_percent": 150 }),
+        serde_json::json!({ "ignore_percent": 60, "emoji_reaction_percent": 50 }),
+    ] {
+        assert_eq!(put_overrides(invalid).await.unwrap().status(), StatusCode::BAD_REQUEST);
+    }
+    let valid = put_overrides(serde_json::json!({ "temperature": 0.7, "ignore_percent": 50, "emoji_reaction_percent": 50 }));
+    assert_eq!(valid.await.unwrap().status(), StatusCode::OK);
+    let person = bot::db::find_person(&env.conn(), &bot.public_key().to_hex()).unwrap().unwrap();
+    assert_eq!((person.overrides.temperature, person.overrides.ignore_percent), (Some(0.7), Some(50)));
+
+    // スクリプト向けに Authorization: Bearer でも使える
+    let bearer = dashboard
+        .http
+        .get(dashboard.url("/api/bots/generate-key"))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(bearer.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn nip98_login_uses_admin_and_viewer_pubkeys() {
+    let mut env = TestEnv::new().await;
+    let admin = Keys::generate();
+    let viewer = Keys::generate();
+    configure(&mut env, &admin, &viewer);
+    let dashboard = Dashboard::start(&env).await;
+    let login_url = dashboard.url("/api/auth/login");
+
+    let login = dashboard.login_with_nip98(&admin, &login_url).await;
+    assert_eq!(login.status(), StatusCode::OK);
+    let cookie = session_cookie(&login);
+    let me: serde_json::Value = dashboard.get("/api/auth/me", &cookie).await.json().await.unwrap();
+    assert_eq!(me["role"], "admin");
+    assert_eq!(me["pubkey"], admin.public_key().to_hex());
+
+    let login = dashboard.login_with_nip98(&viewer, &login_url).await;
+    let me: serde_json::Value = dashboard.get("/api/auth/me", &session_cookie(&login)).await.json().await.unwrap();
+    assert_eq!(me["role"], "viewer");
+
+    // 権限のないpubkey、別のURLに向けた署名は拒否する
+    assert_eq!(dashboard.login_with_nip98(&Keys::generate(), &login_url).await.status(), StatusCode::FORBIDDEN);
+    assert_eq!(
+        dashboard.login_with_nip98(&admin, "http://example.com/api/auth/login").await.status(),
+        StatusCode::UNAUTHORIZED
+    );
+}
+
+#[tokio::test]
+async fn nip98_event_can_be_used_only_once() {
+    let mut env = TestEnv::new().await;
+    let admin = Keys::generate();
+    configure(&mut env, &admin, &Keys::generate());
+    let dashboard = Dashboard::start(&env).await;
+
+    let authorization = nip98_authorization(&admin, &dashboard.url("/api/auth/login")).await;
+    assert_eq!(dashboard.login_with_authorization(&authorization).await.status(), StatusCode::OK);
+    assert_eq!(dashboard.login_with_authorization(&authorization).await.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn https_public_url_is_used_for_nip98_and_secure_cookie() {
+    let mut env = TestEnv::new().await;
+    let admin = Keys::generate();
+    configure(&mut env, &admin, &Keys::generate());
+    env.config.dashboard.auth.public_url = Some("https://bot.example.com/".to_string());
+    let dashboard = Dashboard::start(&env).await;
+
+    // Hostヘッダーではなくpublic_urlと照合する
+    let login = dashboard.login_with_nip98(&admin, "https://bot.example.com/api/auth/login").await;
+    assert_eq!(login.status(), StatusCode::OK);
+    assert!(login.headers()[header::SET_COOKIE].to_str().unwrap().ends_with("; Secure"));
+    assert_eq!(
+        dashboard.login_with_nip98(&admin, &dashboard.url("/api/auth/login")).await.status(),
+        StatusCode::UNAUTHORIZED
+    );
+}
+
+#[tokio::test]
+async fn login_requires_public_url() {
+    let mut env = TestEnv::new().await;
+    configure(&mut env, &Keys::generate(), &Keys::generate());
+    let auth = Arc::new(AuthState::new(&env.config));
+    let db_path = env.dir.join("test.db").display().to_string();
+    let started = dashboard::start_dashboard("127.0.0.1".to_string(), 0, db_path, env.bot_info.clone(), auth).await;
+    assert!(started.unwrap_err().to_string().contains("public_url"));
+}
+
+#[tokio::test]
+async fn disabled_auth_allows_everything() {
+    let mut env = TestEnv::new().await;
+    env.add_bot("だっしゅちゃん");
+    env.config.dashboard.auth.enabled = false;
+    let dashboard = Dashboard::start(&env).await;
+
+    assert_eq!(dashboard.get("/api/bots", "").await.status(), StatusCode::OK);
+    let me: serde_json::Value = dashboard.get("/api/auth/me", "").await.json().await.unwrap();
+    assert_eq!(me["auth_enabled"], false);
+}
@@ -0,0 +1,215 @@
+// 暗号化DM（NIP-17 / NIP-04）の受信から返信・記録までのテスト
+
+mod common;
+
+use bot::config::ScriptedRule;
+use bot::conversation;
+use bot::db;
+use bot::gpt;
+use common::{scripted_json_response, TestEnv, SCRIPTED_REPLY};
+use nostr_sdk::prelude::*;
+
+/// Botの会話ログのうちDMとして記録された件数
+fn dm_log_count(env: &TestEnv, bot: &Keys) -> i64 {
+    env.count(
+        "SELECT COUNT(*) FROM conversation_logs WHERE bot_pubkey = ? AND is_direct_message = 1",
+        [bot.public_key().to_hex()],
+    )
+}
+
+/// DMの本文がエアリプ・公開の会話履歴に出てこないこと
+fn assert_not_public(env: &TestEnv, bot: &Keys, secret: &str) {
+    let conn = env.conn();
+    let timeline = conversation::build_japanese_timeline_for_air_reply(&conn, 100).unwrap();
+    assert!(timeline.iter().all(|e| !e.content.contains(secret)));
+    let public = db::get_conversation_timeline(&conn, &bot.public_key().to_hex(), 100).unwrap();
+    assert!(public.iter().all(|e| !e.content.contains(secret) && e.content != SCRIPTED_REPLY));
+}
+
+#[tokio::test]
+async fn nip17_dm_is_answered_with_gift_wrap() {
+    let env = TestEnv::new().await;
+    let bot = env.add_bot("ひみつちゃん");
+    let user = Keys::generate();
+    env.follow(&user, &bot).await;
+
+    let secret = "ここだけの相談なんだけど、聞いてくれる？";
+    let dm = EventBuilder::private_msg(&user, bot.public_key(), secret, [])
+        .await
+        .unwrap();
+    env.deliver(&dm).await;
+    assert_eq!(env.drain_queue().await, 1);
+
+    // ユーザー宛てのGift Wrapが届き、Botからの返信として復号できる
+    let wraps = env
+        .fetch(Filter::new().kind(Kind::GiftWrap).pubkey(user.public_key()))
+        .await;
+    assert_eq!(wraps.len(), 1);
+    let unwrapped = UnwrappedGift::from_gift_wrap(&user, &wraps[0]).await.unwrap();
+    assert_eq!(unwrapped.sender, bot.public_key());
+    assert_eq!(unwrapped.rumor.kind, Kind::PrivateDirectMessage);
+    assert_eq!(unwrapped.rumor.content, SCRIPTED_REPLY);
+
+    // 公開の返信は出さない
+    let notes = env
+        .fetch(Filter::new().author(bot.public_key()).kind(Kind::TextNote))
+        .await;
+    assert!(notes.is_empty());
+
+    // DM履歴として記録され、DM用カテゴリでトークンが記録される
+    assert_eq!(dm_log_count(&env, &bot), 2);
+    assert_eq!(
+        env.count(
+            "SELECT COUNT(*) FROM token_usage tu JOIN token_categories tc ON tu.category_id = tc.id
+             WHERE tu.bot_pubkey = ? AND tc.name = 'direct_message'",
+            [bot.public_key().to_hex()]
+        ),
+        1
+    );
+    let history = db::get_direct_message_timeline(
+        &env.conn(),
+        &bot.public_key().to_hex(),
+        &user.public_key().to_hex(),
+        10,
+    )
+    .unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].content, secret);
+
+    assert_not_public(&env, &bot, secret);
+}
+
+#[tokio::test]
+async fn nip04_dm_is_answered_with_nip04() {
+    let env = TestEnv::new().await;
+    let bot = env.add_bot("レガシーちゃん");
+    let user = Keys::generate();
+    env.follow(&user, &bot).await;
+
+    let secret = "昔ながらのDMです";
+    let encrypted = nip04::encrypt(user.secret_key(), &bot.public_key(), secret).unwrap();
+    let dm = EventBuilder::new(Kind::EncryptedDirectMessage, encrypted)
+        .tag(Tag::public_key(bot.public_key()))
+        .sign_with_keys(&user)
+        .unwrap();
+    env.deliver(&dm).await;
+    assert_eq!(env.drain_queue().await, 1);
+
+    let replies = env
+        .fetch(Filter::new().author(bot.public_key()).kind(Kind::EncryptedDirectMessage))
+        .await;
+    assert_eq!(replies.len(), 1);
+    let decrypted = nip04::decrypt(user.secret_key(), &bot.public_key(), &replies[0].content).unwrap();
+    assert_eq!(decrypted, SCRIPTED_REPLY);
+
+    assert_eq!(dm_log_count(&env, &bot), 2);
+    assert_not_public(&env, &bot, secret);
+}
+
+#[tokio::test]
+async fn dm_to_unknown_pubkey_is_ignored() {
+    let env = TestEnv::new().await;
+    env.add_bot("だれかちゃん");
+    let user = Keys::generate();
+    let stranger = Keys::generate();
+
+    let dm = EventBuilder::private_msg(&user, stranger.public_key(), "こんにちは", [])
+        .await
+        .unwrap();
+    env.deliver(&dm).await;
+    assert_eq!(env.drain_queue().await, 1);
+
+    assert_eq!(env.count("SELECT COUNT(*) FROM conversation_logs", []), 0);
+    assert_eq!(env.count("SELECT COUNT(*) FROM token_usage", []), 0);
+}
+
+#[tokio::test]
+async fn dm_does_not_leak_into_later_prompts() {
+    let mut env = TestEnv::new().await;
+    let secret = "実は転職活動中なんだ";
+    // DMへの応答の心境・ユーザー属性にDMの内容が書かれる
+    let mut response: serde_json::Value = serde_json::from_str(&scripted_json_response()).unwrap();
+    response["user_attributes"]["impression"] = secret.into();
+    response["mental_diary"]["concerns"] = secret.into();
+    env.config.llm.scripted.rules.push(ScriptedRule {
+        contains: Some(secret.to_string()),
+        json_mode: Some(true),
+        response: response.to_string(),
+        tool_call: None,
+    });
+    let bot = env.add_bot("ないしょちゃん");
+    let user = Keys::generate();
+    env.follow(&user, &bot).await;
+
+    let dm = EventBuilder::private_msg(&user, bot.public_key(), secret, [])
+        .await
+        .unwrap();
+    env.deliver(&dm).await;
+    assert_eq!(env.drain_queue().await, 1);
+    assert_eq!(dm_log_count(&env, &bot), 2);
+
+    // 後のエアリプ・メンション返信のプロンプトにDMの内容が出てこない
+    let person = db::get_person(&env.conn(), &bot.public_key().to_hex()).unwrap();
+    gpt::get_air_reply_with_mental_diary(&person, "あなたはないしょちゃんです。", "今日はいい天気", false, None, None, &env.config)
+        .await
+        .unwrap();
+    gpt::get_reply_with_mental_diary(
+        &person,
+        &user.public_key().to_hex(),
+        "あなたはないしょちゃんです。",
+        "最近どう？",
+        None,
+        None,
+        None,
+        &env.config,
+    )
+    .await
+    .unwrap();
+    let prompts: Vec<String> = env
+        .conn()
+        .prepare(
+            "SELECT tu.prompt_text FROM token_usage tu JOIN token_categories tc ON tu.category_id = tc.id
+             WHERE tc.name IN ('air_reply', 'reply')",
+        )
+        .unwrap()
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(prompts.len(), 2);
+    assert!(prompts.iter().all(|prompt| !prompt.contains(secret)));
+}
+
+#[tokio::test]
+async fn dm_subscription_follows_new_bots() {
+    let env = TestEnv::new().await;
+    env.add_bot("さいしょちゃん");
+    let listener = env.client(&Keys::generate()).await;
+    let mut subscriptions = bot::event_processor::BotSubscriptions::default();
+    assert!(subscriptions.refresh(&listener, &env.conn()).await.unwrap());
+    assert!(!subscriptions.refresh(&listener, &env.conn()).await.unwrap());
+
+    // 起動後に追加したBotも購読し直せばDMを受け取れる
+    let bot = env.add_bot("あとからちゃん");
+    assert!(subscriptions.refresh(&listener, &env.conn()).await.unwrap());
+    let mut notifications = listener.notifications();
+    let user = Keys::generate();
+    let dm = EventBuilder::private_msg(&user, bot.public_key(), "はじめまして", [])
+        .await
+        .unwrap();
+    env.publish(&dm).await;
+
+    let subscription_id = tokio::time::timeout(std::time::Duration::from_secs(5), async {
+        loop {
+            if let Ok(RelayPoolNotification::Event { subscription_id, event, .. }) = notifications.recv().await {
+                if event.id == dm.id {
+                    return subscription_id;
+                }
+            }
+        }
+    })
+    .await
+    .expect("追加したBot宛てのDMが配信されませんでした");
+    assert_eq!(subscription_id.as_str(), bot::event_processor::DIRECT_MESSAGE_SUBSCRIPTION);
+    listener.shutdown().await;
+}
@@ -0,0 +1,169 @@
+// 埋め込み（ベクトル化）と類似検索のテスト
+// ネットワーク不要のscriptedプロバイダーを使う
+
+mod common;
+
+use bot::config::EmbeddingProviderKind;
+use bot::db;
+use bot::embedding::{self, ScriptedEmbeddingProvider, SimilarityQuery};
+use common::TestEnv;
+use nostr_sdk::prelude::*;
+
+async fn scripted_env() -> TestEnv {
+    let mut env = TestEnv::new().await;
+    env.config.embedding.provider = EmbeddingProviderKind::Scripted;
+    env.config.embedding.model = "scripted-embedding".to_string();
+    env
+}
+
+/// userの投稿をbotとの会話として記録する
+fn record_conversation(env: &TestEnv, user: &Keys, bot: &Keys, content: &str, created_at: u64) -> i64 {
+    let conn = env.conn();
+    let event = EventBuilder::text_note(content)
+        .tag(Tag::public_key(bot.public_key()))
+        .custom_created_at(Timestamp::from(created_at))
+        .sign_with_keys(user)
+        .unwrap();
+    let id = db::insert_event(&conn, &event, Some("ja")).unwrap();
+    db::insert_conversation_log(&conn, &bot.public_key().to_hex(), id, None, None, false, false).unwrap();
+    id
+}
+
+fn insert_summary(env: &TestEnv, bot: &Keys, user: &Keys, summary: &str) -> i64 {
+    db::insert_conversation_summary(
+        &env.conn(),
+        &bot.public_key().to_hex(),
+        summary,
+        "",
+        Some(&[user.public_key().to_hex()]),
+        0,
+        0,
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn pending_events_and_summaries_are_vectorized() {
+    let env = scripted_env().await;
+    let bot = env.add_bot("べくとるちゃん");
+    let user = Keys::generate();
+    let first = record_conversation(&env, &user, &bot, "ラーメンが食べたい", 1_700_000_000);
+    record_conversation(&env, &user, &bot, "今日は雨が降っている", 1_700_000_100);
+    insert_summary(&env, &bot, &user, "ラーメンの話をした");
+    // Botとの会話でないタイムラインの投稿はベクトル化しない
+    let timeline_note = EventBuilder::text_note("ただのつぶやき").sign_with_keys(&user).unwrap();
+    db::insert_event(&env.conn(), &timeline_note, Some("ja")).unwrap();
+
+    let stats = db::get_dashboard_stats(&env.conn()).unwrap();
+    assert_eq!(stats.vectorized_events, 0);
+    assert_eq!(stats.pending_vectorization, 2);
+
+    assert_eq!(embedding::vectorize_pending(&env.config, &env.conn()).await.unwrap(), 3);
+    // 2回目は何もしない
+    assert_eq!(embedding::vectorize_pending(&env.config, &env.conn()).await.unwrap(), 0);
+
+    let stats = db::get_dashboard_stats(&env.conn()).unwrap();
+    assert_eq!(stats.vectorized_events, 2);
+    assert_eq!(stats.pending_vectorization, 0);
+    let vectors = db::get_embeddings(&env.conn(), db::EmbeddingSource::Event, "scripted-embedding", &[first]).unwrap();
+    assert_eq!(vectors[&first], ScriptedEmbeddingProvider::vectorize("ラーメンが食べたい"));
+
+    // 投稿を削除するとベクトルも消える
+    let conn = env.conn();
+    conn.execute("DELETE FROM conversation_logs WHERE event_ref_id = ?", [first]).unwrap();
+    conn.execute("DELETE FROM events WHERE id = ?", [first]).unwrap();
+    assert_eq!(env.count("SELECT COUNT(*) FROM embeddings WHERE source_type = 'event'", []), 1);
+}
+
+#[tokio::test]
+async fn failing_rows_do_not_stall_vectorization() {
+    let env = scripted_env().await;
+    let bot = env.add_bot("べくとるちゃん");
+    let user = Keys::generate();
+    let too_long = "あ".repeat(embedding::scripted::MAX_INPUT_CHARS + 1);
+    let bad_summary = insert_summary(&env, &bot, &user, &too_long);
+    let bad_event = record_conversation(&env, &user, &bot, &too_long, 1_700_000_000);
+    record_conversation(&env, &user, &bot, "ラーメンが食べたい", 1_700_000_100);
+
+    // 長すぎる行だけ失敗し、同じバッチの他の行や投稿はベクトル化される
+    assert_eq!(embedding::vectorize_pending(&env.config, &env.conn()).await.unwrap(), 1);
+    let failures = |source: &str, id: i64| {
+        env.count(
+            "SELECT COALESCE(SUM(attempts), 0) FROM embedding_failures WHERE source_type = ? AND source_id = ?",
+            rusqlite::params![source, id],
+        )
+    };
+    assert_eq!((failures("summary", bad_summary), failures("event", bad_event)), (1, 1));
+
+    // 上限回数まで失敗したら以後は対象にしない
+    for _ in 1..db::MAX_EMBEDDING_ATTEMPTS {
+        assert_eq!(embedding::vectorize_pending(&env.config, &env.conn()).await.unwrap(), 0);
+    }
+    assert_eq!(failures("event", bad_event), db::MAX_EMBEDDING_ATTEMPTS);
+    assert!(db::get_events_without_embedding(&env.conn(), "scripted-embedding", 10).unwrap().is_empty());
+    assert!(db::get_summaries_without_embedding(&env.conn(), "scripted-embedding", 10).unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn similar_old_events_are_kept_in_timeline() {
+    let env = scripted_env().await;
+    let bot = env.add_bot("べくとるちゃん");
+    let user = Keys::generate();
+    record_conversation(&env, &user, &bot, "駅前のラーメン屋が美味しかった", 1_700_000_000);
+    for (i, content) in ["今日は雨", "猫がかわいい", "明日は晴れるかな", "眠い", "本を読んだ"].iter().enumerate() {
+        record_conversation(&env, &user, &bot, content, 1_700_000_100 + i as u64 * 100);
+    }
+    embedding::vectorize_pending(&env.config, &env.conn()).await.unwrap();
+
+    let query = SimilarityQuery {
+        model: "scripted-embedding".to_string(),
+        vector: ScriptedEmbeddingProvider::vectorize("ラーメン屋のおすすめある？"),
+        threshold: 0.3,
+    };
+    let bot_hex = bot.public_key().to_hex();
+
+    // 時系列のみだと古いラーメンの話は入らない
+    let recent = bot::conversation::build_conversation_timeline_with_diversity(&env.conn(), &bot_hex, None, 3).unwrap();
+    assert!(!recent.contains("ラーメン"));
+
+    // 類似検索ありなら入り、残りは直近の発言で埋まる
+    let timeline = bot::conversation::build_conversation_timeline_with_diversity(&env.conn(), &bot_hex, Some(&query), 3).unwrap();
+    assert_eq!(timeline.lines().count(), 3);
+    assert!(timeline.contains("駅前のラーメン屋が美味しかった"));
+    assert!(timeline.contains("本を読んだ"));
+}
+
+#[tokio::test]
+async fn related_summary_is_added_to_reply_prompt() {
+    let env = scripted_env().await;
+    let bot = env.add_bot("べくとるちゃん");
+    let user = Keys::generate();
+    env.follow(&user, &bot).await;
+    insert_summary(&env, &bot, &user, "駅前のラーメン屋の味について話した");
+    insert_summary(&env, &bot, &user, "週末の天気と洗濯について話した");
+    db::set_system_setting(&env.conn(), "rag_similarity_threshold", "0.3").unwrap();
+    embedding::vectorize_pending(&env.config, &env.conn()).await.unwrap();
+
+    let mention = EventBuilder::text_note("べくとるちゃん 駅前のラーメン屋また行きたい")
+        .tag(Tag::public_key(bot.public_key()))
+        .sign_with_keys(&user)
+        .unwrap();
+    env.deliver(&mention).await;
+    assert_eq!(env.drain_queue().await, 1);
+
+    let prompt: String = env
+        .conn()
+        .query_row("SELECT prompt_text FROM token_usage", [], |row| row.get(0))
+        .unwrap();
+    assert!(prompt.contains("【関連する過去の会話の要約】"));
+    assert!(prompt.contains("駅前のラーメン屋の味について話した"));
+    assert!(!prompt.contains("週末の天気と洗濯について話した"));
+}
+
+#[test]
+fn cosine_similarity_handles_mismatched_and_zero_vectors() {
+    assert!((embedding::cosine_similarity(&[1.0, 0.0], &[1.0, 0.0]) - 1.0).abs() < 1e-9);
+    assert!(embedding::cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]).abs() < 1e-9);
+    assert_eq!(embedding::cosine_similarity(&[1.0, 0.0], &[1.0]), 0.0);
+    assert_eq!(embedding::cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+}
@@ -0,0 +1,200 @@
+// Botの投稿へのリアクション（kind 7）・Zap（kind 9735）の記録とZapのお礼のテスト
+
+mod common;
+
+use bot::db;
+use common::{pubkey_tags, TestEnv, SCRIPTED_REPLY};
+use nostr_sdk::prelude::*;
+
+/// BOLT11のテストベクタ（2500u = 250,000 sats）
+const INVOICE_250K_SATS: &str = "lnbc2500u1pvjluezsp5zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygspp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdq5xysxxatsyp3k7enxv4jsxqzpu9qrsgquk0rl77nj30yxdy8j9vdx85fkpmdla2087ne0xh8nhedh8w27kyke0lp53ut353s06fv3qfegext0eh0ymjpf39tuven09sam30g4vgpfna3rh";
+
+fn bot_note(bot: &Keys) -> Event {
+    EventBuilder::text_note("今日もいい天気！").sign_with_keys(bot).unwrap()
+}
+
+/// Botにlud16を設定し、そのLNURLサーバーのZap用の鍵を返す（問い合わせ結果はキャッシュ済みにする）
+fn set_zapper(env: &TestEnv, bot: &Keys) -> Keys {
+    let zapper = Keys::generate();
+    let lud16 = "bot@zap.example.com";
+    let bot_pubkey = bot.public_key().to_hex();
+    let conn = env.conn();
+    let content: String = conn
+        .query_row("SELECT content FROM Persons WHERE pubkey = ?", [&bot_pubkey], |row| row.get(0))
+        .unwrap();
+    let mut metadata: serde_json::Value = serde_json::from_str(&content).unwrap();
+    metadata["lud16"] = lud16.into();
+    conn.execute(
+        "UPDATE Persons SET content = ? WHERE pubkey = ?",
+        [metadata.to_string(), bot_pubkey.clone()],
+    )
+    .unwrap();
+    db::set_zapper_cache(&conn, &bot_pubkey, lud16, Some(&zapper.public_key().to_hex())).unwrap();
+    zapper
+}
+
+/// userからbotの投稿へのZapレシート（署名はLNURLサーバー役の鍵）
+fn zap_receipt(user: &Keys, bot: &Keys, zapper: &Keys, target: &Event, message: &str) -> Event {
+    zap_receipt_for_amount(user, bot, zapper, target, message, 250_000_000)
+}
+
+fn zap_receipt_for_amount(user: &Keys, bot: &Keys, zapper: &Keys, target: &Event, message: &str, msats: u64) -> Event {
+    let data = ZapRequestData::new(bot.public_key(), [])
+        .amount(msats)
+        .event_id(target.id)
+        .message(message);
+    let zap_request = EventBuilder::public_zap_request(data).sign_with_keys(user).unwrap();
+    EventBuilder::zap_receipt(INVOICE_250K_SATS, None::<String>, &zap_request)
+        .sign_with_keys(zapper)
+        .unwrap()
+}
+
+fn engagement_count(env: &TestEnv, kind: &str) -> i64 {
+    env.count("SELECT COUNT(*) FROM bot_engagements WHERE kind = ?", [kind])
+}
+
+#[tokio::test]
+async fn reaction_is_recorded_and_shown_to_mental_diary() {
+    let env = TestEnv::new().await;
+    let bot = env.add_bot("いいねちゃん");
+    let user = Keys::generate();
+    env.follow(&user, &bot).await;
+
+    let note = bot_note(&bot);
+    let reaction = EventBuilder::reaction(&note, "+").sign_with_keys(&user).unwrap();
+    env.deliver(&reaction).await;
+    // 同じリアクションを再受信しても1件のまま
+    bot::event_processor::handle_incoming_event(&env.config, &env.conn(), &reaction)
+        .await
+        .unwrap();
+
+    assert_eq!(engagement_count(&env, "reaction"), 1);
+    assert_eq!(env.count("SELECT COUNT(*) FROM event_queue", []), 0);
+    let target: String = env
+        .conn()
+        .query_row("SELECT target_event_id FROM bot_engagements", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(target, note.id.to_hex());
+
+    let stats = db::get_dashboard_stats(&env.conn()).unwrap();
+    assert_eq!(stats.reactions_today, 1);
+    assert_eq!(stats.zaps_today, 0);
+
+    // 次の返信時の心境更新プロンプトに「いいねしてくれた人」が入る
+    let mention = EventBuilder::text_note("いいねちゃん おはよう")
+        .tag(Tag::public_key(bot.public_key()))
+        .sign_with_keys(&user)
+        .unwrap();
+    env.deliver(&mention).await;
+    assert_eq!(env.drain_queue().await, 1);
+    let prompt: String = env
+        .conn()
+        .query_row("SELECT prompt_text FROM token_usage", [], |row| row.get(0))
+        .unwrap();
+    assert!(prompt.contains("いいねしてくれた人"));
+}
+
+#[tokio::test]
+async fn zap_above_threshold_is_thanked() {
+    let env = TestEnv::new().await;
+    let bot = env.add_bot("ざっぷちゃん");
+    let zapper = set_zapper(&env, &bot);
+    let user = Keys::generate();
+    db::set_system_setting(&env.conn(), "zap_thanks_min_sats", "1000").unwrap();
+
+    let note = bot_note(&bot);
+    env.deliver(&zap_receipt(&user, &bot, &zapper, &note, "いつもありがとう")).await;
+
+    let (from, amount, content): (String, i64, String) = env
+        .conn()
+        .query_row(
+            "SELECT from_pubkey, amount_sats, content FROM bot_engagements WHERE kind = 'zap'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .unwrap();
+    assert_eq!(from, user.public_key().to_hex());
+    assert_eq!(amount, 250_000);
+    assert_eq!(content, "いつもありがとう");
+
+    assert_eq!(env.drain_queue().await, 1);
+
+    // Zapした人宛てにお礼が投稿される
+    let thanks = env
+        .fetch(Filter::new().author(bot.public_key()).kind(Kind::TextNote))
+        .await;
+    assert_eq!(thanks.len(), 1);
+    assert_eq!(thanks[0].content, SCRIPTED_REPLY);
+    assert!(pubkey_tags(&thanks[0]).contains(&user.public_key()));
+    assert_eq!(
+        env.count(
+            "SELECT COUNT(*) FROM token_usage tu JOIN token_categories tc ON tu.category_id = tc.id
+             WHERE tc.name = 'zap_thanks'",
+            []
+        ),
+        1
+    );
+
+    let stats = db::get_dashboard_stats(&env.conn()).unwrap();
+    assert_eq!(stats.zaps_today, 1);
+    assert_eq!(stats.zap_sats_today, 250_000);
+}
+
+#[tokio::test]
+async fn zap_is_only_recorded_when_thanks_disabled() {
+    let env = TestEnv::new().await;
+    let bot = env.add_bot("ざっぷちゃん");
+    let zapper = set_zapper(&env, &bot);
+    let user = Keys::generate();
+
+    env.deliver(&zap_receipt(&user, &bot, &zapper, &bot_note(&bot), "")).await;
+
+    assert_eq!(engagement_count(&env, "zap"), 1);
+    assert_eq!(env.count("SELECT COUNT(*) FROM event_queue", []), 0);
+    let notes = env
+        .fetch(Filter::new().author(bot.public_key()).kind(Kind::TextNote))
+        .await;
+    assert!(notes.is_empty());
+}
+
+#[tokio::test]
+async fn forged_zap_receipts_are_ignored() {
+    let env = TestEnv::new().await;
+    let bot = env.add_bot("ざっぷちゃん");
+    let zapper = set_zapper(&env, &bot);
+    let user = Keys::generate();
+    db::set_system_setting(&env.conn(), "zap_thanks_min_sats", "1000").unwrap();
+    let note = bot_note(&bot);
+
+    // BotのLNURLサーバー以外が署名したレシート
+    env.deliver(&zap_receipt(&user, &bot, &Keys::generate(), &note, "偽物")).await;
+    // zap requestの金額が請求書と違うレシート
+    env.deliver(&zap_receipt_for_amount(&user, &bot, &zapper, &note, "偽物", 1_000)).await;
+    // lud16のないBotへのレシート
+    let other = env.add_bot("むざっぷちゃん");
+    env.deliver(&zap_receipt(&user, &other, &zapper, &bot_note(&other), "偽物")).await;
+
+    assert_eq!(engagement_count(&env, "zap"), 0);
+    assert_eq!(env.count("SELECT COUNT(*) FROM event_queue", []), 0);
+}
+
+#[tokio::test]
+async fn zap_thanks_follow_conversation_limit() {
+    let env = TestEnv::new().await;
+    let bot = env.add_bot("ざっぷちゃん");
+    let zapper = set_zapper(&env, &bot);
+    let user = Keys::generate();
+    db::set_system_setting(&env.conn(), "zap_thanks_min_sats", "1000").unwrap();
+    db::set_system_setting(&env.conn(), "conversation_limit_count", "1").unwrap();
+
+    let note = bot_note(&bot);
+    env.deliver(&zap_receipt(&user, &bot, &zapper, &note, "1回目")).await;
+    env.deliver(&zap_receipt(&user, &bot, &zapper, &note, "2回目")).await;
+    assert_eq!(engagement_count(&env, "zap"), 2);
+    assert_eq!(env.drain_queue().await, 2);
+
+    let thanks = env
+        .fetch(Filter::new().author(bot.public_key()).kind(Kind::TextNote))
+        .await;
+    assert_eq!(thanks.len(), 1);
+}
@@ -0,0 +1,115 @@
+// tracingのログをerror_logテーブルに記録するレイヤーと、/api/statsでの表示のテスト
+
+mod common;
+
+use bot::dashboard::{self, AuthState, DashboardState};
+use bot::logging::ErrorLogLayer;
+use common::TestEnv;
+use nostr_sdk::prelude::*;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{error, info, info_span, warn, Level};
+use tracing_subscriber::layer::SubscriberExt;
+
+/// このテストのスレッドだけでレイヤーを有効にする
+fn record_errors(max_persisted: usize) -> tracing::subscriber::DefaultGuard {
+    let subscriber = tracing_subscriber::registry().with(ErrorLogLayer::new(Level::WARN, max_persisted));
+    tracing::subscriber::set_default(subscriber)
+}
+
+#[tokio::test]
+async fn warnings_and_errors_are_stored_with_span_context() {
+    let mut env = TestEnv::new().await;
+    env.config.dashboard.auth.enabled = false;
+    let _guard = record_errors(1000);
+    let bot = Keys::generate().public_key().to_hex();
+    let user = Keys::generate().public_key().to_hex();
+
+    let span = info_span!("event", event_id = "abc123", bot = tracing::field::Empty, user = %user);
+    span.in_scope(|| {
+        info!("記録しないログ");
+        // 処理の途中で分かったBotも記録される
+        span.record("bot", bot.as_str());
+        let _command = info_span!("command", command = "help").entered();
+        error!(attempts = 3, "返信に失敗しました");
+    });
+    warn!("spanの外の警告");
+
+    let errors = bot::db::get_recent_errors(&env.conn(), 10).unwrap();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].level, "WARN");
+    assert_eq!(errors[0].message, "spanの外の警告");
+    assert!(errors[0].event_id.is_none());
+    assert_eq!(errors[1].level, "ERROR");
+    assert_eq!(errors[1].target, "error_log");
+    assert_eq!(errors[1].message, "返信に失敗しました attempts=3");
+    assert_eq!(errors[1].event_id.as_deref(), Some("abc123"));
+    assert_eq!(errors[1].bot_pubkey.as_deref(), Some(bot.as_str()));
+    assert_eq!(errors[1].user_pubkey.as_deref(), Some(user.as_str()));
+
+    // /api/statsで最近のエラーとして返す
+    let state = DashboardState {
+        db_path: env.dir.join("nostrchan.db").display().to_string(),
+        start_time: Arc::new(Instant::now()),
+        bot_info: env.bot_info.clone(),
+        auth: Arc::new(AuthState::new(&env.config)),
+    };
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        axum::serve(listener, dashboard::build_router(state)).await.unwrap();
+    });
+    let stats: serde_json::Value = reqwest::get(format!("{}/api/stats", base)).await.unwrap().json().await.unwrap();
+    let error_log = stats["error_log"].as_array().unwrap();
+    assert_eq!(error_log.len(), 2);
+    assert_eq!(error_log[1]["level"], "ERROR");
+    assert_eq!(error_log[1]["error_type"], "error_log");
+    assert_eq!(error_log[1]["event_id"], "abc123");
+    assert_eq!(error_log[1]["bot_pubkey"], bot);
+    assert_eq!(error_log[1]["user_pubkey"], user);
+}
+
+#[tokio::test]
+async fn queue_failures_are_logged_with_the_queued_event() {
+    let env = TestEnv::new().await;
+    let _guard = record_errors(1000);
+    let bot = Keys::generate().public_key().to_hex();
+    let user = Keys::generate().public_key().to_hex();
+    let event_id = EventId::all_zeros().to_hex();
+
+    // Eventとして復元できないJSONはdead_letterに移し、エラーを記録する
+    let lane = bot::db::QueueLane {
+        bot_pubkey: Some(bot.clone()),
+        user_pubkey: Some(user.clone()),
+    };
+    let json = format!(r#"{{"id":"{}","content":"こわれたイベント"}}"#, event_id);
+    bot::db::enqueue_event(&env.conn(), &json, bot::db::QueuePriority::Mention, &lane).unwrap();
+    assert_eq!(env.drain_queue().await, 1);
+
+    let errors = bot::db::get_recent_errors(&env.conn(), 10).unwrap();
+    let failure = errors
+        .iter()
+        .find(|e| e.message.contains("イベント復元エラー"))
+        .expect("エラーが記録されていません");
+    assert_eq!(failure.target, "bot::event_processor");
+    assert_eq!(failure.event_id.as_deref(), Some(event_id.as_str()));
+    assert_eq!(failure.bot_pubkey.as_deref(), Some(bot.as_str()));
+    assert_eq!(failure.user_pubkey.as_deref(), Some(user.as_str()));
+}
+
+#[tokio::test]
+async fn only_the_newest_entries_are_kept() {
+    let env = TestEnv::new().await;
+    let _guard = record_errors(3);
+
+    for i in 0..5 {
+        error!("エラー{}", i);
+    }
+
+    let messages: Vec<String> = bot::db::get_recent_errors(&env.conn(), 10)
+        .unwrap()
+        .into_iter()
+        .map(|e| e.message)
+        .collect();
+    assert_eq!(messages, vec!["エラー4", "エラー3", "エラー2"]);
+}
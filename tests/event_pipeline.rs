@@ -0,0 +1,146 @@
+// メンション受信から返信・DB記録までのエンドツーエンドテスト
+
+mod common;
+
+use common::{event_tags, pubkey_tags, TestEnv, SCRIPTED_REPLY};
+use nostr_sdk::prelude::*;
+
+#[tokio::test]
+async fn mention_gets_tagged_reply_and_is_recorded() {
+    let env = TestEnv::new().await;
+    let bot = env.add_bot("テストちゃん");
+    let user = Keys::generate();
+    env.follow(&user, &bot).await;
+
+    let mention = EventBuilder::text_note("テストちゃん こんにちは！")
+        .tag(Tag::public_key(bot.public_key()))
+        .sign_with_keys(&user)
+        .unwrap();
+    env.deliver(&mention).await;
+
+    // コマンドではないのでキューに入り、ワーカーが処理する
+    assert_eq!(env.count("SELECT COUNT(*) FROM event_queue", []), 1);
+    assert_eq!(env.drain_queue().await, 1);
+    assert_eq!(env.count("SELECT COUNT(*) FROM event_queue", []), 0);
+
+    // リレーに返信が届いている
+    let replies = env
+        .fetch(Filter::new().author(bot.public_key()).kind(Kind::TextNote))
+        .await;
+    assert_eq!(replies.len(), 1);
+    let reply = &replies[0];
+    assert_eq!(reply.content, SCRIPTED_REPLY);
+    assert_eq!(event_tags(reply), vec![(mention.id, Some(Marker::Root))]);
+    assert!(pubkey_tags(reply).contains(&user.public_key()));
+
+    let bot_hex = bot.public_key().to_hex();
+    let user_hex = user.public_key().to_hex();
+
+    // events: メンションと返信の両方
+    assert_eq!(
+        env.count("SELECT COUNT(*) FROM events WHERE event_id IN (?, ?)", [mention.id.to_hex(), reply.id.to_hex()]),
+        2
+    );
+
+    // conversation_logs: ユーザーの発言とBotの発言
+    assert_eq!(
+        env.count("SELECT COUNT(*) FROM conversation_logs WHERE bot_pubkey = ? AND is_bot_message = 0", [&bot_hex]),
+        1
+    );
+    assert_eq!(
+        env.count("SELECT COUNT(*) FROM conversation_logs WHERE bot_pubkey = ? AND is_bot_message = 1", [&bot_hex]),
+        1
+    );
+
+    // token_usage: 返信カテゴリで1件
+    assert_eq!(
+        env.count(
+            "SELECT COUNT(*) FROM token_usage tu JOIN token_categories tc ON tu.category_id = tc.id
+             WHERE tu.bot_pubkey = ? AND tc.name = 'reply' AND tu.completion_tokens > 0",
+            [&bot_hex]
+        ),
+        1
+    );
+
+    // bot_mental_state: スクリプトの心境が保存されている
+    let mental_state: String = env
+        .conn()
+        .query_row(
+            "SELECT mental_state_json FROM bot_mental_state WHERE bot_pubkey = ? ORDER BY created_at DESC LIMIT 1",
+            [&bot_hex],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert!(mental_state.contains("上機嫌"));
+
+    // user_impressions: ユーザー属性も保存されている
+    assert_eq!(
+        env.count("SELECT COUNT(*) FROM user_impressions WHERE bot_pubkey = ? AND user_pubkey = ?", [&bot_hex, &user_hex]),
+        1
+    );
+}
+
+#[tokio::test]
+async fn reply_in_thread_keeps_root_and_marks_reply() {
+    let env = TestEnv::new().await;
+    let bot = env.add_bot("スレッドちゃん");
+    let user = Keys::generate();
+    let other = Keys::generate();
+    env.follow(&user, &bot).await;
+
+    let root = EventBuilder::text_note("スレッドの最初の投稿")
+        .sign_with_keys(&other)
+        .unwrap();
+    env.publish(&root).await;
+
+    let mention = EventBuilder::text_note("スレッドちゃん どう思う？")
+        .tags([
+            Tag::from_standardized(TagStandard::Event {
+                event_id: root.id,
+                relay_url: None,
+                marker: Some(Marker::Root),
+                public_key: None,
+                uppercase: false,
+            }),
+            Tag::public_key(other.public_key()),
+            Tag::public_key(bot.public_key()),
+        ])
+        .sign_with_keys(&user)
+        .unwrap();
+    env.deliver(&mention).await;
+    assert_eq!(env.drain_queue().await, 1);
+
+    let replies = env
+        .fetch(Filter::new().author(bot.public_key()).kind(Kind::TextNote))
+        .await;
+    assert_eq!(replies.len(), 1);
+    let reply = &replies[0];
+    assert_eq!(
+        event_tags(reply),
+        vec![(root.id, Some(Marker::Root)), (mention.id, Some(Marker::Reply))]
+    );
+    let p_tags = pubkey_tags(reply);
+    assert!(p_tags.contains(&user.public_key()));
+    assert!(p_tags.contains(&other.public_key()));
+}
+
+#[tokio::test]
+async fn mention_from_non_follower_is_ignored() {
+    let env = TestEnv::new().await;
+    let bot = env.add_bot("フォロワー限定ちゃん");
+    let stranger = Keys::generate();
+
+    let mention = EventBuilder::text_note("フォロワー限定ちゃん こんにちは")
+        .tag(Tag::public_key(bot.public_key()))
+        .sign_with_keys(&stranger)
+        .unwrap();
+    env.deliver(&mention).await;
+    assert_eq!(env.drain_queue().await, 1);
+
+    let replies = env
+        .fetch(Filter::new().author(bot.public_key()).kind(Kind::TextNote))
+        .await;
+    assert!(replies.is_empty());
+    assert_eq!(env.count("SELECT COUNT(*) FROM token_usage", []), 0);
+    assert_eq!(env.count("SELECT COUNT(*) FROM bot_mental_state", []), 0);
+}
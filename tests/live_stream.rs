@@ -0,0 +1,115 @@
+// ダッシュボードのライブ配信（broadcastチャンネルとSSE）のテスト
+
+mod common;
+
+use bot::dashboard::{self, AuthState, DashboardState};
+use bot::live::{self, LiveEvent, LiveMessage, QueueStatus};
+use common::{TestEnv, SCRIPTED_REPLY};
+use nostr_sdk::prelude::*;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+/// 受信済みのメッセージのうち、指定したBotのものを取り出す
+fn drain_for_bot(receiver: &mut broadcast::Receiver<LiveMessage>, bot: &str) -> Vec<LiveMessage> {
+    let mut messages = Vec::new();
+    while let Ok(message) = receiver.try_recv() {
+        if message.bot_pubkey() == Some(bot) {
+            messages.push(message);
+        }
+    }
+    messages
+}
+
+#[tokio::test]
+async fn mention_is_streamed_through_queue_llm_and_reply() {
+    let env = TestEnv::new().await;
+    let bot = env.add_bot("らいぶちゃん");
+    let bot_hex = bot.public_key().to_hex();
+    let user = Keys::generate();
+    env.follow(&user, &bot).await;
+    let mut receiver = live::subscribe();
+
+    let mention = EventBuilder::text_note("らいぶちゃん こんにちは！")
+        .tag(Tag::public_key(bot.public_key()))
+        .sign_with_keys(&user)
+        .unwrap();
+    env.deliver(&mention).await;
+    assert_eq!(env.drain_queue().await, 1);
+
+    let messages = drain_for_bot(&mut receiver, &bot_hex);
+    let statuses: Vec<QueueStatus> = messages
+        .iter()
+        .filter_map(|m| match &m.event {
+            LiveEvent::Queue { status, event_id, .. } if *event_id == mention.id.to_hex() => Some(*status),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(statuses, vec![QueueStatus::Enqueued, QueueStatus::Processing, QueueStatus::Completed]);
+
+    let llm_calls: Vec<&LiveMessage> = messages.iter().filter(|m| m.name() == "llm").collect();
+    assert!(!llm_calls.is_empty());
+    match &llm_calls[0].event {
+        LiveEvent::Llm { provider, prompt_tokens, completion_tokens, error, .. } => {
+            assert_eq!(provider, "scripted");
+            assert!(*prompt_tokens > 0 && *completion_tokens > 0);
+            assert!(error.is_none());
+        }
+        other => panic!("LLM呼び出しではありません: {:?}", other),
+    }
+
+    let outgoing = messages.iter().find(|m| m.name() == "outgoing").expect("返信が配信されていません");
+    match &outgoing.event {
+        LiveEvent::Outgoing { reply_to, content, kind, .. } => {
+            assert_eq!(reply_to.as_deref(), Some(mention.id.to_hex().as_str()));
+            assert_eq!(content.as_deref(), Some(SCRIPTED_REPLY));
+            assert_eq!(*kind, 1);
+        }
+        other => panic!("送信ではありません: {:?}", other),
+    }
+    // LLMの応答を受けてから返信している
+    let position = |name: &str| messages.iter().position(|m| m.name() == name).unwrap();
+    assert!(position("llm") < position("outgoing"));
+}
+
+#[tokio::test]
+async fn sse_endpoint_streams_messages_for_the_requested_bot() {
+    let mut env = TestEnv::new().await;
+    env.config.dashboard.auth.enabled = false;
+    let state = DashboardState {
+        db_path: env.dir.join("test.db").display().to_string(),
+        start_time: Arc::new(Instant::now()),
+        bot_info: env.bot_info.clone(),
+        auth: Arc::new(AuthState::new(&env.config)),
+    };
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        axum::serve(listener, dashboard::build_router(state)).await.unwrap();
+    });
+
+    let bot = Keys::generate().public_key().to_hex();
+    let other_bot = Keys::generate().public_key().to_hex();
+    let mut response = reqwest::get(format!("{}/api/live?bot={}", base, bot)).await.unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "text/event-stream");
+
+    // 購読が始まってから配信する
+    live::outgoing(&other_bot, EventId::all_zeros(), Kind::TextNote, None, Some("ほかのBot"));
+    live::outgoing(&bot, EventId::all_zeros(), Kind::TextNote, None, Some("やっほー"));
+
+    let mut body = String::new();
+    let received = tokio::time::timeout(Duration::from_secs(5), async {
+        while let Some(chunk) = response.chunk().await.unwrap() {
+            body.push_str(&String::from_utf8_lossy(&chunk));
+            if body.contains("やっほー") {
+                return;
+            }
+        }
+    })
+    .await;
+    assert!(received.is_ok(), "SSEで配信されませんでした: {}", body);
+    assert!(body.contains("event: outgoing"));
+    assert!(body.contains(&format!("\"bot_pubkey\":\"{}\"", bot)));
+    assert!(!body.contains("ほかのBot"));
+}
@@ -0,0 +1,113 @@
+// Prometheusメトリクス（/metrics）のテスト
+
+mod common;
+
+use bot::dashboard::{self, AuthState, DashboardState};
+use common::TestEnv;
+use nostr_sdk::prelude::*;
+use reqwest::StatusCode;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const METRICS_TOKEN: &str = "metrics-token-for-test";
+
+/// ダッシュボードを起動し、/metrics のURLを返す
+async fn start_dashboard(env: &mut TestEnv) -> String {
+    env.config.dashboard.auth.viewer_token = Some(METRICS_TOKEN.to_string());
+    let state = DashboardState {
+        db_path: env.dir.join("test.db").display().to_string(),
+        start_time: Arc::new(Instant::now()),
+        bot_info: env.bot_info.clone(),
+        auth: Arc::new(AuthState::new(&env.config)),
+    };
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/metrics", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        axum::serve(listener, dashboard::build_router(state)).await.unwrap();
+    });
+    url
+}
+
+async fn scrape(url: &str) -> String {
+    let response = reqwest::Client::new().get(url).bearer_auth(METRICS_TOKEN).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers()["content-type"].to_str().unwrap().starts_with("text/plain"));
+    response.text().await.unwrap()
+}
+
+/// 指定した行（メトリクス名とラベル）の値
+fn value(body: &str, series: &str) -> Option<f64> {
+    body.lines()
+        .find_map(|line| line.strip_prefix(series)?.strip_prefix(' '))
+        .and_then(|v| v.parse().ok())
+}
+
+#[tokio::test]
+async fn replies_llm_calls_and_follower_cache_are_counted() {
+    let mut env = TestEnv::new().await;
+    let url = start_dashboard(&mut env).await;
+    let bot = env.add_bot("めとりくすちゃん");
+    let bot_hex = bot.public_key().to_hex();
+    let user = Keys::generate();
+    env.follow(&user, &bot).await;
+
+    // ログインしていなければ見られない
+    assert_eq!(reqwest::get(&url).await.unwrap().status(), StatusCode::UNAUTHORIZED);
+    let before = scrape(&url).await;
+    let cache = |body: &str, result: &str| {
+        value(body, &format!("nostrchan_follower_cache_requests_total{{result=\"{}\"}}", result)).unwrap_or(0.0)
+    };
+
+    for text in ["めとりくすちゃん こんにちは！", "めとりくすちゃん また来たよ"] {
+        let mention = EventBuilder::text_note(text)
+            .tag(Tag::public_key(bot.public_key()))
+            .sign_with_keys(&user)
+            .unwrap();
+        env.deliver(&mention).await;
+        assert_eq!(env.drain_queue().await, 1);
+    }
+
+    let body = scrape(&url).await;
+    assert_eq!(value(&body, "nostrchan_queue_depth"), Some(0.0));
+    assert!(value(&body, &format!("nostrchan_llm_tokens_total{{bot=\"{}\",type=\"completion\"}}", bot_hex)).unwrap() > 0.0);
+    assert!(body.contains("nostrchan_llm_request_seconds_count{category="));
+    assert!(value(&body, &format!("nostrchan_bot_last_reply_timestamp_seconds{{bot=\"{}\"}}", bot_hex)).unwrap() > 0.0);
+    let relay = env.relay_url.trim_end_matches('/');
+    assert!(body
+        .lines()
+        .any(|line| line.starts_with("nostrchan_replies_published_total{") && line.contains(relay) && line.contains("result=\"accepted\"")));
+    // 1回目はリレーに問い合わせ、2回目はキャッシュを使う
+    assert!(cache(&body, "miss") > cache(&before, "miss"));
+    assert!(cache(&body, "hit") > cache(&before, "hit"));
+    assert!(value(&body, "nostrchan_event_processing_seconds_count{result=\"ok\"}").unwrap() >= 2.0);
+}
+
+#[tokio::test]
+async fn command_results_are_counted() {
+    let mut env = TestEnv::new().await;
+    let url = start_dashboard(&mut env).await;
+    let bot = env.add_bot("めとりくすちゃん");
+    let admin = Keys::generate();
+    env.config.bot.admin_pubkeys = vec![admin.public_key().to_hex()];
+
+    let command = |text: &str| {
+        EventBuilder::text_note(format!("nostr:{} {}", bot.public_key().to_bech32().unwrap(), text))
+            .tag(Tag::public_key(bot.public_key()))
+            .sign_with_keys(&admin)
+            .unwrap()
+    };
+    env.deliver(&command("help")).await;
+    env.deliver(&command("token report 0")).await;
+
+    // コマンドは別タスクで実行されるので、数えられるまで待つ
+    let usage_error = "nostrchan_commands_total{command=\"token_report\",result=\"usage_error\"}";
+    let ok = "nostrchan_commands_total{command=\"help\",result=\"ok\"}";
+    for _ in 0..50 {
+        let body = scrape(&url).await;
+        if value(&body, ok).is_some() && value(&body, usage_error).is_some() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("コマンドの実行回数が記録されていません");
+}
@@ -0,0 +1,181 @@
+// Botのプロンプト・kind 0の変更履歴（差分・ロールバック・トークン使用量の版）のテスト
+
+mod common;
+
+use bot::dashboard::{self, AuthState, DashboardState};
+use bot::db;
+use common::TestEnv;
+use nostr_sdk::prelude::*;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// 認証なしでダッシュボードを起動し、ベースURLを返す
+async fn start_dashboard(env: &mut TestEnv) -> String {
+    env.config.dashboard.auth.enabled = false;
+    let state = DashboardState {
+        db_path: env.dir.join("nostrchan.db").display().to_string(),
+        start_time: Arc::new(Instant::now()),
+        bot_info: env.bot_info.clone(),
+        auth: Arc::new(AuthState::new(&env.config)),
+    };
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        axum::serve(listener, dashboard::build_router(state)).await.unwrap();
+    });
+    base
+}
+
+async fn update_bot(base: &str, bot: &Keys, prompt: &str, name: &str) -> StatusCode {
+    reqwest::Client::new()
+        .put(format!("{}/api/bots/{}", base, bot.public_key().to_hex()))
+        .json(&json!({ "prompt": prompt, "content": json!({ "name": name, "display_name": name }).to_string() }))
+        .send()
+        .await
+        .unwrap()
+        .status()
+}
+
+async fn get_json(url: String) -> (StatusCode, Value) {
+    let response = reqwest::get(url).await.unwrap();
+    let status = response.status();
+    (status, response.json().await.unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn dashboard_updates_are_versioned_and_diffed() {
+    let mut env = TestEnv::new().await;
+    let base = start_dashboard(&mut env).await;
+    let bot = env.add_bot("はじめちゃん");
+    let bot_hex = bot.public_key().to_hex();
+
+    // 起動時に履歴のないBotの最初の版を作る
+    db::initialize_db(&env.conn()).unwrap();
+    db::initialize_db(&env.conn()).unwrap();
+    let versions = db::list_persona_versions(&env.conn(), &bot_hex).unwrap();
+    assert_eq!(versions.len(), 1);
+    assert_eq!(versions[0].source, "initial");
+    assert_eq!(versions[0].prompt, "あなたははじめちゃんです。");
+
+    assert_eq!(update_bot(&base, &bot, "あなたははじめちゃんです。\n語尾は「のだ」です。", "はじめちゃん2").await, StatusCode::OK);
+    // 内容が変わらなければ版は増えない
+    assert_eq!(update_bot(&base, &bot, "あなたははじめちゃんです。\n語尾は「のだ」です。", "はじめちゃん2").await, StatusCode::OK);
+
+    let (status, list) = get_json(format!("{}/api/bots/{}/persona-versions", base, bot_hex)).await;
+    assert_eq!(status, StatusCode::OK);
+    let list = list.as_array().unwrap();
+    assert_eq!(list.len(), 2);
+    assert_eq!(list[0]["source"], "dashboard");
+    assert_eq!(list[0]["author"], "dashboard");
+    assert_eq!(list[0]["active"], true);
+    assert_eq!(list[1]["source"], "initial");
+    assert_eq!(list[1]["active"], false);
+
+    // 省略時は1つ前の版との差分
+    let latest = list[0]["id"].as_i64().unwrap();
+    let initial = list[1]["id"].as_i64().unwrap();
+    let (status, diff) = get_json(format!("{}/api/bots/{}/persona-versions/{}/diff", base, bot_hex, latest)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(diff["from"]["id"], initial);
+    assert_eq!(diff["to"]["id"], latest);
+    assert_eq!(
+        diff["prompt"],
+        json!([
+            { "op": "equal", "text": "あなたははじめちゃんです。" },
+            { "op": "insert", "text": "語尾は「のだ」です。" }
+        ])
+    );
+    // kind 0は1項目1行に整形して比べる
+    let content = diff["content"].as_array().unwrap();
+    assert!(content.contains(&json!({ "op": "delete", "text": "  \"name\": \"はじめちゃん\"" })));
+    assert!(content.contains(&json!({ "op": "insert", "text": "  \"name\": \"はじめちゃん2\"" })));
+    assert!(content.contains(&json!({ "op": "equal", "text": "{" })));
+
+    // 最初の版は空との差分、別のBotの版は404
+    let (_, diff) = get_json(format!("{}/api/bots/{}/persona-versions/{}/diff", base, bot_hex, initial)).await;
+    assert!(diff["from"].is_null());
+    assert_eq!(diff["prompt"][0]["op"], "insert");
+    let other = env.add_bot("べつのこ").public_key().to_hex();
+    let (status, _) = get_json(format!("{}/api/bots/{}/persona-versions/{}/diff", base, other, latest)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) =
+        get_json(format!("{}/api/bots/{}/persona-versions/{}/diff?against=999", base, bot_hex, latest)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn rollback_restores_a_version_and_token_usage_is_stamped() {
+    let mut env = TestEnv::new().await;
+    let base = start_dashboard(&mut env).await;
+    let bot = env.add_bot("もどしちゃん");
+    let bot_hex = bot.public_key().to_hex();
+    assert_eq!(update_bot(&base, &bot, "あなたは新しいもどしちゃんです。", "もどしちゃん").await, StatusCode::OK);
+    let versions = db::list_persona_versions(&env.conn(), &bot_hex).unwrap();
+    let (updated, initial) = (versions[0].id, versions[1].id);
+
+    // 使用時の版をtoken_usageに記録する
+    db::record_token_usage(&env.conn(), &bot_hex, "reply", 100, 20, "prompt", "completion").unwrap();
+    let stamped = env.count("SELECT COUNT(*) FROM token_usage WHERE persona_version_id = ?", [updated]);
+    assert_eq!(stamped, 1);
+
+    let response = reqwest::Client::new()
+        .post(format!("{}/api/bots/{}/persona-versions/{}/rollback", base, bot_hex, initial))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let restored: Value = response.json().await.unwrap();
+    assert_eq!(restored["source"], "rollback");
+    assert_eq!(restored["prompt"], "あなたはもどしちゃんです。");
+    let person = db::find_person(&env.conn(), &bot_hex).unwrap().unwrap();
+    assert_eq!(person.prompt, "あなたはもどしちゃんです。");
+
+    let (_, list) = get_json(format!("{}/api/bots/{}/persona-versions", base, bot_hex)).await;
+    let list = list.as_array().unwrap();
+    assert_eq!(list.len(), 3);
+    assert_eq!(list[0]["id"], restored["id"]);
+    assert_eq!(list[0]["active"], true);
+    assert_eq!(list[1]["usage"], json!({ "calls": 1, "prompt_tokens": 100, "completion_tokens": 20 }));
+    assert_eq!(list[0]["usage"]["calls"], 0);
+
+    // 別のBotの版には戻せない
+    let other = env.add_bot("べつのこ").public_key().to_hex();
+    let response = reqwest::Client::new()
+        .post(format!("{}/api/bots/{}/persona-versions/{}/rollback", base, other, initial))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn admin_kind0_update_is_versioned_with_the_admin() {
+    let mut env = TestEnv::new().await;
+    let bot = env.add_bot("かんりちゃん");
+    let bot_hex = bot.public_key().to_hex();
+    let admin = Keys::generate();
+    env.config.bot.admin_pubkeys = vec![admin.public_key().to_hex()];
+
+    let kind0 = json!({ "name": "かんりちゃん", "display_name": "かんりちゃん", "about": "更新しました" }).to_string();
+    let event = EventBuilder::text_note(format!("nostr:{} update kind 0\n{}", bot.public_key().to_bech32().unwrap(), kind0))
+        .tag(Tag::public_key(bot.public_key()))
+        .sign_with_keys(&admin)
+        .unwrap();
+    env.deliver(&event).await;
+
+    let mut versions = Vec::new();
+    for _ in 0..50 {
+        versions = db::list_persona_versions(&env.conn(), &bot_hex).unwrap();
+        if versions.len() == 2 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    assert_eq!(versions.len(), 2);
+    assert_eq!(versions[0].source, "admin_command");
+    assert_eq!(versions[0].author.as_deref(), Some(admin.public_key().to_hex().as_str()));
+    assert_eq!(versions[0].content, kind0);
+    assert_eq!(versions[1].source, "initial");
+}
@@ -0,0 +1,145 @@
+// ダッシュボードのプロンプト試行（返信を送らずに生成だけ行う）のテスト
+
+mod common;
+
+use bot::dashboard::{self, AuthState, DashboardState};
+use bot::db;
+use common::{TestEnv, SCRIPTED_REPLY};
+use nostr_sdk::prelude::*;
+use reqwest::StatusCode;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// 認証なしでダッシュボードを起動し、ベースURLを返す
+async fn start_dashboard(env: &mut TestEnv) -> String {
+    env.config.dashboard.auth.enabled = false;
+    let state = DashboardState {
+        db_path: env.dir.join("nostrchan.db").display().to_string(),
+        start_time: Arc::new(Instant::now()),
+        bot_info: env.bot_info.clone(),
+        auth: Arc::new(AuthState::new(&env.config)),
+    };
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        axum::serve(listener, dashboard::build_router(state)).await.unwrap();
+    });
+    base
+}
+
+async fn playground(base: &str, bot: &Keys, body: serde_json::Value) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(format!("{}/api/bots/{}/playground", base, bot.public_key().to_hex()))
+        .json(&body)
+        .send()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn mention_is_generated_without_publishing_or_saving() {
+    let mut env = TestEnv::new().await;
+    let base = start_dashboard(&mut env).await;
+    let bot = env.add_bot("ためしちゃん");
+    let bot_hex = bot.public_key().to_hex();
+    let user = Keys::generate().public_key().to_hex();
+
+    let response = playground(
+        &base,
+        &bot,
+        serde_json::json!({ "user_pubkey": user, "user_name": "テストさん", "message": "ためしちゃん こんにちは！" }),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let result: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(result["mode"], "mention");
+    assert_eq!(result["user_pubkey"], user);
+    let system_prompt = result["system_prompt"].as_str().unwrap();
+    assert!(system_prompt.contains("あなたはためしちゃんです。"));
+    assert!(system_prompt.contains("「テストさん」"));
+    assert!(system_prompt.contains("user_attributes"));
+    assert_eq!(result["raw_output"], common::scripted_json_response());
+    assert_eq!(result["parsed"]["reply"], SCRIPTED_REPLY);
+    assert_eq!(result["parsed"]["mental_diary"]["mood"], "上機嫌");
+    assert!(result["parse_error"].is_null());
+    assert!(result["prompt_tokens"].as_u64().unwrap() > 0);
+    assert!(result["completion_tokens"].as_u64().unwrap() > 0);
+
+    // 送信も保存もしない（トークン使用量だけは記録する）
+    let conn = env.conn();
+    assert!(db::get_bot_mental_state(&conn, &bot_hex).unwrap().is_none());
+    assert!(db::get_user_attributes(&conn, &bot_hex, &user).unwrap().is_none());
+    assert_eq!(env.count("SELECT COUNT(*) FROM conversation_logs", []), 0);
+    assert_eq!(env.count("SELECT COUNT(*) FROM events", []), 0);
+    assert!(env.fetch(Filter::new().author(bot.public_key())).await.is_empty());
+    assert_eq!(
+        env.count(
+            "SELECT COUNT(*) FROM token_usage t JOIN token_categories c ON t.category_id = c.id WHERE c.name = 'playground'",
+            []
+        ),
+        1
+    );
+}
+
+#[tokio::test]
+async fn conversation_summary_is_used_but_not_saved() {
+    let mut env = TestEnv::new().await;
+    let base = start_dashboard(&mut env).await;
+    let bot = env.add_bot("ためしちゃん");
+    let user = Keys::generate();
+    env.follow(&user, &bot).await;
+
+    // 要約が必要になるくらいの会話履歴を作る
+    for i in 0..3 {
+        let mention = EventBuilder::text_note(format!("ためしちゃん {}回目の話をするね", i))
+            .tag(Tag::public_key(bot.public_key()))
+            .custom_created_at(Timestamp::from(Timestamp::now().as_u64() - 100 + i))
+            .sign_with_keys(&user)
+            .unwrap();
+        env.deliver(&mention).await;
+        assert_eq!(env.drain_queue().await, 1);
+    }
+    db::set_system_setting(&env.conn(), "summary_threshold", "10").unwrap();
+    db::set_system_setting(&env.conn(), "recent_context_count", "2").unwrap();
+
+    let response = playground(
+        &base,
+        &bot,
+        serde_json::json!({ "user_pubkey": user.public_key().to_bech32().unwrap(), "message": "前に何を話したっけ？" }),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let result: serde_json::Value = response.json().await.unwrap();
+    assert!(result["context"].as_str().unwrap().contains("【会話の要約】"));
+    assert_eq!(env.count("SELECT COUNT(*) FROM conversation_summaries", []), 0);
+}
+
+#[tokio::test]
+async fn air_reply_mode_and_invalid_requests() {
+    let mut env = TestEnv::new().await;
+    let base = start_dashboard(&mut env).await;
+    let bot = env.add_bot("ためしちゃん");
+
+    let response = playground(&base, &bot, serde_json::json!({ "message": "今日はいい天気", "mode": "air_reply" })).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let result: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(result["mode"], "air_reply");
+    assert!(result["context"].as_str().unwrap().contains("【投稿】"));
+    assert!(result["context"].as_str().unwrap().contains("今日はいい天気"));
+    assert!(result["system_prompt"].as_str().unwrap().contains("独り言のように"));
+    assert!(!result["system_prompt"].as_str().unwrap().contains("user_attributes"));
+    assert_eq!(result["parsed"]["reply"], SCRIPTED_REPLY);
+
+    assert_eq!(
+        playground(&base, &bot, serde_json::json!({ "message": "  " })).await.status(),
+        StatusCode::BAD_REQUEST
+    );
+    assert_eq!(
+        playground(&base, &bot, serde_json::json!({ "message": "やあ", "user_pubkey": "not-a-key" })).await.status(),
+        StatusCode::BAD_REQUEST
+    );
+    assert_eq!(
+        playground(&base, &Keys::generate(), serde_json::json!({ "message": "やあ" })).await.status(),
+        StatusCode::NOT_FOUND
+    );
+}
@@ -0,0 +1,278 @@
+// イベントキューの優先度・再試行・dead_letter・並行処理のテスト
+
+mod common;
+
+use bot::config::LlmProviderKind;
+use bot::db;
+use common::{TestEnv, SCRIPTED_REPLY};
+use nostr_sdk::prelude::*;
+use std::time::Duration;
+
+fn note_json(content: &str) -> String {
+    EventBuilder::text_note(content)
+        .sign_with_keys(&Keys::generate())
+        .unwrap()
+        .as_json()
+}
+
+fn lane(bot: Option<&str>, user: Option<&str>) -> db::QueueLane {
+    db::QueueLane {
+        bot_pubkey: bot.map(str::to_string),
+        user_pubkey: user.map(str::to_string),
+    }
+}
+
+#[tokio::test]
+async fn dequeues_direct_messages_then_mentions_then_air_replies() {
+    let env = TestEnv::new().await;
+    let conn = env.conn();
+
+    let air = db::enqueue_event(&conn, &note_json("エアリプ候補"), db::QueuePriority::AirReply, &db::QueueLane::default()).unwrap().unwrap();
+    let mention = db::enqueue_event(&conn, &note_json("メンション"), db::QueuePriority::Mention, &db::QueueLane::default()).unwrap().unwrap();
+    let dm = db::enqueue_event(&conn, &note_json("DM"), db::QueuePriority::DirectMessage, &db::QueueLane::default()).unwrap().unwrap();
+
+    let mut order = Vec::new();
+    while let Some(item) = db::dequeue_event(&conn).unwrap() {
+        order.push(item.id);
+        db::complete_queue_event(&conn, item.id).unwrap();
+    }
+    assert_eq!(order, vec![dm, mention, air]);
+}
+
+#[tokio::test]
+async fn same_event_is_enqueued_once() {
+    let env = TestEnv::new().await;
+    let conn = env.conn();
+    let json = note_json("二重受信");
+
+    // キューに残っている間は既存のIDが返る
+    let first = db::enqueue_event(&conn, &json, db::QueuePriority::Mention, &db::QueueLane::default()).unwrap().unwrap();
+    let second = db::enqueue_event(&conn, &json, db::QueuePriority::Mention, &db::QueueLane::default()).unwrap().unwrap();
+    assert_eq!(first, second);
+    assert_eq!(env.count("SELECT COUNT(*) FROM event_queue", []), 1);
+
+    // 処理済みになったら追加されない
+    let item = db::dequeue_event(&conn).unwrap().unwrap();
+    db::complete_queue_event(&conn, item.id).unwrap();
+    assert!(db::enqueue_event(&conn, &json, db::QueuePriority::Mention, &db::QueueLane::default()).unwrap().is_none());
+}
+
+#[tokio::test]
+async fn failed_event_backs_off_then_moves_to_dead_letter() {
+    let env = TestEnv::new().await;
+    let conn = env.conn();
+    let id = db::enqueue_event(&conn, &note_json("失敗する"), db::QueuePriority::Mention, &db::QueueLane::default()).unwrap().unwrap();
+
+    let item = db::dequeue_event(&conn).unwrap().unwrap();
+    assert_eq!(item.id, id);
+    let now = chrono::Utc::now().timestamp();
+    match db::retry_queue_event(&conn, id, "LLMタイムアウト").unwrap() {
+        db::RetryOutcome::Retrying { attempts, next_attempt_at } => {
+            assert_eq!(attempts, 1);
+            assert!(next_attempt_at >= now + 30);
+        }
+        outcome => panic!("unexpected outcome: {:?}", outcome),
+    }
+    // 待ち時間が過ぎるまでは取り出されない
+    assert!(db::dequeue_event(&conn).unwrap().is_none());
+
+    // 失敗のたびに待ち時間が伸び、上限回数でdead_letterへ
+    let mut last_delay = 0;
+    for _ in 2..db::MAX_QUEUE_ATTEMPTS {
+        match db::retry_queue_event(&conn, id, "LLMタイムアウト").unwrap() {
+            db::RetryOutcome::Retrying { next_attempt_at, .. } => {
+                let delay = next_attempt_at - chrono::Utc::now().timestamp();
+                assert!(delay > last_delay);
+                last_delay = delay;
+            }
+            outcome => panic!("unexpected outcome: {:?}", outcome),
+        }
+    }
+    assert_eq!(
+        db::retry_queue_event(&conn, id, "最後のエラー").unwrap(),
+        db::RetryOutcome::DeadLettered
+    );
+    assert_eq!(env.count("SELECT COUNT(*) FROM event_queue", []), 0);
+
+    let letters = db::list_dead_letters(&conn, 10).unwrap();
+    assert_eq!(letters.len(), 1);
+    assert_eq!(letters[0].attempts, db::MAX_QUEUE_ATTEMPTS);
+    assert_eq!(letters[0].last_error, "最後のエラー");
+
+    // キューに戻すと試行回数がリセットされてすぐ取り出せる
+    let requeued = db::requeue_dead_letter(&conn, letters[0].id).unwrap().unwrap();
+    assert_eq!(db::count_dead_letters(&conn).unwrap(), 0);
+    let item = db::dequeue_event(&conn).unwrap().unwrap();
+    assert_eq!(item.id, requeued);
+    assert_eq!(item.attempts, 0);
+    assert_eq!(item.priority, db::QueuePriority::Mention as i64);
+
+    db::dead_letter_queue_event(&conn, requeued, "手動").unwrap();
+    assert_eq!(db::purge_dead_letters(&conn).unwrap(), 1);
+    assert_eq!(db::count_dead_letters(&conn).unwrap(), 0);
+}
+
+#[tokio::test]
+async fn mention_is_enqueued_with_mention_priority() {
+    let env = TestEnv::new().await;
+    let bot = env.add_bot("ゆうせんちゃん");
+    let user = Keys::generate();
+    env.follow(&user, &bot).await;
+
+    let mention = EventBuilder::text_note("ゆうせんちゃん 聞いて")
+        .tag(Tag::public_key(bot.public_key()))
+        .sign_with_keys(&user)
+        .unwrap();
+    env.deliver(&mention).await;
+
+    assert_eq!(
+        env.count(
+            "SELECT COUNT(*) FROM event_queue WHERE event_id = ? AND priority = ?",
+            rusqlite::params![mention.id.to_hex(), db::QueuePriority::Mention as i64]
+        ),
+        1
+    );
+}
+
+#[tokio::test]
+async fn mention_is_retried_when_llm_fails() {
+    let mut env = TestEnv::new().await;
+    let bot = env.add_bot("さいしこうちゃん");
+    let user = Keys::generate();
+    env.follow(&user, &bot).await;
+
+    let mention = EventBuilder::text_note("さいしこうちゃん 聞いて")
+        .tag(Tag::public_key(bot.public_key()))
+        .sign_with_keys(&user)
+        .unwrap();
+    env.deliver(&mention).await;
+
+    // LLMが使えない間は返信せず、キューに残して再試行を待つ
+    env.config.llm.provider = LlmProviderKind::OpenAiCompatible;
+    env.config.llm.base_url = None;
+    assert_eq!(env.drain_queue().await, 1);
+    let replies = Filter::new().author(bot.public_key()).kind(Kind::TextNote);
+    assert!(env.fetch(replies.clone()).await.is_empty());
+    let (attempts, last_error): (i64, String) = env
+        .conn()
+        .query_row("SELECT attempts, last_error FROM event_queue WHERE event_id = ?", [mention.id.to_hex()], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .unwrap();
+    assert_eq!(attempts, 1);
+    assert!(last_error.contains("LLM"));
+
+    // 再試行でLLMが応答すれば返信し、会話ログは二重に記録しない
+    env.config.llm.provider = LlmProviderKind::Scripted;
+    env.conn().execute("UPDATE event_queue SET next_attempt_at = 0", []).unwrap();
+    assert_eq!(env.drain_queue().await, 1);
+    let sent = env.fetch(replies).await;
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].content, SCRIPTED_REPLY);
+    assert_eq!(env.count("SELECT COUNT(*) FROM event_queue", []), 0);
+    assert_eq!(
+        env.count("SELECT COUNT(*) FROM conversation_logs WHERE bot_pubkey = ?", [bot.public_key().to_hex()]),
+        2
+    );
+}
+
+#[tokio::test]
+async fn same_bot_and_user_are_not_processed_concurrently() {
+    let env = TestEnv::new().await;
+    let conn = env.conn();
+    let enqueue = |l: db::QueueLane| {
+        db::enqueue_event(&conn, &note_json("並行"), db::QueuePriority::Mention, &l).unwrap().unwrap()
+    };
+
+    let first = enqueue(lane(Some("bot_a"), Some("alice")));
+    let same_lane = enqueue(lane(Some("bot_a"), Some("alice")));
+    let air_reply = enqueue(lane(None, Some("alice")));
+    let gift_wrap = enqueue(lane(Some("bot_a"), None));
+    let other_bot = enqueue(lane(Some("bot_b"), Some("alice")));
+    let other_user = enqueue(lane(Some("bot_a"), Some("bob")));
+
+    // bot_a×aliceの処理中は、同じBot・ユーザーに重なりうるものは飛ばされる
+    assert_eq!(db::dequeue_event(&conn).unwrap().unwrap().id, first);
+    assert_eq!(db::dequeue_event(&conn).unwrap().unwrap().id, other_bot);
+    assert_eq!(db::dequeue_event(&conn).unwrap().unwrap().id, other_user);
+    assert!(db::dequeue_event(&conn).unwrap().is_none());
+
+    // 処理が終われば同じBot・ユーザーの次のイベントをキューの順に取り出せる
+    db::complete_queue_event(&conn, first).unwrap();
+    db::complete_queue_event(&conn, other_bot).unwrap();
+    db::complete_queue_event(&conn, other_user).unwrap();
+    let mut order = Vec::new();
+    while let Some(item) = db::dequeue_event(&conn).unwrap() {
+        order.push(item.id);
+        db::complete_queue_event(&conn, item.id).unwrap();
+    }
+    assert_eq!(order, vec![same_lane, air_reply, gift_wrap]);
+}
+
+#[tokio::test]
+async fn retrying_event_is_not_overtaken_in_its_lane() {
+    let env = TestEnv::new().await;
+    let conn = env.conn();
+    let enqueue = |l: db::QueueLane| {
+        db::enqueue_event(&conn, &note_json("再試行"), db::QueuePriority::Mention, &l).unwrap().unwrap()
+    };
+
+    let first = enqueue(lane(Some("bot_a"), Some("alice")));
+    let same_lane = enqueue(lane(Some("bot_a"), Some("alice")));
+    let gift_wrap = enqueue(lane(Some("bot_a"), None));
+    let other_user = enqueue(lane(Some("bot_a"), Some("bob")));
+
+    assert_eq!(db::dequeue_event(&conn).unwrap().unwrap().id, first);
+    db::retry_queue_event(&conn, first, "LLMタイムアウト").unwrap();
+
+    // 再試行待ちの間も同じBot・ユーザーの後のイベントは追い越さない（Bot・ユーザーが分からないものは止めない）
+    let mut order = Vec::new();
+    while let Some(item) = db::dequeue_event(&conn).unwrap() {
+        order.push(item.id);
+        db::complete_queue_event(&conn, item.id).unwrap();
+    }
+    assert_eq!(order, vec![gift_wrap, other_user]);
+
+    // 再試行待ちが終われば、先に入ったものからキューの順に取り出せる
+    conn.execute("UPDATE event_queue SET next_attempt_at = 0 WHERE id = ?", [first]).unwrap();
+    assert_eq!(db::dequeue_event(&conn).unwrap().unwrap().id, first);
+    db::complete_queue_event(&conn, first).unwrap();
+    assert_eq!(db::dequeue_event(&conn).unwrap().unwrap().id, same_lane);
+}
+
+#[tokio::test]
+async fn idle_worker_wakes_up_when_event_is_enqueued() {
+    let env = TestEnv::new().await;
+    let bot = env.add_bot("わーかーちゃん");
+    let user = Keys::generate();
+    env.follow(&user, &bot).await;
+
+    let mention = EventBuilder::text_note("わーかーちゃん 起きてる？")
+        .tag(Tag::public_key(bot.public_key()))
+        .sign_with_keys(&user)
+        .unwrap();
+    let filter = Filter::new().author(bot.public_key()).kind(Kind::TextNote);
+
+    // 待機中のワーカーは通知で起きる（通知がなければ次の確認は1分後）
+    let replies = tokio::select! {
+        _ = bot::event_processor::run_queue_worker(env.config.clone(), env.bot_info.clone()) => unreachable!(),
+        replies = async {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            env.deliver(&mention).await;
+            tokio::time::timeout(Duration::from_secs(10), async {
+                loop {
+                    // 返信の公開後にキューから消えるので、両方を待つ
+                    let replies = env.fetch(filter.clone()).await;
+                    if !replies.is_empty() && env.count("SELECT COUNT(*) FROM event_queue", []) == 0 {
+                        break replies;
+                    }
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+            })
+            .await
+            .expect("ワーカーがイベントを処理しませんでした")
+        } => replies,
+    };
+    assert_eq!(replies.len(), 1);
+    assert_eq!(replies[0].content, SCRIPTED_REPLY);
+}
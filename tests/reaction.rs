@@ -0,0 +1,133 @@
+// 返信の代わりの絵文字リアクション（NIP-25 / NIP-30）のテスト
+
+mod common;
+
+use bot::config::ScriptedRule;
+use bot::db;
+use bot::reaction::{response_mode_for_roll, ResponseMode};
+use common::{event_tags, scripted_json_response, TestEnv};
+use nostr_sdk::prelude::*;
+
+const EMOJI_URL: &str = "https://example.com/nostrchan.png";
+
+fn set_overrides(env: &TestEnv, bot: &Keys, overrides: &db::PersonOverrides) {
+    db::update_person_overrides(&env.conn(), &bot.public_key().to_hex(), overrides).unwrap();
+}
+
+fn mention(user: &Keys, bot: &Keys) -> Event {
+    EventBuilder::text_note("えもじちゃん 見て見て！")
+        .tag(Tag::public_key(bot.public_key()))
+        .sign_with_keys(user)
+        .unwrap()
+}
+
+#[test]
+fn response_mode_follows_per_bot_split() {
+    let overrides = db::PersonOverrides {
+        ignore_percent: Some(10),
+        emoji_reaction_percent: Some(30),
+        ..Default::default()
+    };
+    assert_eq!(response_mode_for_roll(&overrides, 0), ResponseMode::Ignore);
+    assert_eq!(response_mode_for_roll(&overrides, 9), ResponseMode::Ignore);
+    assert_eq!(response_mode_for_roll(&overrides, 10), ResponseMode::Reaction);
+    assert_eq!(response_mode_for_roll(&overrides, 39), ResponseMode::Reaction);
+    assert_eq!(response_mode_for_roll(&overrides, 40), ResponseMode::Reply);
+
+    // 未設定なら常に返信
+    let defaults = db::PersonOverrides::default();
+    assert_eq!(response_mode_for_roll(&defaults, 0), ResponseMode::Reply);
+}
+
+#[tokio::test]
+async fn mention_is_answered_with_custom_emoji_reaction() {
+    let mut env = TestEnv::new().await;
+    let bot = env.add_bot("えもじちゃん");
+    let user = Keys::generate();
+    env.follow(&user, &bot).await;
+    set_overrides(
+        &env,
+        &bot,
+        &db::PersonOverrides {
+            emoji_reaction_percent: Some(100),
+            custom_emojis: Some(vec![db::CustomEmoji {
+                shortcode: "nostrchan".to_string(),
+                url: EMOJI_URL.to_string(),
+            }]),
+            ..Default::default()
+        },
+    );
+
+    // リアクションを選べるプロンプトのときだけリアクションを返す
+    env.config.llm.scripted.rules.push(ScriptedRule {
+        contains: Some(":nostrchan:".to_string()),
+        json_mode: Some(true),
+        response: {
+            let mut json: serde_json::Value = serde_json::from_str(&scripted_json_response()).unwrap();
+            json["reply"] = "".into();
+            json["reaction"] = ":nostrchan:".into();
+            json.to_string()
+        },
+        tool_call: None,
+    });
+
+    let event = mention(&user, &bot);
+    env.deliver(&event).await;
+    assert_eq!(env.drain_queue().await, 1);
+
+    let reactions = env
+        .fetch(Filter::new().author(bot.public_key()).kind(Kind::Reaction))
+        .await;
+    assert_eq!(reactions.len(), 1);
+    assert_eq!(reactions[0].content, ":nostrchan:");
+    assert!(event_tags(&reactions[0]).iter().any(|(id, _)| *id == event.id));
+    assert!(reactions[0].tags.iter().any(|tag| matches!(
+        tag.as_standardized(),
+        Some(TagStandard::Emoji { shortcode, url }) if shortcode == "nostrchan" && url.as_str() == EMOJI_URL
+    )));
+
+    let notes = env
+        .fetch(Filter::new().author(bot.public_key()).kind(Kind::TextNote))
+        .await;
+    assert!(notes.is_empty());
+
+    // リアクションも会話ログに残り、会話回数制限に数えられる
+    assert_eq!(
+        env.count(
+            "SELECT COUNT(*) FROM conversation_logs WHERE bot_pubkey = ? AND is_bot_message = 1",
+            [bot.public_key().to_hex()]
+        ),
+        1
+    );
+    let count = db::get_conversation_count_with_user(
+        &env.conn(),
+        &bot.public_key().to_hex(),
+        &user.public_key().to_hex(),
+        3,
+    )
+    .unwrap();
+    assert_eq!(count, 2);
+}
+
+#[tokio::test]
+async fn ignored_mention_gets_no_response() {
+    let env = TestEnv::new().await;
+    let bot = env.add_bot("むしちゃん");
+    let user = Keys::generate();
+    env.follow(&user, &bot).await;
+    set_overrides(
+        &env,
+        &bot,
+        &db::PersonOverrides {
+            ignore_percent: Some(100),
+            ..Default::default()
+        },
+    );
+
+    env.deliver(&mention(&user, &bot)).await;
+    assert_eq!(env.drain_queue().await, 1);
+
+    let responses = env.fetch(Filter::new().author(bot.public_key())).await;
+    assert!(responses.is_empty());
+    assert_eq!(env.count("SELECT COUNT(*) FROM token_usage", []), 0);
+}
@@ -0,0 +1,176 @@
+// リマインド（時刻の読み取り・コマンド・送信）のテスト
+
+mod common;
+
+use bot::db;
+use bot::scheduler::reminder::{self, ParsedReminder};
+use chrono::{FixedOffset, TimeZone};
+use common::TestEnv;
+use nostr_sdk::prelude::*;
+
+/// JSTの日時をUNIX秒に
+fn jst(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> i64 {
+    FixedOffset::east_opt(9 * 3600)
+        .unwrap()
+        .with_ymd_and_hms(year, month, day, hour, minute, 0)
+        .unwrap()
+        .timestamp()
+}
+
+fn parsed(remind_at: i64, message: &str) -> Option<ParsedReminder> {
+    Some(ParsedReminder {
+        remind_at,
+        message: message.to_string(),
+    })
+}
+
+#[test]
+fn parses_relative_and_absolute_times() {
+    // 2025-01-06(月) 10:00 JST
+    let now = jst(2025, 1, 6, 10, 0);
+    let parse = |text: &str| reminder::parse_reminder(text, now);
+
+    assert_eq!(parse("30分後に会議ってリマインドして"), parsed(now + 30 * 60, "会議"));
+    assert_eq!(parse("１時間半後にお風呂"), parsed(now + 90 * 60, "お風呂"));
+    assert_eq!(parse("2日後にゴミ出しをリマインドお願い"), parsed(now + 2 * 24 * 3600, "ゴミ出し"));
+    assert_eq!(parse("明日9時に薬を飲むってリマインドして"), parsed(jst(2025, 1, 7, 9, 0), "薬を飲む"));
+    assert_eq!(parse("午後3時半に電話ってリマインドして"), parsed(jst(2025, 1, 6, 15, 30), "電話"));
+    // 今日のその時刻を過ぎていれば翌日
+    assert_eq!(parse("8時に起きるってリマインドして"), parsed(jst(2025, 1, 7, 8, 0), "起きる"));
+    assert_eq!(parse("10月20日 18:00 締め切りってリマインド"), parsed(jst(2025, 10, 20, 18, 0), "締め切り"));
+    // 日付だけなら9時、過ぎた日付は翌年
+    assert_eq!(parse("明日 ゴミ出し"), parsed(jst(2025, 1, 7, 9, 0), "ゴミ出し"));
+    assert_eq!(parse("1/5に年賀状の返事"), parsed(jst(2026, 1, 5, 9, 0), "年賀状の返事"));
+
+    assert_eq!(parse("remind me in 2h to stretch"), parsed(now + 2 * 3600, "stretch"));
+    assert_eq!(parse("remind me in 1h30m about lunch"), parsed(now + 90 * 60, "lunch"));
+    assert_eq!(parse("remind me in 10 minutes to check the oven"), parsed(now + 10 * 60, "check the oven"));
+    assert_eq!(parse("remind me tomorrow at 9am to call mom"), parsed(jst(2025, 1, 7, 9, 0), "call mom"));
+    assert_eq!(parse("remind me at 21:30 to sleep"), parsed(jst(2025, 1, 6, 21, 30), "sleep"));
+
+    assert_eq!(parse("いつかリマインドして"), None);
+    assert_eq!(parse("25時に寝る"), None);
+}
+
+/// Botの投稿がcount件になるまで待つ（コマンドは別タスクで実行される）
+async fn wait_for_notes(env: &TestEnv, bot: &Keys, count: usize) -> Vec<Event> {
+    let mut notes = Vec::new();
+    for _ in 0..50 {
+        notes = env
+            .fetch(Filter::new().author(bot.public_key()).kind(Kind::TextNote))
+            .await;
+        if notes.len() >= count {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    notes
+}
+
+/// ユーザーがBotにコマンドを送る
+async fn send(env: &TestEnv, user: &Keys, bot: &Keys, content: &str) -> Event {
+    let event = EventBuilder::text_note(content)
+        .tag(Tag::public_key(bot.public_key()))
+        .sign_with_keys(user)
+        .unwrap();
+    env.deliver(&event).await;
+    event
+}
+
+fn reply_to<'a>(notes: &'a [Event], event: &Event) -> Option<&'a Event> {
+    notes
+        .iter()
+        .find(|note| common::event_tags(note).iter().any(|(id, _)| *id == event.id))
+}
+
+#[tokio::test]
+async fn reminder_is_stored_listed_and_sent_in_thread() {
+    let env = TestEnv::new().await;
+    let bot = env.add_bot("りまいんどちゃん");
+    let user = Keys::generate();
+    env.follow(&user, &bot).await;
+
+    let request = send(&env, &user, &bot, "30分後に会議ってリマインドして").await;
+    let notes = wait_for_notes(&env, &bot, 1).await;
+    let confirmation = reply_to(&notes, &request).expect("登録の返信がない");
+    assert!(confirmation.content.starts_with("⏰"));
+
+    let reminders = db::get_pending_reminders(&env.conn(), &bot.public_key().to_hex(), &user.public_key().to_hex()).unwrap();
+    assert_eq!(reminders.len(), 1);
+    let id = reminders[0].id;
+    assert_eq!(reminders[0].message, "会議");
+    assert!(confirmation.content.contains(&format!("#{}", id)));
+
+    let list = send(&env, &user, &bot, "リマインド一覧").await;
+    let notes = wait_for_notes(&env, &bot, 2).await;
+    assert!(reply_to(&notes, &list).expect("一覧の返信がない").content.contains(&format!("#{} ", id)));
+
+    // 時刻になるまでは送らない
+    let now = Timestamp::now().as_u64() as i64;
+    assert_eq!(reminder::deliver_due_reminders(&env.config, now).await.unwrap(), 0);
+
+    // 時刻を過ぎると、依頼した投稿へのリプライでペルソナの文章を送る
+    assert_eq!(reminder::deliver_due_reminders(&env.config, now + 31 * 60).await.unwrap(), 1);
+    let notes = wait_for_notes(&env, &bot, 3).await;
+    let sent: Vec<&Event> = notes
+        .iter()
+        .filter(|note| note.content == "スクリプト応答")
+        .collect();
+    assert_eq!(sent.len(), 1);
+    assert!(common::event_tags(sent[0]).iter().any(|(id, _)| *id == request.id));
+    assert!(common::pubkey_tags(sent[0]).contains(&user.public_key()));
+
+    assert_eq!(
+        env.count("SELECT COUNT(*) FROM reminders WHERE id = ? AND status = 'sent'", [id]),
+        1
+    );
+    assert_eq!(
+        env.count(
+            "SELECT COUNT(*) FROM token_usage tu JOIN token_categories tc ON tu.category_id = tc.id WHERE tc.name = 'reminder'",
+            [],
+        ),
+        1
+    );
+    // 2度は送らない
+    assert_eq!(reminder::deliver_due_reminders(&env.config, now + 31 * 60).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn reminder_can_be_cancelled_only_by_its_owner() {
+    let env = TestEnv::new().await;
+    let bot = env.add_bot("りまいんどちゃん");
+    let user = Keys::generate();
+    let other = Keys::generate();
+    env.follow(&user, &bot).await;
+    env.follow(&other, &bot).await;
+
+    send(&env, &user, &bot, "remind me in 2h to stretch").await;
+    wait_for_notes(&env, &bot, 1).await;
+    let id = db::get_pending_reminders(&env.conn(), &bot.public_key().to_hex(), &user.public_key().to_hex()).unwrap()[0].id;
+    let later = Timestamp::now().as_u64() as i64 + 3 * 3600;
+
+    // グローバル一時停止中は送らずに残す
+    db::set_system_setting(&env.conn(), "global_pause", "true").unwrap();
+    assert_eq!(reminder::deliver_due_reminders(&env.config, later).await.unwrap(), 0);
+    assert_eq!(
+        env.count("SELECT COUNT(*) FROM reminders WHERE id = ? AND status = 'pending'", [id]),
+        1
+    );
+    db::set_system_setting(&env.conn(), "global_pause", "false").unwrap();
+
+    // 他の人は取り消せない
+    let attempt = send(&env, &other, &bot, &format!("リマインド取消 {}", id)).await;
+    let notes = wait_for_notes(&env, &bot, 2).await;
+    assert!(reply_to(&notes, &attempt).unwrap().content.contains("見つからなかった"));
+
+    let cancel = send(&env, &user, &bot, &format!("remind cancel #{}", id)).await;
+    let notes = wait_for_notes(&env, &bot, 3).await;
+    assert!(reply_to(&notes, &cancel).unwrap().content.contains("取り消した"));
+    assert_eq!(
+        env.count("SELECT COUNT(*) FROM reminders WHERE id = ? AND status = 'cancelled'", [id]),
+        1
+    );
+
+    // 取り消したリマインドは送らない
+    assert_eq!(reminder::deliver_due_reminders(&env.config, later).await.unwrap(), 0);
+}
@@ -0,0 +1,149 @@
+// 定期投稿（cronの解釈・スケジュールの実行）のテスト
+
+mod common;
+
+use bot::db;
+use bot::scheduler::{self, CronSchedule};
+use chrono::{FixedOffset, TimeZone};
+use common::{TestEnv, SCRIPTED_REPLY};
+use nostr_sdk::prelude::*;
+
+/// JSTの日時をUNIX秒に
+fn jst(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> i64 {
+    FixedOffset::east_opt(9 * 3600)
+        .unwrap()
+        .with_ymd_and_hms(year, month, day, hour, minute, 0)
+        .unwrap()
+        .timestamp()
+}
+
+fn next(cron: &str, after: i64) -> Option<i64> {
+    CronSchedule::parse(cron).unwrap().next_after(after)
+}
+
+#[test]
+fn cron_finds_next_run_in_jst() {
+    // 2025-01-06は月曜日
+    let monday_0630 = jst(2025, 1, 6, 6, 30);
+
+    assert_eq!(next("0 7 * * *", monday_0630), Some(jst(2025, 1, 6, 7, 0)));
+    // ちょうどの時刻からは次の回
+    assert_eq!(next("0 7 * * *", jst(2025, 1, 6, 7, 0)), Some(jst(2025, 1, 7, 7, 0)));
+    assert_eq!(next("*/15 9-18 * * 1-5", monday_0630), Some(jst(2025, 1, 6, 9, 0)));
+    assert_eq!(next("*/15 9-18 * * 1-5", jst(2025, 1, 6, 18, 45)), Some(jst(2025, 1, 7, 9, 0)));
+    // 週末は土曜日に
+    assert_eq!(next("30 8 * * 6,0", monday_0630), Some(jst(2025, 1, 11, 8, 30)));
+    // 7も日曜日
+    assert_eq!(next("0 21 * * 7", monday_0630), Some(jst(2025, 1, 12, 21, 0)));
+    // 日と曜日の両方を指定するとどちらか早い方
+    assert_eq!(next("0 0 15 * 3", monday_0630), Some(jst(2025, 1, 8, 0, 0)));
+    // うるう日は次のうるう年
+    assert_eq!(next("0 0 29 2 *", monday_0630), Some(jst(2028, 2, 29, 0, 0)));
+    // 存在しない日付
+    assert_eq!(next("0 0 31 2 *", monday_0630), None);
+}
+
+#[test]
+fn cron_rejects_invalid_expressions() {
+    for cron in ["", "0 7 * *", "60 * * * *", "0 24 * * *", "0 0 0 * *", "0 0 * 13 *", "0 0 * * 8", "*/0 * * * *", "a * * * *", "5-1 * * * *"] {
+        assert!(CronSchedule::parse(cron).is_err(), "{} は不正", cron);
+    }
+    assert!(scheduler::next_run_at("0 7 * * *", 0).unwrap().is_some());
+}
+
+fn schedule_row(env: &TestEnv, id: i64) -> db::BotSchedule {
+    db::get_schedule(&env.conn(), id).unwrap().unwrap()
+}
+
+#[tokio::test]
+async fn due_schedule_posts_and_records_bot_post() {
+    let env = TestEnv::new().await;
+    let bot = env.add_bot("ていきちゃん");
+    let bot_pubkey = bot.public_key().to_hex();
+    let now = Timestamp::now().as_u64() as i64;
+
+    // 1週間の振り返りには最近の会話が渡る
+    let user = Keys::generate();
+    let talk = EventBuilder::text_note("週末はキャンプに行ったよ")
+        .custom_created_at(Timestamp::from((now - 3600) as u64))
+        .sign_with_keys(&user)
+        .unwrap();
+    {
+        let conn = env.conn();
+        let event_ref_id = db::insert_event(&conn, &talk, Some("ja")).unwrap();
+        db::insert_conversation_log(&conn, &bot_pubkey, event_ref_id, None, None, false, false).unwrap();
+    }
+
+    let id = db::insert_schedule(&env.conn(), &bot_pubkey, "weekly_summary", "0 21 * * 0", Some("絵文字を1つ使う"), true, Some(now - 60)).unwrap();
+    // 未来のスケジュールと無効なスケジュールは実行しない
+    db::insert_schedule(&env.conn(), &bot_pubkey, "greeting", "0 7 * * *", None, true, Some(now + 3600)).unwrap();
+    db::insert_schedule(&env.conn(), &bot_pubkey, "mood", "0 12 * * *", None, false, Some(now - 60)).unwrap();
+
+    assert_eq!(scheduler::run_due_schedules(&env.config, now).await.unwrap(), 1);
+
+    // ペルソナで生成した文章が返信ではない投稿としてリレーに届く
+    let posts = env.fetch(Filter::new().author(bot.public_key()).kind(Kind::TextNote)).await;
+    assert_eq!(posts.len(), 1);
+    assert_eq!(posts[0].content, SCRIPTED_REPLY);
+    assert!(common::event_tags(&posts[0]).is_empty());
+
+    // events・timelineに記録され、心境も更新される
+    assert_eq!(
+        env.count("SELECT COUNT(*) FROM events WHERE event_id = ?", [posts[0].id.to_hex()]),
+        1
+    );
+    assert_eq!(
+        env.count("SELECT COUNT(*) FROM timeline WHERE pubkey = ? AND content = ?", [&bot_pubkey, SCRIPTED_REPLY]),
+        1
+    );
+    assert_eq!(db::get_bot_mental_state(&env.conn(), &bot_pubkey).unwrap().unwrap().mood, "上機嫌");
+
+    // 定期投稿として記録され、プロンプトに会話と指示が入る
+    let prompt: String = env
+        .conn()
+        .query_row(
+            "SELECT tu.prompt_text FROM token_usage tu JOIN token_categories tc ON tu.category_id = tc.id
+             WHERE tc.name = 'scheduled_post'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert!(prompt.contains("週末はキャンプに行ったよ"));
+    assert!(prompt.contains("絵文字を1つ使う"));
+
+    // 次回は次の日曜21時（JST）
+    let schedule = schedule_row(&env, id);
+    assert_eq!(schedule.last_run_at, Some(now));
+    assert_eq!(schedule.next_run_at, scheduler::next_run_at("0 21 * * 0", now).unwrap());
+
+    // 同じ時刻で再実行しても投稿しない
+    assert_eq!(scheduler::run_due_schedules(&env.config, now).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn paused_or_disabled_bots_skip_scheduled_posts() {
+    let env = TestEnv::new().await;
+    let bot = env.add_bot("おやすみちゃん");
+    let bot_pubkey = bot.public_key().to_hex();
+    let now = Timestamp::now().as_u64() as i64;
+
+    // グローバル一時停止中は投稿せず、次回の時刻だけ進める
+    db::set_system_setting(&env.conn(), "global_pause", "true").unwrap();
+    let id = db::insert_schedule(&env.conn(), &bot_pubkey, "greeting", "0 7 * * *", None, true, Some(now - 60)).unwrap();
+    assert_eq!(scheduler::run_due_schedules(&env.config, now).await.unwrap(), 0);
+    assert!(schedule_row(&env, id).next_run_at.unwrap() > now);
+
+    // Botが無効化されていても投稿しない
+    db::set_system_setting(&env.conn(), "global_pause", "false").unwrap();
+    db::update_person_status(&env.conn(), &bot_pubkey, 1).unwrap();
+    db::insert_schedule(&env.conn(), &bot_pubkey, "mood", "0 12 * * *", None, true, Some(now - 60)).unwrap();
+    assert_eq!(scheduler::run_due_schedules(&env.config, now).await.unwrap(), 0);
+
+    let posts = env.fetch(Filter::new().author(bot.public_key()).kind(Kind::TextNote)).await;
+    assert!(posts.is_empty());
+    assert_eq!(env.count("SELECT COUNT(*) FROM token_usage", []), 0);
+
+    // Botを削除するとスケジュールも消える
+    db::delete_person(&env.conn(), &bot_pubkey).unwrap();
+    assert!(db::get_bot_schedules(&env.conn(), &bot_pubkey).unwrap().is_empty());
+}
@@ -0,0 +1,174 @@
+// ローカルの全文検索（FTS5）と検索コマンドのテスト
+
+mod common;
+
+use bot::config::{ScriptedRule, ScriptedToolCall};
+use bot::db;
+use common::TestEnv;
+use nostr_sdk::prelude::*;
+
+fn store_note(env: &TestEnv, author: &Keys, content: &str, created_at: u64) -> Event {
+    let event = EventBuilder::text_note(content)
+        .custom_created_at(Timestamp::from(created_at))
+        .sign_with_keys(author)
+        .unwrap();
+    db::insert_event(&env.conn(), &event, Some("ja")).unwrap();
+    event
+}
+
+fn search(env: &TestEnv, query: db::LocalSearchQuery) -> Vec<String> {
+    db::search_events_local(&env.conn(), &db::LocalSearchQuery { limit: 10, ..query })
+        .unwrap()
+        .into_iter()
+        .map(|e| e.content)
+        .collect()
+}
+
+fn keyword(keyword: &str) -> db::LocalSearchQuery {
+    db::LocalSearchQuery {
+        keyword: keyword.to_string(),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn local_search_matches_japanese_substrings_with_filters() {
+    let env = TestEnv::new().await;
+    let alice = Keys::generate();
+    let bob = Keys::generate();
+    store_note(&env, &alice, "駅前のラーメン屋が美味しかった", 1_700_000_000);
+    store_note(&env, &bob, "今日はラーメンを食べに行く", 1_700_000_100);
+    let deleted = store_note(&env, &alice, "ラーメンの写真を撮った", 1_700_000_200);
+    store_note(&env, &bob, "猫がかわいい", 1_700_000_300);
+    store_note(&env, &bob, "割引は5%です", 1_700_000_400);
+
+    // 3文字以上は索引で部分一致（新しい順）
+    assert_eq!(
+        search(&env, keyword("ラーメン")),
+        vec!["ラーメンの写真を撮った", "今日はラーメンを食べに行く", "駅前のラーメン屋が美味しかった"]
+    );
+    // 2文字以下もLIKEで探せる
+    assert_eq!(search(&env, keyword("猫")), vec!["猫がかわいい"]);
+    // LIKEのワイルドカードは文字として扱う
+    assert_eq!(search(&env, keyword("%")), vec!["割引は5%です"]);
+    assert!(search(&env, keyword("_")).is_empty());
+    // 複数の語は全てを含むもの
+    assert_eq!(search(&env, keyword("ラーメン 駅前")), vec!["駅前のラーメン屋が美味しかった"]);
+    // FTS5の演算子として解釈されない
+    assert!(search(&env, keyword("\"ラーメン OR")).is_empty());
+
+    // 投稿者・期間
+    let by_alice = db::LocalSearchQuery {
+        author: Some(alice.public_key().to_hex()),
+        ..keyword("ラーメン")
+    };
+    assert_eq!(search(&env, by_alice).len(), 2);
+    let in_range = db::LocalSearchQuery {
+        since: Some(1_700_000_050),
+        until: Some(1_700_000_150),
+        ..keyword("ラーメン")
+    };
+    assert_eq!(search(&env, in_range), vec!["今日はラーメンを食べに行く"]);
+
+    // 削除すると索引からも消える
+    env.conn()
+        .execute("DELETE FROM events WHERE event_id = ?", [deleted.id.to_hex()])
+        .unwrap();
+    assert_eq!(search(&env, keyword("ラーメン")).len(), 2);
+}
+
+/// Botの返信を待つ（コマンドは別タスクで実行される）
+async fn wait_for_reply(env: &TestEnv, bot: &Keys) -> Vec<Event> {
+    let mut replies = Vec::new();
+    for _ in 0..50 {
+        replies = env
+            .fetch(Filter::new().author(bot.public_key()).kind(Kind::TextNote))
+            .await;
+        if !replies.is_empty() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    replies
+}
+
+#[tokio::test]
+async fn search_command_answers_from_local_posts() {
+    let env = TestEnv::new().await;
+    let bot = env.add_bot("けんさくちゃん");
+    let user = Keys::generate();
+    env.follow(&user, &bot).await;
+    let now = Timestamp::now().as_u64();
+    let notes: Vec<Event> = (0..5)
+        .map(|i| store_note(&env, &Keys::generate(), &format!("ラーメン日記 その{}", i), now - 1000 + i))
+        .collect();
+
+    let command = EventBuilder::text_note("検索 ラーメン日記")
+        .tag(Tag::public_key(bot.public_key()))
+        .sign_with_keys(&user)
+        .unwrap();
+    env.deliver(&command).await;
+
+    let replies = wait_for_reply(&env, &bot).await;
+    assert_eq!(replies.len(), 1);
+    let reply = &replies[0].content;
+    assert!(reply.contains("【検索結果: ラーメン日記】"));
+    for note in &notes {
+        assert!(reply.contains(&note.id.to_bech32().unwrap()));
+    }
+}
+
+#[tokio::test]
+async fn natural_language_search_uses_tool_call() {
+    let mut env = TestEnv::new().await;
+    let bot = env.add_bot("けんさくちゃん");
+    let user = Keys::generate();
+    env.follow(&user, &bot).await;
+    // 2024-08-10 12:00 JST と 2025-01-10 12:00 JST
+    let august = store_note(&env, &Keys::generate(), "夏のラーメンは冷やしに限る", 1_723_258_800);
+    let january = store_note(&env, &Keys::generate(), "冬のラーメンはあったかい", 1_736_478_000);
+
+    // 質問にはツール呼び出しで答え、検索結果を受け取った後は既定の応答を返す
+    env.config.llm.scripted.rules.push(ScriptedRule {
+        contains: Some("なんだっけ".to_string()),
+        json_mode: None,
+        response: String::new(),
+        tool_call: Some(ScriptedToolCall {
+            name: "search_events".to_string(),
+            arguments: r#"{"keywords":["ラーメン"],"start_date":"2024-08-01","end_date":"2024-08-31"}"#.to_string(),
+            repeat: false,
+        }),
+    });
+
+    let command = EventBuilder::text_note("検索 去年の8月くらいのラーメンの話題なんだっけ？")
+        .tag(Tag::public_key(bot.public_key()))
+        .sign_with_keys(&user)
+        .unwrap();
+    env.deliver(&command).await;
+
+    let replies = wait_for_reply(&env, &bot).await;
+    assert_eq!(replies.len(), 1);
+    // 回答にリンクがなければ見つかった投稿のリンクが付く
+    let reply = &replies[0].content;
+    assert!(reply.starts_with("スクリプト応答"));
+    assert!(reply.contains(&august.id.to_bech32().unwrap()));
+    assert!(!reply.contains(&january.id.to_bech32().unwrap()));
+
+    // 検索条件の抽出と最終回答がそれぞれ記録され、最終回答には検索結果が渡る
+    let categories: Vec<(String, String)> = {
+        let conn = env.conn();
+        let mut stmt = conn
+            .prepare(
+                "SELECT tc.name, tu.prompt_text FROM token_usage tu JOIN token_categories tc ON tu.category_id = tc.id
+                 ORDER BY tu.id",
+            )
+            .unwrap();
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?))).unwrap();
+        rows.collect::<Result<_, _>>().unwrap()
+    };
+    assert_eq!(categories.len(), 2);
+    assert_eq!(categories[0].0, "search_keyword_extraction");
+    assert!(categories[0].1.contains("search_events"));
+    assert_eq!(categories[1].0, "search_final_reply");
+    assert!(categories[1].1.contains("夏のラーメンは冷やしに限る"));
+}
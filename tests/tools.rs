@@ -0,0 +1,198 @@
+// メンション返信でのツール呼び出し（許可リスト・記録・回数上限）のテスト
+
+mod common;
+
+use bot::config::{ScriptedRule, ScriptedToolCall};
+use bot::db;
+use bot::llm::tools::{ToolContext, ToolRegistry};
+use bot::llm::ToolCall;
+use common::{TestEnv, SCRIPTED_REPLY};
+use nostr_sdk::prelude::*;
+
+fn allow_tools(env: &TestEnv, bot: &Keys, tools: &[&str]) {
+    let overrides = db::PersonOverrides {
+        allowed_tools: Some(tools.iter().map(|t| t.to_string()).collect()),
+        ..Default::default()
+    };
+    db::update_person_overrides(&env.conn(), &bot.public_key().to_hex(), &overrides).unwrap();
+}
+
+fn tool_rule(contains: &str, name: &str, repeat: bool) -> ScriptedRule {
+    ScriptedRule {
+        contains: Some(contains.to_string()),
+        json_mode: None,
+        response: String::new(),
+        tool_call: Some(ScriptedToolCall {
+            name: name.to_string(),
+            arguments: "{}".to_string(),
+            repeat,
+        }),
+    }
+}
+
+fn mention(user: &Keys, bot: &Keys, content: &str) -> Event {
+    EventBuilder::text_note(content)
+        .tag(Tag::public_key(bot.public_key()))
+        .sign_with_keys(user)
+        .unwrap()
+}
+
+/// カテゴリ毎のプロンプト（記録順）
+fn prompts(env: &TestEnv, category: &str) -> Vec<String> {
+    let conn = env.conn();
+    let mut stmt = conn
+        .prepare(
+            "SELECT tu.prompt_text FROM token_usage tu JOIN token_categories tc ON tu.category_id = tc.id
+             WHERE tc.name = ? ORDER BY tu.id",
+        )
+        .unwrap();
+    let rows = stmt.query_map([category], |row| row.get(0)).unwrap();
+    rows.collect::<Result<_, _>>().unwrap()
+}
+
+#[tokio::test]
+async fn allowed_tool_result_is_passed_to_reply_and_logged() {
+    let mut env = TestEnv::new().await;
+    let bot = env.add_bot("ツールちゃん");
+    let user = Keys::generate();
+    env.follow(&user, &bot).await;
+    allow_tools(&env, &bot, &["get_user_profile", "get_current_time"]);
+    let profile = EventBuilder::metadata(&Metadata::new().name("たろう").about("ラーメンが好き"))
+        .sign_with_keys(&user)
+        .unwrap();
+    db::insert_event(&env.conn(), &profile, None).unwrap();
+    env.config.llm.scripted.rules.push(tool_rule("プロフィール", "get_user_profile", false));
+
+    let mention = mention(&user, &bot, "ツールちゃん 私のプロフィールを見て");
+    env.deliver(&mention).await;
+    assert_eq!(env.drain_queue().await, 1);
+
+    let replies = env
+        .fetch(Filter::new().author(bot.public_key()).kind(Kind::TextNote))
+        .await;
+    assert_eq!(replies.len(), 1);
+    assert_eq!(replies[0].content, SCRIPTED_REPLY);
+
+    // 呼び出しと結果が記録される
+    let (tool_name, user_pubkey, event_id, result, is_error): (String, String, String, String, bool) = env
+        .conn()
+        .query_row(
+            "SELECT tool_name, user_pubkey, event_id, result, is_error FROM tool_call_logs WHERE bot_pubkey = ?",
+            [bot.public_key().to_hex()],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+        )
+        .unwrap();
+    assert_eq!(tool_name, "get_user_profile");
+    assert_eq!(user_pubkey, user.public_key().to_hex());
+    assert_eq!(event_id, mention.id.to_hex());
+    assert!(result.contains("ラーメンが好き"));
+    assert!(!is_error);
+
+    // ツールを選ぶ呼び出しと結果を受け取った呼び出し、その結果を渡した返信
+    assert_eq!(prompts(&env, "tool_call").len(), 2);
+    let reply_prompts = prompts(&env, "reply");
+    assert_eq!(reply_prompts.len(), 1);
+    assert!(reply_prompts[0].contains("【ツールの実行結果】"));
+    assert!(reply_prompts[0].contains("ラーメンが好き"));
+}
+
+#[tokio::test]
+async fn tool_calls_stop_at_max_rounds() {
+    let mut env = TestEnv::new().await;
+    let bot = env.add_bot("ツールちゃん");
+    let user = Keys::generate();
+    env.follow(&user, &bot).await;
+    allow_tools(&env, &bot, &["get_current_time"]);
+    db::set_system_setting(&env.conn(), "tool_max_rounds", "2").unwrap();
+    // 結果を受け取った後も呼び出し続ける
+    env.config.llm.scripted.rules.push(tool_rule("何時", "get_current_time", true));
+
+    env.deliver(&mention(&user, &bot, "ツールちゃん 今何時？")).await;
+    assert_eq!(env.drain_queue().await, 1);
+
+    // 上限まで実行した後はツールなしで答えさせ、返信は届く
+    assert_eq!(env.count("SELECT COUNT(*) FROM tool_call_logs WHERE tool_name = 'get_current_time'", []), 2);
+    assert_eq!(prompts(&env, "tool_call").len(), 3);
+    let replies = env
+        .fetch(Filter::new().author(bot.public_key()).kind(Kind::TextNote))
+        .await;
+    assert_eq!(replies.len(), 1);
+}
+
+#[tokio::test]
+async fn bot_without_allowed_tools_does_not_call_tools() {
+    let mut env = TestEnv::new().await;
+    let bot = env.add_bot("ツールちゃん");
+    let user = Keys::generate();
+    env.follow(&user, &bot).await;
+    env.config.llm.scripted.rules.push(tool_rule("何時", "get_current_time", true));
+
+    env.deliver(&mention(&user, &bot, "ツールちゃん 今何時？")).await;
+    assert_eq!(env.drain_queue().await, 1);
+
+    assert_eq!(env.count("SELECT COUNT(*) FROM tool_call_logs", []), 0);
+    assert!(prompts(&env, "tool_call").is_empty());
+    assert_eq!(prompts(&env, "reply").len(), 1);
+}
+
+#[tokio::test]
+async fn registry_rejects_tools_outside_allowlist_and_reads_threads() {
+    let env = TestEnv::new().await;
+    let bot = env.add_bot("ツールちゃん");
+    let user = Keys::generate();
+    let root = EventBuilder::text_note("週末はどこに行こうかな")
+        .sign_with_keys(&Keys::generate())
+        .unwrap();
+    env.publish(&root).await;
+    let reply = EventBuilder::text_note("ツールちゃん どう思う？")
+        .tag(Tag::from_standardized(TagStandard::Event {
+            event_id: root.id,
+            relay_url: None,
+            marker: Some(Marker::Root),
+            public_key: None,
+            uppercase: false,
+        }))
+        .tag(Tag::public_key(bot.public_key()))
+        .sign_with_keys(&user)
+        .unwrap();
+    env.publish(&reply).await;
+
+    let person = db::get_person(&env.conn(), &bot.public_key().to_hex()).unwrap();
+    let ctx = ToolContext {
+        config: &env.config,
+        bot: &person,
+        event: &reply,
+    };
+    let tools = ToolRegistry::builtin().only(&["get_thread"]);
+    let call = |name: &str| ToolCall {
+        id: "call_1".to_string(),
+        name: name.to_string(),
+        arguments: "{}".to_string(),
+    };
+
+    // 許可されていないツールはエラーとしてLLMに返す
+    let rejected = tools.execute(&ctx, &call("get_zap_total")).await;
+    assert!(rejected.is_error);
+    assert!(rejected.content.contains("get_zap_total"));
+
+    // スレッドのルートから古い順に返す
+    let thread = tools.execute(&ctx, &call("get_thread")).await;
+    assert!(!thread.is_error, "{}", thread.content);
+    let posts: serde_json::Value = serde_json::from_str(&thread.content).unwrap();
+    let contents: Vec<&str> = posts["posts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|post| post["content"].as_str().unwrap())
+        .collect();
+    assert_eq!(contents, vec!["週末はどこに行こうかな", "ツールちゃん どう思う？"]);
+
+    assert_eq!(
+        env.count("SELECT COUNT(*) FROM tool_call_logs WHERE bot_pubkey = ?", [&person.pubkey]),
+        2
+    );
+    assert_eq!(
+        env.count("SELECT COUNT(*) FROM tool_call_logs WHERE bot_pubkey = ? AND is_error = 1", [&person.pubkey]),
+        1
+    );
+}
@@ -0,0 +1,209 @@
+// Web検索プロバイダーと「調べて」コマンドのテスト（ネットワーク不要）
+
+mod common;
+
+use axum::{extract::Query, routing::get, Json, Router};
+use bot::config::{ScriptedRule, WebSearchConfig, WebSearchProviderKind};
+use bot::web_search::{self, WebSearchResult};
+use common::TestEnv;
+use nostr_sdk::prelude::*;
+use std::collections::HashMap;
+
+const FIXTURE: &str = r#"[
+  {
+    "query": "Rust",
+    "results": [
+      {"title": "Rust公式サイト", "url": "https://www.rust-lang.org/", "snippet": "Rustは安全で速いプログラミング言語です"},
+      {"title": "Rust 1.90リリース", "url": "https://blog.rust-lang.org/1.90/", "snippet": "Rust 1.90が公開されました"}
+    ]
+  }
+]"#;
+
+fn fake_config(env: &TestEnv) -> WebSearchConfig {
+    let fixture = env.dir.join("web_search.json");
+    std::fs::write(&fixture, FIXTURE).unwrap();
+    WebSearchConfig {
+        provider: WebSearchProviderKind::Fake,
+        fixture: Some(fixture.to_str().unwrap().to_string()),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn fake_provider_returns_fixture_results() {
+    let env = TestEnv::new().await;
+    let provider = web_search::create_provider(&fake_config(&env)).unwrap();
+
+    let results = provider.search("Rust 最新バージョン", 5).await.unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].url, "https://www.rust-lang.org/");
+    assert_eq!(provider.search("Rust", 1).await.unwrap().len(), 1);
+    // 当てはまるfixtureがなければ0件
+    assert!(provider.search("今日の天気", 5).await.unwrap().is_empty());
+
+    // fixtureがなければ生成できない
+    let missing = WebSearchConfig {
+        provider: WebSearchProviderKind::Fake,
+        ..Default::default()
+    };
+    assert!(web_search::create_provider(&missing).is_err());
+}
+
+#[tokio::test]
+async fn searxng_provider_reads_json_api() {
+    let app = Router::new().route(
+        "/search",
+        get(|Query(params): Query<HashMap<String, String>>| async move {
+            assert_eq!(params.get("format").map(String::as_str), Some("json"));
+            let query = params.get("q").cloned().unwrap_or_default();
+            Json(serde_json::json!({
+                "query": query,
+                "results": [
+                    {"title": format!("{}の解説", query), "url": "https://example.com/1", "content": "一つ目"},
+                    {"title": "二つ目", "url": "https://example.com/2", "content": "二つ目の説明"},
+                    {"url": "https://example.com/3"}
+                ]
+            }))
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let config = WebSearchConfig {
+        provider: WebSearchProviderKind::Searxng,
+        base_url: Some(format!("http://{}/", addr)),
+        ..Default::default()
+    };
+    let provider = web_search::create_provider(&config).unwrap();
+    let results = provider.search("ラーメン", 2).await.unwrap();
+    assert_eq!(
+        results,
+        vec![
+            WebSearchResult {
+                title: "ラーメンの解説".to_string(),
+                url: "https://example.com/1".to_string(),
+                snippet: "一つ目".to_string(),
+            },
+            WebSearchResult {
+                title: "二つ目".to_string(),
+                url: "https://example.com/2".to_string(),
+                snippet: "二つ目の説明".to_string(),
+            },
+        ]
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn gemini_cli_provider_parses_json_output() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let env = TestEnv::new().await;
+    // Gemini CLIの代わりに前置き付きのJSONを出力するスクリプト
+    let script = env.dir.join("gemini");
+    std::fs::write(
+        &script,
+        "#!/bin/sh\necho '検索結果です'\necho '[{\"title\": \"Nostr\", \"url\": \"https://nostr.com/\", \"snippet\": \"分散型のプロトコル\"}]'\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let config = WebSearchConfig {
+        provider: WebSearchProviderKind::GeminiCli,
+        command: script.to_str().unwrap().to_string(),
+        ..Default::default()
+    };
+    let provider = web_search::create_provider(&config).unwrap();
+    let results = provider.search("Nostr", 5).await.unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].url, "https://nostr.com/");
+    assert_eq!(results[0].snippet, "分散型のプロトコル");
+}
+
+#[test]
+fn append_sources_lists_cited_results() {
+    let results = vec![
+        WebSearchResult {
+            title: "一".to_string(),
+            url: "https://example.com/1".to_string(),
+            snippet: String::new(),
+        },
+        WebSearchResult {
+            title: "二".to_string(),
+            url: "https://example.com/2".to_string(),
+            snippet: String::new(),
+        },
+    ];
+
+    let cited = web_search::append_sources("答えだよ[2]", &results);
+    assert_eq!(cited, "答えだよ[2]\n\n出典:\n[2] 二 https://example.com/2");
+    // 引用がなければ上位の結果を並べる
+    let uncited = web_search::append_sources("答えだよ", &results);
+    assert!(uncited.contains("[1] 一 https://example.com/1"));
+    assert!(uncited.contains("[2] 二 https://example.com/2"));
+}
+
+/// Botの投稿がcount件になるまで待つ（コマンドは別タスクで実行される）
+async fn wait_for_notes(env: &TestEnv, bot: &Keys, count: usize) -> Vec<Event> {
+    let mut notes = Vec::new();
+    for _ in 0..100 {
+        notes = env
+            .fetch(Filter::new().author(bot.public_key()).kind(Kind::TextNote))
+            .await;
+        if notes.len() >= count {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    notes
+}
+
+#[tokio::test]
+async fn search_web_command_cites_sources() {
+    let mut env = TestEnv::new().await;
+    env.config.web_search = fake_config(&env);
+    env.config.llm.scripted.rules.push(ScriptedRule {
+        contains: Some("効率の良い検索キーワード".to_string()),
+        json_mode: None,
+        response: "Rust 最新バージョン".to_string(),
+        tool_call: None,
+    });
+    env.config.llm.scripted.rules.push(ScriptedRule {
+        contains: Some("番号付きの検索結果".to_string()),
+        json_mode: None,
+        response: "最新はRust 1.90だよ[2]".to_string(),
+        tool_call: None,
+    });
+    let bot = env.add_bot("しらべちゃん");
+    let user = Keys::generate();
+    env.follow(&user, &bot).await;
+
+    let command = EventBuilder::text_note("Rustの最新バージョンを調べて")
+        .tag(Tag::public_key(bot.public_key()))
+        .sign_with_keys(&user)
+        .unwrap();
+    env.deliver(&command).await;
+
+    // 一次回答と、それへのリプライとしての最終回答
+    let notes = wait_for_notes(&env, &bot, 2).await;
+    assert_eq!(notes.len(), 2);
+    let answer = notes
+        .iter()
+        .find(|note| note.content.starts_with("最新はRust 1.90だよ"))
+        .expect("最終回答がない");
+    assert!(answer.content.contains("出典:\n[2] Rust 1.90リリース https://blog.rust-lang.org/1.90/"));
+    assert!(!answer.content.contains("https://www.rust-lang.org/"));
+
+    // 要約には番号付きの検索結果が渡る
+    let prompt: String = env
+        .conn()
+        .query_row(
+            "SELECT tu.prompt_text FROM token_usage tu JOIN token_categories tc ON tu.category_id = tc.id
+             WHERE tc.name = 'search_final_reply'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert!(prompt.contains("[1] Rust公式サイト\nURL: https://www.rust-lang.org/"));
+}